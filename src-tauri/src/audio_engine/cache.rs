@@ -0,0 +1,149 @@
+use crate::audio_engine::peaks::PeakPyramid;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// File size and modification time, to tell whether a file changed since it was hashed
+type FileStamp = (u64, Option<SystemTime>);
+
+struct Loaded {
+    stamp: FileStamp,
+    key: String,
+    pyramid: Arc<PeakPyramid>,
+}
+
+fn loaded() -> &'static Mutex<HashMap<String, Loaded>> {
+    static LOADED: OnceLock<Mutex<HashMap<String, Loaded>>> = OnceLock::new();
+    LOADED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Stable hash of a file's contents (64-bit FNV-1a) followed by its size
+pub fn file_hash(path: &str) -> Result<String, String> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    let mut buffer = vec![0u8; 1 << 16];
+    let mut hash = OFFSET_BASIS;
+    let mut size = 0u64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {path}: {e}"))?;
+        if read == 0 {
+            break;
+        }
+        for byte in &buffer[..read] {
+            hash = (hash ^ *byte as u64).wrapping_mul(PRIME);
+        }
+        size += read as u64;
+    }
+    Ok(format!("{hash:016x}-{size}"))
+}
+
+fn cache_file(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{key}.peaks"))
+}
+
+/// The peak pyramid of `path`, from memory, from `cache_dir` or else built with `build` and
+/// stored. Returns the cache key (the file hash) with the pyramid.
+///
+/// Pyramids are stored under the hash of the file's contents, so a file is decoded once
+/// however often it is moved, renamed or re-imported.
+pub fn load_or_build(
+    cache_dir: &Path,
+    path: &str,
+    build: impl FnOnce() -> Result<PeakPyramid, String>,
+) -> Result<(String, Arc<PeakPyramid>), String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let stamp = (metadata.len(), metadata.modified().ok());
+    if let Some(entry) = loaded()
+        .lock()
+        .map_err(|e| format!("Failed to lock waveform cache: {e}"))?
+        .get(path)
+        .filter(|entry| entry.stamp == stamp)
+    {
+        return Ok((entry.key.clone(), entry.pyramid.clone()));
+    }
+
+    let key = file_hash(path)?;
+    let file = cache_file(cache_dir, &key);
+    let pyramid = match fs::read(&file)
+        .ok()
+        .and_then(|bytes| PeakPyramid::from_bytes(&bytes).ok())
+    {
+        Some(pyramid) => pyramid,
+        None => {
+            let pyramid = build()?;
+            store(&file, &pyramid)?;
+            pyramid
+        }
+    };
+
+    let pyramid = Arc::new(pyramid);
+    loaded()
+        .lock()
+        .map_err(|e| format!("Failed to lock waveform cache: {e}"))?
+        .insert(
+            path.to_string(),
+            Loaded {
+                stamp,
+                key: key.clone(),
+                pyramid: pyramid.clone(),
+            },
+        );
+    Ok((key, pyramid))
+}
+
+/// Write through a temporary file so a crash never leaves a truncated cache entry
+fn store(file: &Path, pyramid: &PeakPyramid) -> Result<(), String> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create waveform cache directory: {e}"))?;
+    }
+    let partial = file.with_extension("peaks.tmp");
+    fs::write(&partial, pyramid.to_bytes())
+        .map_err(|e| format!("Failed to write waveform cache: {e}"))?;
+    fs::rename(&partial, file).map_err(|e| format!("Failed to write waveform cache: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_or_build_caches_on_disk() {
+        let dir = std::env::temp_dir().join(format!("shortforge-peaks-{}", std::process::id()));
+        let audio = dir.join("song.raw");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&audio, [1u8, 2, 3, 4]).unwrap();
+        let audio = audio.to_string_lossy().to_string();
+        let cache_dir = dir.join("cache");
+
+        let build = || Ok(PeakPyramid::build(&[vec![0.5; 1024]], 1000));
+        let (key, first) = load_or_build(&cache_dir, &audio, build).unwrap();
+        assert_eq!(key, file_hash(&audio).unwrap());
+        assert!(key.ends_with("-4"));
+        assert!(cache_file(&cache_dir, &key).exists());
+
+        // served from memory (or disk) without rebuilding
+        let (_, second) = load_or_build(&cache_dir, &audio, || Err("rebuilt".to_string())).unwrap();
+        assert_eq!(second, first);
+
+        // the same contents under another name hit the disk cache
+        let copy = dir.join("copy.raw");
+        fs::copy(&audio, &copy).unwrap();
+        let (copy_key, third) = load_or_build(&cache_dir, &copy.to_string_lossy(), || {
+            Err("rebuilt".to_string())
+        })
+        .unwrap();
+        assert_eq!(copy_key, key);
+        assert_eq!(third, first);
+
+        assert!(load_or_build(&cache_dir, "/nonexistent/file.mp3", build).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
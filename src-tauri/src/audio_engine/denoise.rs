@@ -0,0 +1,255 @@
+use crate::beat_sync::spectrum::{hann_window, stft_magnitudes};
+use crate::models::effects::{NoiseProfile, NoiseReduction};
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+/// Analysis frame of profiles and processing, ~43 ms at 48 kHz
+const FFT_SIZE: usize = 2048;
+
+/// 75% overlap, for which squared Hann windows sum to a constant
+const HOP_SIZE: usize = FFT_SIZE / 4;
+
+/// Weight of the previous frame in each bin's power estimate
+const POWER_SMOOTHING: f32 = 0.5;
+
+/// Share of the quietest frames a profile is estimated from when none was captured
+const QUIET_SHARE: f32 = 0.1;
+
+/// Average magnitude spectrum of noise-only audio, e.g. room tone before the voice starts
+pub fn capture_noise_profile(samples: &[f32], sample_rate: u32) -> NoiseProfile {
+    profile_from(&stft_magnitudes(samples, FFT_SIZE, HOP_SIZE), sample_rate)
+}
+
+/// Noise profile from the quietest frames of audio that has no noise-only stretch marked
+pub fn estimate_noise_profile(samples: &[f32], sample_rate: u32) -> NoiseProfile {
+    let mut spectra = stft_magnitudes(samples, FFT_SIZE, HOP_SIZE);
+    let energy = |spectrum: &Vec<f32>| spectrum.iter().map(|m| m * m).sum::<f32>();
+    spectra.sort_by(|a, b| energy(a).total_cmp(&energy(b)));
+    let quiet = ((spectra.len() as f32 * QUIET_SHARE).ceil() as usize).max(1);
+    profile_from(&spectra[..quiet.min(spectra.len())], sample_rate)
+}
+
+fn profile_from(spectra: &[Vec<f32>], sample_rate: u32) -> NoiseProfile {
+    let mut magnitudes = vec![0.0; FFT_SIZE / 2 + 1];
+    for spectrum in spectra {
+        for (sum, m) in magnitudes.iter_mut().zip(spectrum) {
+            *sum += m;
+        }
+    }
+    for m in &mut magnitudes {
+        *m /= spectra.len().max(1) as f32;
+    }
+    NoiseProfile {
+        sample_rate,
+        fft_size: FFT_SIZE,
+        magnitudes,
+    }
+}
+
+/// The profile's magnitude per bin of a `FFT_SIZE` frame at `sample_rate`, so profiles
+/// captured from audio at another rate or frame size still line up by frequency
+fn noise_per_bin(profile: &NoiseProfile, sample_rate: u32) -> Vec<f32> {
+    let last = profile.magnitudes.len().saturating_sub(1);
+    // Noise magnitudes grow with the square root of the frame length
+    let scale = (FFT_SIZE as f32 / profile.fft_size.max(1) as f32).sqrt();
+    (0..=FFT_SIZE / 2)
+        .map(|bin| {
+            let hz = bin as f32 * sample_rate as f32 / FFT_SIZE as f32;
+            let position = hz * profile.fft_size as f32 / profile.sample_rate.max(1) as f32;
+            let index = (position as usize).min(last);
+            let a = profile.magnitudes.get(index).copied().unwrap_or(0.0);
+            let b = profile.magnitudes.get(index + 1).copied().unwrap_or(a);
+            let mix = (position - index as f32).clamp(0.0, 1.0);
+            (a + (b - a) * mix) * scale
+        })
+        .collect()
+}
+
+/// Reduce noise in mono samples in place, bin by bin over an STFT that is overlap-added back.
+///
+/// Each bin keeps the share of its power the noise (times `sensitivity`) doesn't account for,
+/// but never less than `reduction_db` below the input. The power is smoothed over the
+/// previous frame and neighbouring bins first, so stray noise peaks don't survive as
+/// warbling tones.
+pub fn reduce_noise(samples: &mut [f32], sample_rate: u32, options: &NoiseReduction) {
+    if samples.is_empty() {
+        return;
+    }
+    let profile = options
+        .profile
+        .clone()
+        .unwrap_or_else(|| estimate_noise_profile(samples, sample_rate));
+    let noise = noise_per_bin(&profile, sample_rate);
+    let floor = 10f32.powf(-options.reduction_db.abs() / 20.0);
+    let sensitivity = options.sensitivity.max(0.0);
+
+    let window = hann_window(FFT_SIZE);
+    let mut planner = FftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(FFT_SIZE);
+    let inverse = planner.plan_fft_inverse(FFT_SIZE);
+    let bins = FFT_SIZE / 2 + 1;
+
+    // Frames start a full frame before the audio so every sample is covered by four
+    let offset = FFT_SIZE;
+    let mut output = vec![0.0f32; samples.len() + 2 * FFT_SIZE];
+    let mut weight = vec![0.0f32; output.len()];
+    let mut buffer = vec![Complex::new(0.0, 0.0); FFT_SIZE];
+    let mut gains = vec![1.0f32; bins];
+    let mut powers = vec![0.0f32; bins];
+
+    for start in (0..samples.len() + offset).step_by(HOP_SIZE) {
+        for (i, value) in buffer.iter_mut().enumerate() {
+            let sample = (start + i)
+                .checked_sub(offset)
+                .and_then(|index| samples.get(index))
+                .copied()
+                .unwrap_or(0.0);
+            *value = Complex::new(sample * window[i], 0.0);
+        }
+        forward.process(&mut buffer);
+
+        for (smoothed, c) in powers.iter_mut().zip(&buffer[..bins]) {
+            *smoothed = POWER_SMOOTHING * *smoothed + (1.0 - POWER_SMOOTHING) * c.norm_sqr();
+        }
+        for (bin, gain) in gains.iter_mut().enumerate() {
+            let neighbours = &powers[bin.saturating_sub(1)..(bin + 2).min(bins)];
+            let power = neighbours.iter().sum::<f32>() / neighbours.len() as f32;
+            let noise_power = (sensitivity * noise[bin]).powi(2);
+            *gain = if power > 0.0 {
+                ((power - noise_power).max(0.0) / power).sqrt().max(floor)
+            } else {
+                1.0
+            };
+        }
+        for (bin, gain) in gains.iter().enumerate() {
+            buffer[bin] *= gain;
+            // Mirror onto the negative frequencies so the frame stays real
+            if bin > 0 && bin < FFT_SIZE / 2 {
+                buffer[FFT_SIZE - bin] *= gain;
+            }
+        }
+
+        inverse.process(&mut buffer);
+        for (i, value) in buffer.iter().enumerate() {
+            output[start + i] += value.re / FFT_SIZE as f32 * window[i];
+            weight[start + i] += window[i] * window[i];
+        }
+    }
+
+    for (i, sample) in samples.iter_mut().enumerate() {
+        let w = weight[i + offset];
+        if w > 1e-6 {
+            *sample = output[i + offset] / w;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    /// Uniform white noise of a given peak amplitude
+    fn hiss(amplitude: f32, len: usize) -> Vec<f32> {
+        let mut seed = 7u32;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                amplitude * ((seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    /// Amplitude of the 1 kHz component of samples starting at absolute index `start`
+    fn tone_amplitude(samples: &[f32], start: usize) -> f32 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, s) in samples.iter().enumerate() {
+            let phase =
+                2.0 * std::f64::consts::PI * 1000.0 * (start + i) as f64 / SAMPLE_RATE as f64;
+            re += *s as f64 * phase.cos();
+            im += *s as f64 * phase.sin();
+        }
+        (2.0 * (re * re + im * im).sqrt() / samples.len() as f64) as f32
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_reduce_noise_with_profile() {
+        // 1 s of hiss, then 2 s of hiss under a 1 kHz tone
+        let second = SAMPLE_RATE as usize;
+        let mut signal = hiss(0.05, 3 * second);
+        for (i, sample) in signal.iter_mut().enumerate().skip(second) {
+            *sample += 0.3 * (2.0 * PI * 1000.0 * i as f32 / SAMPLE_RATE as f32).sin();
+        }
+        let options = NoiseReduction {
+            profile: Some(capture_noise_profile(&signal[..second], SAMPLE_RATE)),
+            reduction_db: 24.0,
+            sensitivity: 2.0,
+        };
+        let mut output = signal.clone();
+        reduce_noise(&mut output, SAMPLE_RATE, &options);
+
+        let hiss_before = rms(&signal[second / 10..second * 9 / 10]);
+        let hiss_after = rms(&output[second / 10..second * 9 / 10]);
+        let reduction = 20.0 * (hiss_after / hiss_before).log10();
+        assert!(reduction < -18.0, "{reduction}");
+
+        let voiced = second * 3 / 2..second * 5 / 2;
+        let tone = tone_amplitude(&output[voiced.clone()], voiced.start);
+        assert!((20.0 * (tone / 0.3).log10()).abs() < 0.5, "{tone}");
+        // what remains around the tone is far below the hiss it started with
+        let residual: Vec<f32> = output[voiced.clone()]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                s - 0.3 * (2.0 * PI * 1000.0 * (voiced.start + i) as f32 / SAMPLE_RATE as f32).sin()
+            })
+            .collect();
+        assert!(rms(&residual) < hiss_before * 0.3, "{}", rms(&residual));
+    }
+
+    #[test]
+    fn test_reduce_noise_transparent_without_noise() {
+        // a silent profile leaves the audio as it was
+        let mut signal = hiss(0.5, SAMPLE_RATE as usize / 2);
+        let original = signal.clone();
+        let options = NoiseReduction {
+            profile: Some(capture_noise_profile(&[0.0; 4096], SAMPLE_RATE)),
+            ..Default::default()
+        };
+        reduce_noise(&mut signal, SAMPLE_RATE, &options);
+        let error = signal
+            .iter()
+            .zip(&original)
+            .fold(0.0f32, |max, (a, b)| max.max((a - b).abs()));
+        assert!(error < 1e-4, "{error}");
+    }
+
+    #[test]
+    fn test_estimated_profile() {
+        // quiet hiss with loud bursts: the estimate follows the hiss, not the bursts
+        let mut signal = hiss(0.01, SAMPLE_RATE as usize * 2);
+        for sample in signal[20_000..30_000].iter_mut() {
+            *sample *= 50.0;
+        }
+        let estimated = estimate_noise_profile(&signal, SAMPLE_RATE);
+        let captured = capture_noise_profile(&signal[40_000..], SAMPLE_RATE);
+        let mean = |p: &NoiseProfile| p.magnitudes.iter().sum::<f32>() / p.magnitudes.len() as f32;
+        assert!((mean(&estimated) / mean(&captured) - 1.0).abs() < 0.3);
+
+        // resampled onto a different rate, a flat profile stays flat
+        let flat = NoiseProfile {
+            sample_rate: 16_000,
+            fft_size: 512,
+            magnitudes: vec![2.0; 257],
+        };
+        let per_bin = noise_per_bin(&flat, 32_000);
+        assert_eq!(per_bin.len(), FFT_SIZE / 2 + 1);
+        assert!((per_bin[100] - 4.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::beat_sync::silence::TimeRange;
+use crate::beat_sync::spectrum::stft_magnitudes;
+use crate::models::timeline::GainPoint;
+use serde::{Deserialize, Serialize};
+
+/// Band holding most of the energy of speech
+const SPEECH_LOW_HZ: f32 = 300.0;
+const SPEECH_HIGH_HZ: f32 = 3400.0;
+
+/// Share of a frame's energy that must lie in the speech band for it to count as voice
+const SPEECH_BAND_RATIO: f32 = 0.6;
+
+/// How far above the noise floor (the quietest tenth of frames) voice must be
+const NOISE_MARGIN_DB: f32 = 9.0;
+
+/// Analysis window and hop
+const WINDOW_SECONDS: f32 = 0.03;
+const HOP_SECONDS: f32 = 0.01;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingOptions {
+    /// BGM gain under speech (negative dB)
+    #[serde(default = "default_depth_db")]
+    pub depth_db: f32,
+    /// Fade down before speech starts
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: u64,
+    /// Fade back up after speech ends
+    #[serde(default = "default_release_ms")]
+    pub release_ms: u64,
+    /// Quietest level (dBFS) counted as speech
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f32,
+    /// Shorter bursts of voice are ignored
+    #[serde(default = "default_min_speech_ms")]
+    pub min_speech_ms: u64,
+    /// Pauses shorter than this keep the BGM down
+    #[serde(default = "default_hold_ms")]
+    pub hold_ms: u64,
+}
+
+fn default_depth_db() -> f32 {
+    -12.0
+}
+
+fn default_attack_ms() -> u64 {
+    100
+}
+
+fn default_release_ms() -> u64 {
+    400
+}
+
+fn default_threshold_db() -> f32 {
+    -45.0
+}
+
+fn default_min_speech_ms() -> u64 {
+    150
+}
+
+fn default_hold_ms() -> u64 {
+    300
+}
+
+impl Default for DuckingOptions {
+    fn default() -> Self {
+        Self {
+            depth_db: default_depth_db(),
+            attack_ms: default_attack_ms(),
+            release_ms: default_release_ms(),
+            threshold_db: default_threshold_db(),
+            min_speech_ms: default_min_speech_ms(),
+            hold_ms: default_hold_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingAnalysis {
+    /// Where voice was detected
+    pub speech: Vec<TimeRange>,
+    /// BGM gain over time
+    pub envelope: Vec<GainPoint>,
+    pub duration_ms: u64,
+}
+
+/// Voice activity in mono samples: frames loud enough, clearly above the noise floor and
+/// with most of their energy in the speech band. Pauses shorter than `hold_ms` are bridged
+/// and bursts shorter than `min_speech_ms` dropped.
+pub fn detect_speech(
+    samples: &[f32],
+    sample_rate: u32,
+    options: &DuckingOptions,
+) -> Vec<TimeRange> {
+    if sample_rate == 0 {
+        return Vec::new();
+    }
+    let window = ((sample_rate as f32 * WINDOW_SECONDS) as usize).next_power_of_two();
+    let hop = ((sample_rate as f32 * HOP_SECONDS) as usize).max(1);
+    let bin_hz = sample_rate as f32 / window as f32;
+    let speech_bins =
+        (SPEECH_LOW_HZ / bin_hz).ceil() as usize..(SPEECH_HIGH_HZ / bin_hz) as usize + 1;
+    let duration_ms = samples.len() as u64 * 1000 / sample_rate as u64;
+
+    // (level in dBFS, share of energy in the speech band) per frame
+    let frames: Vec<(f32, f32)> = stft_magnitudes(samples, window, hop)
+        .iter()
+        .map(|spectrum| {
+            let energy: f32 = spectrum.iter().map(|m| m * m).sum();
+            let speech: f32 = spectrum
+                .get(speech_bins.start..speech_bins.end.min(spectrum.len()))
+                .map_or(0.0, |band| band.iter().map(|m| m * m).sum());
+            // Parseval over the one-sided spectrum of a Hann-windowed frame (mean w² = 3/8)
+            let mean_square = 2.0 * energy / (window * window) as f32 / 0.375;
+            let level = 10.0 * mean_square.max(1e-12).log10();
+            (level, if energy > 0.0 { speech / energy } else { 0.0 })
+        })
+        .collect();
+
+    let mut levels: Vec<f32> = frames.iter().map(|(level, _)| *level).collect();
+    levels.sort_by(f32::total_cmp);
+    let noise_floor = levels.get(levels.len() / 10).copied().unwrap_or(-120.0);
+    let threshold = options.threshold_db.max(noise_floor + NOISE_MARGIN_DB);
+
+    let to_ms =
+        |frame: usize| (frame as u64 * hop as u64 * 1000 / sample_rate as u64).min(duration_ms);
+    let mut speech: Vec<TimeRange> = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for (i, &(level, ratio)) in frames.iter().chain([&(f32::MIN, 0.0)]).enumerate() {
+        let voiced = level >= threshold && ratio >= SPEECH_BAND_RATIO;
+        match (voiced, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                let range = TimeRange {
+                    start_ms: to_ms(start),
+                    end_ms: to_ms(i),
+                };
+                match speech.last_mut() {
+                    Some(last) if range.start_ms <= last.end_ms + options.hold_ms => {
+                        last.end_ms = range.end_ms
+                    }
+                    _ => speech.push(range),
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    speech.retain(|r| r.end_ms - r.start_ms >= options.min_speech_ms);
+    speech
+}
+
+/// BGM gain envelope for speech ranges: down to `depth_db` over `attack_ms` before each
+/// range, back to 0 dB over `release_ms` after it. Ranges too close to fade up in between
+/// stay ducked throughout.
+pub fn duck_envelope(speech: &[TimeRange], options: &DuckingOptions) -> Vec<GainPoint> {
+    let depth = options.depth_db.min(0.0);
+    let mut merged: Vec<TimeRange> = Vec::new();
+    for range in speech {
+        match merged.last_mut() {
+            Some(last)
+                if range.start_ms.saturating_sub(options.attack_ms)
+                    <= last.end_ms + options.release_ms =>
+            {
+                last.end_ms = last.end_ms.max(range.end_ms)
+            }
+            _ => merged.push(*range),
+        }
+    }
+
+    let mut points = Vec::new();
+    for range in merged {
+        let fade_start = range.start_ms.saturating_sub(options.attack_ms);
+        if fade_start < range.start_ms {
+            points.push(GainPoint {
+                time_ms: fade_start,
+                gain_db: 0.0,
+                ..Default::default()
+            });
+        }
+        points.push(GainPoint {
+            time_ms: range.start_ms,
+            gain_db: depth,
+            ..Default::default()
+        });
+        points.push(GainPoint {
+            time_ms: range.end_ms,
+            gain_db: depth,
+            ..Default::default()
+        });
+        points.push(GainPoint {
+            time_ms: range.end_ms + options.release_ms,
+            gain_db: 0.0,
+            ..Default::default()
+        });
+    }
+    points
+}
+
+/// Speech and the resulting BGM envelope for the primary audio
+pub fn analyze_ducking(
+    samples: &[f32],
+    sample_rate: u32,
+    options: &DuckingOptions,
+) -> DuckingAnalysis {
+    let speech = detect_speech(samples, sample_rate, options);
+    DuckingAnalysis {
+        envelope: duck_envelope(&speech, options),
+        speech,
+        duration_ms: if sample_rate > 0 {
+            samples.len() as u64 * 1000 / sample_rate as u64
+        } else {
+            0
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 16_000;
+
+    /// Voice-like harmonics of 180 Hz between 300 Hz and 3 kHz, with a faint 60 Hz hum
+    /// throughout and a loud bass note (not speech) at 5.5–6.5 s
+    fn dialogue() -> Vec<f32> {
+        let voice = [(1000, 2500), (2700, 3100), (4000, 4100)];
+        (0..SAMPLE_RATE as usize * 8)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let ms = (t * 1000.0) as u64;
+                let mut sample = 0.003 * (2.0 * PI * 60.0 * t).sin();
+                if voice.iter().any(|&(start, end)| ms >= start && ms < end) {
+                    sample += (2..=16)
+                        .map(|h| 0.05 * (2.0 * PI * 180.0 * h as f32 * t).sin())
+                        .sum::<f32>();
+                }
+                if (5500..6500).contains(&ms) {
+                    sample += 0.5 * (2.0 * PI * 55.0 * t).sin();
+                }
+                sample
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_speech() {
+        let options = DuckingOptions::default();
+        let speech = detect_speech(&dialogue(), SAMPLE_RATE, &options);
+        // the 200 ms pause is held, the 100 ms burst dropped, the bass note ignored
+        assert_eq!(speech.len(), 1, "{speech:?}");
+        assert!((speech[0].start_ms as i64 - 1000).abs() <= 30, "{speech:?}");
+        assert!((speech[0].end_ms as i64 - 3100).abs() <= 30, "{speech:?}");
+    }
+
+    #[test]
+    fn test_duck_envelope() {
+        let options = DuckingOptions {
+            depth_db: -10.0,
+            attack_ms: 100,
+            release_ms: 500,
+            ..Default::default()
+        };
+        let speech = [
+            TimeRange {
+                start_ms: 50,
+                end_ms: 1000,
+            },
+            TimeRange {
+                start_ms: 1400,
+                end_ms: 2000,
+            },
+            TimeRange {
+                start_ms: 5000,
+                end_ms: 6000,
+            },
+        ];
+        let points: Vec<(u64, f32)> = duck_envelope(&speech, &options)
+            .iter()
+            .map(|p| (p.time_ms, p.gain_db))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (0, 0.0),
+                (50, -10.0),
+                (2000, -10.0),
+                (2500, 0.0),
+                (4900, 0.0),
+                (5000, -10.0),
+                (6000, -10.0),
+                (6500, 0.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::models::effects::{Compressor, Limiter, NoiseGate};
+
+/// One-pole smoothing coefficient reaching ~63% of a step in `ms`
+fn coefficient(ms: f32, sample_rate: u32) -> f32 {
+    if ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (ms * 0.001 * sample_rate as f32)).exp()
+    }
+}
+
+fn frame_count(channels: &[Vec<f32>]) -> usize {
+    channels.iter().map(Vec::len).max().unwrap_or(0)
+}
+
+/// Largest absolute sample across channels at `frame`
+fn peak(channels: &[Vec<f32>], frame: usize) -> f32 {
+    channels
+        .iter()
+        .filter_map(|c| c.get(frame))
+        .fold(0.0, |peak, s| peak.max(s.abs()))
+}
+
+/// Apply one gain to every channel, so the stereo image doesn't shift
+fn apply_gain(channels: &mut [Vec<f32>], frame: usize, gain: f32) {
+    for sample in channels.iter_mut().filter_map(|c| c.get_mut(frame)) {
+        *sample *= gain;
+    }
+}
+
+fn to_db(level: f32) -> f32 {
+    20.0 * level.max(1e-9).log10()
+}
+
+fn from_db(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Gain reduction (dB, positive) the compressor's static curve asks for at a level
+fn gain_reduction(level_db: f32, options: &Compressor) -> f32 {
+    let over = level_db - options.threshold_db;
+    let slope = 1.0 - 1.0 / options.ratio.max(1.0);
+    let knee = options.knee_db.max(0.0);
+    if 2.0 * over <= -knee {
+        0.0
+    } else if 2.0 * over < knee {
+        slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+    } else {
+        slope * over
+    }
+}
+
+/// Compress in place: reduce the gain above the threshold by the ratio, smoothed by the
+/// attack and release times, then add the makeup gain.
+///
+/// The level is a peak follower that jumps to each peak and decays over the release time,
+/// so the gain doesn't pump with every cycle of a low note.
+pub fn compress(channels: &mut [Vec<f32>], sample_rate: u32, options: &Compressor) {
+    let attack = coefficient(options.attack_ms, sample_rate);
+    let release = coefficient(options.release_ms, sample_rate);
+    let mut level = 0.0f32;
+    let mut reduction = 0.0f32;
+    for frame in 0..frame_count(channels) {
+        level = peak(channels, frame).max(level * release);
+        let target = gain_reduction(to_db(level), options);
+        let coef = if target > reduction { attack } else { release };
+        reduction = target + coef * (reduction - target);
+        apply_gain(channels, frame, from_db(options.makeup_db - reduction));
+    }
+}
+
+/// Limit in place so no sample exceeds the ceiling. The audio is processed offline, so the
+/// gain can look ahead: it ramps down over the lookahead into each peak instead of
+/// clipping it, then recovers over the release time.
+pub fn limit(channels: &mut [Vec<f32>], sample_rate: u32, options: &Limiter) {
+    let ceiling = from_db(options.ceiling_db.min(0.0));
+    let frames = frame_count(channels);
+    let mut gains: Vec<f32> = (0..frames)
+        .map(|frame| {
+            let peak = peak(channels, frame);
+            if peak > ceiling {
+                ceiling / peak
+            } else {
+                1.0
+            }
+        })
+        .collect();
+
+    // A full drop from unity takes the lookahead; every gain stays at or below the one its
+    // own sample needs, so the ceiling holds exactly
+    let lookahead = (options.lookahead_ms * 0.001 * sample_rate as f32).max(1.0);
+    for frame in (0..frames.saturating_sub(1)).rev() {
+        gains[frame] = gains[frame].min(gains[frame + 1] + 1.0 / lookahead);
+    }
+
+    let release = coefficient(options.release_ms, sample_rate);
+    let mut gain = 1.0f32;
+    for (frame, &target) in gains.iter().enumerate() {
+        gain = if target < gain {
+            target
+        } else {
+            target + release * (gain - target)
+        };
+        apply_gain(channels, frame, gain);
+    }
+}
+
+/// Gate in place: below the threshold for longer than the hold time, fade down to the
+/// range gain over the release time; fade back up over the attack time once it's exceeded.
+/// The gain moves in dB, so the fades sound even however deep the range is.
+pub fn gate(channels: &mut [Vec<f32>], sample_rate: u32, options: &NoiseGate) {
+    let threshold = from_db(options.threshold_db);
+    let closed = options.range_db.min(0.0);
+    let hold = (options.hold_ms.max(0.0) * 0.001 * sample_rate as f32) as usize;
+    let attack = coefficient(options.attack_ms, sample_rate);
+    let release = coefficient(options.release_ms, sample_rate);
+
+    // Closed until the first time the level exceeds the threshold
+    let mut since_open = usize::MAX;
+    let mut gain_db = closed;
+    for frame in 0..frame_count(channels) {
+        if peak(channels, frame) >= threshold {
+            since_open = 0;
+        } else {
+            since_open = since_open.saturating_add(1);
+        }
+        let (target, coef) = if since_open <= hold {
+            (0.0, attack)
+        } else {
+            (closed, release)
+        };
+        gain_db = target + coef * (gain_db - target);
+        apply_gain(channels, frame, from_db(gain_db));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    /// 440 Hz tone of a given peak amplitude
+    fn tone(amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..(SAMPLE_RATE as f32 * seconds) as usize)
+            .map(|i| amplitude * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn peak_of(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_compressor() {
+        let options = Compressor {
+            threshold_db: -18.0,
+            ratio: 4.0,
+            knee_db: 0.0,
+            makeup_db: 3.0,
+            ..Default::default()
+        };
+        // -6 dBFS is 12 dB over the threshold: 9 dB of reduction, 3 dB of makeup
+        let mut loud = vec![tone(0.5, 1.0)];
+        compress(&mut loud, SAMPLE_RATE, &options);
+        let level = to_db(peak_of(&loud[0][SAMPLE_RATE as usize / 2..]));
+        assert!((level + 12.0).abs() < 0.5, "{level}");
+
+        // below the threshold only the makeup gain applies
+        let mut quiet = vec![tone(0.05, 1.0)];
+        compress(&mut quiet, SAMPLE_RATE, &options);
+        let level = to_db(peak_of(&quiet[0][SAMPLE_RATE as usize / 2..]));
+        assert!((level - (to_db(0.05) + 3.0)).abs() < 0.1, "{level}");
+
+        assert_eq!(gain_reduction(-30.0, &Compressor::default()), 0.0);
+        // quadratic inside the soft knee
+        let knee = gain_reduction(-18.0, &Compressor::default());
+        assert!((knee - 0.75 * 3.0 * 3.0 / 12.0).abs() < 1e-4, "{knee}");
+    }
+
+    #[test]
+    fn test_limiter() {
+        // a quiet tone with a loud burst in the middle, on two channels
+        let mut signal = tone(0.2, 1.0);
+        for (i, sample) in signal.iter_mut().enumerate().skip(20_000).take(4_000) {
+            *sample = 1.5 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin();
+        }
+        let mut channels = vec![signal.clone(), signal.iter().map(|s| s * 0.5).collect()];
+        let options = Limiter::default();
+        limit(&mut channels, SAMPLE_RATE, &options);
+
+        let ceiling = from_db(options.ceiling_db);
+        assert!(peak_of(&channels[0]) <= ceiling + 1e-6);
+        // the gain is linked: the quieter channel keeps its balance
+        assert!((channels[1][22_000] * 2.0 - channels[0][22_000]).abs() < 1e-6);
+        // away from the burst the tone is untouched
+        assert_eq!(&channels[0][..10_000], &signal[..10_000]);
+        assert!((rms(&channels[0][40_000..]) - rms(&signal[40_000..])).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_noise_gate() {
+        // hiss at -60 dBFS, a tone from 0.5 s to 1.5 s, hiss again
+        let mut seed = 1u32;
+        let mut signal: Vec<f32> = (0..SAMPLE_RATE as usize * 2)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                0.001 * ((seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0)
+            })
+            .collect();
+        let burst = tone(0.3, 1.0);
+        for (sample, tone) in signal[SAMPLE_RATE as usize / 2..].iter_mut().zip(&burst) {
+            *sample += tone;
+        }
+        let mut channels = vec![signal.clone()];
+        let options = NoiseGate::default();
+        gate(&mut channels, SAMPLE_RATE, &options);
+
+        let second = |s: f32| (s * SAMPLE_RATE as f32) as usize;
+        let lead = to_db(rms(&channels[0][..second(0.4)]) / rms(&signal[..second(0.4)]));
+        assert!(lead < -55.0, "{lead}");
+        let open = &channels[0][second(0.6)..second(1.4)];
+        let open_gain = to_db(rms(open) / rms(&signal[second(0.6)..second(1.4)]));
+        assert!(open_gain.abs() < 0.1, "{open_gain}");
+        // after hold and release the tail is down again
+        let tail = to_db(rms(&channels[0][second(1.9)..]) / rms(&signal[second(1.9)..]));
+        assert!(tail < -40.0, "{tail}");
+    }
+}
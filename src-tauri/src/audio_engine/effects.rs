@@ -0,0 +1,238 @@
+use crate::audio_engine::denoise::reduce_noise;
+use crate::audio_engine::dynamics::{compress, gate, limit};
+use crate::models::effects::{AudioEffect, EqBand, EqBandKind, HighPass};
+use std::f64::consts::PI;
+
+/// Second-order IIR section from the RBJ audio EQ cookbook
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Biquad {
+    fn normalized(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+        }
+    }
+
+    /// (cos w0, alpha) of a section at `freq_hz`
+    fn angle(sample_rate: u32, freq_hz: f32, q: f32) -> (f64, f64) {
+        let w0 = 2.0 * PI * freq_hz as f64 / sample_rate as f64;
+        (w0.cos(), w0.sin() / (2.0 * q.max(0.01) as f64))
+    }
+
+    fn high_pass(sample_rate: u32, freq_hz: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq_hz, q);
+        Self::normalized(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn eq_band(sample_rate: u32, band: &EqBand) -> Self {
+        let a = 10f64.powf(band.gain_db as f64 / 40.0);
+        match band.kind {
+            EqBandKind::Peak => {
+                let (cos, alpha) = Self::angle(sample_rate, band.freq_hz, band.q);
+                Self::normalized(
+                    [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+                    [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+                )
+            }
+            // Shelves use the maximally steep slope without overshoot (Q = 1/√2)
+            EqBandKind::LowShelf | EqBandKind::HighShelf => {
+                let (cos, alpha) = Self::angle(sample_rate, band.freq_hz, 0.5f32.sqrt());
+                let root = 2.0 * a.sqrt() * alpha;
+                let sign = if band.kind == EqBandKind::LowShelf {
+                    1.0
+                } else {
+                    -1.0
+                };
+                Self::normalized(
+                    [
+                        a * ((a + 1.0) - sign * (a - 1.0) * cos + root),
+                        sign * 2.0 * a * ((a - 1.0) - sign * (a + 1.0) * cos),
+                        a * ((a + 1.0) - sign * (a - 1.0) * cos - root),
+                    ],
+                    [
+                        (a + 1.0) + sign * (a - 1.0) * cos + root,
+                        -sign * 2.0 * ((a - 1.0) + sign * (a + 1.0) * cos),
+                        (a + 1.0) + sign * (a - 1.0) * cos - root,
+                    ],
+                )
+            }
+        }
+    }
+
+    /// Filter in place (transposed direct form II)
+    fn process(&self, samples: &mut [f32]) {
+        let (mut z1, mut z2) = (0.0, 0.0);
+        for sample in samples {
+            let x = *sample as f64;
+            let y = self.b0 * x + z1;
+            z1 = self.b1 * x - self.a1 * y + z2;
+            z2 = self.b2 * x - self.a2 * y;
+            *sample = y as f32;
+        }
+    }
+}
+
+/// Cascaded sections of a Butterworth high-pass; each section adds 12 dB per octave
+fn high_pass_sections(sample_rate: u32, filter: &HighPass) -> Vec<Biquad> {
+    let sections = (filter.slope_db / 12).clamp(1, 4);
+    (1..=sections)
+        .map(|k| {
+            let angle = (2 * k - 1) as f32 * std::f32::consts::PI / (4 * sections) as f32;
+            Biquad::high_pass(sample_rate, filter.cutoff_hz, 0.5 / angle.cos())
+        })
+        .collect()
+}
+
+/// Whether a filter frequency can be realized at the sample rate
+fn in_band(sample_rate: u32, freq_hz: f32) -> bool {
+    freq_hz > 0.0 && freq_hz < sample_rate as f32 * 0.49
+}
+
+/// Run an effect chain over a clip's channels in place, stage by stage
+pub fn apply_effects(channels: &mut [Vec<f32>], sample_rate: u32, effects: &[AudioEffect]) {
+    if sample_rate == 0 {
+        return;
+    }
+    for effect in effects {
+        let sections = match effect {
+            AudioEffect::HighPass(filter) if in_band(sample_rate, filter.cutoff_hz) => {
+                high_pass_sections(sample_rate, filter)
+            }
+            AudioEffect::Equalizer(eq) => eq
+                .bands
+                .iter()
+                .filter(|band| in_band(sample_rate, band.freq_hz) && band.gain_db != 0.0)
+                .map(|band| Biquad::eq_band(sample_rate, band))
+                .collect(),
+            AudioEffect::Compressor(options) => {
+                compress(channels, sample_rate, options);
+                Vec::new()
+            }
+            AudioEffect::Limiter(options) => {
+                limit(channels, sample_rate, options);
+                Vec::new()
+            }
+            AudioEffect::NoiseGate(options) => {
+                gate(channels, sample_rate, options);
+                Vec::new()
+            }
+            AudioEffect::NoiseReduction(options) => {
+                for channel in channels.iter_mut() {
+                    reduce_noise(channel, sample_rate, options);
+                }
+                Vec::new()
+            }
+            AudioEffect::HighPass(_) => Vec::new(),
+        };
+        for section in &sections {
+            for channel in channels.iter_mut() {
+                section.process(channel);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::effects::Equalizer;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn tone(freq_hz: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * freq_hz * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    /// Level change (dB) of a 1 s tone through an effect chain, ignoring the settling time
+    fn gain_db(freq_hz: f32, effects: &[AudioEffect]) -> f32 {
+        let input = tone(freq_hz);
+        let mut channels = vec![input.clone()];
+        apply_effects(&mut channels, SAMPLE_RATE, effects);
+        let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+        let steady = SAMPLE_RATE as usize / 2..;
+        20.0 * (rms(&channels[0][steady.clone()]) / rms(&input[steady])).log10()
+    }
+
+    #[test]
+    fn test_high_pass() {
+        let filter = |slope_db| {
+            [AudioEffect::HighPass(HighPass {
+                cutoff_hz: 200.0,
+                slope_db,
+            })]
+        };
+        // -3 dB at the cutoff, 12 dB more per octave and section below it
+        assert!((gain_db(200.0, &filter(24)) + 3.0).abs() < 0.2);
+        assert!((gain_db(50.0, &filter(12)) + 24.0).abs() < 1.0);
+        assert!((gain_db(50.0, &filter(24)) + 48.0).abs() < 1.0);
+        assert!(gain_db(2000.0, &filter(24)).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_equalizer() {
+        let eq = [AudioEffect::Equalizer(Equalizer {
+            bands: vec![
+                EqBand {
+                    kind: EqBandKind::Peak,
+                    freq_hz: 1000.0,
+                    gain_db: 6.0,
+                    q: 2.0,
+                },
+                EqBand {
+                    kind: EqBandKind::LowShelf,
+                    freq_hz: 150.0,
+                    gain_db: -9.0,
+                    q: 1.0,
+                },
+                EqBand {
+                    kind: EqBandKind::HighShelf,
+                    freq_hz: 8000.0,
+                    gain_db: 4.0,
+                    q: 1.0,
+                },
+            ],
+        })];
+        assert!((gain_db(1000.0, &eq) - 6.0).abs() < 0.2);
+        assert!((gain_db(30.0, &eq) + 9.0).abs() < 0.3);
+        assert!((gain_db(18_000.0, &eq) - 4.0).abs() < 0.3);
+        // between the bands the level is nearly untouched
+        assert!(gain_db(400.0, &eq).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_effect_serialization() {
+        let json = r#"[
+            {"type": "high_pass", "cutoff_hz": 100, "slope_db": 12},
+            {"type": "equalizer", "bands": [{"kind": "high_shelf", "freq_hz": 6000, "gain_db": 3, "q": 0.7}]},
+            {"type": "noise_reduction", "reduction_db": 12, "sensitivity": 2}
+        ]"#;
+        let effects: Vec<AudioEffect> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            effects[0],
+            AudioEffect::HighPass(HighPass {
+                cutoff_hz: 100.0,
+                slope_db: 12
+            })
+        );
+        assert!(matches!(&effects[2], AudioEffect::NoiseReduction(n) if n.profile.is_none()));
+        let round_trip: Vec<AudioEffect> =
+            serde_json::from_value(serde_json::to_value(&effects).unwrap()).unwrap();
+        assert_eq!(round_trip, effects);
+    }
+}
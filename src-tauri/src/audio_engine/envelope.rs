@@ -0,0 +1,237 @@
+use crate::models::timeline::{GainCurve, GainPoint};
+
+/// Share (0.0–1.0) of a segment's change in dB reached at `progress` through it
+fn shaped(curve: GainCurve, progress: f32) -> f32 {
+    match curve {
+        GainCurve::Linear => progress,
+        GainCurve::Hold => 0.0,
+        GainCurve::EaseIn => progress * progress,
+        GainCurve::EaseOut => 1.0 - (1.0 - progress) * (1.0 - progress),
+        GainCurve::SCurve => progress * progress * (3.0 - 2.0 * progress),
+    }
+}
+
+/// [`shaped`] as an ffmpeg expression of the segment progress `x`
+fn shaped_expression(curve: GainCurve, x: &str) -> String {
+    match curve {
+        GainCurve::Linear => x.to_string(),
+        GainCurve::Hold => "0".to_string(),
+        GainCurve::EaseIn => format!("pow({x},2)"),
+        GainCurve::EaseOut => format!("(1-pow(1-{x},2))"),
+        GainCurve::SCurve => format!("pow({x},2)*(3-2*{x})"),
+    }
+}
+
+/// Gain in dB at a time. Before the first point and after the last the gain holds.
+pub fn gain_db_at(points: &[GainPoint], time_ms: u64) -> f32 {
+    let Some(first) = points.first() else {
+        return 0.0;
+    };
+    let next = points.partition_point(|p| p.time_ms <= time_ms);
+    if next == 0 {
+        return first.gain_db;
+    }
+    let previous = points[next - 1];
+    match points.get(next) {
+        Some(point) => {
+            let progress =
+                (time_ms - previous.time_ms) as f32 / (point.time_ms - previous.time_ms) as f32;
+            previous.gain_db + (point.gain_db - previous.gain_db) * shaped(previous.curve, progress)
+        }
+        None => previous.gain_db,
+    }
+}
+
+/// The curve of the segment playing at a time
+fn curve_at(points: &[GainPoint], time_ms: u64) -> GainCurve {
+    let next = points.partition_point(|p| p.time_ms <= time_ms);
+    match next {
+        0 => GainCurve::Hold,
+        _ => points[next - 1].curve,
+    }
+}
+
+/// The part of an envelope between `from_ms` and `to_ms`, with times relative to `from_ms`
+/// and points added at both ends. A curved segment cut in two keeps its shape only when it
+/// is linear or held.
+pub fn slice_envelope(points: &[GainPoint], from_ms: u64, to_ms: u64) -> Vec<GainPoint> {
+    if points.is_empty() || to_ms <= from_ms {
+        return Vec::new();
+    }
+    let mut sliced = vec![GainPoint {
+        time_ms: 0,
+        gain_db: gain_db_at(points, from_ms),
+        curve: curve_at(points, from_ms),
+    }];
+    sliced.extend(
+        points
+            .iter()
+            .filter(|p| p.time_ms > from_ms && p.time_ms < to_ms)
+            .map(|p| GainPoint {
+                time_ms: p.time_ms - from_ms,
+                ..*p
+            }),
+    );
+    sliced.push(GainPoint {
+        time_ms: to_ms - from_ms,
+        gain_db: gain_db_at(points, to_ms),
+        curve: curve_at(points, to_ms),
+    });
+    sliced
+}
+
+/// A clip's envelope (relative to its start) in timeline time. Outside the clip the gain is
+/// 0 dB, so the envelopes of clips sharing one audio stream can be concatenated.
+pub fn place_envelope(points: &[GainPoint], start_ms: u64, end_ms: u64) -> Vec<GainPoint> {
+    let inside = slice_envelope(points, 0, end_ms.saturating_sub(start_ms));
+    if inside.is_empty() {
+        return inside;
+    }
+    let unity = |time_ms: u64| GainPoint {
+        time_ms,
+        gain_db: 0.0,
+        curve: GainCurve::Hold,
+    };
+    let mut placed = vec![unity(start_ms)];
+    placed.extend(inside.into_iter().map(|p| GainPoint {
+        time_ms: p.time_ms + start_ms,
+        ..p
+    }));
+    placed.push(unity(end_ms));
+    placed
+}
+
+/// ffmpeg expression of the linear gain factor at `t` (seconds), for `aeval` or `volume`.
+///
+/// A flat sum of one term per segment rather than nested `if`s, so long envelopes don't hit
+/// the expression parser's nesting limit.
+pub fn gain_expression(points: &[GainPoint]) -> String {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return "1".to_string();
+    };
+    let seconds = |ms: u64| ms as f64 / 1000.0;
+    let factor = |db: f32| 10f64.powf(db as f64 / 20.0);
+
+    let mut terms = vec![format!(
+        "lt(t,{:.3})*{:.6}",
+        seconds(first.time_ms),
+        factor(first.gain_db)
+    )];
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if b.time_ms <= a.time_ms {
+            continue;
+        }
+        let (start, end) = (seconds(a.time_ms), seconds(b.time_ms));
+        let gain = if a.gain_db == b.gain_db || a.curve == GainCurve::Hold {
+            format!("{:.6}", factor(a.gain_db))
+        } else {
+            let progress = format!("(t-{start:.3})/{:.3}", end - start);
+            format!(
+                "pow(10,({:.2}{:+.2}*{})/20)",
+                a.gain_db,
+                b.gain_db - a.gain_db,
+                shaped_expression(a.curve, &progress)
+            )
+        };
+        terms.push(format!("gte(t,{start:.3})*lt(t,{end:.3})*{gain}"));
+    }
+    terms.push(format!(
+        "gte(t,{:.3})*{:.6}",
+        seconds(last.time_ms),
+        factor(last.gain_db)
+    ));
+    terms.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(time_ms: u64, gain_db: f32) -> GainPoint {
+        GainPoint {
+            time_ms,
+            gain_db,
+            ..Default::default()
+        }
+    }
+
+    fn curved(time_ms: u64, gain_db: f32, curve: GainCurve) -> GainPoint {
+        GainPoint {
+            time_ms,
+            gain_db,
+            curve,
+        }
+    }
+
+    #[test]
+    fn test_gain_envelope() {
+        let points = [
+            point(1000, 0.0),
+            point(1100, -12.0),
+            point(2000, -12.0),
+            point(2500, 0.0),
+        ];
+        assert_eq!(gain_db_at(&points, 0), 0.0);
+        assert_eq!(gain_db_at(&points, 1050), -6.0);
+        assert_eq!(gain_db_at(&points, 1500), -12.0);
+        assert_eq!(gain_db_at(&points, 2250), -6.0);
+        assert_eq!(gain_db_at(&points, 9000), 0.0);
+        assert_eq!(gain_db_at(&[], 500), 0.0);
+
+        assert_eq!(gain_expression(&[]), "1");
+        assert_eq!(
+            gain_expression(&points[..2]),
+            "lt(t,1.000)*1.000000\
+             +gte(t,1.000)*lt(t,1.100)*pow(10,(0.00-12.00*(t-1.000)/0.100)/20)\
+             +gte(t,1.100)*0.251189"
+        );
+    }
+
+    #[test]
+    fn test_gain_curves() {
+        let at_quarter = |curve| gain_db_at(&[curved(0, 0.0, curve), point(1000, -16.0)], 250);
+        assert_eq!(at_quarter(GainCurve::Linear), -4.0);
+        assert_eq!(at_quarter(GainCurve::Hold), 0.0);
+        assert_eq!(at_quarter(GainCurve::EaseIn), -1.0);
+        assert_eq!(at_quarter(GainCurve::EaseOut), -7.0);
+        assert_eq!(at_quarter(GainCurve::SCurve), -2.5);
+
+        assert_eq!(
+            gain_expression(&[curved(0, 0.0, GainCurve::EaseIn), point(2000, -6.0)]),
+            "lt(t,0.000)*1.000000\
+             +gte(t,0.000)*lt(t,2.000)*pow(10,(0.00-6.00*pow((t-0.000)/2.000,2))/20)\
+             +gte(t,2.000)*0.501187"
+        );
+        assert_eq!(
+            gain_expression(&[curved(0, -6.0, GainCurve::Hold), point(1000, 0.0)]),
+            "lt(t,0.000)*0.501187+gte(t,0.000)*lt(t,1.000)*0.501187+gte(t,1.000)*1.000000"
+        );
+    }
+
+    #[test]
+    fn test_slice_and_place_envelope() {
+        // fade out over the last second of a 3 s clip
+        let fade = [point(2000, 0.0), point(3000, -60.0)];
+        let sliced = slice_envelope(&fade, 1500, 2500);
+        assert_eq!(
+            sliced,
+            [
+                curved(0, 0.0, GainCurve::Hold),
+                point(500, 0.0),
+                point(1000, -30.0)
+            ]
+        );
+        assert!(slice_envelope(&[], 0, 1000).is_empty());
+
+        let placed = place_envelope(&fade, 10_000, 13_000);
+        assert_eq!(gain_db_at(&placed, 5000), 0.0);
+        assert_eq!(gain_db_at(&placed, 11_000), 0.0);
+        assert_eq!(gain_db_at(&placed, 12_500), -30.0);
+        assert!((gain_db_at(&placed, 12_999) + 59.94).abs() < 1e-3);
+        // back to unity once the clip has ended
+        assert_eq!(gain_db_at(&placed, 13_000), 0.0);
+        assert_eq!(gain_db_at(&placed, 20_000), 0.0);
+        assert!(place_envelope(&[], 0, 1000).is_empty());
+    }
+}
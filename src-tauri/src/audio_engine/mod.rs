@@ -0,0 +1,9 @@
+pub mod cache;
+pub mod denoise;
+pub mod ducking;
+pub mod dynamics;
+pub mod effects;
+pub mod envelope;
+pub mod peaks;
+pub mod spectrogram;
+pub mod wav;
@@ -0,0 +1,462 @@
+use serde::{Deserialize, Serialize};
+
+/// Samples summarized by one peak of the finest level
+pub const BASE_SAMPLES_PER_PEAK: u32 = 256;
+
+/// Levels stop halving once a level has at most this many peaks
+const MIN_LEVEL_PEAKS: usize = 256;
+
+/// Identifies the binary format; bump the version when the layout changes
+const MAGIC: &[u8; 4] = b"SFPK";
+const FORMAT_VERSION: u32 = 1;
+
+/// Sanity limits for counts read from a cache file; real files stay far below them
+const MAX_CHANNELS: usize = 64;
+const MAX_LEVELS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Peak {
+    pub min: f32,
+    pub max: f32,
+    pub rms: f32,
+}
+
+impl Peak {
+    const SILENT: Peak = Peak {
+        min: 0.0,
+        max: 0.0,
+        rms: 0.0,
+    };
+
+    fn of(samples: &[f32]) -> Self {
+        if samples.is_empty() {
+            return Self::SILENT;
+        }
+        let (min, max, sum_sq) = samples.iter().fold(
+            (f32::INFINITY, f32::NEG_INFINITY, 0.0f64),
+            |(min, max, sum_sq), s| (min.min(*s), max.max(*s), sum_sq + (*s as f64).powi(2)),
+        );
+        Self {
+            min,
+            max,
+            rms: (sum_sq / samples.len() as f64).sqrt() as f32,
+        }
+    }
+
+    /// Combine peaks, each weighted by the number of samples it covers
+    fn merge(peaks: impl Iterator<Item = (Peak, u64)>) -> Self {
+        let (min, max, sum_sq, count) = peaks.fold(
+            (f32::INFINITY, f32::NEG_INFINITY, 0.0f64, 0u64),
+            |(min, max, sum_sq, count), (peak, weight)| {
+                (
+                    min.min(peak.min),
+                    max.max(peak.max),
+                    sum_sq + (peak.rms as f64).powi(2) * weight as f64,
+                    count + weight,
+                )
+            },
+        );
+        if count == 0 {
+            return Self::SILENT;
+        }
+        Self {
+            min,
+            max,
+            rms: (sum_sq / count as f64).sqrt() as f32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakLevel {
+    pub samples_per_peak: u32,
+    /// Peaks of each channel
+    pub channels: Vec<Vec<Peak>>,
+}
+
+/// Peaks at a fine base resolution, halved level by level, so any zoom is drawn from a few
+/// thousand precomputed peaks instead of the audio
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakPyramid {
+    pub sample_rate: u32,
+    /// Samples per channel
+    pub length: u64,
+    /// Finest level first
+    pub levels: Vec<PeakLevel>,
+}
+
+/// Peaks for drawing `start_ms..end_ms`, one per point per channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveformPeaks {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Resolution of the pyramid level the peaks were taken from
+    pub samples_per_peak: u32,
+    pub channels: Vec<Vec<Peak>>,
+}
+
+impl PeakPyramid {
+    /// Build the pyramid from per-channel samples
+    pub fn build(channels: &[Vec<f32>], sample_rate: u32) -> Self {
+        let length = channels.iter().map(Vec::len).max().unwrap_or(0) as u64;
+        let base = PeakLevel {
+            samples_per_peak: BASE_SAMPLES_PER_PEAK,
+            channels: channels
+                .iter()
+                .map(|samples| {
+                    samples
+                        .chunks(BASE_SAMPLES_PER_PEAK as usize)
+                        .map(Peak::of)
+                        .collect()
+                })
+                .collect(),
+        };
+
+        let mut levels = vec![base];
+        while let Some(level) = levels.last() {
+            if level.peak_count() <= MIN_LEVEL_PEAKS {
+                break;
+            }
+            let next = level.halve(length);
+            levels.push(next);
+        }
+
+        Self {
+            sample_rate,
+            length,
+            levels,
+        }
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.levels.first().map_or(0, |level| level.channels.len())
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        self.length * 1000 / self.sample_rate as u64
+    }
+
+    /// Peaks of `start_ms..end_ms` at `points` points per channel.
+    ///
+    /// Uses the coarsest level that still has at least one peak per point, then merges its
+    /// peaks into the points. Zoomed in past the finest level, neighbouring points repeat the
+    /// same peak.
+    pub fn query(&self, start_ms: u64, end_ms: u64, points: usize) -> WaveformPeaks {
+        let end_ms = end_ms.min(self.duration_ms());
+        let start_ms = start_ms.min(end_ms);
+        let to_sample = |ms: u64| ms * self.sample_rate as u64 / 1000;
+        let (start, end) = (to_sample(start_ms), to_sample(end_ms));
+        let per_point = (end - start) as f64 / points.max(1) as f64;
+
+        let Some(level) = self
+            .levels
+            .iter()
+            .rev()
+            .find(|level| level.samples_per_peak as f64 <= per_point)
+            .or(self.levels.first())
+        else {
+            return WaveformPeaks {
+                start_ms,
+                end_ms,
+                samples_per_peak: BASE_SAMPLES_PER_PEAK,
+                channels: Vec::new(),
+            };
+        };
+        let spp = level.samples_per_peak as u64;
+
+        let channels = level
+            .channels
+            .iter()
+            .map(|peaks| {
+                (0..points)
+                    .map(|point| {
+                        let from = start + (point as f64 * per_point) as u64;
+                        let to = (start + ((point + 1) as f64 * per_point) as u64).max(from + 1);
+                        let first = (from / spp) as usize;
+                        let last = (to.div_ceil(spp) as usize).max(first + 1).min(peaks.len());
+                        Peak::merge(
+                            peaks
+                                .get(first..last)
+                                .unwrap_or_default()
+                                .iter()
+                                .enumerate()
+                                .map(|(i, peak)| (*peak, level.weight(first + i, self.length))),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        WaveformPeaks {
+            start_ms,
+            end_ms,
+            samples_per_peak: level.samples_per_peak,
+            channels,
+        }
+    }
+
+    /// Serialize to the cache format: a header, then each level's peaks as little-endian f32
+    /// triples, channel after channel
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&self.length.to_le_bytes());
+        bytes.extend_from_slice(&(self.channel_count() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.levels.len() as u32).to_le_bytes());
+        for level in &self.levels {
+            bytes.extend_from_slice(&level.samples_per_peak.to_le_bytes());
+            bytes.extend_from_slice(&(level.peak_count() as u32).to_le_bytes());
+            for peaks in &level.channels {
+                for peak in peaks {
+                    for value in [peak.min, peak.max, peak.rms] {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err("Not a waveform peak file".to_string());
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported waveform peak file version: {version}"));
+        }
+        let sample_rate = reader.u32()?;
+        let length = reader.u64()?;
+        let channel_count = reader.u32()? as usize;
+        let level_count = reader.u32()? as usize;
+        if channel_count > MAX_CHANNELS || level_count > MAX_LEVELS {
+            return Err("Corrupt waveform peak file".to_string());
+        }
+
+        let mut levels = Vec::with_capacity(level_count);
+        for _ in 0..level_count {
+            let samples_per_peak = reader.u32()?;
+            let peak_count = reader.u32()? as usize;
+            // Check the counts against the file size before allocating for them
+            let fits = peak_count
+                .checked_mul(channel_count * 12)
+                .is_some_and(|len| len <= reader.remaining());
+            if !fits {
+                return Err("Truncated waveform peak file".to_string());
+            }
+            let mut channels = Vec::with_capacity(channel_count);
+            for _ in 0..channel_count {
+                let mut peaks = Vec::with_capacity(peak_count);
+                for _ in 0..peak_count {
+                    peaks.push(Peak {
+                        min: reader.f32()?,
+                        max: reader.f32()?,
+                        rms: reader.f32()?,
+                    });
+                }
+                channels.push(peaks);
+            }
+            levels.push(PeakLevel {
+                samples_per_peak,
+                channels,
+            });
+        }
+
+        Ok(Self {
+            sample_rate,
+            length,
+            levels,
+        })
+    }
+}
+
+impl WaveformPeaks {
+    /// One RMS level per point with the channels mixed
+    pub fn mixed_rms(&self) -> Vec<f32> {
+        let points = self.channels.first().map_or(0, Vec::len);
+        (0..points)
+            .map(|point| {
+                let power: f32 = self.channels.iter().map(|c| c[point].rms.powi(2)).sum();
+                (power / self.channels.len() as f32).sqrt()
+            })
+            .collect()
+    }
+}
+
+impl PeakLevel {
+    fn peak_count(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    /// Samples covered by peak `index`; the last peak may be partial
+    fn weight(&self, index: usize, length: u64) -> u64 {
+        let spp = self.samples_per_peak as u64;
+        length.saturating_sub(index as u64 * spp).min(spp)
+    }
+
+    /// The next coarser level: every two peaks merged into one
+    fn halve(&self, length: u64) -> Self {
+        let channels = self
+            .channels
+            .iter()
+            .map(|peaks| {
+                peaks
+                    .chunks(2)
+                    .enumerate()
+                    .map(|(i, pair)| {
+                        Peak::merge(
+                            pair.iter()
+                                .enumerate()
+                                .map(|(j, peak)| (*peak, self.weight(2 * i + j, length))),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            samples_per_peak: self.samples_per_peak * 2,
+            channels,
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let slice = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| "Truncated waveform peak file".to_string())?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1 kHz: 2 s of a 0.5 square wave on the left, then 1 s of silence; the right channel
+    /// is the left inverted and halved
+    fn stereo() -> Vec<Vec<f32>> {
+        let left: Vec<f32> = (0..3000)
+            .map(|i| {
+                if i >= 2000 {
+                    0.0
+                } else if i % 2 == 0 {
+                    0.5
+                } else {
+                    -0.5
+                }
+            })
+            .collect();
+        let right = left.iter().map(|s| -s / 2.0).collect();
+        vec![left, right]
+    }
+
+    #[test]
+    fn test_build_pyramid_levels() {
+        let pyramid = PeakPyramid::build(&stereo(), 1000);
+        assert_eq!(pyramid.channel_count(), 2);
+        assert_eq!(pyramid.duration_ms(), 3000);
+        // 3000 samples: 12 base peaks, already under the level minimum
+        assert_eq!(pyramid.levels.len(), 1);
+        assert_eq!(pyramid.levels[0].channels[0].len(), 12);
+
+        let long = PeakPyramid::build(&[vec![0.25; 256 * 1000]], 44_100);
+        let sizes: Vec<(u32, usize)> = long
+            .levels
+            .iter()
+            .map(|l| (l.samples_per_peak, l.channels[0].len()))
+            .collect();
+        assert_eq!(sizes, [(256, 1000), (512, 500), (1024, 250)]);
+        assert_eq!(
+            long.levels[2].channels[0][0],
+            Peak {
+                min: 0.25,
+                max: 0.25,
+                rms: 0.25
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_merges_peaks() {
+        let pyramid = PeakPyramid::build(&stereo(), 1000);
+        let peaks = pyramid.query(0, 3000, 3);
+        assert_eq!(peaks.channels.len(), 2);
+
+        let left = &peaks.channels[0];
+        assert_eq!((left[0].min, left[0].max), (-0.5, 0.5));
+        assert!((left[0].rms - 0.5).abs() < 1e-6);
+        // the second point straddles the end of the tone
+        assert!(left[1].rms > 0.0 && left[1].rms < 0.5);
+        // peaks are 256 samples wide, so the tone's last samples reach into the third point
+        assert!(left[2].rms < 0.25);
+        assert_eq!(pyramid.query(2100, 3000, 1).channels[0][0], Peak::SILENT);
+        assert_eq!(
+            (peaks.channels[1][0].min, peaks.channels[1][0].max),
+            (-0.25, 0.25)
+        );
+        let mixed = peaks.mixed_rms()[0];
+        assert!((mixed - (0.3125f32 / 2.0).sqrt()).abs() < 1e-6, "{mixed}");
+
+        // zoomed past the base resolution, points share a peak
+        let zoomed = pyramid.query(100, 200, 50);
+        assert_eq!(zoomed.channels[0].len(), 50);
+        assert_eq!(zoomed.samples_per_peak, BASE_SAMPLES_PER_PEAK);
+        assert!(zoomed.channels[0].iter().all(|p| p.max == 0.5));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let pyramid = PeakPyramid::build(&stereo(), 1000);
+        let bytes = pyramid.to_bytes();
+        assert_eq!(PeakPyramid::from_bytes(&bytes).unwrap(), pyramid);
+
+        assert!(PeakPyramid::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(PeakPyramid::from_bytes(b"RIFF0000").is_err());
+    }
+
+    #[test]
+    fn test_corrupt_counts_are_rejected() {
+        let bytes = PeakPyramid::build(&stereo(), 1000).to_bytes();
+        // Header: magic, version, sample rate, length, channel count, level count, then the
+        // first level's samples per peak and peak count
+        let patched = |at: usize, value: u32| {
+            let mut bytes = bytes.clone();
+            bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            PeakPyramid::from_bytes(&bytes)
+        };
+        assert!(patched(20, u32::MAX).is_err());
+        assert!(patched(24, u32::MAX).is_err());
+        assert!(patched(32, u32::MAX).is_err());
+        assert!(patched(32, 1 << 20).is_err());
+    }
+}
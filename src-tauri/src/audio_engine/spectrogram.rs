@@ -0,0 +1,316 @@
+use crate::beat_sync::onset::OnsetEnvelope;
+use crate::beat_sync::spectrum::stft_magnitudes;
+use serde::{Deserialize, Serialize};
+
+/// Levels this far below the loudest cell of the range are drawn as silence
+const DYNAMIC_RANGE_DB: f32 = 80.0;
+
+/// Width of the moving average removed from the onset envelope
+const ONSET_SMOOTHING_SECONDS: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrequencyScale {
+    Mel,
+    Log,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectrogramOptions {
+    /// STFT frame length in samples (rounded up to a power of two)
+    #[serde(default = "default_fft_size")]
+    pub fft_size: usize,
+    /// Samples between frames
+    #[serde(default = "default_hop_size")]
+    pub hop_size: usize,
+    #[serde(default = "default_scale")]
+    pub scale: FrequencyScale,
+    /// Number of frequency bands
+    #[serde(default = "default_bands")]
+    pub bands: usize,
+    #[serde(default = "default_min_hz")]
+    pub min_hz: f32,
+    /// Upper edge of the top band; the Nyquist frequency when unset
+    #[serde(default)]
+    pub max_hz: Option<f32>,
+}
+
+fn default_fft_size() -> usize {
+    2048
+}
+
+fn default_hop_size() -> usize {
+    512
+}
+
+fn default_scale() -> FrequencyScale {
+    FrequencyScale::Mel
+}
+
+fn default_bands() -> usize {
+    128
+}
+
+fn default_min_hz() -> f32 {
+    30.0
+}
+
+impl Default for SpectrogramOptions {
+    fn default() -> Self {
+        Self {
+            fft_size: default_fft_size(),
+            hop_size: default_hop_size(),
+            scale: default_scale(),
+            bands: default_bands(),
+            min_hz: default_min_hz(),
+            max_hz: None,
+        }
+    }
+}
+
+/// STFT magnitudes folded into mel- or log-spaced bands, one byte per cell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spectrogram {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Frames per second; frame `i` is centered on `start_ms + i * 1000 / frame_rate`
+    pub frame_rate: f64,
+    pub frame_count: usize,
+    /// Center frequency of each band in Hz, lowest first
+    pub band_hz: Vec<f32>,
+    /// Level of the loudest cell, which `data` maps to 255
+    pub max_db: f32,
+    /// Level that `data` maps to 0
+    pub min_db: f32,
+    /// `frame_count * band_hz.len()` levels, frame by frame, lowest band first
+    pub data: Vec<u8>,
+    /// Onset strength (spectral flux) per frame, 0.0–1.0
+    pub onset: Vec<f32>,
+}
+
+/// Triangular filters mapping FFT bins to bands
+struct FilterBank {
+    centers: Vec<f32>,
+    /// Per band: first FFT bin and the weights from there on
+    filters: Vec<(usize, Vec<f32>)>,
+}
+
+impl FrequencyScale {
+    fn scale_hz(self, hz: f32) -> f32 {
+        match self {
+            Self::Mel => 2595.0 * (1.0 + hz / 700.0).log10(),
+            Self::Log => hz.ln(),
+        }
+    }
+
+    fn unscale(self, value: f32) -> f32 {
+        match self {
+            Self::Mel => 700.0 * (10f32.powf(value / 2595.0) - 1.0),
+            Self::Log => value.exp(),
+        }
+    }
+}
+
+impl FilterBank {
+    fn new(options: &SpectrogramOptions, fft_size: usize, sample_rate: u32) -> Self {
+        let nyquist = sample_rate as f32 / 2.0;
+        let max_hz = options.max_hz.unwrap_or(nyquist).min(nyquist);
+        let min_hz = options.min_hz.max(1.0).min(max_hz * 0.5);
+        let scale = options.scale;
+
+        // bands + 2 edges evenly spaced on the scale; band i spans edges i..i + 2
+        let bands = options.bands.max(1);
+        let (low, high) = (scale.scale_hz(min_hz), scale.scale_hz(max_hz));
+        let edges: Vec<f32> = (0..bands + 2)
+            .map(|i| scale.unscale(low + (high - low) * i as f32 / (bands + 1) as f32))
+            .collect();
+
+        let bin_hz = sample_rate as f32 / fft_size as f32;
+        let bin_count = fft_size / 2 + 1;
+        let filters = edges
+            .windows(3)
+            .map(|edge| {
+                let (left, center, right) = (edge[0], edge[1], edge[2]);
+                let first = ((left / bin_hz).ceil() as usize).min(bin_count - 1);
+                let last = ((right / bin_hz).floor() as usize).min(bin_count - 1);
+                let weights: Vec<f32> = (first..=last)
+                    .map(|bin| {
+                        let hz = bin as f32 * bin_hz;
+                        if hz <= center {
+                            (hz - left) / (center - left)
+                        } else {
+                            (right - hz) / (right - center)
+                        }
+                    })
+                    .collect();
+                if weights.iter().any(|w| *w > 0.0) {
+                    (first, weights)
+                } else {
+                    // Narrower than an FFT bin (low bands of the log scale): take the nearest bin
+                    let nearest = ((center / bin_hz).round() as usize).min(bin_count - 1);
+                    (nearest, vec![1.0])
+                }
+            })
+            .collect();
+
+        Self {
+            centers: edges[1..=bands].to_vec(),
+            filters,
+        }
+    }
+
+    /// Band powers in dB of one magnitude spectrum
+    fn apply(&self, spectrum: &[f32]) -> Vec<f32> {
+        self.filters
+            .iter()
+            .map(|(first, weights)| {
+                let power: f32 = weights
+                    .iter()
+                    .zip(&spectrum[*first..])
+                    .map(|(w, m)| w * m * m)
+                    .sum();
+                10.0 * (power + 1e-12).log10()
+            })
+            .collect()
+    }
+}
+
+/// Spectrogram and onset envelope of `start_ms..end_ms` of mono samples. Frames near the
+/// range edges are analyzed with the surrounding audio rather than zero padding.
+pub fn compute_spectrogram(
+    samples: &[f32],
+    sample_rate: u32,
+    start_ms: u64,
+    end_ms: u64,
+    options: &SpectrogramOptions,
+) -> Spectrogram {
+    let fft_size = options.fft_size.max(16).next_power_of_two();
+    let hop_size = options.hop_size.max(1);
+    let frame_rate = sample_rate as f64 / hop_size as f64;
+    let to_sample =
+        |ms: u64| ((ms as u128 * sample_rate as u128 / 1000) as usize).min(samples.len());
+    let start = to_sample(start_ms);
+    let end = to_sample(end_ms).max(start);
+    let bank = FilterBank::new(options, fft_size, sample_rate);
+
+    // Whole hops of audio before the range, so the first frames see real context
+    let skip = start.min(fft_size / 2) / hop_size;
+    let context_start = start - skip * hop_size;
+    let context_end = (end + fft_size / 2).min(samples.len());
+    let frame_count = (end - start).div_ceil(hop_size);
+    let mut spectra = stft_magnitudes(&samples[context_start..context_end], fft_size, hop_size);
+    spectra.truncate(skip + frame_count);
+    let skip = skip.min(spectra.len());
+
+    let levels: Vec<f32> = spectra[skip..]
+        .iter()
+        .flat_map(|spectrum| bank.apply(spectrum))
+        .collect();
+    let max_db = levels.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let max_db = if max_db.is_finite() { max_db } else { 0.0 };
+    let min_db = max_db - DYNAMIC_RANGE_DB;
+    let data = levels
+        .iter()
+        .map(|db| {
+            ((db - min_db) / DYNAMIC_RANGE_DB * 255.0)
+                .clamp(0.0, 255.0)
+                .round() as u8
+        })
+        .collect();
+
+    let smoothing = (ONSET_SMOOTHING_SECONDS * frame_rate).round() as usize;
+    let onset = OnsetEnvelope::spectral_flux(&spectra, 0..fft_size / 2 + 1, frame_rate, smoothing);
+
+    Spectrogram {
+        start_ms,
+        end_ms,
+        frame_rate,
+        frame_count: spectra.len() - skip,
+        band_hz: bank.centers,
+        max_db,
+        min_db,
+        data,
+        onset: onset.values[skip..].to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 16_000;
+
+    fn loudest_band(spectrogram: &Spectrogram, frame: usize) -> f32 {
+        let bands = spectrogram.band_hz.len();
+        let row = &spectrogram.data[frame * bands..(frame + 1) * bands];
+        let band = row
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, level)| **level)
+            .map(|(band, _)| band)
+            .unwrap();
+        spectrogram.band_hz[band]
+    }
+
+    #[test]
+    fn test_spectrogram_bands_and_onsets() {
+        // 1 s of silence, then 1 s of a 1 kHz tone
+        let samples: Vec<f32> = (0..2 * SAMPLE_RATE as usize)
+            .map(|i| {
+                if i < SAMPLE_RATE as usize {
+                    0.0
+                } else {
+                    (2.0 * PI * 1000.0 * i as f32 / SAMPLE_RATE as f32).sin()
+                }
+            })
+            .collect();
+
+        for scale in [FrequencyScale::Mel, FrequencyScale::Log] {
+            let options = SpectrogramOptions {
+                fft_size: 1024,
+                hop_size: 256,
+                scale,
+                bands: 64,
+                ..Default::default()
+            };
+            let spectrogram = compute_spectrogram(&samples, SAMPLE_RATE, 500, 2000, &options);
+            assert_eq!(spectrogram.frame_rate, 62.5);
+            assert_eq!(spectrogram.frame_count, 94);
+            assert_eq!(spectrogram.data.len(), 94 * 64);
+            assert_eq!(spectrogram.onset.len(), 94);
+            assert!(spectrogram.band_hz.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(spectrogram.data.iter().max(), Some(&255));
+
+            // the tone's band is the loudest once it plays; before that nothing shows
+            let center = loudest_band(&spectrogram, 60);
+            assert!((center / 1000.0 - 1.0).abs() < 0.1, "{scale:?}: {center}");
+            assert!(spectrogram.data[..20 * 64].iter().all(|level| *level == 0));
+
+            // the onset is at 1 s, 500 ms into the range
+            let peak = spectrogram
+                .onset
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(frame, _)| frame)
+                .unwrap();
+            let peak_ms = 500 + (peak as f64 * 1000.0 / spectrogram.frame_rate) as u64;
+            assert!((peak_ms as i64 - 1000).abs() <= 40, "{scale:?}: {peak_ms}");
+        }
+    }
+
+    #[test]
+    fn test_spectrogram_empty_range() {
+        let spectrogram = compute_spectrogram(
+            &[0.0; 100],
+            SAMPLE_RATE,
+            50,
+            40,
+            &SpectrogramOptions::default(),
+        );
+        assert_eq!(spectrogram.frame_count, 0);
+        assert!(spectrogram.data.is_empty());
+        assert_eq!(spectrogram.band_hz.len(), 128);
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+
+/// Sample encoding of a written WAV
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 16-bit integer PCM, clamped to full scale
+    Pcm16,
+    /// 32-bit IEEE float, so levels above full scale survive
+    Float32,
+}
+
+impl WavFormat {
+    fn format_tag(self) -> u16 {
+        match self {
+            WavFormat::Pcm16 => 1,
+            WavFormat::Float32 => 3,
+        }
+    }
+
+    fn bytes_per_sample(self) -> u16 {
+        match self {
+            WavFormat::Pcm16 => 2,
+            WavFormat::Float32 => 4,
+        }
+    }
+}
+
+/// Write channels as an interleaved WAV, e.g. for whisper.cpp or the export mix. Channels shorter than the longest are padded
+/// with silence.
+pub fn write_wav(
+    path: &Path,
+    channels: &[Vec<f32>],
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<(), String> {
+    let channel_count = channels.len().max(1) as u16;
+    let sample_bytes = format.bytes_per_sample();
+    let frames = channels.iter().map(Vec::len).max().unwrap_or(0);
+    let data_len = (frames * channel_count as usize * sample_bytes as usize) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&format.format_tag().to_le_bytes());
+    bytes.extend_from_slice(&channel_count.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    let block_align = channel_count * sample_bytes;
+    bytes.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes()); // byte rate
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&(sample_bytes * 8).to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for frame in 0..frames {
+        for channel in 0..channel_count as usize {
+            let sample = channels
+                .get(channel)
+                .and_then(|c| c.get(frame))
+                .copied()
+                .unwrap_or(0.0);
+            match format {
+                WavFormat::Pcm16 => {
+                    let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+                WavFormat::Float32 => bytes.extend_from_slice(&sample.to_le_bytes()),
+            }
+        }
+    }
+
+    std::fs::write(path, bytes).map_err(|e| format!("Failed to write audio: {e}"))
+}
+
+/// Uniquely named directory under the system temp dir, removed with its contents on drop
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create `shortforge-{prefix}-{pid}-{nanos}` in the system temp dir
+    pub fn new(prefix: &str) -> Result<Self, String> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let path = std::env::temp_dir().join(format!(
+            "shortforge-{prefix}-{}-{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create temporary directory: {e}"))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u16(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    #[test]
+    fn test_write_wav_formats() {
+        let dir = TempDir::new("wav-test").unwrap();
+        let channels = vec![vec![0.5, 2.0], vec![-1.0]];
+
+        let pcm_path = dir.path().join("pcm.wav");
+        write_wav(&pcm_path, &channels, 16_000, WavFormat::Pcm16).unwrap();
+        let pcm = std::fs::read(&pcm_path).unwrap();
+        assert_eq!(pcm.len(), 44 + 2 * 2 * 2);
+        assert_eq!(read_u16(&pcm, 20), 1);
+        assert_eq!(read_u16(&pcm, 22), 2);
+        assert_eq!(read_u16(&pcm, 32), 4);
+        assert_eq!(read_u16(&pcm, 34), 16);
+        // frames interleave, clip at full scale and pad the short channel with silence
+        let samples: Vec<i16> = pcm[44..]
+            .chunks(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(samples, vec![i16::MAX / 2, -i16::MAX, i16::MAX, 0]);
+
+        let float_path = dir.path().join("float.wav");
+        write_wav(&float_path, &channels, 16_000, WavFormat::Float32).unwrap();
+        let float = std::fs::read(&float_path).unwrap();
+        assert_eq!(float.len(), 44 + 2 * 2 * 4);
+        assert_eq!(read_u16(&float, 20), 3);
+        assert_eq!(read_u16(&float, 34), 32);
+        assert_eq!(
+            f32::from_le_bytes([float[52], float[53], float[54], float[55]]),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_temp_dir_removed_on_drop() {
+        let dir = TempDir::new("wav-test").unwrap();
+        let path = dir.path().to_path_buf();
+        std::fs::write(path.join("file"), b"data").unwrap();
+        assert!(path.is_dir());
+        drop(dir);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::beat_sync::analyzer::BeatInfo;
+use crate::beat_sync::onset::OnsetEnvelope;
+use crate::models::project::BeatKind;
+use serde::{Deserialize, Serialize};
+
+/// Upper edge of the kick band, also used for the low-band energy of the accent
+const KICK_MAX_HZ: f64 = 150.0;
+
+/// Lower edge of the hi-hat band; the snare band lies between the kick and hi-hat bands
+const HIHAT_MIN_HZ: f64 = 6000.0;
+
+/// Stretch after a beat whose low-band energy counts toward its accent
+const ENERGY_SECONDS: f64 = 0.1;
+
+/// Frames around a beat searched for its band onsets
+const ONSET_RADIUS: usize = 2;
+
+/// Band onset, relative to the strongest on any beat, that counts as a hit of that drum
+const HIT_THRESHOLD: f32 = 0.35;
+
+/// Which beats snapping and auto-edit may use
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BeatFilter {
+    /// Skip beats with a lower accent strength (0.0–1.0)
+    #[serde(default)]
+    pub min_strength: f64,
+    /// Only beats of these kinds; any kind when empty
+    #[serde(default)]
+    pub kinds: Vec<BeatKind>,
+}
+
+impl BeatFilter {
+    pub fn matches(&self, beat: &BeatInfo) -> bool {
+        beat.strength >= self.min_strength
+            && (self.kinds.is_empty() || self.kinds.contains(&beat.kind))
+    }
+
+    /// The beats that pass the filter
+    pub fn apply(&self, beats: &[BeatInfo]) -> Vec<BeatInfo> {
+        beats.iter().filter(|b| self.matches(b)).cloned().collect()
+    }
+}
+
+/// Per-frame RMS magnitudes of the kick, snare and hi-hat bands
+pub struct DrumBands {
+    levels: [Vec<f32>; 3],
+    /// Frames per second
+    frame_rate: f64,
+}
+
+impl DrumBands {
+    /// Band levels of magnitude spectra of `frame_size` samples. Levels are linear rather than
+    /// log-compressed, so faint leakage of one drum into another's band barely registers.
+    pub fn new(spectra: &[Vec<f32>], sample_rate: u32, frame_size: usize, frame_rate: f64) -> Self {
+        let bin = |hz: f64| (hz * frame_size as f64 / sample_rate.max(1) as f64).ceil() as usize;
+        let (kick_bins, hihat_bin) = (bin(KICK_MAX_HZ).max(2), bin(HIHAT_MIN_HZ));
+        let bins = frame_size / 2 + 1;
+        let level = |start: usize, end: usize| -> Vec<f32> {
+            spectra
+                .iter()
+                .map(|spectrum| {
+                    let end = end.min(spectrum.len());
+                    let band = &spectrum[start.min(end)..end];
+                    (band.iter().map(|m| m * m).sum::<f32>() / band.len().max(1) as f32).sqrt()
+                })
+                .collect()
+        };
+
+        Self {
+            levels: [
+                level(1, kick_bins),
+                level(kick_bins, hihat_bin.min(bins)),
+                level(hihat_bin.min(bins), bins),
+            ],
+            frame_rate,
+        }
+    }
+
+    /// How much each band's level rises around `frame` over the level just before it
+    fn onsets(&self, frame: usize) -> [f32; 3] {
+        [0, 1, 2].map(|band| {
+            let levels = &self.levels[band];
+            let end = (frame + ONSET_RADIUS + 1).min(levels.len());
+            let start = frame.saturating_sub(ONSET_RADIUS).min(end);
+            let before = start.checked_sub(1).map_or(0.0, |i| levels[i]);
+            let peak = levels[start..end].iter().copied().fold(0.0, f32::max);
+            (peak - before).max(0.0)
+        })
+    }
+
+    /// Mean kick-band level over the stretch after `frame`
+    fn low_energy(&self, frame: usize) -> f32 {
+        let frames = (ENERGY_SECONDS * self.frame_rate).round().max(1.0) as usize;
+        let low = &self.levels[0];
+        let end = (frame + frames).min(low.len());
+        let window = &low[frame.min(end)..end];
+        window.iter().sum::<f32>() / window.len().max(1) as f32
+    }
+}
+
+/// The drum a beat most likely is, from its band onsets relative to the strongest of each
+/// band on any beat. A kick wins over a simultaneous snare or hi-hat, a snare over a hi-hat.
+pub fn classify(kick: f32, snare: f32, hihat: f32) -> BeatKind {
+    if kick >= HIT_THRESHOLD && kick >= snare {
+        BeatKind::Kick
+    } else if snare >= HIT_THRESHOLD {
+        BeatKind::Snare
+    } else if hihat >= HIT_THRESHOLD {
+        BeatKind::HiHat
+    } else {
+        BeatKind::Other
+    }
+}
+
+/// Set the accent strength and drum kind of beats found at analysis `frames`.
+///
+/// The strength averages the beat's full-band onset flux and the low-band energy just after
+/// it, scaled so the strongest beat of the track is 1.
+pub fn accent_beats(
+    beats: &mut [BeatInfo],
+    frames: &[usize],
+    full: &OnsetEnvelope,
+    bands: &DrumBands,
+) {
+    let onsets: Vec<[f32; 3]> = frames.iter().map(|&frame| bands.onsets(frame)).collect();
+    let peaks = onsets.iter().fold([0.0f32; 3], |peaks, onset| {
+        [0, 1, 2].map(|band| peaks[band].max(onset[band]))
+    });
+    let energies: Vec<f32> = frames.iter().map(|&f| bands.low_energy(f)).collect();
+    let peak_energy = energies.iter().copied().fold(0.0, f32::max);
+
+    let raw: Vec<f64> = frames
+        .iter()
+        .zip(&energies)
+        .map(|(&frame, energy)| {
+            let flux = full.local_max(frame, ONSET_RADIUS).clamp(0.0, 1.0) as f64;
+            let energy = if peak_energy > 0.0 {
+                (energy / peak_energy) as f64
+            } else {
+                0.0
+            };
+            0.5 * flux + 0.5 * energy
+        })
+        .collect();
+    let peak = raw.iter().copied().fold(0.0, f64::max);
+
+    for ((beat, strength), onset) in beats.iter_mut().zip(raw).zip(onsets) {
+        beat.strength = if peak > 0.0 { strength / peak } else { 0.0 };
+        let [kick, snare, hihat] = [0, 1, 2].map(|band| {
+            if peaks[band] > 0.0 {
+                onset[band] / peaks[band]
+            } else {
+                0.0
+            }
+        });
+        beat.kind = classify(kick, snare, hihat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_and_filter() {
+        assert_eq!(classify(1.0, 0.6, 0.8), BeatKind::Kick);
+        assert_eq!(classify(0.1, 0.9, 1.0), BeatKind::Snare);
+        assert_eq!(classify(0.4, 0.9, 0.2), BeatKind::Snare);
+        assert_eq!(classify(0.0, 0.2, 0.7), BeatKind::HiHat);
+        assert_eq!(classify(0.1, 0.1, 0.1), BeatKind::Other);
+
+        let beats: Vec<BeatInfo> = [
+            (0.9, BeatKind::Kick),
+            (0.5, BeatKind::Snare),
+            (0.2, BeatKind::HiHat),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (strength, kind))| BeatInfo {
+            time_ms: i as u64 * 500,
+            strength,
+            kind,
+            ..Default::default()
+        })
+        .collect();
+        let snares = BeatFilter {
+            kinds: vec![BeatKind::Snare],
+            ..Default::default()
+        };
+        assert_eq!(snares.apply(&beats).len(), 1);
+        assert_eq!(snares.apply(&beats)[0].time_ms, 500);
+        let strong = BeatFilter {
+            min_strength: 0.4,
+            ..Default::default()
+        };
+        assert_eq!(strong.apply(&beats).len(), 2);
+        assert_eq!(BeatFilter::default().apply(&beats).len(), 3);
+    }
+}
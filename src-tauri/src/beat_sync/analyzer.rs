@@ -1,3 +1,6 @@
+use crate::beat_sync::rhythm::analyze_rhythm;
+use crate::beat_sync::tempo::TempoPoint;
+use crate::models::project::{BeatKind, TempoSegment};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,16 +9,61 @@ pub struct BeatAnalysis {
     pub beats: Vec<BeatInfo>,
     pub sample_rate: u32,
     pub duration_ms: u64,
+    #[serde(default = "default_beats_per_bar")]
+    pub beats_per_bar: u8,
+    /// Local tempo at each beat
+    #[serde(default)]
+    pub tempo_curve: Vec<TempoPoint>,
+    /// Steady-tempo segments; more than one when the tempo changes
+    #[serde(default)]
+    pub tempo_map: Vec<TempoSegment>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_beats_per_bar() -> u8 {
+    4
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BeatInfo {
     pub time_ms: u64,
+    /// Accent strength from onset flux and low-band energy (0.0–1.0, 1 = strongest beat)
     pub strength: f64,
+    /// 1-based bar number; 0 for a pickup before the first downbeat or when unknown
+    #[serde(default)]
+    pub bar: u32,
+    /// Beat within the bar (1 = downbeat); 0 when unknown
+    #[serde(default)]
+    pub phase: u8,
+    /// Drum hit at the beat
+    #[serde(default)]
+    pub kind: BeatKind,
 }
 
 /// Decode audio from a media file into mono f32 samples
 pub fn decode_audio_mono(path: &str) -> Result<(Vec<f32>, u32), String> {
+    let (samples, sample_rate, _) = decode_audio(path, Some(ffmpeg_next::ChannelLayout::MONO))?;
+    Ok((samples, sample_rate))
+}
+
+/// Decode audio from a media file into one f32 sample buffer per channel. Mono stays mono;
+/// anything with more channels is mixed down to stereo.
+pub fn decode_audio_channels(path: &str) -> Result<(Vec<Vec<f32>>, u32), String> {
+    let (interleaved, sample_rate, channels) = decode_audio(path, None)?;
+    let mut planar = vec![Vec::with_capacity(interleaved.len() / channels); channels];
+    for frame in interleaved.chunks_exact(channels) {
+        for (channel, sample) in planar.iter_mut().zip(frame) {
+            channel.push(*sample);
+        }
+    }
+    Ok((planar, sample_rate))
+}
+
+/// Decode into interleaved f32 samples with `layout` (by default the source's, capped at
+/// stereo). Returns the samples, the sample rate and the channel count.
+fn decode_audio(
+    path: &str,
+    layout: Option<ffmpeg_next::ChannelLayout>,
+) -> Result<(Vec<f32>, u32, usize), String> {
     ffmpeg_next::init().map_err(|e| format!("Failed to init ffmpeg: {e}"))?;
 
     let mut format_ctx =
@@ -38,15 +86,24 @@ pub fn decode_audio_mono(path: &str) -> Result<(Vec<f32>, u32), String> {
         .map_err(|e| format!("Failed to create audio decoder: {e}"))?;
 
     let sample_rate = decoder.rate();
-    let _channels = decoder.channels() as u32;
+    let layout = layout.unwrap_or(if decoder.channels() >= 2 {
+        ffmpeg_next::ChannelLayout::STEREO
+    } else {
+        ffmpeg_next::ChannelLayout::MONO
+    });
+    let channels = if layout == ffmpeg_next::ChannelLayout::MONO {
+        1
+    } else {
+        2
+    };
 
-    // Create resampler to convert to mono f32 (FLT format)
+    // Create resampler to convert to interleaved f32 (FLT format)
     let mut resampler = ffmpeg_next::software::resampling::Context::get(
         decoder.format(),
         decoder.channel_layout(),
         sample_rate,
         ffmpeg_next::format::Sample::F32(ffmpeg_next::format::sample::Type::Packed),
-        ffmpeg_next::ChannelLayout::MONO,
+        layout,
         sample_rate,
     )
     .map_err(|e| format!("Failed to create resampler: {e}"))?;
@@ -102,81 +159,13 @@ pub fn decode_audio_mono(path: &str) -> Result<(Vec<f32>, u32), String> {
         delay = resampler.flush(&mut ffmpeg_next::frame::Audio::empty());
     }
 
-    Ok((samples, sample_rate))
+    Ok((samples, sample_rate, channels))
 }
 
-/// Analyze beats in audio using aubio
-pub fn analyze_beats(path: &str) -> Result<BeatAnalysis, String> {
+/// Analyze the rhythm of a media file's audio: tempo, beats, downbeats and bar positions
+pub fn analyze_beats(path: &str, beats_per_bar: u8) -> Result<BeatAnalysis, String> {
     let (samples, sample_rate) = decode_audio_mono(path)?;
-
-    let buf_size = 1024_usize;
-    let hop_size = 512_usize;
-
-    let mut tempo = aubio::Tempo::new(aubio::OnsetMode::default(), buf_size, hop_size, sample_rate)
-        .map_err(|e| format!("Failed to create tempo detector: {e}"))?;
-
-    let mut beats: Vec<BeatInfo> = Vec::new();
-
-    // Process audio in chunks of hop_size
-    let mut pos = 0;
-    while pos + hop_size <= samples.len() {
-        let chunk = &samples[pos..pos + hop_size];
-
-        let result = tempo
-            .do_result(chunk)
-            .map_err(|e| format!("Tempo detection error: {e}"))?;
-
-        if result > 0.0 {
-            let time_ms = tempo.get_last_ms() as u64;
-            let confidence = tempo.get_confidence() as f64;
-            beats.push(BeatInfo {
-                time_ms,
-                strength: confidence.clamp(0.0, 1.0),
-            });
-        }
-
-        pos += hop_size;
-    }
-
-    let bpm = tempo.get_bpm() as f64;
-    let duration_ms = if sample_rate > 0 {
-        (samples.len() as u64 * 1000) / sample_rate as u64
-    } else {
-        0
-    };
-
-    Ok(BeatAnalysis {
-        bpm,
-        beats,
-        sample_rate,
-        duration_ms,
-    })
-}
-
-/// Generate a downsampled waveform for visualization
-pub fn generate_waveform(path: &str, num_points: usize) -> Result<Vec<f32>, String> {
-    let (samples, _) = decode_audio_mono(path)?;
-
-    if samples.is_empty() || num_points == 0 {
-        return Ok(Vec::new());
-    }
-
-    let chunk_size = samples.len() / num_points;
-    if chunk_size == 0 {
-        return Ok(samples.iter().map(|s| s.abs()).collect());
-    }
-
-    let waveform: Vec<f32> = samples
-        .chunks(chunk_size)
-        .take(num_points)
-        .map(|chunk| {
-            // RMS amplitude for each chunk
-            let sum_sq: f32 = chunk.iter().map(|s| s * s).sum();
-            (sum_sq / chunk.len() as f32).sqrt()
-        })
-        .collect();
-
-    Ok(waveform)
+    Ok(analyze_rhythm(&samples, sample_rate, beats_per_bar))
 }
 
 #[cfg(test)]
@@ -191,25 +180,23 @@ mod tests {
                 BeatInfo {
                     time_ms: 500,
                     strength: 0.8,
+                    ..Default::default()
                 },
                 BeatInfo {
                     time_ms: 1000,
                     strength: 0.9,
+                    ..Default::default()
                 },
             ],
             sample_rate: 44100,
             duration_ms: 5000,
+            beats_per_bar: 4,
+            tempo_curve: Vec::new(),
+            tempo_map: Vec::new(),
         };
 
         assert_eq!(analysis.bpm, 120.0);
         assert_eq!(analysis.beats.len(), 2);
         assert_eq!(analysis.beats[0].time_ms, 500);
     }
-
-    #[test]
-    fn test_waveform_empty() {
-        // Can't test with real file in unit tests, but test empty case
-        let result = generate_waveform("/nonexistent/file.mp3", 100);
-        assert!(result.is_err());
-    }
 }
@@ -38,18 +38,22 @@ mod tests {
             BeatInfo {
                 time_ms: 500,
                 strength: 0.8,
+                ..Default::default()
             },
             BeatInfo {
                 time_ms: 1000,
                 strength: 0.9,
+                ..Default::default()
             },
             BeatInfo {
                 time_ms: 1500,
                 strength: 0.7,
+                ..Default::default()
             },
             BeatInfo {
                 time_ms: 2000,
                 strength: 0.85,
+                ..Default::default()
             },
         ]
     }
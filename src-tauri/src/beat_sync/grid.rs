@@ -0,0 +1,197 @@
+use crate::beat_sync::analyzer::{BeatAnalysis, BeatInfo};
+use crate::models::project::{BeatGrid, BeatMarker, BeatSettings, ShortForgeProject, TempoSegment};
+
+pub const MIN_GRID_BPM: f64 = 20.0;
+pub const MAX_GRID_BPM: f64 = 400.0;
+
+fn check_bpm(bpm: f64) -> Result<(), String> {
+    if !(MIN_GRID_BPM..=MAX_GRID_BPM).contains(&bpm) {
+        return Err(format!(
+            "BPM must be between {MIN_GRID_BPM} and {MAX_GRID_BPM}: {bpm}"
+        ));
+    }
+    Ok(())
+}
+
+/// Beats of a grid over `0..=duration_ms`.
+///
+/// The anchor is a downbeat (bar 1 unless beats come before it); beats before the anchor are
+/// generated too, so the grid covers the whole timeline. Bars are numbered as in detection:
+/// beats before the first downbeat form a pickup bar 0. The beats follow from the grid alone,
+/// so only the grid is stored with the project.
+pub fn generate_grid(
+    grid: &BeatGrid,
+    beats_per_bar: u8,
+    duration_ms: u64,
+) -> Result<BeatAnalysis, String> {
+    check_bpm(grid.bpm)?;
+    let beats_per_bar = beats_per_bar.max(1) as i64;
+    let period = 60_000.0 / grid.bpm;
+
+    // Index of the first beat relative to the anchor (zero or negative)
+    let first = -((grid.anchor_ms as f64 / period).floor() as i64);
+    let first_bar =
+        first.div_euclid(beats_per_bar) + i64::from(first.rem_euclid(beats_per_bar) != 0);
+
+    let beats: Vec<BeatInfo> = (first..)
+        .map(|index| {
+            (
+                index,
+                (grid.anchor_ms as f64 + index as f64 * period).round() as u64,
+            )
+        })
+        .take_while(|(_, time_ms)| *time_ms <= duration_ms)
+        .map(|(index, time_ms)| BeatInfo {
+            time_ms,
+            strength: 1.0,
+            bar: (index.div_euclid(beats_per_bar) - first_bar + 1) as u32,
+            phase: (index.rem_euclid(beats_per_bar) + 1) as u8,
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(BeatAnalysis {
+        bpm: grid.bpm,
+        beats,
+        sample_rate: 0,
+        duration_ms,
+        beats_per_bar: beats_per_bar as u8,
+        tempo_curve: Vec::new(),
+        tempo_map: vec![TempoSegment {
+            start_ms: 0,
+            end_ms: duration_ms,
+            bpm: grid.bpm,
+        }],
+    })
+}
+
+/// Fit a grid to tapped beat times by least squares. The first tap becomes the anchor.
+pub fn tap_tempo(taps_ms: &[u64]) -> Result<BeatGrid, String> {
+    if taps_ms.len() < 2 {
+        return Err("Tap at least two beats".to_string());
+    }
+    if taps_ms.windows(2).any(|w| w[1] <= w[0]) {
+        return Err("Taps must be in increasing order".to_string());
+    }
+
+    let n = taps_ms.len() as f64;
+    let mean_index = (n - 1.0) / 2.0;
+    let mean_time = taps_ms.iter().map(|t| *t as f64).sum::<f64>() / n;
+    let (covariance, variance) =
+        taps_ms
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(cov, var), (i, t)| {
+                let di = i as f64 - mean_index;
+                (cov + di * (*t as f64 - mean_time), var + di * di)
+            });
+    let period = covariance / variance;
+    let bpm = 60_000.0 / period;
+    check_bpm(bpm)?;
+
+    Ok(BeatGrid {
+        anchor_ms: (mean_time - period * mean_index).round().max(0.0) as u64,
+        bpm,
+    })
+}
+
+/// Move a grid by `delta_ms`. An anchor pushed before zero is moved later by whole bars, so
+/// the downbeats stay where they were put.
+pub fn shift_grid(grid: &BeatGrid, delta_ms: i64, beats_per_bar: u8) -> Result<BeatGrid, String> {
+    check_bpm(grid.bpm)?;
+    let bar = 60_000.0 / grid.bpm * beats_per_bar.max(1) as f64;
+    let mut anchor = grid.anchor_ms as f64 + delta_ms as f64;
+    if anchor < 0.0 {
+        anchor += (-anchor / bar).ceil() * bar;
+    }
+
+    Ok(BeatGrid {
+        anchor_ms: anchor.round() as u64,
+        bpm: grid.bpm,
+    })
+}
+
+/// Store beats as the project's beat markers together with how they were produced
+pub fn store_beats(
+    project: &ShortForgeProject,
+    analysis: &BeatAnalysis,
+    audio_path: Option<String>,
+    grid: Option<BeatGrid>,
+) -> ShortForgeProject {
+    let mut project = project.clone();
+    project.beat_markers = analysis
+        .beats
+        .iter()
+        .map(|beat| BeatMarker {
+            time_ms: beat.time_ms,
+            strength: beat.strength,
+            bar: beat.bar,
+            phase: beat.phase,
+            kind: beat.kind,
+        })
+        .collect();
+    project.beat_settings = Some(BeatSettings {
+        audio_path,
+        beats_per_bar: analysis.beats_per_bar,
+        grid,
+        tempo_map: analysis.tempo_map.clone(),
+    });
+    project
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(anchor_ms: u64, bpm: f64) -> BeatGrid {
+        BeatGrid { anchor_ms, bpm }
+    }
+
+    #[test]
+    fn test_generate_grid_covers_timeline() {
+        let analysis = generate_grid(&grid(1250, 120.0), 4, 4000).unwrap();
+        let beats: Vec<(u64, u32, u8)> = analysis
+            .beats
+            .iter()
+            .map(|b| (b.time_ms, b.bar, b.phase))
+            .collect();
+        assert_eq!(
+            beats,
+            [
+                (250, 0, 3),
+                (750, 0, 4),
+                (1250, 1, 1),
+                (1750, 1, 2),
+                (2250, 1, 3),
+                (2750, 1, 4),
+                (3250, 2, 1),
+                (3750, 2, 2),
+            ]
+        );
+        assert!(generate_grid(&grid(0, 0.0), 4, 4000).is_err());
+    }
+
+    #[test]
+    fn test_tap_tempo() {
+        let grid = tap_tempo(&[1010, 1490, 2005, 2500, 2995]).unwrap();
+        assert!((grid.bpm - 120.0).abs() < 1.0, "{}", grid.bpm);
+        assert!(
+            (grid.anchor_ms as i64 - 1000).abs() <= 10,
+            "{}",
+            grid.anchor_ms
+        );
+
+        assert!(tap_tempo(&[1000]).is_err());
+        assert!(tap_tempo(&[1000, 900]).is_err());
+    }
+
+    #[test]
+    fn test_shift_grid_keeps_downbeats() {
+        let shifted = shift_grid(&grid(500, 120.0), 120, 4).unwrap();
+        assert_eq!(shifted.anchor_ms, 620);
+
+        // 2 s bars: 500 - 900 = -400 -> 1600
+        let shifted = shift_grid(&grid(500, 120.0), -900, 4).unwrap();
+        assert_eq!(shifted.anchor_ms, 1600);
+    }
+}
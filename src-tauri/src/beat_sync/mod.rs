@@ -1,2 +1,12 @@
+pub mod accent;
 pub mod analyzer;
 pub mod detector;
+pub mod grid;
+pub mod montage;
+pub mod onset;
+pub mod rhythm;
+pub mod silence;
+pub mod spectrum;
+pub mod structure;
+pub mod tempo;
+pub mod tracker;
@@ -0,0 +1,520 @@
+use crate::beat_sync::accent::BeatFilter;
+use crate::beat_sync::analyzer::BeatAnalysis;
+use crate::models::project::ShortForgeProject;
+use crate::models::timeline::{TimelineTrack, TrackType, VideoClip};
+use crate::video_core::motion::{mean_motion, MotionSample};
+use serde::{Deserialize, Serialize};
+
+/// Id of the track holding the generated edit; generating again replaces it
+pub const AUTO_EDIT_TRACK_ID: &str = "video-auto-edit";
+
+/// Step between candidate segment starts for the motion strategy
+const MOTION_SEARCH_STEP_MS: u64 = 250;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutOn {
+    #[default]
+    Beats,
+    Downbeats,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentStrategy {
+    /// Sources in turn, each continuing where it left off
+    #[default]
+    Sequential,
+    /// Random sources and positions from `seed`
+    Random,
+    /// The unused stretches with the most motion
+    Motion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoEditOptions {
+    #[serde(default)]
+    pub cut_on: CutOn,
+    /// Cut on every n-th beat (or downbeat)
+    #[serde(default = "default_every")]
+    pub every: u32,
+    /// Only cut on strong beats or certain drum hits, e.g. every snare
+    #[serde(default)]
+    pub beat_filter: BeatFilter,
+    #[serde(default)]
+    pub strategy: SegmentStrategy,
+    /// Seed of the random strategy; the same seed gives the same edit
+    #[serde(default)]
+    pub seed: u64,
+    pub target_duration_ms: u64,
+}
+
+fn default_every() -> u32 {
+    1
+}
+
+/// A video the edit takes segments from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MontageSource {
+    pub path: String,
+    pub duration_ms: u64,
+    /// Per-frame motion, used by the motion strategy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub motion: Vec<MotionSample>,
+}
+
+/// Timeline positions of the cuts, from 0 to `target_ms`.
+///
+/// Cuts fall on every `every`-th beat passing `filter`, or downbeat when bars are known. Past
+/// the last beat the median cut interval continues up to the target. Pieces shorter than half
+/// an interval at either end are merged into their neighbour.
+pub fn cut_points(
+    analysis: &BeatAnalysis,
+    cut_on: CutOn,
+    every: u32,
+    filter: &BeatFilter,
+    target_ms: u64,
+) -> Result<Vec<u64>, String> {
+    if target_ms == 0 {
+        return Err("Target duration must be greater than zero".to_string());
+    }
+    let bars_known = analysis.beats.iter().any(|b| b.phase == 1);
+    let every = every.max(1) as usize;
+    let mut cuts: Vec<u64> = analysis
+        .beats
+        .iter()
+        .filter(|b| cut_on == CutOn::Beats || !bars_known || b.phase == 1)
+        .filter(|b| filter.matches(b))
+        .map(|b| b.time_ms)
+        .step_by(every)
+        .collect();
+
+    let interval = if cuts.len() >= 2 {
+        let mut intervals: Vec<u64> = cuts.windows(2).map(|w| w[1] - w[0]).collect();
+        intervals.sort_unstable();
+        intervals[intervals.len() / 2]
+    } else if analysis.bpm > 0.0 {
+        let beats = match cut_on {
+            CutOn::Beats => 1,
+            CutOn::Downbeats => analysis.beats_per_bar.max(1) as usize,
+        };
+        (60_000.0 / analysis.bpm * (beats * every) as f64).round() as u64
+    } else {
+        0
+    };
+    if interval == 0 {
+        return Err("No beats to cut on".to_string());
+    }
+
+    while let Some(&last) = cuts.last() {
+        if last + interval >= target_ms {
+            break;
+        }
+        cuts.push(last + interval);
+    }
+    cuts.retain(|t| *t >= interval / 2 && *t + interval / 2 <= target_ms);
+
+    let mut points = vec![0];
+    points.extend(cuts);
+    points.push(target_ms);
+    Ok(points)
+}
+
+/// SplitMix64, so a seed gives the same edit on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next() % bound
+        }
+    }
+}
+
+/// Choose a source segment for each cut. Returns (source index, source start) per piece.
+fn pick_segments(
+    sources: &[MontageSource],
+    lengths: &[u64],
+    options: &AutoEditOptions,
+) -> Result<Vec<(usize, u64)>, String> {
+    let mut picks: Vec<(usize, u64)> = Vec::with_capacity(lengths.len());
+    let mut cursors = vec![0u64; sources.len()];
+    let mut rng = Rng(options.seed);
+
+    for &length in lengths {
+        let fitting: Vec<usize> = (0..sources.len())
+            .filter(|&i| sources[i].duration_ms >= length)
+            .collect();
+        if fitting.is_empty() {
+            return Err(format!("No clip is long enough for a {length} ms cut"));
+        }
+        let previous = picks.last().map(|(source, _)| *source);
+
+        let pick = match options.strategy {
+            SegmentStrategy::Sequential => {
+                let next = previous.map_or(0, |p| p + 1);
+                let source = (0..sources.len())
+                    .map(|i| (next + i) % sources.len())
+                    .find(|i| fitting.contains(i))
+                    .unwrap_or(fitting[0]);
+                if cursors[source] + length > sources[source].duration_ms {
+                    cursors[source] = 0;
+                }
+                (source, cursors[source])
+            }
+            SegmentStrategy::Random => {
+                let choices: Vec<usize> = if fitting.len() > 1 {
+                    fitting
+                        .iter()
+                        .copied()
+                        .filter(|i| Some(*i) != previous)
+                        .collect()
+                } else {
+                    fitting.clone()
+                };
+                let source = choices[rng.below(choices.len() as u64) as usize];
+                let start = rng.below(sources[source].duration_ms - length + 1);
+                (source, start)
+            }
+            SegmentStrategy::Motion => best_motion(sources, &fitting, &picks, lengths, length),
+        };
+        cursors[pick.0] = pick.1 + length;
+        picks.push(pick);
+    }
+    Ok(picks)
+}
+
+/// The stretch of `length` with the most motion that overlaps no earlier pick, preferring a
+/// different source than the previous piece. Reuses footage only when everything is taken.
+fn best_motion(
+    sources: &[MontageSource],
+    fitting: &[usize],
+    picks: &[(usize, u64)],
+    lengths: &[u64],
+    length: u64,
+) -> (usize, u64) {
+    let previous = picks.last().map(|(source, _)| *source);
+    let overlaps_pick = |source: usize, start: u64| {
+        picks
+            .iter()
+            .zip(lengths)
+            .any(|((s, picked), picked_length)| {
+                *s == source && start < picked + picked_length && *picked < start + length
+            })
+    };
+
+    let candidates = || {
+        fitting.iter().flat_map(|&source| {
+            (0..=sources[source].duration_ms - length)
+                .step_by(MOTION_SEARCH_STEP_MS as usize)
+                .map(move |start| (source, start))
+        })
+    };
+    let score = |&(source, start): &(usize, u64)| {
+        mean_motion(&sources[source].motion, start, start + length)
+    };
+    let best = |allow: &dyn Fn(&(usize, u64)) -> bool| {
+        candidates()
+            .filter(|c| allow(c))
+            .fold(None, |best: Option<((usize, u64), f32)>, c| {
+                let s = score(&c);
+                match best {
+                    Some((_, best_score)) if best_score >= s => best,
+                    _ => Some((c, s)),
+                }
+            })
+            .map(|(c, _)| c)
+    };
+
+    best(&|c| !overlaps_pick(c.0, c.1) && (fitting.len() == 1 || Some(c.0) != previous))
+        .or_else(|| best(&|c| !overlaps_pick(c.0, c.1)))
+        .or_else(|| best(&|_| true))
+        .unwrap_or((fitting[0], 0))
+}
+
+/// Video clips of a beat-synced edit of `sources`, back to back from 0 to the target duration
+pub fn build_montage(
+    sources: &[MontageSource],
+    analysis: &BeatAnalysis,
+    options: &AutoEditOptions,
+) -> Result<Vec<VideoClip>, String> {
+    if sources.is_empty() {
+        return Err("Add at least one video clip".to_string());
+    }
+    let points = cut_points(
+        analysis,
+        options.cut_on,
+        options.every,
+        &options.beat_filter,
+        options.target_duration_ms,
+    )?;
+    let lengths: Vec<u64> = points.windows(2).map(|w| w[1] - w[0]).collect();
+    let picks = pick_segments(sources, &lengths, options)?;
+
+    Ok(picks
+        .iter()
+        .zip(points.windows(2))
+        .enumerate()
+        .map(|(n, (&(source, trim_start_ms), piece))| VideoClip {
+            id: format!("auto-edit-{}", n + 1),
+            source: sources[source].path.clone(),
+            start_ms: piece[0],
+            end_ms: piece[1],
+            trim_start_ms,
+            trim_end_ms: trim_start_ms + (piece[1] - piece[0]),
+            gain_envelope: Vec::new(),
+        })
+        .collect())
+}
+
+/// Put a beat-synced edit on the project's first track, replacing a previous one. The
+/// canvas is lengthened to the edit if needed.
+pub fn auto_edit(
+    project: &ShortForgeProject,
+    sources: &[MontageSource],
+    analysis: &BeatAnalysis,
+    options: &AutoEditOptions,
+) -> Result<ShortForgeProject, String> {
+    let clips = build_montage(sources, analysis, options)?
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to serialize video clip: {e}"))?;
+
+    let mut project = project.clone();
+    project.canvas.duration_ms = project.canvas.duration_ms.max(options.target_duration_ms);
+    project.tracks.retain(|t| t.id != AUTO_EDIT_TRACK_ID);
+    project.tracks.insert(
+        0,
+        TimelineTrack {
+            id: AUTO_EDIT_TRACK_ID.to_string(),
+            track_type: TrackType::Video,
+            clips,
+        },
+    );
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat_sync::analyzer::BeatInfo;
+    use crate::models::project::{BeatKind, Canvas, Platform, ProjectMetadata};
+
+    /// 120 BPM in 4/4 from 0 ms over `seconds`
+    fn beats(seconds: u64) -> BeatAnalysis {
+        BeatAnalysis {
+            bpm: 120.0,
+            beats: (0..seconds * 2)
+                .map(|i| BeatInfo {
+                    time_ms: i * 500,
+                    strength: 1.0,
+                    bar: (i / 4 + 1) as u32,
+                    phase: (i % 4 + 1) as u8,
+                    ..Default::default()
+                })
+                .collect(),
+            sample_rate: 44_100,
+            duration_ms: seconds * 1000,
+            beats_per_bar: 4,
+            tempo_curve: Vec::new(),
+            tempo_map: Vec::new(),
+        }
+    }
+
+    fn source(path: &str, duration_ms: u64) -> MontageSource {
+        MontageSource {
+            path: path.to_string(),
+            duration_ms,
+            motion: Vec::new(),
+        }
+    }
+
+    fn options(strategy: SegmentStrategy, target_duration_ms: u64) -> AutoEditOptions {
+        AutoEditOptions {
+            cut_on: CutOn::Beats,
+            every: 2,
+            beat_filter: BeatFilter::default(),
+            strategy,
+            seed: 7,
+            target_duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_cut_points() {
+        let analysis = beats(4);
+        assert_eq!(
+            cut_points(&analysis, CutOn::Beats, 2, &BeatFilter::default(), 3000).unwrap(),
+            [0, 1000, 2000, 3000]
+        );
+        // bars are 2 s and continue past the last beat; a cut 0.8 s before the end is dropped
+        assert_eq!(
+            cut_points(&analysis, CutOn::Downbeats, 1, &BeatFilter::default(), 8800).unwrap(),
+            [0, 2000, 4000, 6000, 8800]
+        );
+        // a cut on every snare (beats 2 and 4 of each bar)
+        let mut backbeat = analysis.clone();
+        for beat in backbeat.beats.iter_mut().filter(|b| b.phase % 2 == 0) {
+            beat.kind = BeatKind::Snare;
+        }
+        let snares = BeatFilter {
+            kinds: vec![BeatKind::Snare],
+            ..Default::default()
+        };
+        assert_eq!(
+            cut_points(&backbeat, CutOn::Beats, 1, &snares, 4000).unwrap(),
+            [0, 500, 1500, 2500, 3500, 4000]
+        );
+        assert!(cut_points(
+            &BeatAnalysis {
+                bpm: 0.0,
+                beats: Vec::new(),
+                ..analysis
+            },
+            CutOn::Beats,
+            1,
+            &BeatFilter::default(),
+            3000
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sequential_montage() {
+        let sources = [source("a.mp4", 2500), source("b.mp4", 10_000)];
+        let clips = build_montage(
+            &sources,
+            &beats(10),
+            &options(SegmentStrategy::Sequential, 6000),
+        )
+        .unwrap();
+
+        let cuts: Vec<(&str, u64, u64, u64)> = clips
+            .iter()
+            .map(|c| (c.source.as_str(), c.start_ms, c.end_ms, c.trim_start_ms))
+            .collect();
+        assert_eq!(
+            cuts,
+            [
+                ("a.mp4", 0, 1000, 0),
+                ("b.mp4", 1000, 2000, 0),
+                ("a.mp4", 2000, 3000, 1000),
+                ("b.mp4", 3000, 4000, 1000),
+                // a.mp4 has no full second left and starts over
+                ("a.mp4", 4000, 5000, 0),
+                ("b.mp4", 5000, 6000, 2000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_random_montage_is_seeded() {
+        let sources = [
+            source("a.mp4", 5000),
+            source("b.mp4", 8000),
+            source("c.mp4", 3000),
+        ];
+        let first = build_montage(
+            &sources,
+            &beats(10),
+            &options(SegmentStrategy::Random, 8000),
+        )
+        .unwrap();
+        let again = build_montage(
+            &sources,
+            &beats(10),
+            &options(SegmentStrategy::Random, 8000),
+        )
+        .unwrap();
+        let positions = |clips: &[VideoClip]| -> Vec<(String, u64)> {
+            clips
+                .iter()
+                .map(|c| (c.source.clone(), c.trim_start_ms))
+                .collect()
+        };
+        assert_eq!(positions(&first), positions(&again));
+
+        for (clip, next) in first.iter().zip(&first[1..]) {
+            assert_ne!(clip.source, next.source);
+        }
+        for clip in &first {
+            let source = sources.iter().find(|s| s.path == clip.source).unwrap();
+            assert!(clip.trim_end_ms <= source.duration_ms);
+        }
+    }
+
+    #[test]
+    fn test_motion_montage_prefers_action() {
+        let mut busy = source("busy.mp4", 10_000);
+        busy.motion = (0..250)
+            .map(|i| MotionSample {
+                time_ms: i * 40,
+                motion: if (4000..6000).contains(&(i * 40)) {
+                    0.8
+                } else {
+                    0.05
+                },
+            })
+            .collect();
+        let sources = [busy, source("calm.mp4", 10_000)];
+
+        let clips = build_montage(
+            &sources,
+            &beats(10),
+            &options(SegmentStrategy::Motion, 3000),
+        )
+        .unwrap();
+        assert_eq!(clips[0].source, "busy.mp4");
+        assert_eq!(clips[0].trim_start_ms, 4000);
+        // the next cut has to come from another source, then the rest of the action
+        assert_eq!(clips[1].source, "calm.mp4");
+        assert_eq!(
+            (clips[2].source.as_str(), clips[2].trim_start_ms),
+            ("busy.mp4", 5000)
+        );
+    }
+
+    #[test]
+    fn test_auto_edit_replaces_track() {
+        let project = ShortForgeProject {
+            version: "1.0.0".to_string(),
+            metadata: ProjectMetadata {
+                name: "montage".to_string(),
+                created_at: String::new(),
+                platform: Platform::Tiktok,
+            },
+            canvas: Canvas {
+                width: 1080,
+                height: 1920,
+                fps: 30,
+                duration_ms: 0,
+            },
+            tracks: Vec::new(),
+            beat_markers: Vec::new(),
+            beat_settings: None,
+        };
+        let sources = [source("a.mp4", 10_000)];
+        let options = options(SegmentStrategy::Sequential, 4000);
+
+        let edited = auto_edit(&project, &sources, &beats(10), &options).unwrap();
+        let edited = auto_edit(&edited, &sources, &beats(10), &options).unwrap();
+        assert_eq!(edited.tracks.len(), 1);
+        assert_eq!(edited.canvas.duration_ms, 4000);
+        assert_eq!(edited.tracks[0].id, AUTO_EDIT_TRACK_ID);
+        assert_eq!(edited.tracks[0].clips.len(), 4);
+        let last: VideoClip = serde_json::from_value(edited.tracks[0].clips[3].clone()).unwrap();
+        assert_eq!((last.start_ms, last.end_ms), (3000, 4000));
+    }
+}
@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+/// Log compression applied to magnitudes before differencing, so quiet onsets still register
+const LOG_COMPRESSION: f32 = 100.0;
+
+/// An onset strength curve, one value per analysis frame
+#[derive(Debug, Clone)]
+pub struct OnsetEnvelope {
+    pub values: Vec<f32>,
+    /// Frames per second
+    pub frame_rate: f64,
+}
+
+impl OnsetEnvelope {
+    /// Spectral flux over `bins` of the spectra: the summed increase of log-compressed
+    /// magnitude from one frame to the next. The slowly varying part (a moving average over
+    /// `smoothing_frames`) is removed and the result normalized to a peak of 1.
+    pub fn spectral_flux(
+        spectra: &[Vec<f32>],
+        bins: Range<usize>,
+        frame_rate: f64,
+        smoothing_frames: usize,
+    ) -> Self {
+        let compress = |spectrum: &[f32]| -> Vec<f32> {
+            let end = bins.end.min(spectrum.len());
+            spectrum[bins.start.min(end)..end]
+                .iter()
+                .map(|m| (1.0 + LOG_COMPRESSION * m).ln())
+                .collect()
+        };
+
+        let mut values = Vec::with_capacity(spectra.len());
+        let mut previous: Option<Vec<f32>> = None;
+        for spectrum in spectra {
+            let current = compress(spectrum);
+            let flux = previous.as_ref().map_or(0.0, |prev| {
+                current
+                    .iter()
+                    .zip(prev)
+                    .map(|(c, p)| (c - p).max(0.0))
+                    .sum()
+            });
+            values.push(flux);
+            previous = Some(current);
+        }
+
+        let mut envelope = Self { values, frame_rate };
+        envelope.remove_trend(smoothing_frames);
+        envelope
+    }
+
+    /// Frame index nearest to a time
+    pub fn frame_at(&self, time_ms: u64) -> usize {
+        (time_ms as f64 / 1000.0 * self.frame_rate).round() as usize
+    }
+
+    pub fn time_ms(&self, frame: usize) -> u64 {
+        (frame as f64 * 1000.0 / self.frame_rate).round() as u64
+    }
+
+    /// Largest value within `radius` frames of `frame`, to tolerate small timing offsets
+    pub fn local_max(&self, frame: usize, radius: usize) -> f32 {
+        let start = frame.saturating_sub(radius);
+        let end = (frame + radius + 1).min(self.values.len());
+        self.values
+            .get(start..end)
+            .and_then(|window| window.iter().copied().reduce(f32::max))
+            .unwrap_or(0.0)
+    }
+
+    fn remove_trend(&mut self, smoothing_frames: usize) {
+        let half = smoothing_frames / 2;
+        let prefix: Vec<f64> = std::iter::once(0.0)
+            .chain(self.values.iter().scan(0.0, |sum, v| {
+                *sum += *v as f64;
+                Some(*sum)
+            }))
+            .collect();
+
+        let detrended: Vec<f32> = (0..self.values.len())
+            .map(|i| {
+                let start = i.saturating_sub(half);
+                let end = (i + half + 1).min(self.values.len());
+                let mean = (prefix[end] - prefix[start]) / (end - start) as f64;
+                (self.values[i] - mean as f32).max(0.0)
+            })
+            .collect();
+
+        let peak = detrended.iter().copied().fold(0.0, f32::max);
+        self.values = if peak > 0.0 {
+            detrended.iter().map(|v| v / peak).collect()
+        } else {
+            detrended
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spectral_flux_peaks_at_onsets() {
+        // Silence, then a sudden broadband frame, then it holds steady
+        let mut spectra = vec![vec![0.0; 8]; 10];
+        for spectrum in spectra.iter_mut().skip(5) {
+            *spectrum = vec![1.0; 8];
+        }
+
+        let envelope = OnsetEnvelope::spectral_flux(&spectra, 0..8, 100.0, 4);
+        let peak = envelope
+            .values
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i);
+        assert_eq!(peak, Some(5));
+        assert_eq!(envelope.values[5], 1.0);
+        assert_eq!(envelope.values[7], 0.0);
+        assert_eq!(envelope.local_max(4, 1), 1.0);
+        assert_eq!(envelope.time_ms(5), 50);
+        assert_eq!(envelope.frame_at(50), 5);
+    }
+}
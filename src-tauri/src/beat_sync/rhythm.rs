@@ -0,0 +1,225 @@
+use crate::beat_sync::accent::{self, DrumBands};
+use crate::beat_sync::analyzer::{BeatAnalysis, BeatInfo};
+use crate::beat_sync::onset::OnsetEnvelope;
+use crate::beat_sync::spectrum::stft_magnitudes;
+use crate::beat_sync::{tempo, tracker};
+
+pub const FRAME_SIZE: usize = 2048;
+pub const HOP_SIZE: usize = 512;
+
+/// Upper edge of the band used for downbeat (kick drum) detection
+const LOW_BAND_HZ: f64 = 200.0;
+
+/// Window of the moving average removed from the onset envelopes
+const SMOOTHING_SECONDS: f64 = 0.5;
+
+/// Tempo deviation that starts a new tempo map segment
+const TEMPO_TOLERANCE: f64 = 0.04;
+
+/// Onset envelopes of STFT magnitudes: full band and low band (below 200 Hz)
+pub fn onset_envelopes(spectra: &[Vec<f32>], sample_rate: u32) -> (OnsetEnvelope, OnsetEnvelope) {
+    let frame_rate = sample_rate as f64 / HOP_SIZE as f64;
+    let smoothing = (SMOOTHING_SECONDS * frame_rate).round() as usize;
+    let low_bins = (LOW_BAND_HZ * FRAME_SIZE as f64 / sample_rate.max(1) as f64).ceil() as usize;
+
+    (
+        OnsetEnvelope::spectral_flux(spectra, 0..FRAME_SIZE / 2 + 1, frame_rate, smoothing),
+        OnsetEnvelope::spectral_flux(spectra, 1..low_bins.max(2), frame_rate, smoothing),
+    )
+}
+
+/// Analyze the rhythm of mono samples.
+///
+/// Onsets are detected by spectral flux, the tempo is estimated by autocorrelation of the
+/// onset envelope, beats are placed by dynamic programming and the downbeat phase is chosen
+/// from low-frequency onsets. The tempo is re-estimated along the track so beats follow
+/// tempo changes. Each beat gets its bar number, beat-in-bar phase, accent strength and drum
+/// kind; the reported BPM is the median beat interval and `tempo_map` splits the track into
+/// steady-tempo segments.
+pub fn analyze_rhythm(samples: &[f32], sample_rate: u32, beats_per_bar: u8) -> BeatAnalysis {
+    let duration_ms = if sample_rate > 0 {
+        samples.len() as u64 * 1000 / sample_rate as u64
+    } else {
+        0
+    };
+    let empty = BeatAnalysis {
+        bpm: 0.0,
+        beats: Vec::new(),
+        sample_rate,
+        duration_ms,
+        beats_per_bar,
+        tempo_curve: Vec::new(),
+        tempo_map: Vec::new(),
+    };
+    if sample_rate == 0 {
+        return empty;
+    }
+
+    let spectra = stft_magnitudes(samples, FRAME_SIZE, HOP_SIZE);
+    let (full, low) = onset_envelopes(&spectra, sample_rate);
+    let Some(estimate) = tempo::estimate_tempo(&full.values, full.frame_rate) else {
+        return empty;
+    };
+
+    let bpm = tempo::local_tempo(&full.values, full.frame_rate, estimate);
+    let frames = tracker::track_beats(&full, &bpm, tracker::DEFAULT_TIGHTNESS);
+    let offset = tracker::downbeat_offset(&frames, &low, &full, beats_per_bar as usize);
+
+    let mut beats: Vec<BeatInfo> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, &frame)| {
+            let (bar, phase) = tracker::bar_position(i, offset, beats_per_bar as usize);
+            BeatInfo {
+                time_ms: full.time_ms(frame).min(duration_ms),
+                bar,
+                phase,
+                ..Default::default()
+            }
+        })
+        .collect();
+    let bands = DrumBands::new(&spectra, sample_rate, FRAME_SIZE, full.frame_rate);
+    accent::accent_beats(&mut beats, &frames, &full, &bands);
+
+    let times: Vec<u64> = beats.iter().map(|b| b.time_ms).collect();
+    let intervals: Vec<u64> = times.windows(2).map(|w| w[1] - w[0]).collect();
+
+    BeatAnalysis {
+        bpm: tempo::median_bpm(&intervals).unwrap_or(estimate),
+        tempo_curve: tempo::tempo_curve(&times),
+        tempo_map: tempo::tempo_map(&times, duration_ms, TEMPO_TOLERANCE),
+        beats,
+        ..empty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::project::BeatKind;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 22_050;
+
+    /// 120 BPM: a low kick on every fourth beat starting with the second, hi-hat-like
+    /// clicks on the others
+    fn drum_loop(seconds: usize) -> Vec<f32> {
+        let mut samples = vec![0.0f32; SAMPLE_RATE as usize * seconds];
+        let beat_samples = SAMPLE_RATE as usize / 2;
+        for (beat, start) in (0..samples.len()).step_by(beat_samples).enumerate() {
+            let kick = beat % 4 == 1;
+            let length = if kick { 2000 } else { 400 };
+            for i in 0..length.min(samples.len() - start) {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let decay = 1.0 - i as f32 / length as f32;
+                samples[start + i] = if kick {
+                    0.9 * decay * (2.0 * PI * 60.0 * t).sin()
+                } else {
+                    0.4 * decay * (2.0 * PI * 3000.0 * t).sin() * (2.0 * PI * 4700.0 * t).sin()
+                };
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_analyze_rhythm_drum_loop() {
+        let analysis = analyze_rhythm(&drum_loop(8), SAMPLE_RATE, 4);
+        assert!((analysis.bpm - 120.0).abs() < 3.0, "bpm {}", analysis.bpm);
+        assert!(analysis.beats.len() >= 14, "{} beats", analysis.beats.len());
+
+        for beat in &analysis.beats {
+            // beats land within a frame or two of the 500 ms grid
+            let distance = (beat.time_ms % 500).min(500 - beat.time_ms % 500);
+            assert!(distance < 60, "beat at {}", beat.time_ms);
+            // kicks (500, 2500, ... ms) are the downbeats
+            let kick = (beat.time_ms + 100) / 500 % 4 == 1;
+            assert_eq!(beat.phase == 1, kick, "beat at {}", beat.time_ms);
+        }
+        assert!(analysis
+            .tempo_curve
+            .iter()
+            .all(|p| (p.bpm - 120.0).abs() < 10.0));
+    }
+
+    #[test]
+    fn test_beat_accents_and_kinds() {
+        // 120 BPM: a hi-hat on every beat, with a kick on the first and a snare on the
+        // second of each bar
+        let mut samples = vec![0.0f32; SAMPLE_RATE as usize * 8];
+        let mut noise = 1u32;
+        for beat in 0..16 {
+            let start = beat * SAMPLE_RATE as usize / 2;
+            for i in 0..3000 {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                noise = noise.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let white = (noise >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                let hihat: f32 = [7000.0, 8300.0, 9100.0, 10_200.0]
+                    .iter()
+                    .map(|hz| 0.1 * (-(i as f32) / 300.0).exp() * (2.0 * PI * hz * t).sin())
+                    .sum();
+                samples[start + i] = hihat
+                    + match beat % 4 {
+                        0 => 0.9 * (1.0 - i as f32 / 3000.0) * (2.0 * PI * 60.0 * t).sin(),
+                        1 => 0.6 * (-(i as f32) / 600.0).exp() * white,
+                        _ => 0.0,
+                    };
+            }
+        }
+
+        let analysis = analyze_rhythm(&samples, SAMPLE_RATE, 4);
+        assert!(analysis.beats.len() >= 14, "{} beats", analysis.beats.len());
+        let strongest = analysis
+            .beats
+            .iter()
+            .map(|b| b.strength)
+            .fold(0.0, f64::max);
+        assert_eq!(strongest, 1.0);
+        for beat in &analysis.beats {
+            let expected = match (beat.time_ms + 100) / 500 % 4 {
+                0 => BeatKind::Kick,
+                1 => BeatKind::Snare,
+                _ => BeatKind::HiHat,
+            };
+            assert_eq!(beat.kind, expected, "beat at {}", beat.time_ms);
+            if expected == BeatKind::HiHat {
+                assert!(
+                    beat.strength < 0.5,
+                    "hi-hat at {}: {}",
+                    beat.time_ms,
+                    beat.strength
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_rhythm_tempo_change() {
+        // clicks at 120 BPM for 10 s, then at 150 BPM for 10 s
+        let mut samples = vec![0.0f32; SAMPLE_RATE as usize * 20];
+        let clicks = (0..20)
+            .map(|i| i * 500)
+            .chain((1..25).map(|i| 10_000 + i * 400));
+        for time_ms in clicks {
+            let start = time_ms as usize * SAMPLE_RATE as usize / 1000;
+            for i in 0..400.min(samples.len() - start) {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                samples[start + i] = 0.5 * (1.0 - i as f32 / 400.0) * (2.0 * PI * 2500.0 * t).sin();
+            }
+        }
+
+        let analysis = analyze_rhythm(&samples, SAMPLE_RATE, 4);
+        let map = &analysis.tempo_map;
+        assert_eq!(map.len(), 2, "{map:?}");
+        assert!((map[0].bpm - 120.0).abs() < 3.0, "{map:?}");
+        assert!((map[1].bpm - 150.0).abs() < 3.0, "{map:?}");
+        assert!((map[1].start_ms as i64 - 10_000).abs() < 1000, "{map:?}");
+    }
+
+    #[test]
+    fn test_analyze_rhythm_silence() {
+        let analysis = analyze_rhythm(&vec![0.0; SAMPLE_RATE as usize * 2], SAMPLE_RATE, 4);
+        assert!(analysis.beats.is_empty());
+        assert_eq!(analysis.duration_ms, 2000);
+    }
+}
@@ -0,0 +1,655 @@
+use crate::audio_engine::envelope::slice_envelope;
+use crate::models::project::ShortForgeProject;
+use crate::models::timeline::{AudioClip, GainPoint, TimelineTrack, TrackType, VideoClip};
+use serde::{Deserialize, Serialize};
+
+/// A span of media time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceOptions {
+    /// Windows quieter than this RMS level (dBFS) count as silent
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f32,
+    /// Shorter pauses are kept
+    #[serde(default = "default_min_silence_ms")]
+    pub min_silence_ms: u64,
+    /// Audio kept on each side of a cut so speech isn't clipped
+    #[serde(default = "default_padding_ms")]
+    pub padding_ms: u64,
+    /// RMS analysis window
+    #[serde(default = "default_window_ms")]
+    pub window_ms: u64,
+}
+
+fn default_threshold_db() -> f32 {
+    -40.0
+}
+
+fn default_min_silence_ms() -> u64 {
+    500
+}
+
+fn default_padding_ms() -> u64 {
+    100
+}
+
+fn default_window_ms() -> u64 {
+    20
+}
+
+impl Default for SilenceOptions {
+    fn default() -> Self {
+        Self {
+            threshold_db: default_threshold_db(),
+            min_silence_ms: default_min_silence_ms(),
+            padding_ms: default_padding_ms(),
+            window_ms: default_window_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceAnalysis {
+    /// Silent regions at least `min_silence_ms` long
+    pub silences: Vec<TimeRange>,
+    /// What remains after cutting the silences minus their padding
+    pub keep: Vec<TimeRange>,
+    pub duration_ms: u64,
+}
+
+/// Find silent and low-energy regions in mono samples using windowed RMS and derive the
+/// ranges to keep.
+pub fn detect_silence(
+    samples: &[f32],
+    sample_rate: u32,
+    options: &SilenceOptions,
+) -> SilenceAnalysis {
+    let duration_ms = if sample_rate > 0 {
+        samples.len() as u64 * 1000 / sample_rate as u64
+    } else {
+        0
+    };
+    let window = (sample_rate as u64 * options.window_ms.max(1) / 1000).max(1) as usize;
+    let to_ms = |sample: usize| (sample as u64 * 1000 / sample_rate.max(1) as u64).min(duration_ms);
+
+    let mut silences = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for (i, chunk) in samples.chunks(window).enumerate() {
+        let sum_sq: f32 = chunk.iter().map(|s| s * s).sum();
+        let rms = (sum_sq / chunk.len() as f32).sqrt();
+        let db = 20.0 * rms.max(1e-10).log10();
+
+        match (db < options.threshold_db, run_start) {
+            (true, None) => run_start = Some(i * window),
+            (false, Some(start)) => {
+                silences.push(TimeRange {
+                    start_ms: to_ms(start),
+                    end_ms: to_ms(i * window),
+                });
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start {
+        silences.push(TimeRange {
+            start_ms: to_ms(start),
+            end_ms: duration_ms,
+        });
+    }
+    silences.retain(|s| s.end_ms - s.start_ms >= options.min_silence_ms);
+
+    let keep = keep_ranges(&silences, duration_ms, options.padding_ms);
+    SilenceAnalysis {
+        silences,
+        keep,
+        duration_ms,
+    }
+}
+
+/// Complement of the silences within `0..duration_ms`. Each silence is shrunk by
+/// `padding_ms` on the sides that touch speech before it is cut.
+pub fn keep_ranges(silences: &[TimeRange], duration_ms: u64, padding_ms: u64) -> Vec<TimeRange> {
+    let mut keep = Vec::new();
+    let mut cursor = 0;
+    for silence in silences {
+        let cut_start = if silence.start_ms == 0 {
+            0
+        } else {
+            silence.start_ms + padding_ms
+        };
+        let cut_end = if silence.end_ms >= duration_ms {
+            duration_ms
+        } else {
+            silence.end_ms.saturating_sub(padding_ms)
+        };
+        if cut_end <= cut_start || cut_start < cursor {
+            continue;
+        }
+        if cut_start > cursor {
+            keep.push(TimeRange {
+                start_ms: cursor,
+                end_ms: cut_start,
+            });
+        }
+        cursor = cut_end;
+    }
+    if cursor < duration_ms {
+        keep.push(TimeRange {
+            start_ms: cursor,
+            end_ms: duration_ms,
+        });
+    }
+    keep
+}
+
+/// Old-to-new timeline time of a jump cut: removed spans close up, and everything after
+/// them moves earlier by their length
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeMap {
+    /// Removed timeline spans, sorted and disjoint
+    removed: Vec<TimeRange>,
+}
+
+impl TimeMap {
+    /// Timeline spans of clips playing the cut source whose media is outside the keep
+    /// ranges (sorted by start). Each clip is its timeline span and the source position it
+    /// starts at.
+    pub fn from_cuts(clips: &[(TimeRange, u64)], keep: &[TimeRange]) -> Self {
+        let mut removed = Vec::new();
+        for (span, trim_start_ms) in clips {
+            let trim_end_ms = trim_start_ms + span.end_ms.saturating_sub(span.start_ms);
+            let to_timeline = |source_ms: u64| span.start_ms + source_ms - trim_start_ms;
+            let mut cursor = *trim_start_ms;
+            for range in keep {
+                let kept_start = range.start_ms.clamp(cursor, trim_end_ms);
+                if kept_start > cursor {
+                    removed.push(TimeRange {
+                        start_ms: to_timeline(cursor),
+                        end_ms: to_timeline(kept_start),
+                    });
+                }
+                cursor = cursor.max(range.end_ms.min(trim_end_ms));
+            }
+            if trim_end_ms > cursor {
+                removed.push(TimeRange {
+                    start_ms: to_timeline(cursor),
+                    end_ms: span.end_ms,
+                });
+            }
+        }
+
+        removed.sort_by_key(|r| r.start_ms);
+        let mut merged: Vec<TimeRange> = Vec::with_capacity(removed.len());
+        for range in removed {
+            match merged.last_mut() {
+                Some(last) if range.start_ms <= last.end_ms => {
+                    last.end_ms = last.end_ms.max(range.end_ms)
+                }
+                _ => merged.push(range),
+            }
+        }
+        Self { removed: merged }
+    }
+
+    /// New time of `time_ms`; times inside a removed span land on the cut
+    pub fn map(&self, time_ms: u64) -> u64 {
+        let removed_before: u64 = self
+            .removed
+            .iter()
+            .take_while(|range| range.start_ms < time_ms)
+            .map(|range| range.end_ms.min(time_ms) - range.start_ms)
+            .sum();
+        time_ms - removed_before
+    }
+
+    /// Whether `time_ms` falls in a removed span
+    pub fn is_removed(&self, time_ms: u64) -> bool {
+        self.removed
+            .iter()
+            .any(|range| range.start_ms <= time_ms && time_ms < range.end_ms)
+    }
+}
+
+/// Kept parts of a clip's source window `trim_start_ms..trim_end_ms`
+fn kept_parts(
+    keep: &[TimeRange],
+    trim_start_ms: u64,
+    trim_end_ms: u64,
+) -> impl Iterator<Item = (u64, u64)> + '_ {
+    keep.iter().filter_map(move |range| {
+        let start_ms = range.start_ms.max(trim_start_ms);
+        let end_ms = range.end_ms.min(trim_end_ms);
+        (end_ms > start_ms).then_some((start_ms, end_ms))
+    })
+}
+
+/// Id of the `n`th (0-based) part of a split clip
+fn part_id(id: &str, n: usize) -> String {
+    if n == 0 {
+        id.to_string()
+    } else {
+        format!("{id}-{}", n + 1)
+    }
+}
+
+/// Cut video clips along a time map: clips of `source` are split into one clip per kept
+/// part of their trim window, other clips move with the timeline
+fn cut_video_clips(
+    clips: &[VideoClip],
+    source: &str,
+    keep: &[TimeRange],
+    map: &TimeMap,
+) -> Vec<VideoClip> {
+    let mut result = Vec::new();
+    for clip in clips {
+        if clip.source != source {
+            let start_ms = map.map(clip.start_ms);
+            let end_ms = map.map(clip.end_ms);
+            if end_ms > start_ms {
+                result.push(VideoClip {
+                    start_ms,
+                    end_ms,
+                    ..clip.clone()
+                });
+            }
+            continue;
+        }
+
+        for (n, (trim_start_ms, trim_end_ms)) in
+            kept_parts(keep, clip.trim_start_ms, clip.trim_end_ms).enumerate()
+        {
+            let start_ms = map.map(clip.start_ms + trim_start_ms - clip.trim_start_ms);
+            result.push(VideoClip {
+                id: part_id(&clip.id, n),
+                source: clip.source.clone(),
+                start_ms,
+                end_ms: start_ms + trim_end_ms - trim_start_ms,
+                trim_start_ms,
+                trim_end_ms,
+                gain_envelope: slice_envelope(
+                    &clip.gain_envelope,
+                    trim_start_ms - clip.trim_start_ms,
+                    trim_end_ms - clip.trim_start_ms,
+                ),
+            });
+        }
+    }
+    result.sort_by_key(|c| c.start_ms);
+    result
+}
+
+/// Cut an audio clip of the cut source like its video: one clip per kept part
+fn cut_audio_clip(clip: &AudioClip, keep: &[TimeRange], map: &TimeMap) -> Vec<AudioClip> {
+    let trim_end_ms = clip.trim_start_ms + clip.end_ms.saturating_sub(clip.start_ms);
+    kept_parts(keep, clip.trim_start_ms, trim_end_ms)
+        .enumerate()
+        .map(|(n, (trim_start_ms, part_end_ms))| {
+            let start_ms = map.map(clip.start_ms + trim_start_ms - clip.trim_start_ms);
+            AudioClip {
+                id: part_id(&clip.id, n),
+                start_ms,
+                end_ms: start_ms + part_end_ms - trim_start_ms,
+                trim_start_ms,
+                duck_envelope: remap_envelope(&clip.duck_envelope, map),
+                gain_envelope: slice_envelope(
+                    &clip.gain_envelope,
+                    trim_start_ms - clip.trim_start_ms,
+                    part_end_ms - clip.trim_start_ms,
+                ),
+                ..clip.clone()
+            }
+        })
+        .collect()
+}
+
+/// A timeline-time envelope moved along a time map
+fn remap_envelope(points: &[GainPoint], map: &TimeMap) -> Vec<GainPoint> {
+    points
+        .iter()
+        .map(|p| GainPoint {
+            time_ms: map.map(p.time_ms),
+            ..*p
+        })
+        .collect()
+}
+
+/// Move a clip kept as JSON along a time map, in place, keeping fields the models don't
+/// know (editor labels, colors). Reads the snake_case and the editor's camelCase names.
+/// Returns false when nothing of the clip is left.
+fn remap_clip_value(clip: &mut serde_json::Value, map: &TimeMap) -> bool {
+    fn remap_fields(value: &mut serde_json::Value, keys: &[&str], map: &TimeMap) {
+        for key in keys {
+            if let Some(field) = value.get_mut(*key) {
+                if let Some(time_ms) = field.as_u64() {
+                    *field = map.map(time_ms).into();
+                }
+            }
+        }
+    }
+
+    remap_fields(clip, &["start_ms", "startMs", "end_ms", "endMs"], map);
+    for (key, fields) in [
+        ("words", &["start_ms", "end_ms"][..]),
+        ("duck_envelope", &["time_ms"][..]),
+        ("duckEnvelope", &["time_ms"][..]),
+    ] {
+        if let Some(items) = clip.get_mut(key).and_then(|v| v.as_array_mut()) {
+            for item in items {
+                remap_fields(item, fields, map);
+            }
+        }
+    }
+
+    let time = |keys: [&str; 2]| keys.iter().find_map(|key| clip.get(*key)?.as_u64());
+    match (time(["start_ms", "startMs"]), time(["end_ms", "endMs"])) {
+        (Some(start_ms), Some(end_ms)) => end_ms > start_ms,
+        _ => true,
+    }
+}
+
+/// Jump-cut clips of `source` down to the keep ranges (in source time).
+///
+/// Each clip is split into one clip per kept part of its trim window; the first part keeps
+/// the clip id and the others get `-2`, `-3`, ... suffixes. Removed time is closed up, so
+/// later clips (including clips of other sources) move earlier. Gaps that already existed
+/// between clips are preserved.
+pub fn apply_keep_ranges(clips: &[VideoClip], source: &str, keep: &[TimeRange]) -> Vec<VideoClip> {
+    let mut keep = keep.to_vec();
+    keep.sort_by_key(|r| r.start_ms);
+    let cut: Vec<(TimeRange, u64)> = clips
+        .iter()
+        .filter(|clip| clip.source == source)
+        .map(|clip| (video_span(clip), clip.trim_start_ms))
+        .collect();
+    cut_video_clips(clips, source, &keep, &TimeMap::from_cuts(&cut, &keep))
+}
+
+fn video_span(clip: &VideoClip) -> TimeRange {
+    TimeRange {
+        start_ms: clip.start_ms,
+        end_ms: clip.end_ms,
+    }
+}
+
+fn parse_clips<T: serde::de::DeserializeOwned>(track: &TimelineTrack) -> Result<Vec<T>, String> {
+    track
+        .clips
+        .iter()
+        .map(|value| serde_json::from_value(value.clone()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid clip in track {}: {e}", track.id))
+}
+
+fn plays_source(clip: &serde_json::Value, source: &str) -> bool {
+    clip.get("source").and_then(|v| v.as_str()) == Some(source)
+}
+
+/// Apply keep ranges to a project. The video and audio clips of `source` are cut; the
+/// removed time is closed up on every track, so telops, other audio and beat markers stay
+/// in sync with the footage around them.
+pub fn apply_jump_cuts(
+    project: &ShortForgeProject,
+    source: &str,
+    keep: &[TimeRange],
+) -> Result<ShortForgeProject, String> {
+    let mut keep = keep.to_vec();
+    keep.sort_by_key(|r| r.start_ms);
+
+    // One time map from every clip that plays the source, whatever track it is on
+    let mut cut = Vec::new();
+    for track in &project.tracks {
+        match track.track_type {
+            TrackType::Video => {
+                let clips: Vec<VideoClip> = parse_clips(track)?;
+                cut.extend(
+                    clips
+                        .iter()
+                        .filter(|clip| clip.source == source)
+                        .map(|clip| (video_span(clip), clip.trim_start_ms)),
+                );
+            }
+            TrackType::Audio => {
+                for value in track.clips.iter().filter(|v| plays_source(v, source)) {
+                    let clip: AudioClip = serde_json::from_value(value.clone())
+                        .map_err(|e| format!("Invalid clip in track {}: {e}", track.id))?;
+                    let span = TimeRange {
+                        start_ms: clip.start_ms,
+                        end_ms: clip.end_ms,
+                    };
+                    cut.push((span, clip.trim_start_ms));
+                }
+            }
+            TrackType::Telop => {}
+        }
+    }
+    let map = TimeMap::from_cuts(&cut, &keep);
+
+    let mut project = project.clone();
+    for track in &mut project.tracks {
+        track.clips = match track.track_type {
+            TrackType::Video => {
+                let clips: Vec<VideoClip> = parse_clips(track)?;
+                cut_video_clips(&clips, source, &keep, &map)
+                    .iter()
+                    .map(serde_json::to_value)
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("Failed to serialize video clip: {e}"))?
+            }
+            TrackType::Audio | TrackType::Telop => {
+                let mut clips = Vec::new();
+                for mut value in std::mem::take(&mut track.clips) {
+                    if track.track_type == TrackType::Audio && plays_source(&value, source) {
+                        let clip: AudioClip = serde_json::from_value(value)
+                            .map_err(|e| format!("Invalid clip in track {}: {e}", track.id))?;
+                        for part in cut_audio_clip(&clip, &keep, &map) {
+                            clips.push(
+                                serde_json::to_value(part)
+                                    .map_err(|e| format!("Failed to serialize audio clip: {e}"))?,
+                            );
+                        }
+                    } else if remap_clip_value(&mut value, &map) {
+                        clips.push(value);
+                    }
+                }
+                clips
+            }
+        };
+    }
+
+    project
+        .beat_markers
+        .retain(|marker| !map.is_removed(marker.time_ms));
+    for marker in &mut project.beat_markers {
+        marker.time_ms = map.map(marker.time_ms);
+    }
+    project.canvas.duration_ms = map.map(project.canvas.duration_ms);
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_ms: u64, end_ms: u64) -> TimeRange {
+        TimeRange { start_ms, end_ms }
+    }
+
+    fn clip(id: &str, source: &str, start_ms: u64, end_ms: u64, trim_start_ms: u64) -> VideoClip {
+        VideoClip {
+            id: id.to_string(),
+            source: source.to_string(),
+            start_ms,
+            end_ms,
+            trim_start_ms,
+            trim_end_ms: trim_start_ms + (end_ms - start_ms),
+            gain_envelope: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_detect_silence() {
+        // 1 kHz sample rate: 0.5s silence, 1s tone, 1s silence, 0.5s tone, 0.2s silence
+        let mut samples = vec![0.0; 500];
+        samples.extend((0..1000).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }));
+        samples.extend(vec![0.001; 1000]);
+        samples.extend((0..500).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }));
+        samples.extend(vec![0.0; 200]);
+
+        let analysis = detect_silence(&samples, 1000, &SilenceOptions::default());
+        assert_eq!(analysis.duration_ms, 3200);
+        assert_eq!(analysis.silences, [range(0, 500), range(1500, 2500)]);
+        assert_eq!(analysis.keep, [range(400, 1600), range(2400, 3200)]);
+    }
+
+    #[test]
+    fn test_keep_ranges_padding() {
+        assert_eq!(
+            keep_ranges(&[range(1000, 1150)], 3000, 100),
+            [range(0, 3000)]
+        );
+        assert_eq!(
+            keep_ranges(&[range(1000, 2000), range(2800, 3000)], 3000, 100),
+            [range(0, 1100), range(1900, 2900)]
+        );
+        assert!(keep_ranges(&[range(0, 3000)], 3000, 100).is_empty());
+    }
+
+    #[test]
+    fn test_apply_keep_ranges_closes_gaps() {
+        let clips = vec![
+            clip("b", "broll.mp4", 11_000, 12_000, 0),
+            clip("a", "talk.mp4", 1000, 11_000, 2000),
+        ];
+        let keep = [range(0, 3000), range(5000, 6000), range(8000, 20_000)];
+
+        let result = apply_keep_ranges(&clips, "talk.mp4", &keep);
+        let ids: Vec<&str> = result.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["a", "a-2", "a-3", "b"]);
+        assert_eq!(
+            (
+                result[0].start_ms,
+                result[0].end_ms,
+                result[0].trim_start_ms
+            ),
+            (1000, 2000, 2000)
+        );
+        assert_eq!(
+            (
+                result[1].start_ms,
+                result[1].trim_start_ms,
+                result[1].trim_end_ms
+            ),
+            (2000, 5000, 6000)
+        );
+        assert_eq!(
+            (result[2].start_ms, result[2].end_ms, result[2].trim_end_ms),
+            (3000, 7000, 12_000)
+        );
+        assert_eq!((result[3].start_ms, result[3].end_ms), (7000, 8000));
+    }
+
+    #[test]
+    fn test_time_map() {
+        // source 2000..12000 at 1000 on the timeline, media 3000..5000 and 6000..8000 cut
+        let keep = [range(0, 3000), range(5000, 6000), range(8000, 20_000)];
+        let map = TimeMap::from_cuts(&[(range(1000, 11_000), 2000)], &keep);
+        assert_eq!(map.removed, [range(2000, 4000), range(5000, 7000)]);
+        assert_eq!(map.map(500), 500);
+        assert_eq!(map.map(3000), 2000);
+        assert_eq!(map.map(4500), 2500);
+        assert_eq!(map.map(12_000), 8000);
+        assert!(map.is_removed(2000));
+        assert!(!map.is_removed(4000));
+    }
+
+    #[test]
+    fn test_apply_jump_cuts_moves_every_track() {
+        let project: ShortForgeProject = serde_json::from_value(serde_json::json!({
+            "version": "1.0.0",
+            "metadata": { "name": "test", "created_at": "", "platform": "tiktok" },
+            "canvas": { "width": 1080, "height": 1920, "fps": 30, "duration_ms": 10000 },
+            "tracks": [
+                {
+                    "id": "video-1",
+                    "type": "video",
+                    "clips": [{
+                        "id": "v", "source": "talk.mp4", "start_ms": 0, "end_ms": 10000,
+                        "trim_start_ms": 0, "trim_end_ms": 10000
+                    }]
+                },
+                {
+                    "id": "telop-1",
+                    "type": "telop",
+                    "clips": [
+                        { "id": "t1", "startMs": 500, "endMs": 1500, "label": "hi", "color": "#fff" },
+                        { "id": "t2", "startMs": 2200, "endMs": 2800, "label": "um" },
+                        {
+                            "id": "t3", "start_ms": 6000, "end_ms": 7000, "text": "bye",
+                            "words": [{ "text": "bye", "start_ms": 6200, "end_ms": 6800 }]
+                        }
+                    ]
+                },
+                {
+                    "id": "audio-1",
+                    "type": "audio",
+                    "clips": [
+                        {
+                            "id": "bgm", "source": "music.mp3", "start_ms": 0, "end_ms": 10000,
+                            "volume": 0.5,
+                            "duck_envelope": [{ "time_ms": 5000, "gain_db": -12.0 }]
+                        },
+                        {
+                            "id": "dialogue", "source": "talk.mp4", "start_ms": 0,
+                            "end_ms": 10000, "volume": 1.0
+                        }
+                    ]
+                }
+            ],
+            "beat_markers": [
+                { "time_ms": 1000, "strength": 1.0 },
+                { "time_ms": 2500, "strength": 1.0 },
+                { "time_ms": 8000, "strength": 1.0 }
+            ]
+        }))
+        .unwrap();
+
+        // 2000..4000 of the talk is cut
+        let keep = [range(0, 2000), range(4000, 10_000)];
+        let result = apply_jump_cuts(&project, "talk.mp4", &keep).unwrap();
+
+        let video: Vec<VideoClip> = parse_clips(&result.tracks[0]).unwrap();
+        let spans: Vec<(u64, u64)> = video.iter().map(|c| (c.start_ms, c.end_ms)).collect();
+        assert_eq!(spans, [(0, 2000), (2000, 8000)]);
+
+        // telops keep their editor fields; one inside the cut is dropped
+        let telops = &result.tracks[1].clips;
+        assert_eq!(telops.len(), 2);
+        assert_eq!(telops[0]["startMs"], 500);
+        assert_eq!(telops[0]["color"], "#fff");
+        assert_eq!(
+            (&telops[1]["start_ms"], &telops[1]["end_ms"]),
+            (&4000.into(), &5000.into())
+        );
+        assert_eq!(telops[1]["words"][0]["start_ms"], 4200);
+
+        // the BGM moves with the timeline, the dialogue is cut like the video
+        let audio: Vec<AudioClip> = parse_clips(&result.tracks[2]).unwrap();
+        assert_eq!((audio[0].start_ms, audio[0].end_ms), (0, 8000));
+        assert_eq!(audio[0].duck_envelope[0].time_ms, 3000);
+        let dialogue: Vec<(u64, u64, u64)> = audio[1..]
+            .iter()
+            .map(|c| (c.start_ms, c.end_ms, c.trim_start_ms))
+            .collect();
+        assert_eq!(dialogue, [(0, 2000, 0), (2000, 8000, 4000)]);
+
+        let markers: Vec<u64> = result.beat_markers.iter().map(|m| m.time_ms).collect();
+        assert_eq!(markers, [1000, 6000]);
+        assert_eq!(result.canvas.duration_ms, 8000);
+    }
+}
@@ -0,0 +1,65 @@
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+/// Periodic Hann window
+pub fn hann_window(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+/// Magnitude spectra of Hann-windowed frames.
+///
+/// Frame `i` is centered on sample `i * hop_size`, zero-padded past either end of the audio.
+/// Each spectrum has `frame_size / 2 + 1` bins, bin `k` being `k * sample_rate / frame_size` Hz.
+pub fn stft_magnitudes(samples: &[f32], frame_size: usize, hop_size: usize) -> Vec<Vec<f32>> {
+    if samples.is_empty() || frame_size == 0 || hop_size == 0 {
+        return Vec::new();
+    }
+
+    let window = hann_window(frame_size);
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(frame_size);
+    let mut buffer = vec![Complex::new(0.0, 0.0); frame_size];
+
+    (0..samples.len())
+        .step_by(hop_size)
+        .map(|center| {
+            for (i, value) in buffer.iter_mut().enumerate() {
+                let sample = (center + i)
+                    .checked_sub(frame_size / 2)
+                    .and_then(|index| samples.get(index))
+                    .copied()
+                    .unwrap_or(0.0);
+                *value = Complex::new(sample * window[i], 0.0);
+            }
+            fft.process(&mut buffer);
+            buffer[..frame_size / 2 + 1]
+                .iter()
+                .map(|c| c.norm())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stft_peak_bin() {
+        // 1 kHz tone at 8 kHz with 256-sample frames: bin width 31.25 Hz, peak at bin 32
+        let samples: Vec<f32> = (0..2048)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 8000.0).sin())
+            .collect();
+        let spectra = stft_magnitudes(&samples, 256, 128);
+        assert_eq!(spectra.len(), 16);
+        assert_eq!(spectra[0].len(), 129);
+
+        let peak = spectra[4]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(bin, _)| bin);
+        assert_eq!(peak, Some(32));
+    }
+}
@@ -0,0 +1,576 @@
+use crate::beat_sync::analyzer::BeatAnalysis;
+use crate::beat_sync::rhythm::{analyze_rhythm, FRAME_SIZE, HOP_SIZE};
+use crate::beat_sync::spectrum::stft_magnitudes;
+use crate::models::timeline::AudioClip;
+use serde::{Deserialize, Serialize};
+
+/// Log-spaced bands of the per-bar timbre features
+const BANDS: usize = 16;
+const LOWEST_BAND_HZ: f64 = 40.0;
+const HIGHEST_BAND_HZ: f64 = 11_000.0;
+
+/// Half width of the checkerboard kernel, in bars
+const NOVELTY_RADIUS: usize = 4;
+
+/// Shortest section, in bars
+pub const MIN_SECTION_BARS: usize = 4;
+
+/// Bar length used when no downbeats were found
+const FALLBACK_BAR_MS: u64 = 2000;
+
+/// Relative energy (0–1) from which a section counts as loud
+const LOUD_ENERGY: f64 = 0.6;
+
+/// Relative energy under which a section counts as quiet; a loud section right after a
+/// quiet one is a drop
+const QUIET_ENERGY: f64 = 0.25;
+
+/// Novelty peaks weaker than this fraction of the strongest one are not section boundaries
+const PEAK_THRESHOLD: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionLabel {
+    Intro,
+    Verse,
+    Chorus,
+    Drop,
+    Outro,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub label: SectionLabel,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Index of the first bar in `StructureAnalysis::bars`
+    pub start_bar: usize,
+    pub bar_count: usize,
+    /// Mean loudness relative to the rest of the track (0.0–1.0)
+    pub energy: f64,
+    /// How closely the section's bars recur elsewhere in the track (0.0–1.0)
+    pub repetition: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructureAnalysis {
+    pub bpm: f64,
+    pub duration_ms: u64,
+    /// Bar boundaries: bar `i` spans `bars[i]..bars[i + 1]`
+    pub bars: Vec<u64>,
+    /// Per-bar relative loudness (0.0–1.0)
+    pub bar_energy: Vec<f64>,
+    /// Per-bar repetition score (0.0–1.0)
+    pub bar_repetition: Vec<f64>,
+    pub sections: Vec<Section>,
+}
+
+/// A bar-aligned excerpt proposed as the highlight of a track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub bar_count: usize,
+    pub score: f64,
+}
+
+/// Split mono samples into sections.
+///
+/// Bars come from the detected downbeats. Each bar gets its loudness and a timbre vector of
+/// log band energies; a cosine self-similarity matrix of the timbre vectors is scanned with
+/// a checkerboard kernel (Foote novelty), and section boundaries are placed at the strongest
+/// novelty peaks, at least `MIN_SECTION_BARS` apart. Sections are then labelled from their
+/// loudness, their position and how much they repeat. Sections are whole bars, so they start
+/// on downbeats.
+pub fn analyze_structure(
+    samples: &[f32],
+    sample_rate: u32,
+    beats_per_bar: u8,
+) -> StructureAnalysis {
+    let rhythm = analyze_rhythm(samples, sample_rate, beats_per_bar);
+    let bars = bar_boundaries(&rhythm);
+    let empty = StructureAnalysis {
+        bpm: rhythm.bpm,
+        duration_ms: rhythm.duration_ms,
+        bars: Vec::new(),
+        bar_energy: Vec::new(),
+        bar_repetition: Vec::new(),
+        sections: Vec::new(),
+    };
+    if bars.len() < 2 {
+        return empty;
+    }
+
+    let (timbre, loudness) = bar_features(samples, sample_rate, &bars);
+    let similarity = self_similarity(&timbre);
+    let bar_energy = relative(&loudness);
+    let bar_repetition = repetition(&similarity);
+    let boundaries = section_boundaries(&novelty(&similarity, &bar_energy));
+    let sections = label_sections(&bars, &boundaries, &bar_energy, &bar_repetition);
+
+    StructureAnalysis {
+        bars,
+        bar_energy,
+        bar_repetition,
+        sections,
+        ..empty
+    }
+}
+
+/// Bar boundaries from the downbeats. Beats before the first downbeat join the first bar;
+/// past the last downbeat the median bar length is repeated up to the end of the track.
+/// Without downbeats the track is cut into fixed two-second bars.
+fn bar_boundaries(rhythm: &BeatAnalysis) -> Vec<u64> {
+    let duration = rhythm.duration_ms;
+    let mut bars: Vec<u64> = rhythm
+        .beats
+        .iter()
+        .filter(|beat| beat.phase == 1)
+        .map(|beat| beat.time_ms)
+        .collect();
+
+    let bar_ms = if bars.len() >= 2 {
+        let mut lengths: Vec<u64> = bars.windows(2).map(|w| w[1] - w[0]).collect();
+        lengths.sort_unstable();
+        lengths[lengths.len() / 2].max(1)
+    } else {
+        bars.clear();
+        FALLBACK_BAR_MS
+    };
+    if duration == 0 {
+        return Vec::new();
+    }
+
+    if bars.first() != Some(&0) {
+        match bars.first_mut() {
+            Some(first) if *first < bar_ms / 2 => *first = 0,
+            _ => bars.insert(0, 0),
+        }
+    }
+    if bars.len() > 1 && bars[1] < bar_ms / 2 {
+        bars.remove(1);
+    }
+    while let Some(&last) = bars.last() {
+        if last + bar_ms >= duration {
+            break;
+        }
+        bars.push(last + bar_ms);
+    }
+    // A last bar under half a bar long joins the one before
+    if bars.len() > 1 && duration - bars[bars.len() - 1] < bar_ms / 2 {
+        bars.pop();
+    }
+    bars.push(duration);
+    bars
+}
+
+/// Per-bar timbre vectors (mean log band energies) and loudness (dB)
+fn bar_features(samples: &[f32], sample_rate: u32, bars: &[u64]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let spectra = stft_magnitudes(samples, FRAME_SIZE, HOP_SIZE);
+    let bin_hz = sample_rate as f64 / FRAME_SIZE as f64;
+    let band_edges: Vec<usize> = (0..=BANDS)
+        .map(|i| {
+            let hz =
+                LOWEST_BAND_HZ * (HIGHEST_BAND_HZ / LOWEST_BAND_HZ).powf(i as f64 / BANDS as f64);
+            ((hz / bin_hz).round() as usize).clamp(1, FRAME_SIZE / 2)
+        })
+        .collect();
+    let frame_at =
+        |ms: u64| ((ms as f64 / 1000.0 * sample_rate as f64) / HOP_SIZE as f64).round() as usize;
+    let sample_at =
+        |ms: u64| ((ms as f64 / 1000.0 * sample_rate as f64) as usize).min(samples.len());
+
+    bars.windows(2)
+        .map(|bar| {
+            let frames =
+                &spectra[frame_at(bar[0]).min(spectra.len())..frame_at(bar[1]).min(spectra.len())];
+            let timbre = band_edges
+                .windows(2)
+                .map(|edges| {
+                    let bins = edges[0]..edges[1].max(edges[0] + 1);
+                    let power = frames
+                        .iter()
+                        .map(|spectrum| {
+                            spectrum[bins.clone()]
+                                .iter()
+                                .map(|m| (*m as f64).powi(2))
+                                .sum::<f64>()
+                        })
+                        .sum::<f64>()
+                        / frames.len().max(1) as f64;
+                    (power + 1e-6).ln()
+                })
+                .collect();
+
+            let chunk = &samples[sample_at(bar[0])..sample_at(bar[1])];
+            let power =
+                chunk.iter().map(|s| (*s as f64).powi(2)).sum::<f64>() / chunk.len().max(1) as f64;
+            (timbre, 10.0 * (power + 1e-10).log10())
+        })
+        .unzip()
+}
+
+/// Cosine similarity between bars, on timbre vectors centered over the whole track
+fn self_similarity(timbre: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = timbre.len();
+    let mean: Vec<f64> = (0..BANDS)
+        .map(|band| timbre.iter().map(|t| t[band]).sum::<f64>() / n.max(1) as f64)
+        .collect();
+    let centered: Vec<Vec<f64>> = timbre
+        .iter()
+        .map(|t| t.iter().zip(&mean).map(|(v, m)| v - m).collect())
+        .collect();
+    let norms: Vec<f64> = centered
+        .iter()
+        .map(|v| v.iter().map(|x| x * x).sum::<f64>().sqrt())
+        .collect();
+
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    let dot: f64 = centered[i]
+                        .iter()
+                        .zip(&centered[j])
+                        .map(|(a, b)| a * b)
+                        .sum();
+                    let norm = norms[i] * norms[j];
+                    if norm > 1e-9 {
+                        dot / norm
+                    } else {
+                        1.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Values scaled to 0–1 between their 5th and 95th percentiles
+fn relative(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let Some(last) = sorted.len().checked_sub(1) else {
+        return Vec::new();
+    };
+    let low = sorted[last * 5 / 100];
+    let high = sorted[last * 95 / 100];
+    values
+        .iter()
+        .map(|v| {
+            if high - low > 1e-9 {
+                ((v - low) / (high - low)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            }
+        })
+        .collect()
+}
+
+/// For each bar, its best similarity to a bar at least a section away
+fn repetition(similarity: &[Vec<f64>]) -> Vec<f64> {
+    let n = similarity.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|j| i.abs_diff(*j) >= MIN_SECTION_BARS)
+                .map(|j| similarity[i][j])
+                .fold(0.0f64, f64::max)
+        })
+        .collect()
+}
+
+/// Novelty at the start of each bar: the checkerboard kernel response of the similarity
+/// matrix plus the change in mean energy across the boundary, each scaled to a peak of 1.
+/// Index 0 is always zero.
+fn novelty(similarity: &[Vec<f64>], energy: &[f64]) -> Vec<f64> {
+    let n = similarity.len();
+    let mean = |range: std::ops::Range<usize>| {
+        let len = range.len().max(1) as f64;
+        energy[range].iter().sum::<f64>() / len
+    };
+
+    let (timbre, loudness): (Vec<f64>, Vec<f64>) = (0..n)
+        .map(|i| {
+            if i == 0 {
+                return (0.0, 0.0);
+            }
+            let start = i.saturating_sub(NOVELTY_RADIUS);
+            let end = (i + NOVELTY_RADIUS).min(n);
+            let sum: f64 = (start..end)
+                .flat_map(|u| (start..end).map(move |v| (u, v)))
+                .map(|(u, v)| {
+                    let sign = if (u < i) == (v < i) { 1.0 } else { -1.0 };
+                    sign * similarity[u][v]
+                })
+                .sum();
+            (
+                (sum / ((end - start) * (end - start)) as f64).max(0.0),
+                (mean(i..end) - mean(start..i)).abs(),
+            )
+        })
+        .unzip();
+
+    let scale = |values: &[f64]| {
+        let peak = values.iter().copied().fold(0.0f64, f64::max);
+        if peak > 1e-9 {
+            1.0 / peak
+        } else {
+            0.0
+        }
+    };
+    let (timbre_scale, loudness_scale) = (scale(&timbre), scale(&loudness));
+    timbre
+        .iter()
+        .zip(&loudness)
+        .map(|(t, l)| t * timbre_scale + l * loudness_scale)
+        .collect()
+}
+
+/// Section starts (bar indices, always including 0) at the strongest novelty peaks, at least `MIN_SECTION_BARS` from each other and from either end
+fn section_boundaries(novelty: &[f64]) -> Vec<usize> {
+    let n = novelty.len();
+    let threshold = PEAK_THRESHOLD * novelty.iter().copied().fold(0.0f64, f64::max);
+    let mut peaks: Vec<usize> = (MIN_SECTION_BARS..n.saturating_sub(MIN_SECTION_BARS - 1))
+        .filter(|&i| {
+            novelty[i] > threshold
+                && novelty[i] >= novelty[i - 1]
+                && (i + 1 == n || novelty[i] > novelty[i + 1])
+        })
+        .collect();
+    peaks.sort_by(|a, b| novelty[*b].total_cmp(&novelty[*a]));
+
+    let mut boundaries = vec![0];
+    for peak in peaks {
+        if boundaries
+            .iter()
+            .all(|b: &usize| b.abs_diff(peak) >= MIN_SECTION_BARS)
+        {
+            boundaries.push(peak);
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries
+}
+
+fn label_sections(
+    bars: &[u64],
+    boundaries: &[usize],
+    energy: &[f64],
+    repetition: &[f64],
+) -> Vec<Section> {
+    let bar_count = energy.len();
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+
+    let mut sections: Vec<Section> = boundaries
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = boundaries.get(i + 1).copied().unwrap_or(bar_count);
+            Section {
+                label: SectionLabel::Verse,
+                start_ms: bars[start],
+                end_ms: bars[end],
+                start_bar: start,
+                bar_count: end - start,
+                energy: mean(&energy[start..end]),
+                repetition: mean(&repetition[start..end]).clamp(0.0, 1.0),
+            }
+        })
+        .collect();
+
+    let count = sections.len();
+    for i in 0..count {
+        let energy = sections[i].energy;
+        let loud = energy >= LOUD_ENERGY;
+        sections[i].label = if i == 0 && !loud && count > 1 {
+            SectionLabel::Intro
+        } else if i == count - 1 && !loud && count > 1 {
+            SectionLabel::Outro
+        } else if loud && i > 0 && sections[i - 1].energy < QUIET_ENERGY {
+            SectionLabel::Drop
+        } else if loud {
+            SectionLabel::Chorus
+        } else {
+            SectionLabel::Verse
+        };
+    }
+    sections
+}
+
+/// The best excerpt of whole bars lasting at most `target_ms` (at least one bar).
+///
+/// Every bar is tried as a start. An excerpt scores its mean energy plus half its mean
+/// repetition, scaled by how much of the target it fills, with a bonus for starting a section
+/// (more for a chorus or drop), so highlights begin where the music does.
+pub fn best_excerpt(structure: &StructureAnalysis, target_ms: u64) -> Option<Highlight> {
+    let bars = &structure.bars;
+    let bar_count = bars.len().checked_sub(1)?;
+    let section_bonus = |bar: usize| {
+        structure
+            .sections
+            .iter()
+            .find(|section| section.start_bar == bar)
+            .map_or(0.0, |section| match section.label {
+                SectionLabel::Chorus | SectionLabel::Drop => 0.3,
+                _ => 0.1,
+            })
+    };
+
+    (0..bar_count)
+        .map(|start| {
+            let end = (start + 1..=bar_count)
+                .take_while(|&end| bars[end] - bars[start] <= target_ms)
+                .last()
+                .unwrap_or(start + 1);
+            let len = (end - start) as f64;
+            let energy = structure.bar_energy[start..end].iter().sum::<f64>() / len;
+            let repetition = structure.bar_repetition[start..end].iter().sum::<f64>() / len;
+            let fill = ((bars[end] - bars[start]) as f64 / target_ms.max(1) as f64).min(1.0);
+            Highlight {
+                start_ms: bars[start],
+                end_ms: bars[end],
+                bar_count: end - start,
+                score: (energy + 0.5 * repetition) * fill + section_bonus(start),
+            }
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+/// An audio clip playing `highlight` of `source`, placed on the timeline at `at_ms`
+pub fn excerpt_clip(source: &str, highlight: &Highlight, at_ms: u64) -> AudioClip {
+    AudioClip {
+        id: format!("audio-highlight-{}", highlight.start_ms),
+        source: source.to_string(),
+        start_ms: at_ms,
+        end_ms: at_ms + (highlight.end_ms - highlight.start_ms),
+        volume: 1.0,
+        trim_start_ms: highlight.start_ms,
+        duck_envelope: Vec::new(),
+        gain_envelope: Vec::new(),
+        effects: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 22_050;
+
+    /// 120 BPM with a kick on every downbeat (2 s bars) and a pad whose level and timbre
+    /// follow `sections`: (bars, level, bright)
+    fn song(sections: &[(usize, f32, bool)]) -> Vec<f32> {
+        let bar_samples = SAMPLE_RATE as usize * 2;
+        let total: usize = sections.iter().map(|s| s.0).sum();
+        let mut samples = vec![0.0f32; bar_samples * total];
+        let mut noise = 12345u32;
+
+        let mut bar = 0;
+        for &(bars, level, bright) in sections {
+            let range = bar * bar_samples..(bar + bars) * bar_samples;
+            for (sample, i) in samples[range.clone()].iter_mut().zip(range) {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                noise = noise.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let white = (noise >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+                let pad = if bright {
+                    0.6 * (2.0 * PI * 880.0 * t).sin() + 0.15 * white
+                } else {
+                    (2.0 * PI * 220.0 * t).sin()
+                };
+                *sample = level * pad;
+            }
+            bar += bars;
+        }
+
+        for beat in 0..total * 4 {
+            let start = beat * bar_samples / 4;
+            let (length, amp) = if beat % 4 == 0 {
+                (2000, 0.5)
+            } else {
+                (300, 0.15)
+            };
+            for i in 0..length {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let decay = 1.0 - i as f32 / length as f32;
+                samples[start + i] += amp * decay * (2.0 * PI * 60.0 * t).sin();
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_sections_and_highlight() {
+        let samples = song(&[
+            (6, 0.05, false),
+            (8, 0.2, false),
+            (8, 0.8, true),
+            (8, 0.2, false),
+            (8, 0.8, true),
+            (6, 0.05, false),
+        ]);
+        let structure = analyze_structure(&samples, SAMPLE_RATE, 4);
+        let labels: Vec<(SectionLabel, u64)> = structure
+            .sections
+            .iter()
+            .map(|s| (s.label, s.start_ms))
+            .collect();
+
+        assert_eq!(labels.len(), 6, "{labels:?}");
+        assert_eq!(labels[0].0, SectionLabel::Intro, "{labels:?}");
+        assert_eq!(labels[5].0, SectionLabel::Outro, "{labels:?}");
+        for (i, expected_ms) in [(2, 28_000), (4, 60_000)] {
+            assert_eq!(labels[i].0, SectionLabel::Chorus, "{labels:?}");
+            assert!((labels[i].1 as i64 - expected_ms).abs() < 300, "{labels:?}");
+        }
+
+        // 15 s fits seven 2 s bars, starting on a chorus
+        let highlight = best_excerpt(&structure, 15_000).unwrap();
+        assert_eq!(highlight.bar_count, 7);
+        assert!(
+            [28_000, 60_000]
+                .iter()
+                .any(|ms| (highlight.start_ms as i64 - ms).abs() < 300),
+            "{highlight:?}"
+        );
+        assert!(highlight.end_ms - highlight.start_ms <= 15_000);
+    }
+
+    #[test]
+    fn test_drop_after_quiet_build() {
+        let sections = label_sections(
+            &(0..=12).map(|bar| bar * 2000).collect::<Vec<u64>>(),
+            &[0, 4, 8],
+            &[0.5, 0.5, 0.5, 0.5, 0.1, 0.1, 0.1, 0.1, 1.0, 1.0, 1.0, 1.0],
+            &[0.5; 12],
+        );
+        let labels: Vec<SectionLabel> = sections.iter().map(|s| s.label).collect();
+        assert_eq!(
+            labels,
+            [SectionLabel::Intro, SectionLabel::Verse, SectionLabel::Drop]
+        );
+        assert_eq!(sections[2].start_ms, 16_000);
+        assert_eq!(sections[2].end_ms, 24_000);
+    }
+
+    #[test]
+    fn test_excerpt_clip_is_trimmed() {
+        let highlight = Highlight {
+            start_ms: 28_000,
+            end_ms: 42_000,
+            bar_count: 7,
+            score: 1.0,
+        };
+        let clip = excerpt_clip("/music/song.mp3", &highlight, 500);
+        assert_eq!(clip.trim_start_ms, 28_000);
+        assert_eq!((clip.start_ms, clip.end_ms), (500, 14_500));
+    }
+
+    #[test]
+    fn test_silence_has_no_highlight() {
+        let structure = analyze_structure(&[], SAMPLE_RATE, 4);
+        assert!(structure.sections.is_empty());
+        assert!(best_excerpt(&structure, 15_000).is_none());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::models::project::TempoSegment;
+use serde::{Deserialize, Serialize};
+
+pub const MIN_BPM: f64 = 60.0;
+pub const MAX_BPM: f64 = 200.0;
+
+/// Center of the tempo prior; tempos an octave away are weighted about 0.6
+const PRIOR_BPM: f64 = 120.0;
+
+/// Window and hop of the local tempo estimates
+const LOCAL_WINDOW_SECONDS: f64 = 6.0;
+const LOCAL_HOP_SECONDS: f64 = 1.0;
+
+/// Shorter tempo segments are merged into their neighbor
+const MIN_SEGMENT_BEATS: usize = 4;
+
+/// Local tempo at a beat
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoPoint {
+    pub time_ms: u64,
+    pub bpm: f64,
+}
+
+/// Estimate the global tempo of an onset envelope by autocorrelation.
+///
+/// Lags between `MIN_BPM` and `MAX_BPM` are weighted with a log-normal prior around 120 BPM,
+/// which settles half/double tempo ambiguities toward the usual range, and the best lag is
+/// refined by parabolic interpolation. Returns `None` when there is no periodicity.
+pub fn estimate_tempo(envelope: &[f32], frame_rate: f64) -> Option<f64> {
+    let min_lag = (frame_rate * 60.0 / MAX_BPM).floor().max(1.0) as usize;
+    let max_lag =
+        ((frame_rate * 60.0 / MIN_BPM).ceil() as usize).min(envelope.len().saturating_sub(1));
+    if min_lag + 2 > max_lag {
+        return None;
+    }
+
+    let weighted: Vec<f64> = (min_lag - 1..=max_lag + 1)
+        .map(|lag| {
+            if lag >= envelope.len() {
+                return 0.0;
+            }
+            let acf: f64 = envelope
+                .iter()
+                .zip(&envelope[lag..])
+                .map(|(a, b)| (*a as f64) * (*b as f64))
+                .sum::<f64>()
+                / (envelope.len() - lag) as f64;
+            let bpm = frame_rate * 60.0 / lag as f64;
+            acf * (-0.5 * (bpm / PRIOR_BPM).log2().powi(2)).exp()
+        })
+        .collect();
+
+    // weighted[i] is lag min_lag - 1 + i; the first and last entries are only neighbors
+    let best = (1..weighted.len() - 1).max_by(|a, b| weighted[*a].total_cmp(&weighted[*b]))?;
+    if weighted[best] <= 0.0 {
+        return None;
+    }
+
+    let (left, center, right) = (weighted[best - 1], weighted[best], weighted[best + 1]);
+    let denominator = left - 2.0 * center + right;
+    let shift = if denominator.abs() > f64::EPSILON {
+        (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let lag = (min_lag - 1 + best) as f64 + shift;
+    Some(frame_rate * 60.0 / lag)
+}
+
+/// Tempo at every frame of an onset envelope, for tracking songs whose tempo changes.
+///
+/// The tempo is estimated in 6 s windows every second, folded by octaves toward `global_bpm`
+/// so that windows don't jump between half and double time and median-smoothed over three
+/// windows. Audio shorter than a window gets the global tempo throughout.
+pub fn local_tempo(envelope: &[f32], frame_rate: f64, global_bpm: f64) -> Vec<f64> {
+    let window = (LOCAL_WINDOW_SECONDS * frame_rate).round() as usize;
+    let hop = (LOCAL_HOP_SECONDS * frame_rate).round().max(1.0) as usize;
+    if envelope.len() <= window || window == 0 {
+        return vec![global_bpm; envelope.len()];
+    }
+
+    let estimates: Vec<(f64, f64)> = (0..=envelope.len() - window)
+        .step_by(hop)
+        .map(|start| {
+            let bpm = estimate_tempo(&envelope[start..start + window], frame_rate).map_or(
+                global_bpm,
+                |bpm| {
+                    [bpm / 2.0, bpm, bpm * 2.0]
+                        .into_iter()
+                        .min_by(|a, b| {
+                            (a / global_bpm)
+                                .log2()
+                                .abs()
+                                .total_cmp(&(b / global_bpm).log2().abs())
+                        })
+                        .unwrap_or(bpm)
+                },
+            );
+            ((start + window / 2) as f64, bpm)
+        })
+        .collect();
+
+    let smoothed: Vec<(f64, f64)> = (0..estimates.len())
+        .map(|i| {
+            let start = i.saturating_sub(1);
+            let end = (i + 2).min(estimates.len());
+            let mut neighbors: Vec<f64> = estimates[start..end].iter().map(|e| e.1).collect();
+            neighbors.sort_by(f64::total_cmp);
+            (estimates[i].0, neighbors[neighbors.len() / 2])
+        })
+        .collect();
+
+    // Each frame takes the estimate of the nearest window center, so a tempo change stays a
+    // step instead of becoming a ramp the tracker would follow between the two tempos
+    let first_center = smoothed[0].0;
+    (0..envelope.len())
+        .map(|frame| {
+            let index = ((frame as f64 - first_center) / hop as f64)
+                .round()
+                .max(0.0) as usize;
+            smoothed[index.min(smoothed.len() - 1)].1
+        })
+        .collect()
+}
+
+/// Tempo implied by the median of a list of beat intervals
+pub fn median_bpm(intervals_ms: &[u64]) -> Option<f64> {
+    let mut sorted: Vec<u64> = intervals_ms.iter().copied().filter(|i| *i > 0).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_unstable();
+    let median = (sorted[(sorted.len() - 1) / 2] + sorted[sorted.len() / 2]) as f64 / 2.0;
+    Some(60_000.0 / median)
+}
+
+/// Local tempo at each beat from the median of the (up to four) surrounding beat intervals
+pub fn tempo_curve(beat_times_ms: &[u64]) -> Vec<TempoPoint> {
+    let intervals: Vec<u64> = beat_times_ms.windows(2).map(|w| w[1] - w[0]).collect();
+    if intervals.is_empty() {
+        return Vec::new();
+    }
+
+    beat_times_ms
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &time_ms)| {
+            let start = i.saturating_sub(2);
+            let end = (i + 2).min(intervals.len());
+            median_bpm(&intervals[start..end]).map(|bpm| TempoPoint { time_ms, bpm })
+        })
+        .collect()
+}
+
+/// Split beats into steady-tempo segments covering `0..duration_ms`.
+///
+/// A new segment starts at the beat where the local tempo (see `tempo_curve`) departs from
+/// the running average of the current segment by more than `tolerance` (a fraction).
+/// Segments of fewer than four beats are merged into the previous one, then each boundary is
+/// moved to the beat that best separates the two tempos. Each segment's BPM is the average
+/// over its beats.
+pub fn tempo_map(beat_times_ms: &[u64], duration_ms: u64, tolerance: f64) -> Vec<TempoSegment> {
+    let curve = tempo_curve(beat_times_ms);
+    if curve.is_empty() {
+        return Vec::new();
+    }
+
+    let mut starts = vec![0];
+    let (mut sum, mut count) = (0.0, 0);
+    for (i, point) in curve.iter().enumerate() {
+        if count > 0 {
+            let mean = sum / count as f64;
+            if (point.bpm - mean).abs() / mean > tolerance {
+                starts.push(i);
+                (sum, count) = (0.0, 0);
+            }
+        }
+        sum += point.bpm;
+        count += 1;
+    }
+
+    // Drop segment starts that leave too few beats before the next start (or the end)
+    let mut merged: Vec<usize> = Vec::new();
+    for (n, &start) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).copied().unwrap_or(beat_times_ms.len());
+        if merged.is_empty() || end - start >= MIN_SEGMENT_BEATS {
+            merged.push(start);
+        }
+    }
+    if merged.len() > 1 && beat_times_ms.len() - merged[merged.len() - 1] < MIN_SEGMENT_BEATS {
+        merged.pop();
+    }
+
+    // The smoothed curve changes gradually, so move each boundary to the beat that best
+    // splits the raw intervals between the median periods of the two segments
+    let intervals: Vec<u64> = beat_times_ms.windows(2).map(|w| w[1] - w[0]).collect();
+    let last_beat = beat_times_ms.len() - 1;
+    for n in 1..merged.len() {
+        let (previous, end) = (
+            merged[n - 1],
+            merged.get(n + 1).copied().unwrap_or(last_beat),
+        );
+        let period = |range: &[u64]| median_bpm(range).map_or(0.0, |bpm| 60_000.0 / bpm);
+        let before = period(&intervals[previous..merged[n]]);
+        let after = period(&intervals[merged[n]..end]);
+        let cost = |split: usize| -> f64 {
+            let error = |range: &[u64], period: f64| {
+                range
+                    .iter()
+                    .map(|i| (*i as f64 - period).powi(2))
+                    .sum::<f64>()
+            };
+            error(&intervals[previous..split], before) + error(&intervals[split..end], after)
+        };
+        if let Some(best) = (previous + 1..end).min_by(|a, b| cost(*a).total_cmp(&cost(*b))) {
+            merged[n] = best;
+        }
+    }
+
+    merged
+        .iter()
+        .enumerate()
+        .map(|(n, &first)| {
+            let last = merged.get(n + 1).copied().unwrap_or(last_beat);
+            let span = beat_times_ms[last] - beat_times_ms[first];
+            TempoSegment {
+                start_ms: if n == 0 { 0 } else { beat_times_ms[first] },
+                end_ms: merged
+                    .get(n + 1)
+                    .map_or(duration_ms.max(beat_times_ms[last]), |&next| {
+                        beat_times_ms[next]
+                    }),
+                bpm: if span > 0 {
+                    60_000.0 * (last - first) as f64 / span as f64
+                } else {
+                    curve[first].bpm
+                },
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pulse_train(period_frames: usize, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| if i % period_frames == 0 { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_tempo() {
+        // 100 frames per second, a pulse every 50 frames = 120 BPM
+        let bpm = estimate_tempo(&pulse_train(50, 1000), 100.0).unwrap();
+        assert!((bpm - 120.0).abs() < 1.0, "{bpm}");
+
+        // every 40 frames = 150 BPM; the prior must not pull it to 75
+        let bpm = estimate_tempo(&pulse_train(40, 1000), 100.0).unwrap();
+        assert!((bpm - 150.0).abs() < 1.5, "{bpm}");
+
+        assert_eq!(estimate_tempo(&[0.0; 1000], 100.0), None);
+        assert_eq!(estimate_tempo(&[1.0; 10], 100.0), None);
+    }
+
+    #[test]
+    fn test_tempo_curve() {
+        let beats = [0, 500, 1000, 1500, 2000, 2400, 2800, 3200, 3600];
+        let curve = tempo_curve(&beats);
+        assert_eq!(curve.len(), beats.len());
+        assert_eq!(curve[0].bpm, 120.0);
+        assert_eq!(curve[8].bpm, 150.0);
+        assert_eq!(median_bpm(&[]), None);
+    }
+
+    #[test]
+    fn test_tempo_map_splits_at_tempo_change() {
+        let mut beats: Vec<u64> = (0..16).map(|i| 1000 + i * 500).collect();
+        beats.extend((1..=16).map(|i| 8500 + i * 400));
+        // a single late beat is not a tempo change
+        beats[5] += 40;
+
+        let map = tempo_map(&beats, 16_000, 0.04);
+        assert_eq!(map.len(), 2, "{map:?}");
+        assert_eq!(map[0].start_ms, 0);
+        assert!((map[0].bpm - 120.0).abs() < 2.0, "{map:?}");
+        assert!((map[1].start_ms as i64 - 8500).abs() <= 800, "{map:?}");
+        assert!((map[1].bpm - 150.0).abs() < 2.0, "{map:?}");
+        assert_eq!(map[1].end_ms, 16_000);
+        assert_eq!(map[0].end_ms, map[1].start_ms);
+
+        let steady: Vec<u64> = (0..20).map(|i| i * 500).collect();
+        assert_eq!(tempo_map(&steady, 10_000, 0.04).len(), 1);
+    }
+
+    #[test]
+    fn test_local_tempo_follows_change() {
+        // 100 frames per second: 120 BPM for 20 s, then 150 BPM for 20 s
+        let mut envelope = pulse_train(50, 2000);
+        envelope.extend(pulse_train(40, 2000));
+
+        let tempo = local_tempo(&envelope, 100.0, 120.0);
+        assert_eq!(tempo.len(), 4000);
+        assert!((tempo[500] - 120.0).abs() < 2.0, "{}", tempo[500]);
+        assert!((tempo[3500] - 150.0).abs() < 2.0, "{}", tempo[3500]);
+        assert_eq!(
+            local_tempo(&envelope[..500], 100.0, 120.0),
+            vec![120.0; 500]
+        );
+    }
+}
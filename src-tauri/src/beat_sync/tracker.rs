@@ -0,0 +1,187 @@
+use crate::beat_sync::onset::OnsetEnvelope;
+
+/// How strongly the tracker holds to the estimated tempo (Ellis' "tightness")
+pub const DEFAULT_TIGHTNESS: f64 = 100.0;
+
+/// Frames around a beat searched for its onset strength
+const BEAT_RADIUS: usize = 2;
+
+/// Place beats by dynamic programming (Ellis 2007).
+///
+/// Each frame scores its onset strength plus the best score of a previous beat between half
+/// and two periods earlier, penalized by how far that interval is from the period on a log
+/// scale. The period follows `bpm` (one value per envelope frame, see
+/// `tempo::local_tempo`), so the tracker can follow tempo changes. The best-scoring frame in
+/// the last period is then traced back. Beats in quiet stretches at the start and end are
+/// trimmed. Returns frame indices.
+pub fn track_beats(envelope: &OnsetEnvelope, bpm: &[f64], tightness: f64) -> Vec<usize> {
+    let values = &envelope.values;
+    let periods: Vec<f64> = bpm
+        .iter()
+        .map(|bpm| envelope.frame_rate * 60.0 / bpm)
+        .collect();
+    if values.is_empty()
+        || periods.len() != values.len()
+        || periods.iter().any(|p| !p.is_finite() || *p < 1.0)
+    {
+        return Vec::new();
+    }
+
+    // Onset strengths in units of their standard deviation, as in Ellis' formulation, so the
+    // tempo penalty weighs the same whatever the envelope's scale
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / values.len() as f64;
+    let std = (values
+        .iter()
+        .map(|v| (*v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64)
+        .sqrt();
+    let scale = if std > 0.0 { 1.0 / std } else { 1.0 };
+
+    let mut score = vec![0.0f64; values.len()];
+    let mut backlink: Vec<Option<usize>> = vec![None; values.len()];
+
+    for t in 0..values.len() {
+        let period = periods[t];
+        let min_step = (period / 2.0).round().max(1.0) as usize;
+        let max_step = (period * 2.0).round() as usize;
+        let best = (min_step..=max_step.min(t))
+            .map(|step| {
+                let penalty = tightness * (step as f64 / period).ln().powi(2);
+                (score[t - step] - penalty, t - step)
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+
+        score[t] = values[t] as f64 * scale;
+        if let Some((previous, frame)) = best {
+            if previous > 0.0 {
+                score[t] += previous;
+                backlink[t] = Some(frame);
+            }
+        }
+    }
+
+    let tail_start = values
+        .len()
+        .saturating_sub(periods[values.len() - 1].round() as usize);
+    let Some(mut frame) = (tail_start..values.len()).max_by(|a, b| score[*a].total_cmp(&score[*b]))
+    else {
+        return Vec::new();
+    };
+
+    let mut beats = vec![frame];
+    while let Some(previous) = backlink[frame] {
+        beats.push(previous);
+        frame = previous;
+    }
+    beats.reverse();
+
+    trim_quiet_edges(envelope, beats)
+}
+
+/// Drop leading and trailing beats whose onset strength is under half the RMS over all beats
+fn trim_quiet_edges(envelope: &OnsetEnvelope, beats: Vec<usize>) -> Vec<usize> {
+    let strengths: Vec<f32> = beats
+        .iter()
+        .map(|&frame| envelope.local_max(frame, BEAT_RADIUS))
+        .collect();
+    let rms = (strengths.iter().map(|s| s * s).sum::<f32>() / strengths.len().max(1) as f32).sqrt();
+    let threshold = 0.5 * rms;
+
+    let first = strengths.iter().position(|s| *s >= threshold);
+    let last = strengths.iter().rposition(|s| *s >= threshold);
+    match (first, last) {
+        (Some(first), Some(last)) => beats[first..=last].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Which beat index (0..beats_per_bar) is the first downbeat.
+///
+/// Every candidate phase is scored by the average low-frequency onset strength (kick drums)
+/// of its beats, with full-band onsets at half weight; the strongest phase wins.
+pub fn downbeat_offset(
+    beats: &[usize],
+    low: &OnsetEnvelope,
+    full: &OnsetEnvelope,
+    beats_per_bar: usize,
+) -> usize {
+    if beats_per_bar <= 1 {
+        return 0;
+    }
+
+    (0..beats_per_bar.min(beats.len()))
+        .map(|offset| {
+            let strengths: Vec<f32> = beats
+                .iter()
+                .skip(offset)
+                .step_by(beats_per_bar)
+                .map(|&frame| {
+                    low.local_max(frame, BEAT_RADIUS) + 0.5 * full.local_max(frame, BEAT_RADIUS)
+                })
+                .collect();
+            let mean = strengths.iter().sum::<f32>() / strengths.len() as f32;
+            (offset, mean)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(offset, _)| offset)
+}
+
+/// Position of beat `index` given the downbeat offset: (1-based bar, 1-based beat in bar).
+/// Beats before the first downbeat form a pickup bar 0.
+pub fn bar_position(index: usize, offset: usize, beats_per_bar: usize) -> (u32, u8) {
+    let beats_per_bar = beats_per_bar.max(1);
+    let shifted = index + beats_per_bar - offset % beats_per_bar;
+    (
+        (shifted / beats_per_bar) as u32,
+        (shifted % beats_per_bar + 1) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(values: Vec<f32>) -> OnsetEnvelope {
+        OnsetEnvelope {
+            values,
+            frame_rate: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_track_beats_follows_pulses() {
+        // Pulses every 50 frames from frame 20, with a weaker off-beat pulse and a quiet intro
+        let mut values = vec![0.0; 600];
+        for frame in (20..600).step_by(50) {
+            values[frame] = 1.0;
+            if frame + 25 < 600 {
+                values[frame + 25] = 0.2;
+            }
+        }
+
+        let beats = track_beats(&envelope(values), &[120.0; 600], DEFAULT_TIGHTNESS);
+        let expected: Vec<usize> = (20..600).step_by(50).collect();
+        assert_eq!(beats, expected);
+    }
+
+    #[test]
+    fn test_downbeat_offset_and_bar_position() {
+        let beats: Vec<usize> = (0..16).map(|i| 10 + i * 50).collect();
+        let mut low = vec![0.0; 820];
+        let mut full = vec![0.0; 820];
+        for (i, &frame) in beats.iter().enumerate() {
+            full[frame] = 1.0;
+            if i % 4 == 1 {
+                low[frame] = 1.0;
+            }
+        }
+
+        let offset = downbeat_offset(&beats, &envelope(low), &envelope(full), 4);
+        assert_eq!(offset, 1);
+        assert_eq!(bar_position(0, offset, 4), (0, 4));
+        assert_eq!(bar_position(1, offset, 4), (1, 1));
+        assert_eq!(bar_position(6, offset, 4), (2, 2));
+        assert_eq!(bar_position(3, 0, 3), (2, 1));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::audio_engine::cache;
+use crate::audio_engine::denoise;
+use crate::audio_engine::ducking::{self, DuckingAnalysis, DuckingOptions};
+use crate::audio_engine::peaks::{PeakPyramid, WaveformPeaks};
+use crate::audio_engine::spectrogram::{self, Spectrogram, SpectrogramOptions};
+use crate::beat_sync::analyzer::{decode_audio_channels, decode_audio_mono};
+use crate::models::effects::NoiseProfile;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveformInfo {
+    /// Hash of the file contents the peaks are cached under
+    pub hash: String,
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub duration_ms: u64,
+    /// Samples per peak of each pyramid level, finest first
+    pub levels: Vec<u32>,
+}
+
+fn waveform_cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join("waveforms"))
+        .map_err(|e| format!("Failed to find cache directory: {e}"))
+}
+
+/// The peak pyramid of a media file, decoding it only when it isn't cached yet
+pub fn load_peaks(app: &AppHandle, audio_path: &str) -> Result<(String, Arc<PeakPyramid>), String> {
+    cache::load_or_build(&waveform_cache_dir(app)?, audio_path, || {
+        let (channels, sample_rate) = decode_audio_channels(audio_path)?;
+        Ok(PeakPyramid::build(&channels, sample_rate))
+    })
+}
+
+/// Build (or load) the waveform peaks of a media file, e.g. right after import
+#[tauri::command]
+pub fn build_waveform_peaks(app: AppHandle, audio_path: String) -> Result<WaveformInfo, String> {
+    let (hash, pyramid) = load_peaks(&app, &audio_path)?;
+    Ok(WaveformInfo {
+        hash,
+        sample_rate: pyramid.sample_rate,
+        channels: pyramid.channel_count(),
+        duration_ms: pyramid.duration_ms(),
+        levels: pyramid.levels.iter().map(|l| l.samples_per_peak).collect(),
+    })
+}
+
+/// Min/max/RMS peaks per channel for drawing `start_ms..end_ms` at `points` points
+#[tauri::command]
+pub fn query_waveform_peaks(
+    app: AppHandle,
+    audio_path: String,
+    start_ms: u64,
+    end_ms: u64,
+    points: usize,
+) -> Result<WaveformPeaks, String> {
+    let (_, pyramid) = load_peaks(&app, &audio_path)?;
+    Ok(pyramid.query(start_ms, end_ms, points))
+}
+
+/// Mel or log-frequency spectrogram and onset-strength envelope of `start_ms..end_ms`
+#[tauri::command]
+pub fn compute_spectrogram(
+    audio_path: String,
+    start_ms: u64,
+    end_ms: u64,
+    options: Option<SpectrogramOptions>,
+) -> Result<Spectrogram, String> {
+    let (samples, sample_rate) = decode_audio_mono(&audio_path)?;
+    Ok(spectrogram::compute_spectrogram(
+        &samples,
+        sample_rate,
+        start_ms,
+        end_ms,
+        &options.unwrap_or_default(),
+    ))
+}
+
+/// Detect speech on the primary audio (e.g. the video's dialogue) and build the gain
+/// envelope that ducks the BGM under it
+#[tauri::command]
+pub fn analyze_ducking(
+    audio_path: String,
+    options: Option<DuckingOptions>,
+) -> Result<DuckingAnalysis, String> {
+    let (samples, sample_rate) = decode_audio_mono(&audio_path)?;
+    Ok(ducking::analyze_ducking(
+        &samples,
+        sample_rate,
+        &options.unwrap_or_default(),
+    ))
+}
+
+/// Noise profile of `start_ms..end_ms` of a file, a stretch with only background noise, for
+/// a clip's noise reduction
+#[tauri::command]
+pub fn capture_noise_profile(
+    audio_path: String,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<NoiseProfile, String> {
+    let (samples, sample_rate) = decode_audio_mono(&audio_path)?;
+    let to_sample = |ms: u64| ((ms * sample_rate as u64 / 1000) as usize).min(samples.len());
+    let (start, end) = (to_sample(start_ms), to_sample(end_ms));
+    if end <= start {
+        return Err(format!("No audio between {start_ms}ms and {end_ms}ms"));
+    }
+    Ok(denoise::capture_noise_profile(
+        &samples[start..end],
+        sample_rate,
+    ))
+}
@@ -1,23 +1,160 @@
-use crate::beat_sync::analyzer::{analyze_beats, generate_waveform, BeatAnalysis};
+use crate::beat_sync::accent::BeatFilter;
+use crate::beat_sync::analyzer::{analyze_beats, decode_audio_mono, BeatAnalysis};
 use crate::beat_sync::detector::snap_to_beat;
+use crate::beat_sync::grid as beat_grid;
+use crate::beat_sync::silence::{self, SilenceAnalysis, SilenceOptions};
+use crate::beat_sync::structure::{self, Highlight, StructureAnalysis};
+use crate::commands::audio::load_peaks;
+use crate::models::project::{BeatGrid, ShortForgeProject};
+use crate::models::telop::SubtitleCue;
+use crate::models::timeline::AudioClip;
+use crate::telop_engine::timing::{self, BeatSnapResult};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
 
+/// Detect tempo, beats and downbeats. `beats_per_bar` defaults to 4.
 #[tauri::command]
-pub fn detect_beats(audio_path: String) -> Result<BeatAnalysis, String> {
-    analyze_beats(&audio_path)
+pub fn detect_beats(audio_path: String, beats_per_bar: Option<u8>) -> Result<BeatAnalysis, String> {
+    analyze_beats(&audio_path, beats_per_bar.unwrap_or(4).max(1))
 }
 
+/// RMS level of `num_points` equal stretches of a media file, from the cached peak pyramid
 #[tauri::command]
-pub fn get_waveform(audio_path: String, num_points: Option<usize>) -> Result<Vec<f32>, String> {
+pub fn get_waveform(
+    app: AppHandle,
+    audio_path: String,
+    num_points: Option<usize>,
+) -> Result<Vec<f32>, String> {
     let points = num_points.unwrap_or(500);
-    generate_waveform(&audio_path, points)
+    let (_, pyramid) = load_peaks(&app, &audio_path)?;
+    if pyramid.length == 0 {
+        return Ok(Vec::new());
+    }
+    Ok(pyramid.query(0, pyramid.duration_ms(), points).mixed_rms())
 }
 
+/// Fit a beat grid (first tap = downbeat anchor, BPM) to tapped beat times
+#[tauri::command]
+pub fn tap_tempo(taps_ms: Vec<u64>) -> Result<BeatGrid, String> {
+    beat_grid::tap_tempo(&taps_ms)
+}
+
+/// Move a beat grid by `delta_ms`, keeping its downbeats in place
+#[tauri::command]
+pub fn shift_beat_grid(
+    grid: BeatGrid,
+    delta_ms: i64,
+    beats_per_bar: Option<u8>,
+) -> Result<BeatGrid, String> {
+    beat_grid::shift_grid(&grid, delta_ms, beats_per_bar.unwrap_or(4))
+}
+
+/// Regenerate beats from a manual grid (anchor downbeat + BPM) over `0..=duration_ms`
+#[tauri::command]
+pub fn generate_beat_grid(
+    grid: BeatGrid,
+    beats_per_bar: Option<u8>,
+    duration_ms: u64,
+) -> Result<BeatAnalysis, String> {
+    beat_grid::generate_grid(&grid, beats_per_bar.unwrap_or(4), duration_ms)
+}
+
+/// Save beats as the project's beat markers, with the audio they were detected from or the
+/// manual grid they were generated from
+#[tauri::command]
+pub fn store_beat_markers(
+    project: ShortForgeProject,
+    analysis: BeatAnalysis,
+    audio_path: Option<String>,
+    grid: Option<BeatGrid>,
+) -> ShortForgeProject {
+    beat_grid::store_beats(&project, &analysis, audio_path, grid)
+}
+
+/// Find silent and low-energy regions of a media file's audio and the ranges to keep
+/// when jump-cutting them out
+#[tauri::command]
+pub fn detect_silence(
+    media_path: String,
+    options: Option<SilenceOptions>,
+) -> Result<SilenceAnalysis, String> {
+    let (samples, sample_rate) = decode_audio_mono(&media_path)?;
+    Ok(silence::detect_silence(
+        &samples,
+        sample_rate,
+        &options.unwrap_or_default(),
+    ))
+}
+
+/// Split a music file into intro / verse / chorus / drop / outro sections
+#[tauri::command]
+pub fn analyze_music_structure(
+    audio_path: String,
+    beats_per_bar: Option<u8>,
+) -> Result<StructureAnalysis, String> {
+    let (samples, sample_rate) = decode_audio_mono(&audio_path)?;
+    Ok(structure::analyze_structure(
+        &samples,
+        sample_rate,
+        beats_per_bar.unwrap_or(4).max(1),
+    ))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighlightProposal {
+    pub structure: StructureAnalysis,
+    pub highlight: Highlight,
+    /// The highlight as an audio clip trimmed from the source, starting at `at_ms`
+    pub clip: AudioClip,
+}
+
+/// Propose the best bar-aligned excerpt of at most `duration_ms` from a music file
+#[tauri::command]
+pub fn find_music_highlight(
+    audio_path: String,
+    duration_ms: u64,
+    beats_per_bar: Option<u8>,
+    at_ms: Option<u64>,
+) -> Result<HighlightProposal, String> {
+    let structure = analyze_music_structure(audio_path.clone(), beats_per_bar)?;
+    let highlight = structure::best_excerpt(&structure, duration_ms)
+        .ok_or_else(|| format!("No bars found in {audio_path}"))?;
+    let clip = structure::excerpt_clip(&audio_path, &highlight, at_ms.unwrap_or(0));
+    Ok(HighlightProposal {
+        structure,
+        highlight,
+        clip,
+    })
+}
+
+/// Snap a time to the nearest beat, optionally only to strong beats or certain drum hits
 #[tauri::command]
 pub fn snap_time_to_beat(
     beats: Vec<crate::beat_sync::analyzer::BeatInfo>,
     time_ms: u64,
     threshold_ms: Option<u64>,
+    filter: Option<BeatFilter>,
 ) -> u64 {
     let threshold = threshold_ms.unwrap_or(100);
+    let beats = filter.unwrap_or_default().apply(&beats);
     snap_to_beat(&beats, time_ms, threshold)
 }
+
+/// Snap the starts (and optionally ends) of all cues of a telop track to nearby beats,
+/// without making them overlap. Reports every cue that moved.
+#[tauri::command]
+pub fn snap_cues_to_beats(
+    cues: Vec<SubtitleCue>,
+    beats: Vec<crate::beat_sync::analyzer::BeatInfo>,
+    threshold_ms: Option<u64>,
+    snap_end: Option<bool>,
+    filter: Option<BeatFilter>,
+) -> BeatSnapResult {
+    let beats = filter.unwrap_or_default().apply(&beats);
+    timing::snap_to_beats(
+        &cues,
+        &beats,
+        threshold_ms.unwrap_or(100),
+        snap_end.unwrap_or(false),
+    )
+}
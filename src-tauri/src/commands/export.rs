@@ -1,10 +1,18 @@
+use crate::audio_engine::effects::apply_effects;
+use crate::audio_engine::envelope::place_envelope;
+use crate::audio_engine::wav::{write_wav, TempDir, WavFormat};
+use crate::beat_sync::analyzer::decode_audio_channels;
 use crate::export_engine::preset::{ExportPreset, load_all_presets, load_preset_file};
 use crate::export_engine::renderer::{render_export, AudioSource, ExportProgress};
 use crate::export_engine::validator::{validate_for_export, ValidationResult};
+use crate::models::effects::AudioEffect;
 use crate::models::telop::SubtitleCue;
-use crate::telop_engine::parser::parse_srt_file;
+use crate::models::timeline::GainPoint;
+use crate::telop_engine::font::FontService;
+use crate::telop_engine::parser::import_subtitle_file;
 use crate::telop_engine::template::load_template_file;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tauri::{AppHandle, Emitter};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,8 +25,87 @@ pub struct ExportProgressEvent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioSourceParam {
     pub path: String,
+    #[serde(alias = "startMs")]
     pub start_ms: u64,
+    #[serde(alias = "endMs")]
     pub end_ms: u64,
+    /// Position in the source where the clip starts playing
+    #[serde(default, alias = "trimStartMs")]
+    pub trim_start_ms: u64,
+    /// Ducking under speech, in timeline time
+    #[serde(default, alias = "duckEnvelope")]
+    pub duck_envelope: Vec<GainPoint>,
+    /// Volume automation, relative to the clip start
+    #[serde(default, alias = "gainEnvelope")]
+    pub gain_envelope: Vec<GainPoint>,
+    /// Effect chain run over the clip before mixing
+    #[serde(default)]
+    pub effects: Vec<AudioEffect>,
+}
+
+/// Volume automation of a video clip's embedded audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoGainParam {
+    #[serde(alias = "startMs")]
+    pub start_ms: u64,
+    #[serde(alias = "endMs")]
+    pub end_ms: u64,
+    /// Relative to the clip start
+    #[serde(alias = "gainEnvelope")]
+    pub gain_envelope: Vec<GainPoint>,
+}
+
+/// Decode the part of a source its clip plays, run the clip's effect chain over it and write
+/// it to `wav_path`
+fn render_effects(source: &AudioSourceParam, wav_path: &Path) -> Result<(), String> {
+    let (mut channels, sample_rate) = decode_audio_channels(&source.path)?;
+    let to_sample = |ms: u64| (ms * sample_rate as u64 / 1000) as usize;
+    let start = to_sample(source.trim_start_ms);
+    let end = to_sample(source.trim_start_ms + source.end_ms.saturating_sub(source.start_ms));
+    for channel in &mut channels {
+        let end = end.min(channel.len());
+        channel.truncate(end);
+        channel.drain(..start.min(end));
+    }
+    apply_effects(&mut channels, sample_rate, &source.effects);
+    write_wav(wav_path, &channels, sample_rate, WavFormat::Float32)
+}
+
+/// Sources for the export mix. Sources with effects are rendered into WAVs that hold just
+/// the clip, and the mix reads those instead. The WAVs live in a temporary directory,
+/// created only when some source has effects; keep it until the mix is done.
+fn prepare_audio_sources(
+    params: Vec<AudioSourceParam>,
+) -> Result<(Vec<AudioSource>, Option<TempDir>), String> {
+    let work_dir = if params.iter().any(|s| !s.effects.is_empty()) {
+        Some(TempDir::new("effects")?)
+    } else {
+        None
+    };
+
+    let sources = params
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let (path, trim_start_ms) = match &work_dir {
+                Some(dir) if !s.effects.is_empty() => {
+                    let wav_path = dir.path().join(format!("source-{i}.wav"));
+                    render_effects(&s, &wav_path)?;
+                    (wav_path.to_string_lossy().to_string(), 0)
+                }
+                _ => (s.path.clone(), s.trim_start_ms),
+            };
+            Ok(AudioSource {
+                path,
+                start_ms: s.start_ms,
+                end_ms: s.end_ms,
+                trim_start_ms,
+                gain_envelope: place_envelope(&s.gain_envelope, s.start_ms, s.end_ms),
+                duck_envelope: s.duck_envelope,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((sources, work_dir))
 }
 
 #[tauri::command]
@@ -57,6 +144,7 @@ pub fn validate_export(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn export_video(
     app: AppHandle,
     input_video: String,
@@ -66,6 +154,8 @@ pub fn export_video(
     platform: String,
     presets_dir: Option<String>,
     audio_sources: Option<Vec<AudioSourceParam>>,
+    video_gain: Option<Vec<VideoGainParam>>,
+    font_dirs: Option<Vec<String>>,
 ) -> Result<String, String> {
     let dir = presets_dir.unwrap_or_else(|| {
         let manifest_dir = env!("CARGO_MANIFEST_DIR");
@@ -77,8 +167,9 @@ pub fn export_video(
         .find(|p| p.platform == platform)
         .ok_or_else(|| format!("Preset not found: {platform}"))?;
 
+    // ASS sizes are scaled to the export resolution
     let cues: Vec<SubtitleCue> = if let Some(srt) = &srt_file {
-        parse_srt_file(srt)?
+        import_subtitle_file(srt, preset.resolution.height)?.cues
     } else {
         Vec::new()
     };
@@ -89,18 +180,17 @@ pub fn export_video(
         crate::telop_engine::template::TelopTemplate::default()
     };
 
-    let sources: Vec<AudioSource> = audio_sources
-        .unwrap_or_default()
-        .into_iter()
-        .map(|s| AudioSource {
-            path: s.path,
-            start_ms: s.start_ms,
-            end_ms: s.end_ms,
-        })
+    // The original audio is one stream, so the clips' envelopes are joined in timeline order
+    let mut video_gain = video_gain.unwrap_or_default();
+    video_gain.sort_by_key(|clip| clip.start_ms);
+    let video_gain_envelope: Vec<GainPoint> = video_gain
+        .iter()
+        .flat_map(|clip| place_envelope(&clip.gain_envelope, clip.start_ms, clip.end_ms))
         .collect();
 
+    let progress_app = app.clone();
     let progress_cb = move |progress: ExportProgress| {
-        let _ = app.emit(
+        let _ = progress_app.emit(
             "export-progress",
             ExportProgressEvent {
                 current_frame: progress.current_frame,
@@ -110,6 +200,23 @@ pub fn export_video(
         );
     };
 
+    // Fonts bundled next to the template, then project-local fonts. Files that can't be
+    // loaded are skipped and reported once the export is done.
+    let mut fonts = FontService::new();
+    let mut font_warnings = Vec::new();
+    if let Some(template_dir) = template_path
+        .as_deref()
+        .and_then(|tp| std::path::Path::new(tp).parent())
+    {
+        let report = fonts.register_font_dir(&template_dir.join("fonts").to_string_lossy())?;
+        font_warnings.extend(report.warnings);
+    }
+    for font_dir in font_dirs.unwrap_or_default() {
+        font_warnings.extend(fonts.register_font_dir(&font_dir)?.warnings);
+    }
+
+    // Clips with effects are rendered to temporary files, removed once the mix is done
+    let (sources, effects_dir) = prepare_audio_sources(audio_sources.unwrap_or_default())?;
     render_export(
         &input_video,
         &output_path,
@@ -117,8 +224,18 @@ pub fn export_video(
         &template,
         &preset,
         &sources,
+        &video_gain_envelope,
+        &mut fonts,
         Some(&progress_cb),
     )?;
+    drop(effects_dir);
+
+    if !fonts.substitutions().is_empty() {
+        let _ = app.emit("export-font-substitutions", fonts.substitutions());
+    }
+    if !font_warnings.is_empty() {
+        let _ = app.emit("export-font-warnings", &font_warnings);
+    }
 
     Ok(output_path)
 }
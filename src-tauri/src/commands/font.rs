@@ -1,3 +1,5 @@
+use crate::models::telop::TelopStyle;
+use crate::telop_engine::font::{FontDirReport, FontService, FontSubstitution};
 use std::collections::BTreeSet;
 use tauri::command;
 
@@ -16,3 +18,32 @@ pub fn list_system_fonts() -> Result<Vec<String>, String> {
 
     Ok(families.into_iter().collect())
 }
+
+/// List the font files bundled in a project or template `fonts/` directory, with a warning
+/// for each file that can't be loaded
+#[command]
+pub fn list_project_fonts(fonts_dir: String) -> Result<FontDirReport, String> {
+    FontService::new().register_font_dir(&fonts_dir)
+}
+
+/// Report which fonts would be substituted when rendering `texts` with `style`
+#[command]
+pub fn check_telop_fonts(
+    style: TelopStyle,
+    texts: Vec<String>,
+    font_dirs: Option<Vec<String>>,
+) -> Result<Vec<FontSubstitution>, String> {
+    let mut fonts = FontService::new();
+    for dir in font_dirs.unwrap_or_default() {
+        fonts.register_font_dir(&dir)?;
+    }
+    let typeface = fonts.resolve_style(&style);
+
+    for text in &texts {
+        for ch in text.chars() {
+            fonts.typeface_for_char(&typeface, &style, ch);
+        }
+    }
+
+    Ok(fonts.substitutions().to_vec())
+}
@@ -1,6 +1,8 @@
+pub mod audio;
 pub mod beat_sync;
 pub mod export;
 pub mod font;
 pub mod preview;
 pub mod project;
 pub mod telop;
+pub mod transcribe;
@@ -1,5 +1,9 @@
+use crate::beat_sync::analyzer::BeatAnalysis;
+use crate::beat_sync::montage::{self, AutoEditOptions, MontageSource, SegmentStrategy};
+use crate::beat_sync::silence::{self, TimeRange};
 use crate::models::project::{Canvas, Platform, ProjectMetadata, ShortForgeProject};
 use crate::models::timeline::TimelineTrack;
+use crate::video_core::motion::analyze_motion;
 use serde::{Deserialize, Serialize};
 use tauri::command;
 
@@ -31,6 +35,7 @@ pub fn create_project(name: String, platform: Platform) -> Result<ShortForgeProj
         },
         tracks: vec![],
         beat_markers: vec![],
+        beat_settings: None,
     })
 }
 
@@ -168,6 +173,35 @@ pub fn import_media(
     Ok(project)
 }
 
+/// Jump-cut the video and audio clips of `source` down to `keep` (source time ranges, e.g.
+/// from `detect_silence`), closing the removed gaps on every track and the beat markers
+#[command]
+pub fn apply_jump_cuts(
+    project: ShortForgeProject,
+    source: String,
+    keep: Vec<TimeRange>,
+) -> Result<ShortForgeProject, String> {
+    silence::apply_jump_cuts(&project, &source, &keep)
+}
+
+/// Fill a new video track with segments of `sources` cut on the beats of `analysis`.
+/// The motion strategy analyzes sources that come without motion data.
+#[command]
+pub fn auto_edit(
+    project: ShortForgeProject,
+    sources: Vec<MontageSource>,
+    analysis: BeatAnalysis,
+    options: AutoEditOptions,
+) -> Result<ShortForgeProject, String> {
+    let mut sources = sources;
+    if options.strategy == SegmentStrategy::Motion {
+        for source in sources.iter_mut().filter(|s| s.motion.is_empty()) {
+            source.motion = analyze_motion(&source.path)?;
+        }
+    }
+    montage::auto_edit(&project, &sources, &analysis, &options)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
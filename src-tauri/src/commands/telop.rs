@@ -1,16 +1,123 @@
-use crate::models::telop::SubtitleCue;
+use crate::models::project::ShortForgeProject;
+use crate::models::telop::{SubtitleCue, SubtitleImport};
+use crate::telop_engine::captions::{self, CaptionFormat};
+use crate::telop_engine::font::FontService;
 use crate::telop_engine::parser;
 use crate::telop_engine::renderer;
 use crate::telop_engine::template;
+use crate::telop_engine::timing;
+use crate::telop_engine::vtt;
 use crate::video_core::decoder::VideoDecoder;
 use crate::video_core::encoder::VideoEncoder;
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 
 #[command]
-pub fn parse_srt(path: String) -> Result<Vec<SubtitleCue>, String> {
+pub fn parse_srt(path: String) -> Result<SubtitleImport, String> {
     parser::parse_srt_file(&path)
 }
 
+#[command]
+pub fn parse_vtt(path: String) -> Result<SubtitleImport, String> {
+    vtt::parse_vtt_file(&path)
+}
+
+/// Import a subtitle file in any supported format (chosen by extension).
+/// `frame_height` is the project canvas height, used to scale ASS sizes.
+#[command]
+pub fn import_subtitles(path: String, frame_height: Option<u32>) -> Result<SubtitleImport, String> {
+    parser::import_subtitle_file(
+        &path,
+        frame_height.unwrap_or(crate::telop_engine::ass::DEFAULT_FRAME_HEIGHT),
+    )
+}
+
+/// Write a project's telop track as an SRT or WebVTT sidecar (chosen by the output
+/// extension). Without `track_id` every telop track is included.
+#[command]
+pub fn export_captions(
+    project: ShortForgeProject,
+    output_path: String,
+    track_id: Option<String>,
+    offset_ms: Option<i64>,
+) -> Result<String, String> {
+    let format = CaptionFormat::from_path(&output_path)?;
+    let cues = captions::project_cues(&project, track_id.as_deref(), offset_ms.unwrap_or(0))?;
+    std::fs::write(&output_path, format.write(&cues))
+        .map_err(|e| format!("Failed to write captions: {e}"))?;
+    Ok(output_path)
+}
+
+/// Shift all cues by `offset_ms` (negative = earlier)
+#[command]
+pub fn offset_cues(cues: Vec<SubtitleCue>, offset_ms: i64) -> Vec<SubtitleCue> {
+    timing::offset(&cues, offset_ms)
+}
+
+/// Scale cue times around `anchor_ms` (default 0), e.g. by `from_fps / to_fps`
+#[command]
+pub fn stretch_cues(
+    cues: Vec<SubtitleCue>,
+    factor: f64,
+    anchor_ms: Option<u64>,
+) -> Result<Vec<SubtitleCue>, String> {
+    timing::stretch(&cues, factor, anchor_ms.unwrap_or(0))
+}
+
+/// Retime cues so `from_a_ms` lands on `to_a_ms` and `from_b_ms` on `to_b_ms`
+#[command]
+pub fn sync_cues(
+    cues: Vec<SubtitleCue>,
+    from_a_ms: u64,
+    to_a_ms: u64,
+    from_b_ms: u64,
+    to_b_ms: u64,
+) -> Result<Vec<SubtitleCue>, String> {
+    timing::two_point_sync(&cues, (from_a_ms, to_a_ms), (from_b_ms, to_b_ms))
+}
+
+#[command]
+pub fn enforce_cue_durations(
+    cues: Vec<SubtitleCue>,
+    min_duration_ms: Option<u64>,
+    min_gap_ms: Option<u64>,
+) -> Vec<SubtitleCue> {
+    timing::enforce_durations(
+        &cues,
+        min_duration_ms.unwrap_or(1000),
+        min_gap_ms.unwrap_or(80),
+    )
+}
+
+#[command]
+pub fn merge_cues(
+    cues: Vec<SubtitleCue>,
+    max_chars_per_line: usize,
+    max_lines: Option<usize>,
+    max_gap_ms: Option<u64>,
+) -> Result<Vec<SubtitleCue>, String> {
+    timing::merge(
+        &cues,
+        max_chars_per_line,
+        max_lines.unwrap_or(2),
+        max_gap_ms.unwrap_or(500),
+    )
+}
+
+#[command]
+pub fn split_cues(
+    cues: Vec<SubtitleCue>,
+    max_chars_per_line: usize,
+    max_lines: Option<usize>,
+) -> Result<Vec<SubtitleCue>, String> {
+    timing::split(&cues, max_chars_per_line, max_lines.unwrap_or(2))
+}
+
+/// Snap cue times to frame boundaries of the project frame rate (`Canvas.fps`)
+#[command]
+pub fn snap_cues_to_frames(cues: Vec<SubtitleCue>, fps: u32) -> Result<Vec<SubtitleCue>, String> {
+    timing::snap_to_frames(&cues, fps)
+}
+
 #[command]
 pub fn get_templates(templates_dir: Option<String>) -> Result<Vec<template::TelopTemplate>, String> {
     let dir = templates_dir.unwrap_or_else(|| {
@@ -22,12 +129,14 @@ pub fn get_templates(templates_dir: Option<String>) -> Result<Vec<template::Telo
 
 #[command]
 pub fn burn_telop(
+    app: AppHandle,
     input_video: String,
     srt_file: String,
     template_id: String,
     templates_dir: Option<String>,
     output_path: String,
     bitrate: Option<usize>,
+    font_dirs: Option<Vec<String>>,
 ) -> Result<String, String> {
     // Load templates and find the selected one
     let dir = templates_dir.unwrap_or_else(|| {
@@ -40,8 +149,13 @@ pub fn burn_telop(
         .find(|t| t.id == template_id)
         .ok_or_else(|| format!("Template '{template_id}' not found"))?;
 
-    // Parse SRT
-    let cues = parser::parse_srt_file(&srt_file)?;
+    // Fonts bundled with the template package, then project-local fonts. Files that can't
+    // be loaded are skipped and reported once the video is written.
+    let mut fonts = FontService::new();
+    let mut font_warnings = fonts.register_font_dir(&format!("{dir}/fonts"))?.warnings;
+    for font_dir in font_dirs.unwrap_or_default() {
+        font_warnings.extend(fonts.register_font_dir(&font_dir)?.warnings);
+    }
 
     // Open input video
     let mut decoder = VideoDecoder::open(&input_video)?;
@@ -50,21 +164,36 @@ pub fn burn_telop(
     let fps = decoder.fps();
     let bitrate = bitrate.unwrap_or(6_000_000);
 
+    // Parse subtitles (SRT, WebVTT or ASS scaled to the video)
+    let cues = parser::import_subtitle_file(&srt_file, height)?.cues;
+
     // Create encoder
     let mut encoder = VideoEncoder::new(&output_path, width, height, fps, bitrate)?;
 
     // Process each frame
     let tmpl_clone = tmpl.clone();
     let cues_clone = cues.clone();
+    let mut layouts = renderer::TelopLayoutCache::default();
 
     decoder.decode_frames(|mut frame| {
         let current_time_ms = frame.pts_ms;
-        renderer::render_telop_on_frame(&mut frame, &cues_clone, &tmpl_clone, current_time_ms);
+        renderer::render_telop_on_frame(
+            &mut frame,
+            &cues_clone,
+            &tmpl_clone,
+            current_time_ms,
+            &mut fonts,
+            &mut layouts,
+        );
         encoder.write_frame(&frame)?;
         Ok(())
     })?;
 
     encoder.finish()?;
 
+    if !font_warnings.is_empty() {
+        let _ = app.emit("export-font-warnings", &font_warnings);
+    }
+
     Ok(output_path)
 }
@@ -0,0 +1,22 @@
+use crate::beat_sync::analyzer::decode_audio_mono;
+use crate::models::telop::SubtitleCue;
+use crate::transcribe::transcriber::{TranscribeOptions, Transcriber};
+use crate::transcribe::whisper::WhisperCppTranscriber;
+
+/// Transcribe the audio of a media file into word-timed cues with a local whisper.cpp model.
+/// `binary_path` defaults to `whisper-cli` on the PATH.
+#[tauri::command]
+pub fn transcribe_media(
+    media_path: String,
+    model_path: String,
+    binary_path: Option<String>,
+    threads: Option<u32>,
+    options: Option<TranscribeOptions>,
+) -> Result<Vec<SubtitleCue>, String> {
+    let (samples, sample_rate) = decode_audio_mono(&media_path)?;
+
+    let mut transcriber =
+        WhisperCppTranscriber::new(binary_path.as_deref().unwrap_or("whisper-cli"), &model_path);
+    transcriber.threads = threads;
+    transcriber.transcribe(&samples, sample_rate, &options.unwrap_or_default())
+}
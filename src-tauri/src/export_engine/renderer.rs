@@ -1,6 +1,9 @@
+use crate::audio_engine::envelope::gain_expression;
 use crate::export_engine::preset::ExportPreset;
 use crate::models::telop::SubtitleCue;
-use crate::telop_engine::renderer::render_telop_on_frame;
+use crate::models::timeline::GainPoint;
+use crate::telop_engine::font::FontService;
+use crate::telop_engine::renderer::{render_telop_on_frame, TelopLayoutCache};
 use crate::telop_engine::template::TelopTemplate;
 use crate::video_core::decoder::VideoDecoder;
 use crate::video_core::encoder::VideoEncoder;
@@ -18,9 +21,16 @@ pub struct AudioSource {
     pub path: String,
     pub start_ms: u64,
     pub end_ms: u64,
+    /// Position in the source where the clip starts playing
+    pub trim_start_ms: u64,
+    /// Ducking under speech, in timeline time
+    pub duck_envelope: Vec<GainPoint>,
+    /// Volume automation, in timeline time
+    pub gain_envelope: Vec<GainPoint>,
 }
 
 /// Render a video with telop overlay for a specific platform preset
+#[allow(clippy::too_many_arguments)]
 pub fn render_export(
     input_video: &str,
     output_path: &str,
@@ -28,10 +38,12 @@ pub fn render_export(
     template: &TelopTemplate,
     preset: &ExportPreset,
     audio_sources: &[AudioSource],
+    video_gain_envelope: &[GainPoint],
+    fonts: &mut FontService,
     progress_callback: Option<&dyn Fn(ExportProgress)>,
 ) -> Result<(), String> {
-    // If we have audio sources, render video-only first then mux audio
-    let needs_audio_mux = !audio_sources.is_empty();
+    // If we have audio sources or automation, render video-only first then mux audio
+    let needs_audio_mux = !audio_sources.is_empty() || !video_gain_envelope.is_empty();
     let video_only_path = if needs_audio_mux {
         let mut tmp = std::path::PathBuf::from(output_path);
         tmp.set_extension("_video_only.mp4");
@@ -54,10 +66,11 @@ pub fn render_export(
 
     let cues = cues.to_vec();
     let template = template.clone();
+    let mut layouts = TelopLayoutCache::default();
 
     decoder.decode_frames(|mut frame| {
         let pts = frame.pts_ms;
-        render_telop_on_frame(&mut frame, &cues, &template, pts);
+        render_telop_on_frame(&mut frame, &cues, &template, pts, fonts, &mut layouts);
         encoder.write_frame(&frame)?;
 
         frame_count += 1;
@@ -83,6 +96,7 @@ pub fn render_export(
             &video_only_path,
             input_video,
             audio_sources,
+            video_gain_envelope,
             output_path,
             preset,
         )?;
@@ -97,10 +111,12 @@ pub fn render_export(
 ///
 /// Takes the video-only rendered file, the original video (for its audio stream),
 /// and additional audio sources (BGM etc.), and produces the final output.
+/// Gain envelopes (in timeline time) are applied to each stream before mixing.
 fn mux_audio(
     video_only_path: &str,
     original_video: &str,
     audio_sources: &[AudioSource],
+    video_gain_envelope: &[GainPoint],
     output_path: &str,
     preset: &ExportPreset,
 ) -> Result<(), String> {
@@ -119,20 +135,51 @@ fn mux_audio(
             let delay_sec = src.start_ms as f64 / 1000.0;
             cmd.args(["-itsoffset", &format!("{delay_sec:.3}")]);
         }
+        // Trimmed clips play from their in-point for the length of the clip
+        if src.trim_start_ms > 0 {
+            let trim_sec = src.trim_start_ms as f64 / 1000.0;
+            let length_sec = src.end_ms.saturating_sub(src.start_ms) as f64 / 1000.0;
+            cmd.args(["-ss", &format!("{trim_sec:.3}")]);
+            cmd.args(["-t", &format!("{length_sec:.3}")]);
+        }
         cmd.args(["-i", &src.path]);
         // Log for debugging
         eprintln!(
-            "Audio source {}: {} (start={}ms, end={}ms)",
+            "Audio source {}: {} (start={}ms, end={}ms, trim={}ms)",
             i + 2,
             src.path,
             src.start_ms,
-            src.end_ms
+            src.end_ms,
+            src.trim_start_ms
         );
     }
 
+    // Envelopes are applied sample by sample with aeval, labelled [gainN] for input N
+    let mut filter = String::new();
+    let mut apply_envelopes = |input: usize, envelopes: &[&[GainPoint]]| -> String {
+        let factors: Vec<String> = envelopes
+            .iter()
+            .filter(|envelope| !envelope.is_empty())
+            .map(|envelope| format!("({})", gain_expression(envelope)))
+            .collect();
+        if factors.is_empty() {
+            return format!("[{input}:a]");
+        }
+        filter.push_str(&format!(
+            "[{input}:a]aeval='exprs=val(ch)*{}:c=same'[gain{input}];",
+            factors.join("*")
+        ));
+        format!("[gain{input}]")
+    };
+    // [1:a] is the original video's audio
+    let mut mix_inputs = apply_envelopes(1, &[video_gain_envelope]);
+    for (i, src) in audio_sources.iter().enumerate() {
+        mix_inputs.push_str(&apply_envelopes(i + 2, &[&src.duck_envelope, &src.gain_envelope]));
+    }
+
     // Build filter_complex for audio mixing
     let total_audio_inputs = 1 + audio_sources.len(); // original audio + BGM tracks
-    if total_audio_inputs == 1 {
+    if total_audio_inputs == 1 && filter.is_empty() {
         // Only original audio, no mixing needed
         cmd.args([
             "-map", "0:v:0",   // video from rendered file
@@ -143,11 +190,7 @@ fn mux_audio(
         ]);
     } else {
         // Multiple audio streams — use amix filter
-        let mut filter = String::new();
-        // [1:a] is the original video's audio
-        for i in 0..total_audio_inputs {
-            filter.push_str(&format!("[{}:a]", i + 1));
-        }
+        filter.push_str(&mix_inputs);
         filter.push_str(&format!(
             "amix=inputs={total_audio_inputs}:duration=longest:dropout_transition=2[aout]"
         ));
@@ -5,6 +5,8 @@ mod telop_engine;
 mod video_core;
 mod export_engine;
 mod beat_sync;
+mod transcribe;
+mod audio_engine;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,20 +17,48 @@ pub fn run() {
             commands::telop::get_templates,
             commands::telop::burn_telop,
             commands::telop::parse_srt,
+            commands::telop::parse_vtt,
+            commands::telop::import_subtitles,
+            commands::telop::export_captions,
+            commands::telop::offset_cues,
+            commands::telop::stretch_cues,
+            commands::telop::sync_cues,
+            commands::telop::enforce_cue_durations,
+            commands::telop::merge_cues,
+            commands::telop::split_cues,
+            commands::telop::snap_cues_to_frames,
             commands::project::create_project,
             commands::project::save_project,
             commands::project::load_project,
             commands::project::probe_media,
             commands::project::import_media,
+            commands::project::apply_jump_cuts,
+            commands::project::auto_edit,
             commands::preview::extract_frame,
             commands::export::get_presets,
             commands::export::get_preset,
             commands::export::validate_export,
             commands::export::export_video,
             commands::beat_sync::detect_beats,
+            commands::beat_sync::tap_tempo,
+            commands::beat_sync::shift_beat_grid,
+            commands::beat_sync::generate_beat_grid,
+            commands::beat_sync::store_beat_markers,
             commands::beat_sync::get_waveform,
+            commands::beat_sync::detect_silence,
+            commands::audio::build_waveform_peaks,
+            commands::audio::query_waveform_peaks,
+            commands::audio::compute_spectrogram,
+            commands::audio::analyze_ducking,
+            commands::audio::capture_noise_profile,
+            commands::beat_sync::analyze_music_structure,
+            commands::beat_sync::find_music_highlight,
             commands::beat_sync::snap_time_to_beat,
+            commands::beat_sync::snap_cues_to_beats,
+            commands::transcribe::transcribe_media,
             commands::font::list_system_fonts,
+            commands::font::list_project_fonts,
+            commands::font::check_telop_fonts,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
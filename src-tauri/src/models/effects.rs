@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+/// One stage of a clip's audio effect chain. Stages run in order on export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioEffect {
+    HighPass(HighPass),
+    Equalizer(Equalizer),
+    Compressor(Compressor),
+    Limiter(Limiter),
+    NoiseGate(NoiseGate),
+    NoiseReduction(NoiseReduction),
+}
+
+/// Butterworth high-pass filter, e.g. against rumble and handling noise
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighPass {
+    pub cutoff_hz: f32,
+    /// Steepness below the cutoff: 12, 24, 36 or 48 dB per octave
+    pub slope_db: u32,
+}
+
+impl Default for HighPass {
+    fn default() -> Self {
+        Self {
+            cutoff_hz: 80.0,
+            slope_db: 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EqBandKind {
+    /// Boost or cut around the frequency
+    #[default]
+    Peak,
+    /// Boost or cut everything below the frequency
+    LowShelf,
+    /// Boost or cut everything above the frequency
+    HighShelf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    #[serde(default)]
+    pub kind: EqBandKind,
+    pub freq_hz: f32,
+    pub gain_db: f32,
+    /// Bandwidth of a peak band; higher is narrower
+    pub q: f32,
+}
+
+/// Parametric equalizer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Equalizer {
+    #[serde(default)]
+    pub bands: Vec<EqBand>,
+}
+
+/// Downward compressor with a soft knee, linked across channels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compressor {
+    /// Level (dBFS) above which the gain is reduced
+    pub threshold_db: f32,
+    /// Input dB over the threshold per output dB
+    pub ratio: f32,
+    /// Width of the soft knee around the threshold
+    pub knee_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    /// Gain added after compression
+    pub makeup_db: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self {
+            threshold_db: -18.0,
+            ratio: 4.0,
+            knee_db: 6.0,
+            attack_ms: 10.0,
+            release_ms: 150.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+/// Brickwall limiter: no sample exceeds the ceiling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Limiter {
+    /// Highest output level (dBFS)
+    pub ceiling_db: f32,
+    /// How far ahead of a peak the gain starts coming down
+    pub lookahead_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self {
+            ceiling_db: -1.0,
+            lookahead_ms: 5.0,
+            release_ms: 50.0,
+        }
+    }
+}
+
+/// Silences the clip between phrases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoiseGate {
+    /// Level (dBFS) that opens the gate
+    pub threshold_db: f32,
+    /// Gain while closed (negative dB)
+    pub range_db: f32,
+    pub attack_ms: f32,
+    /// How long the gate stays open after the level drops
+    pub hold_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for NoiseGate {
+    fn default() -> Self {
+        Self {
+            threshold_db: -50.0,
+            range_db: -60.0,
+            attack_ms: 2.0,
+            hold_ms: 80.0,
+            release_ms: 150.0,
+        }
+    }
+}
+
+/// Average magnitude spectrum of a stretch of background noise
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoiseProfile {
+    pub sample_rate: u32,
+    pub fft_size: usize,
+    /// Per FFT bin, `fft_size / 2 + 1` values
+    pub magnitudes: Vec<f32>,
+}
+
+/// Spectral noise reduction against a captured noise profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoiseReduction {
+    /// Captured noise; estimated from the quietest parts of the clip when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<NoiseProfile>,
+    /// Most the noise is lowered by
+    pub reduction_db: f32,
+    /// Multiple of the noise level removed from each bin; higher removes more
+    pub sensitivity: f32,
+}
+
+impl Default for NoiseReduction {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            reduction_db: 18.0,
+            sensitivity: 1.5,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod effects;
 pub mod project;
 pub mod telop;
 pub mod timeline;
@@ -8,6 +8,9 @@ pub struct ShortForgeProject {
     pub tracks: Vec<super::timeline::TimelineTrack>,
     #[serde(default)]
     pub beat_markers: Vec<BeatMarker>,
+    /// How `beat_markers` were produced, so they can be regenerated
+    #[serde(default)]
+    pub beat_settings: Option<BeatSettings>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,8 +36,53 @@ pub struct Canvas {
     pub duration_ms: u64,
 }
 
+/// Drum hit at a beat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeatKind {
+    Kick,
+    Snare,
+    HiHat,
+    #[default]
+    Other,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BeatMarker {
     pub time_ms: u64,
     pub strength: f64,
+    #[serde(default)]
+    pub bar: u32,
+    #[serde(default)]
+    pub phase: u8,
+    #[serde(default)]
+    pub kind: BeatKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeatSettings {
+    /// Audio the beats were detected from
+    #[serde(default)]
+    pub audio_path: Option<String>,
+    pub beats_per_bar: u8,
+    /// Manual grid; when set, the markers were generated from it instead of detected
+    #[serde(default)]
+    pub grid: Option<BeatGrid>,
+    #[serde(default)]
+    pub tempo_map: Vec<TempoSegment>,
+}
+
+/// A constant-tempo beat grid. The anchor is a downbeat.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BeatGrid {
+    pub anchor_ms: u64,
+    pub bpm: f64,
+}
+
+/// A stretch of the timeline with a steady tempo
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub bpm: f64,
 }
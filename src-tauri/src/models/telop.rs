@@ -1,16 +1,84 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct SubtitleCue {
     pub index: usize,
     pub start_ms: u64,
     pub end_ms: u64,
     pub text: String,
+    /// Optional per-word timings, used by karaoke templates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<WordTiming>,
+    /// Cue identifier from the source file, e.g. a WebVTT cue id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Speaker name, e.g. from a WebVTT `<v>` tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    /// Positioning hints from the source file. The template position applies when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<CuePlacement>,
+    /// Style of this cue, e.g. from an ASS style. The template style applies when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<TelopStyle>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Cues read from a subtitle file, with notes about anything that could not be imported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubtitleImport {
+    pub cues: Vec<SubtitleCue>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Problems found while reading the file, with the line they were found on
+    #[serde(default)]
+    pub diagnostics: Vec<SubtitleDiagnostic>,
+}
+
+/// A recoverable problem in a subtitle file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubtitleDiagnostic {
+    /// 1-based line number, or 0 when it concerns the whole file (e.g. its encoding)
+    pub line: usize,
+    pub message: String,
+}
+
+/// Where a cue is placed on the frame, following the WebVTT cue settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CuePlacement {
+    /// Vertical position of the cue as a fraction of the frame height (0.0 = top)
+    #[serde(default)]
+    pub line: Option<f32>,
+    /// Which edge of the cue sits at `line`: "start" (top, default), "center" or "end"
+    #[serde(default)]
+    pub line_align: Option<String>,
+    /// Line number from the top, or from the bottom when negative (-1 = last line)
+    #[serde(default)]
+    pub line_number: Option<i32>,
+    /// Horizontal anchor as a fraction of the frame width
+    #[serde(default)]
+    pub position: Option<f32>,
+    /// Text alignment relative to `position`: "start", "center", "end", "left" or "right"
+    #[serde(default)]
+    pub align: Option<String>,
+    /// Width of the cue box as a fraction of the frame width
+    #[serde(default)]
+    pub size: Option<f32>,
+}
+
+/// Timing of a single word inside a cue (absolute times, like the cue itself)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WordTiming {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TelopStyle {
     pub font_family: String,
+    /// Families tried, in order, when `font_family` is missing or lacks a glyph
+    #[serde(default)]
+    pub font_fallbacks: Vec<String>,
     pub font_size: f32,
     pub font_weight: u32,
     pub color: String,
@@ -22,9 +90,20 @@ pub struct TelopStyle {
     pub position: Option<Position>,
     #[serde(default)]
     pub alignment: Option<String>,
+    /// Gradient fill. Replaces `color` when set.
+    #[serde(default)]
+    pub fill: Option<GradientFill>,
+    /// Rounded plate drawn behind each line or the whole cue
+    #[serde(default)]
+    pub background: Option<BackgroundStyle>,
+    /// Additional outlines stacked with `outline`; wider strokes are drawn underneath
+    #[serde(default)]
+    pub outlines: Vec<OutlineStyle>,
+    #[serde(default)]
+    pub inner_glow: Option<GlowStyle>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OutlineStyle {
     pub enabled: bool,
     pub color: String,
@@ -38,7 +117,7 @@ fn default_stroke_join() -> String {
     "miter".to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShadowStyle {
     pub enabled: bool,
     pub color: String,
@@ -47,7 +126,69 @@ pub struct ShadowStyle {
     pub blur: f32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradientFill {
+    #[serde(rename = "type")]
+    pub gradient_type: GradientType,
+    /// At least two CSS hex colors
+    pub colors: Vec<String>,
+    /// Color stop positions (0.0 - 1.0). Evenly spaced when omitted.
+    #[serde(default)]
+    pub stops: Option<Vec<f32>>,
+    /// Direction of a linear gradient in degrees: 0 = left to right, 90 = top to bottom
+    #[serde(default = "default_gradient_angle")]
+    pub angle: f32,
+}
+
+fn default_gradient_angle() -> f32 {
+    90.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientType {
+    Linear,
+    Radial,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundStyle {
+    pub enabled: bool,
+    pub color: String,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub padding_x: f32,
+    #[serde(default)]
+    pub padding_y: f32,
+    #[serde(default)]
+    pub corner_radius: f32,
+    #[serde(default)]
+    pub mode: BackgroundMode,
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundMode {
+    /// One plate per line
+    #[default]
+    Line,
+    /// A single plate around all lines of the cue
+    Cue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlowStyle {
+    pub enabled: bool,
+    pub color: String,
+    pub blur: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -1,3 +1,5 @@
+use super::effects::AudioEffect;
+use super::telop::{CuePlacement, TelopStyle, WordTiming};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +26,9 @@ pub struct VideoClip {
     pub end_ms: u64,
     pub trim_start_ms: u64,
     pub trim_end_ms: u64,
+    /// Volume automation of the embedded audio, relative to the clip start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gain_envelope: Vec<GainPoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,4 +38,63 @@ pub struct AudioClip {
     pub start_ms: u64,
     pub end_ms: u64,
     pub volume: f32,
+    /// Position in the source where playback starts
+    #[serde(default)]
+    pub trim_start_ms: u64,
+    /// Automatic ducking under speech, in timeline time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duck_envelope: Vec<GainPoint>,
+    /// Volume automation, relative to the clip start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gain_envelope: Vec<GainPoint>,
+    /// Effect chain applied on export, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<AudioEffect>,
+}
+
+/// A point of a gain envelope. `curve` shapes the change in dB from this point to the next.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GainPoint {
+    pub time_ms: u64,
+    pub gain_db: f32,
+    #[serde(default)]
+    pub curve: GainCurve,
+}
+
+/// How the gain moves between two envelope points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GainCurve {
+    /// Evenly in dB
+    #[default]
+    Linear,
+    /// Stays at this point's gain, then jumps to the next
+    Hold,
+    /// Slow start, fast finish
+    EaseIn,
+    /// Fast start, slow finish
+    EaseOut,
+    /// Slow start and finish
+    SCurve,
+}
+
+/// A cue on a telop track. Also accepts the editor's camelCase clip fields
+/// (`startMs`, `endMs`, `label`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelopClip {
+    pub id: String,
+    #[serde(alias = "startMs")]
+    pub start_ms: u64,
+    #[serde(alias = "endMs")]
+    pub end_ms: u64,
+    #[serde(alias = "label")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<WordTiming>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<CuePlacement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<TelopStyle>,
 }
@@ -1,5 +1,7 @@
-use crate::models::telop::{AnimationKeyframe, CharRenderState};
-use crate::telop_engine::template::TelopTemplate;
+use crate::models::telop::{AnimationKeyframe, CharRenderState, SubtitleCue};
+use crate::telop_engine::markup::strip_markup;
+use crate::telop_engine::template::{KaraokeTiming, TelopTemplate};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Parse a cubic-bezier string like "cubic-bezier(0.34, 1.56, 0.64, 1)"
 /// Returns (x1, y1, x2, y2) control points
@@ -48,7 +50,246 @@ fn cubic_bezier_component(t: f64, p1: f64, p2: f64) -> f64 {
     3.0 * mt2 * t * p1 + 3.0 * mt * t2 * p2 + t3
 }
 
-/// Apply easing function to a linear progress value (0.0 - 1.0)
+/// Parse a spring easing like "spring(1, 100, 10)" or "spring(1, 100, 10, 0)"
+/// Returns (mass, stiffness, damping, initial_velocity)
+fn parse_spring(easing: &str) -> Option<(f64, f64, f64, f64)> {
+    let inner = easing.strip_prefix("spring(")?.strip_suffix(')')?;
+    let parts: Vec<f64> = inner
+        .split(',')
+        .map(|s| s.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let (mass, stiffness, damping, velocity) = match parts[..] {
+        [m, k, c] => (m, k, c, 0.0),
+        [m, k, c, v] => (m, k, c, v),
+        _ => return None,
+    };
+    if mass <= 0.0 || stiffness <= 0.0 || damping < 0.0 {
+        return None;
+    }
+    Some((mass, stiffness, damping, velocity))
+}
+
+/// Position of a damped spring released at 0 and pulled towards 1, at physical time `t` (seconds)
+fn spring_position(t: f64, mass: f64, stiffness: f64, damping: f64, velocity: f64) -> f64 {
+    let w0 = (stiffness / mass).sqrt();
+    let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+
+    if zeta < 1.0 {
+        // Under-damped: oscillates around the target
+        let wd = w0 * (1.0 - zeta * zeta).sqrt();
+        let b = (zeta * w0 - velocity) / wd;
+        1.0 - (-zeta * w0 * t).exp() * ((wd * t).cos() + b * (wd * t).sin())
+    } else if zeta == 1.0 {
+        // Critically damped
+        let b = w0 - velocity;
+        1.0 - (-w0 * t).exp() * (1.0 + b * t)
+    } else {
+        // Over-damped: sum of two decaying exponentials
+        let root = w0 * (zeta * zeta - 1.0).sqrt();
+        let r1 = -zeta * w0 + root;
+        let r2 = -zeta * w0 - root;
+        let c1 = (velocity + r2) / (r2 - r1);
+        let c2 = 1.0 - c1;
+        1.0 - (c1 * (r1 * t).exp() + c2 * (r2 * t).exp())
+    }
+}
+
+/// Time (seconds) after which the spring stays within 0.1% of its target.
+/// The animation duration is mapped onto this settling time.
+fn spring_settle_time(mass: f64, stiffness: f64, damping: f64, velocity: f64) -> f64 {
+    const TOLERANCE: f64 = 0.001;
+    let w0 = (stiffness / mass).sqrt();
+    let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+
+    if zeta < 1.0 {
+        if zeta == 0.0 {
+            // Undamped springs never settle; use a few periods
+            return 4.0 * std::f64::consts::TAU / w0;
+        }
+        // The oscillation is bounded by its exponential envelope
+        let wd = w0 * (1.0 - zeta * zeta).sqrt();
+        let b = (zeta * w0 - velocity) / wd;
+        let amplitude = (1.0 + b * b).sqrt();
+        return ((amplitude / TOLERANCE).ln() / (zeta * w0)).max(0.0);
+    }
+
+    // Non-oscillating: start from the slowest decay rate and widen until settled
+    let slowest_rate = w0 * (zeta - (zeta * zeta - 1.0).sqrt());
+    let mut t = (1.0 / TOLERANCE).ln() / slowest_rate;
+    for _ in 0..50 {
+        if (1.0 - spring_position(t, mass, stiffness, damping, velocity)).abs() <= TOLERANCE {
+            break;
+        }
+        t *= 1.1;
+    }
+    t
+}
+
+fn spring_at(progress: f64, mass: f64, stiffness: f64, damping: f64, velocity: f64) -> f64 {
+    if progress >= 1.0 {
+        return 1.0;
+    }
+    let settle = spring_settle_time(mass, stiffness, damping, velocity);
+    spring_position(progress * settle, mass, stiffness, damping, velocity)
+}
+
+/// Parse a steps easing like "steps(4)" or "steps(4, jump-start)"
+/// Returns (number of steps, jump position)
+fn parse_steps(easing: &str) -> Option<(u32, &str)> {
+    let inner = easing.strip_prefix("steps(")?.strip_suffix(')')?;
+    let mut parts = inner.split(',').map(str::trim);
+    let steps: u32 = parts.next()?.parse().ok()?;
+    let position = match parts.next() {
+        None => "jump-end",
+        Some("end") => "jump-end",
+        Some("start") => "jump-start",
+        Some(p @ ("jump-start" | "jump-end" | "jump-none" | "jump-both")) => p,
+        Some(_) => return None,
+    };
+    if parts.next().is_some() || steps == 0 || (position == "jump-none" && steps < 2) {
+        return None;
+    }
+    Some((steps, position))
+}
+
+/// Evaluate a CSS steps() function
+fn steps_at(progress: f64, steps: u32, position: &str) -> f64 {
+    let steps = steps as f64;
+    let mut step = (progress * steps).floor();
+    if position == "jump-start" || position == "jump-both" {
+        step += 1.0;
+    }
+    let jumps = match position {
+        "jump-none" => steps - 1.0,
+        "jump-both" => steps + 1.0,
+        _ => steps,
+    };
+    step.clamp(0.0, jumps) / jumps
+}
+
+/// Parse a CSS piecewise-linear easing like "linear(0, 0.25 75%, 1)"
+/// Returns the resolved (input, output) points
+fn parse_linear_points(easing: &str) -> Option<Vec<(f64, f64)>> {
+    let inner = easing.strip_prefix("linear(")?.strip_suffix(')')?;
+
+    // Each stop is an output value followed by zero, one or two input percentages
+    let mut points: Vec<(Option<f64>, f64)> = Vec::new();
+    for stop in inner.split(',') {
+        let mut tokens = stop.split_whitespace();
+        let output: f64 = tokens.next()?.parse().ok()?;
+        let inputs: Vec<f64> = tokens
+            .map(|t| t.strip_suffix('%')?.parse::<f64>().ok().map(|p| p / 100.0))
+            .collect::<Option<_>>()?;
+        match inputs[..] {
+            [] => points.push((None, output)),
+            [a] => points.push((Some(a), output)),
+            [a, b] => {
+                points.push((Some(a), output));
+                points.push((Some(b), output));
+            }
+            _ => return None,
+        }
+    }
+    if points.len() < 2 {
+        return None;
+    }
+
+    // The first and last stops default to 0% and 100%
+    let last = points.len() - 1;
+    points[0].0.get_or_insert(0.0);
+    points[last].0.get_or_insert(1.0);
+
+    // Inputs never go backwards
+    let mut max_input = f64::MIN;
+    for point in points.iter_mut() {
+        if let Some(input) = point.0.as_mut() {
+            *input = input.max(max_input);
+            max_input = *input;
+        }
+    }
+
+    // Stops without an input are spread evenly between their neighbours
+    let mut i = 0;
+    while i < points.len() {
+        if points[i].0.is_some() {
+            i += 1;
+            continue;
+        }
+        let start = i - 1;
+        let end = (i..points.len()).find(|&j| points[j].0.is_some())?;
+        let from = points[start].0?;
+        let to = points[end].0?;
+        for (k, point) in points.iter_mut().enumerate().take(end).skip(i) {
+            point.0 = Some(from + (to - from) * (k - start) as f64 / (end - start) as f64);
+        }
+        i = end;
+    }
+
+    points
+        .into_iter()
+        .map(|(input, output)| input.map(|input| (input, output)))
+        .collect()
+}
+
+/// Evaluate a piecewise-linear easing (CSS Easing Level 2 algorithm)
+fn linear_points_at(progress: f64, points: &[(f64, f64)]) -> f64 {
+    let a = points
+        .iter()
+        .rposition(|&(input, _)| input <= progress)
+        .unwrap_or(0)
+        .min(points.len() - 2);
+    let (in_a, out_a) = points[a];
+    let (in_b, out_b) = points[a + 1];
+
+    if in_a == in_b {
+        return out_b;
+    }
+    lerp(out_a, out_b, (progress - in_a) / (in_b - in_a))
+}
+
+/// easings.net "easeOutBounce"
+fn bounce_out(x: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+
+    if x < 1.0 / D1 {
+        N1 * x * x
+    } else if x < 2.0 / D1 {
+        let x = x - 1.5 / D1;
+        N1 * x * x + 0.75
+    } else if x < 2.5 / D1 {
+        let x = x - 2.25 / D1;
+        N1 * x * x + 0.9375
+    } else {
+        let x = x - 2.625 / D1;
+        N1 * x * x + 0.984375
+    }
+}
+
+/// easings.net "easeOutElastic"
+fn elastic_out(x: f64) -> f64 {
+    if x <= 0.0 || x >= 1.0 {
+        return x;
+    }
+    let c4 = std::f64::consts::TAU / 3.0;
+    2.0_f64.powf(-10.0 * x) * ((x * 10.0 - 0.75) * c4).sin() + 1.0
+}
+
+/// easings.net "easeInOutElastic"
+fn elastic_in_out(x: f64) -> f64 {
+    if x <= 0.0 || x >= 1.0 {
+        return x;
+    }
+    let c5 = std::f64::consts::TAU / 4.5;
+    if x < 0.5 {
+        -(2.0_f64.powf(20.0 * x - 10.0) * ((20.0 * x - 11.125) * c5).sin()) / 2.0
+    } else {
+        2.0_f64.powf(-20.0 * x + 10.0) * ((20.0 * x - 11.125) * c5).sin() / 2.0 + 1.0
+    }
+}
+
+/// Apply easing function to a linear progress value (0.0 - 1.0).
+/// Overshooting easings (spring, elastic, back-style bezier) may return values outside 0.0 - 1.0.
 pub fn apply_easing(progress: f64, easing: &str) -> f64 {
     let progress = progress.clamp(0.0, 1.0);
 
@@ -58,9 +299,29 @@ pub fn apply_easing(progress: f64, easing: &str) -> f64 {
         "ease-in" => cubic_bezier_at(progress, 0.42, 0.0, 1.0, 1.0),
         "ease-out" => cubic_bezier_at(progress, 0.0, 0.0, 0.58, 1.0),
         "ease-in-out" => cubic_bezier_at(progress, 0.42, 0.0, 0.58, 1.0),
+        "step-start" => steps_at(progress, 1, "jump-start"),
+        "step-end" => steps_at(progress, 1, "jump-end"),
+        "bounce" | "bounce-out" => bounce_out(progress),
+        "bounce-in" => 1.0 - bounce_out(1.0 - progress),
+        "bounce-in-out" => {
+            if progress < 0.5 {
+                (1.0 - bounce_out(1.0 - 2.0 * progress)) / 2.0
+            } else {
+                (1.0 + bounce_out(2.0 * progress - 1.0)) / 2.0
+            }
+        }
+        "elastic" | "elastic-out" => elastic_out(progress),
+        "elastic-in" => 1.0 - elastic_out(1.0 - progress),
+        "elastic-in-out" => elastic_in_out(progress),
         other => {
             if let Some((x1, y1, x2, y2)) = parse_cubic_bezier(other) {
                 cubic_bezier_at(progress, x1, y1, x2, y2)
+            } else if let Some((mass, stiffness, damping, velocity)) = parse_spring(other) {
+                spring_at(progress, mass, stiffness, damping, velocity)
+            } else if let Some((steps, position)) = parse_steps(other) {
+                steps_at(progress, steps, position)
+            } else if let Some(points) = parse_linear_points(other) {
+                linear_points_at(progress, &points)
             } else {
                 // Fallback to linear
                 progress
@@ -83,17 +344,25 @@ pub fn interpolate_keyframes(keyframes: &[AnimationKeyframe], progress: f64) ->
         return keyframe_to_state(&keyframes[0]);
     }
 
-    // Find the two keyframes we're between
-    let progress = progress.clamp(0.0, 1.0);
+    let first = &keyframes[0];
+    let last = &keyframes[keyframes.len() - 1];
+
+    // Overshooting easings run past the ends; extrapolate the outer segments
+    if progress < 0.0 && first.t <= 0.0 {
+        return extrapolate_segment(first, &keyframes[1], progress);
+    }
+    if progress > 1.0 && last.t >= 1.0 {
+        return extrapolate_segment(&keyframes[keyframes.len() - 2], last, progress);
+    }
 
     // If before first keyframe
-    if progress <= keyframes[0].t {
-        return keyframe_to_state(&keyframes[0]);
+    if progress <= first.t {
+        return keyframe_to_state(first);
     }
 
     // If after last keyframe
-    if progress >= keyframes[keyframes.len() - 1].t {
-        return keyframe_to_state(&keyframes[keyframes.len() - 1]);
+    if progress >= last.t {
+        return keyframe_to_state(last);
     }
 
     // Find the segment
@@ -120,6 +389,23 @@ pub fn interpolate_keyframes(keyframes: &[AnimationKeyframe], progress: f64) ->
     keyframe_to_state(&keyframes[keyframes.len() - 1])
 }
 
+/// Interpolate along the segment a-b, allowing progress outside of it
+fn extrapolate_segment(
+    kf_a: &AnimationKeyframe,
+    kf_b: &AnimationKeyframe,
+    progress: f64,
+) -> CharRenderState {
+    let segment_duration = kf_b.t - kf_a.t;
+    if segment_duration <= 0.0 {
+        return keyframe_to_state(if progress < kf_a.t { kf_a } else { kf_b });
+    }
+    lerp_states(
+        &keyframe_to_state(kf_a),
+        &keyframe_to_state(kf_b),
+        (progress - kf_a.t) / segment_duration,
+    )
+}
+
 /// Convert a single keyframe to a CharRenderState
 fn keyframe_to_state(kf: &AnimationKeyframe) -> CharRenderState {
     CharRenderState {
@@ -146,7 +432,8 @@ fn lerp(a: f64, b: f64, t: f64) -> f64 {
     a + (b - a) * t
 }
 
-/// Calculate the render state of a single character at a given time.
+/// Calculate the render state of a single character (grapheme cluster, see
+/// `animation_units`) at a given time.
 /// This is the core animation logic from the design spec section 4.1.
 pub fn calc_char_state(
     char_index: usize,
@@ -201,9 +488,191 @@ pub fn calc_char_state(
     }
 }
 
+/// Split text into animation units: extended grapheme clusters, so that emoji sequences
+/// (ZWJ, skin tones, flags) and combining marks animate as a single character.
+pub fn animation_units(text: &str) -> Vec<&str> {
+    text.graphemes(true).collect()
+}
+
+/// Compute the highlight window (start_ms, end_ms) of every animation unit of a karaoke cue.
+/// The highlight of unit `i` sweeps from 0.0 to 1.0 between `timings[i].0` and `timings[i].1`.
+/// Units are those of the displayed text, i.e. with inline markup removed.
+pub fn karaoke_unit_timings(cue: &SubtitleCue, timing: &KaraokeTiming) -> Vec<(u64, u64)> {
+    let text = strip_markup(&cue.text);
+    let units = animation_units(&text);
+
+    if *timing == KaraokeTiming::Auto && !cue.words.is_empty() {
+        if let Some(timings) = timings_from_words(&units, cue) {
+            return timings;
+        }
+    }
+
+    let weights: Vec<f64> = match timing {
+        KaraokeTiming::Even => units
+            .iter()
+            .map(|u| if is_blank(u) { 0.0 } else { 1.0 })
+            .collect(),
+        KaraokeTiming::Auto | KaraokeTiming::Syllable => syllable_weights(&units),
+    };
+
+    distribute_by_weight(&weights, cue.start_ms, cue.end_ms)
+}
+
+/// Highlight fill (0.0 - 1.0) of a single karaoke unit at the given time
+pub fn karaoke_fill(timing: (u64, u64), current_time_ms: u64) -> f64 {
+    let (start, end) = timing;
+    if current_time_ms >= end {
+        1.0
+    } else if current_time_ms <= start {
+        0.0
+    } else {
+        (current_time_ms - start) as f64 / (end - start) as f64
+    }
+}
+
+fn is_blank(unit: &str) -> bool {
+    unit.chars().all(char::is_whitespace)
+}
+
+/// Map the cue's word timings onto its units.
+/// Returns None if a word cannot be located in the cue text.
+fn timings_from_words(units: &[&str], cue: &SubtitleCue) -> Option<Vec<(u64, u64)>> {
+    let mut timings: Vec<Option<(u64, u64)>> = vec![None; units.len()];
+    let mut cursor = 0;
+
+    for word in &cue.words {
+        let word_units = animation_units(word.text.trim());
+        if word_units.is_empty() {
+            continue;
+        }
+        if word_units.len() > units.len() {
+            return None;
+        }
+
+        let pos = (cursor..=units.len() - word_units.len())
+            .find(|&i| units[i..i + word_units.len()] == word_units[..])?;
+
+        let weights: Vec<f64> = word_units
+            .iter()
+            .map(|u| if is_blank(u) { 0.0 } else { 1.0 })
+            .collect();
+        for (offset, t) in distribute_by_weight(&weights, word.start_ms, word.end_ms)
+            .into_iter()
+            .enumerate()
+        {
+            timings[pos + offset] = Some(t);
+        }
+        cursor = pos + word_units.len();
+    }
+
+    // Units between words (spaces, punctuation) switch instantly
+    let mut last_end = cue.start_ms;
+    Some(
+        timings
+            .into_iter()
+            .map(|t| match t {
+                Some(t) => {
+                    last_end = t.1;
+                    t
+                }
+                None => (last_end, last_end),
+            })
+            .collect(),
+    )
+}
+
+/// Split a time range across units proportionally to their weights
+fn distribute_by_weight(weights: &[f64], start_ms: u64, end_ms: u64) -> Vec<(u64, u64)> {
+    let total: f64 = weights.iter().sum();
+    let duration = end_ms.saturating_sub(start_ms) as f64;
+    let at = |acc: f64| {
+        if total > 0.0 {
+            (start_ms as f64 + duration * acc / total).round() as u64
+        } else {
+            start_ms
+        }
+    };
+
+    let mut acc = 0.0;
+    weights
+        .iter()
+        .map(|w| {
+            let start = at(acc);
+            acc += w;
+            (start, at(acc))
+        })
+        .collect()
+}
+
+/// Approximate the spoken length of each unit.
+/// Latin words share their syllable count across their letters; kana count one mora
+/// each (small kana merge into the previous one) and kanji are counted as two.
+fn syllable_weights(units: &[&str]) -> Vec<f64> {
+    let is_latin = |unit: &str| unit.len() == 1 && unit.as_bytes()[0].is_ascii_alphanumeric();
+    let mut weights = vec![0.0; units.len()];
+    let mut i = 0;
+
+    while i < units.len() {
+        if is_latin(units[i]) {
+            let start = i;
+            while i < units.len() && (is_latin(units[i]) || units[i] == "'") {
+                i += 1;
+            }
+            let word: String = units[start..i].concat();
+            let per_char = count_latin_syllables(&word) as f64 / (i - start) as f64;
+            for w in &mut weights[start..i] {
+                *w = per_char;
+            }
+            continue;
+        }
+
+        weights[i] = units[i].chars().next().map_or(0.0, mora_weight);
+        i += 1;
+    }
+
+    weights
+}
+
+/// Rough English syllable count: vowel groups, ignoring a trailing silent "e"
+fn count_latin_syllables(word: &str) -> usize {
+    let lower = word.to_ascii_lowercase();
+    let mut count = 0;
+    let mut prev_vowel = false;
+
+    for c in lower.chars() {
+        let vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+
+    if count > 1 && lower.ends_with('e') && !lower.ends_with("le") {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+fn mora_weight(c: char) -> f64 {
+    match c {
+        c if c.is_whitespace() => 0.0,
+        // Small kana belong to the preceding mora
+        'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'ゃ' | 'ゅ' | 'ょ' | 'ゎ' | 'ァ' | 'ィ' | 'ゥ' | 'ェ'
+        | 'ォ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ' => 0.0,
+        // Hiragana / katakana, including っ and ー
+        '\u{3041}'..='\u{30FA}' | 'ー' => 1.0,
+        // CJK ideographs average about two morae
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => 2.0,
+        c if c.is_alphanumeric() => 1.0,
+        // Punctuation and symbols are not sung
+        _ => 0.0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::telop::WordTiming;
     use crate::telop_engine::template::load_template;
 
     #[test]
@@ -248,6 +717,134 @@ mod tests {
         assert!((apply_easing(1.0, "ease-in") - 1.0).abs() < 0.001);
     }
 
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.001,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_apply_easing_spring_reference_curve() {
+        // Reference values from the closed-form damped oscillator (w0 = 10, zeta = 0.5)
+        let easing = "spring(1, 100, 10)";
+        assert_close(apply_easing(0.0, easing), 0.0);
+        assert_close(apply_easing(0.1, easing), 0.562874);
+        assert_close(apply_easing(0.25, easing), 1.162160);
+        assert_close(apply_easing(0.5, easing), 0.974008);
+        assert_close(apply_easing(0.75, easing), 1.004111);
+        assert_close(apply_easing(1.0, easing), 1.0);
+    }
+
+    #[test]
+    fn test_apply_easing_spring_damping_regimes() {
+        // Critically and over-damped springs approach the target without overshoot
+        for easing in ["spring(1, 100, 20)", "spring(1, 100, 40)"] {
+            let mut prev = 0.0;
+            for i in 1..=20 {
+                let value = apply_easing(i as f64 / 20.0, easing);
+                assert!(value >= prev - 1e-9 && value <= 1.0 + 1e-9, "{easing} at {i}");
+                prev = value;
+            }
+            assert_close(apply_easing(1.0, easing), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_parse_spring_invalid() {
+        assert!(parse_spring("spring(1, 100)").is_none());
+        assert!(parse_spring("spring(0, 100, 10)").is_none());
+        assert!(parse_spring("spring(1, abc, 10)").is_none());
+        assert!(parse_spring("spring(1, 100, 10, 2)").is_some());
+    }
+
+    #[test]
+    fn test_apply_easing_steps() {
+        assert_close(apply_easing(0.0, "steps(4)"), 0.0);
+        assert_close(apply_easing(0.3, "steps(4)"), 0.25);
+        assert_close(apply_easing(0.99, "steps(4)"), 0.75);
+        assert_close(apply_easing(1.0, "steps(4)"), 1.0);
+        assert_close(apply_easing(0.0, "steps(4, jump-start)"), 0.25);
+        assert_close(apply_easing(0.3, "steps(4, start)"), 0.5);
+        assert_close(apply_easing(0.0, "steps(2, jump-both)"), 1.0 / 3.0);
+        assert_close(apply_easing(0.6, "steps(3, jump-none)"), 0.5);
+        assert_close(apply_easing(0.5, "step-end"), 0.0);
+        assert_close(apply_easing(0.5, "step-start"), 1.0);
+        assert!(parse_steps("steps(0)").is_none());
+        assert!(parse_steps("steps(1, jump-none)").is_none());
+        assert!(parse_steps("steps(2, sideways)").is_none());
+    }
+
+    #[test]
+    fn test_apply_easing_bounce_reference_curve() {
+        assert_close(apply_easing(0.2, "bounce"), 0.3025);
+        assert_close(apply_easing(0.5, "bounce-out"), 0.765625);
+        assert_close(apply_easing(0.8, "bounce"), 0.94);
+        assert_close(apply_easing(1.0, "bounce"), 1.0);
+        assert_close(apply_easing(0.5, "bounce-in"), 1.0 - 0.765625);
+        assert_close(apply_easing(0.5, "bounce-in-out"), 0.5);
+    }
+
+    #[test]
+    fn test_apply_easing_elastic_reference_curve() {
+        assert_close(apply_easing(0.1, "elastic"), 1.25);
+        assert_close(apply_easing(0.3, "elastic-out"), 0.875);
+        assert_close(apply_easing(0.5, "elastic"), 1.015625);
+        assert_close(apply_easing(0.9, "elastic-in"), 1.0 - 1.25);
+        assert_close(apply_easing(0.0, "elastic-in-out"), 0.0);
+        assert_close(apply_easing(0.5, "elastic-in-out"), 0.5);
+        assert_close(apply_easing(1.0, "elastic-in-out"), 1.0);
+    }
+
+    #[test]
+    fn test_apply_easing_linear_function() {
+        // Plain list: evenly spaced stops
+        assert_close(apply_easing(0.25, "linear(0, 1, 0)"), 0.5);
+        assert_close(apply_easing(0.75, "linear(0, 1, 0)"), 0.5);
+        // Explicit input position
+        assert_close(apply_easing(0.5, "linear(0, 0.25 75%, 1)"), 0.166667);
+        assert_close(apply_easing(0.875, "linear(0, 0.25 75%, 1)"), 0.625);
+        // Two input positions create a flat section
+        assert_close(apply_easing(0.4, "linear(0, 0.5 25% 75%, 1)"), 0.5);
+        // Missing inputs are spread between their neighbours
+        assert_close(apply_easing(0.6, "linear(0, 0.2, 0.8, 1 80%)"), 0.85);
+        // Inputs that go backwards are clamped to the previous one (a hard step)
+        assert_close(apply_easing(0.5, "linear(0, 0 50%, 1 20%, 1)"), 1.0);
+        assert!(parse_linear_points("linear(0)").is_none());
+        assert!(parse_linear_points("linear(0, 1 50% 60% 70%)").is_none());
+        assert!(parse_linear_points("linear(0, x)").is_none());
+    }
+
+    #[test]
+    fn test_interpolate_keyframes_extrapolates_overshoot() {
+        let keyframes = vec![
+            AnimationKeyframe {
+                t: 0.0,
+                opacity: Some(0.0),
+                translate_y: Some(40.0),
+                scale: Some(0.5),
+                translate_x: None,
+                rotate: None,
+            },
+            AnimationKeyframe {
+                t: 1.0,
+                opacity: Some(1.0),
+                translate_y: Some(0.0),
+                scale: Some(1.0),
+                translate_x: None,
+                rotate: None,
+            },
+        ];
+
+        // A spring overshooting to 1.2 carries the character past its resting position
+        let state = interpolate_keyframes(&keyframes, 1.2);
+        assert_close(state.translate_y, -8.0);
+        assert_close(state.scale, 1.1);
+
+        let state = interpolate_keyframes(&keyframes, -0.1);
+        assert_close(state.translate_y, 44.0);
+    }
+
     #[test]
     fn test_interpolate_keyframes_bounce() {
         let keyframes = vec![
@@ -388,6 +985,99 @@ mod tests {
         assert!((y2 - 1.0).abs() < 0.001);
     }
 
+    fn cue(text: &str, start_ms: u64, end_ms: u64) -> SubtitleCue {
+        SubtitleCue {
+            index: 1,
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_karaoke_even_timings() {
+        let timings = karaoke_unit_timings(&cue("ab cd", 1000, 2000), &KaraokeTiming::Even);
+        assert_eq!(
+            timings,
+            vec![(1000, 1250), (1250, 1500), (1500, 1500), (1500, 1750), (1750, 2000)]
+        );
+    }
+
+    #[test]
+    fn test_karaoke_syllable_timings_small_kana() {
+        // "きょう" is two morae: き+ょ share one, う is the second
+        let timings = karaoke_unit_timings(&cue("きょう", 0, 1000), &KaraokeTiming::Syllable);
+        assert_eq!(timings, vec![(0, 500), (500, 500), (500, 1000)]);
+    }
+
+    #[test]
+    fn test_karaoke_syllable_timings_latin() {
+        // "hello" (2 syllables) gets twice the time of "cat" (1 syllable)
+        let timings = karaoke_unit_timings(&cue("hello cat", 0, 3000), &KaraokeTiming::Syllable);
+        assert_eq!(timings[0].0, 0);
+        assert_eq!(timings[4].1, 2000);
+        assert_eq!(timings[5], (2000, 2000));
+        assert_eq!(timings[8].1, 3000);
+    }
+
+    #[test]
+    fn test_karaoke_word_timings() {
+        let mut c = cue("Hello big world", 0, 3000);
+        c.words = vec![
+            WordTiming { text: "Hello".to_string(), start_ms: 0, end_ms: 500 },
+            WordTiming { text: "big".to_string(), start_ms: 900, end_ms: 1200 },
+            WordTiming { text: "world".to_string(), start_ms: 1200, end_ms: 2200 },
+        ];
+        let timings = karaoke_unit_timings(&c, &KaraokeTiming::Auto);
+        assert_eq!(timings[0], (0, 100));
+        assert_eq!(timings[4], (400, 500));
+        // The space keeps the previous word's end
+        assert_eq!(timings[5], (500, 500));
+        assert_eq!(timings[6], (900, 1000));
+        assert_eq!(timings[14], (2000, 2200));
+    }
+
+    #[test]
+    fn test_karaoke_word_timings_mismatch_falls_back() {
+        let mut c = cue("abc", 0, 300);
+        c.words = vec![WordTiming { text: "xyz".to_string(), start_ms: 0, end_ms: 300 }];
+        let timings = karaoke_unit_timings(&c, &KaraokeTiming::Auto);
+        assert_eq!(timings, vec![(0, 100), (100, 200), (200, 300)]);
+    }
+
+    #[test]
+    fn test_animation_units_grapheme_clusters() {
+        // Skin tone modifier, ZWJ family, flag and combining accent are single units
+        let units = animation_units("👍🏽👨‍👩‍👧🇯🇵e\u{301}あ");
+        assert_eq!(units, vec!["👍🏽", "👨‍👩‍👧", "🇯🇵", "e\u{301}", "あ"]);
+    }
+
+    #[test]
+    fn test_karaoke_timings_count_emoji_as_one_unit() {
+        let timings = karaoke_unit_timings(&cue("ok🔥", 0, 300), &KaraokeTiming::Even);
+        assert_eq!(timings.len(), 3);
+        assert_eq!(timings[2], (200, 300));
+    }
+
+    #[test]
+    fn test_karaoke_timings_ignore_markup() {
+        let timings = karaoke_unit_timings(
+            &cue("a<font color=\"#FF0000\">bc</font>", 0, 300),
+            &KaraokeTiming::Even,
+        );
+        assert_eq!(timings, vec![(0, 100), (100, 200), (200, 300)]);
+    }
+
+    #[test]
+    fn test_karaoke_fill() {
+        assert_eq!(karaoke_fill((1000, 1200), 900), 0.0);
+        assert!((karaoke_fill((1000, 1200), 1100) - 0.5).abs() < 0.001);
+        assert_eq!(karaoke_fill((1000, 1200), 1200), 1.0);
+        // Zero-length units switch instantly
+        assert_eq!(karaoke_fill((500, 500), 500), 1.0);
+    }
+
     #[test]
     fn test_parse_cubic_bezier_invalid() {
         assert!(parse_cubic_bezier("invalid").is_none());
@@ -0,0 +1,835 @@
+use crate::models::telop::{
+    BackgroundMode, BackgroundStyle, CuePlacement, OutlineStyle, ShadowStyle, SubtitleCue,
+    SubtitleImport, TelopStyle, WordTiming,
+};
+use std::collections::HashMap;
+
+/// Frame height that script sizes are scaled to when the target canvas is unknown
+pub const DEFAULT_FRAME_HEIGHT: u32 = 1920;
+
+/// Script resolution assumed by renderers when PlayResX/PlayResY are missing
+const DEFAULT_PLAY_RES: (f32, f32) = (384.0, 288.0);
+
+#[derive(Debug, Clone)]
+struct AssStyle {
+    style: TelopStyle,
+    italic: bool,
+    underline: bool,
+    /// Numpad alignment (1 = bottom left, 5 = middle center, 9 = top right)
+    alignment: u8,
+    /// Margins in script pixels
+    margins: (f32, f32, f32),
+}
+
+/// Inline formatting in effect at a point of the event text
+#[derive(Debug, Clone, PartialEq)]
+struct OverrideState {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    color: Option<String>,
+    font_size: Option<f32>,
+}
+
+/// Parse an ASS timestamp (H:MM:SS.cc) to milliseconds
+fn parse_timestamp(ts: &str) -> Result<u64, String> {
+    let ts = ts.trim();
+    let parts: Vec<&str> = ts.split(':').collect();
+    if parts.len() != 3 {
+        return Err(format!("Invalid timestamp format: {ts}"));
+    }
+
+    let hours: u64 = parts[0]
+        .parse()
+        .map_err(|_| format!("Invalid hours: {}", parts[0]))?;
+    let minutes: u64 = parts[1]
+        .parse()
+        .map_err(|_| format!("Invalid minutes: {}", parts[1]))?;
+    let (seconds, fraction) = parts[2].split_once('.').unwrap_or((parts[2], "0"));
+    let seconds: u64 = seconds
+        .parse()
+        .map_err(|_| format!("Invalid seconds: {seconds}"))?;
+    let fraction_value: u64 = fraction
+        .parse()
+        .map_err(|_| format!("Invalid centiseconds: {fraction}"))?;
+    // Centiseconds by spec, but some tools write milliseconds
+    let millis = match fraction.len() {
+        1 => fraction_value * 100,
+        2 => fraction_value * 10,
+        _ => fraction_value / 10u64.pow(fraction.len() as u32 - 3),
+    };
+
+    Ok(hours * 3_600_000 + minutes * 60_000 + seconds * 1_000 + millis)
+}
+
+/// Parse an ASS color (&HAABBGGRR, &HBBGGRR& or a decimal SSA value)
+/// into a CSS hex color and its opacity
+fn parse_color(value: &str) -> Option<(String, f32)> {
+    let value = value.trim().trim_end_matches('&');
+    let value = match value
+        .strip_prefix("&H")
+        .or_else(|| value.strip_prefix("&h"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => value.parse::<i64>().ok()? as u32,
+    };
+
+    let alpha = (value >> 24) & 0xFF;
+    let blue = (value >> 16) & 0xFF;
+    let green = (value >> 8) & 0xFF;
+    let red = value & 0xFF;
+    // ASS alpha is transparency: 00 = opaque
+    let opacity = 1.0 - alpha as f32 / 255.0;
+    Some((format!("#{red:02X}{green:02X}{blue:02X}"), opacity))
+}
+
+/// Color with its opacity folded into an 8-digit hex color
+fn color_with_opacity((color, opacity): (String, f32)) -> String {
+    if opacity >= 1.0 {
+        color
+    } else {
+        format!("{color}{:02X}", (opacity * 255.0).round() as u8)
+    }
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(value.trim(), "-1" | "1")
+}
+
+/// Convert a legacy SSA alignment (1-3 bottom, +4 top, +8 middle) to numpad alignment
+fn legacy_alignment(value: u8) -> u8 {
+    let column = value & 3;
+    let row = if value & 4 != 0 {
+        6
+    } else if value & 8 != 0 {
+        3
+    } else {
+        0
+    };
+    column.clamp(1, 3) + row
+}
+
+fn resolve_play_res(x: Option<f32>, y: Option<f32>) -> (f32, f32) {
+    match (x, y) {
+        (Some(x), Some(y)) => (x, y),
+        (Some(x), None) if x == 1280.0 => (x, 1024.0),
+        (Some(x), None) => (x, x * 3.0 / 4.0),
+        (None, Some(y)) if y == 1024.0 => (1280.0, y),
+        (None, Some(y)) => (y * 4.0 / 3.0, y),
+        (None, None) => DEFAULT_PLAY_RES,
+    }
+}
+
+/// Parse ASS/SSA content. Sizes are scaled from the script resolution to `frame_height`.
+///
+/// Styles map onto `TelopStyle`, alignment and margins onto `CuePlacement`. The override
+/// tags \b, \i, \u, \c and \fs become inline markup, \pos and \an set the placement and
+/// \k syllables become word timings; other tags are reported as warnings and ignored.
+pub fn parse_ass(content: &str, frame_height: u32) -> Result<SubtitleImport, String> {
+    let content = content.trim_start_matches('\u{FEFF}');
+    let mut warnings: Vec<String> = Vec::new();
+
+    let mut section = String::new();
+    let mut play_res_x = None;
+    let mut play_res_y = None;
+    let mut style_format: Vec<String> = Vec::new();
+    let mut event_format: Vec<String> = Vec::new();
+    let mut raw_styles: Vec<(usize, HashMap<String, String>)> = Vec::new();
+    let mut raw_events: Vec<(usize, HashMap<String, String>)> = Vec::new();
+
+    for (line_index, line) in content.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].to_ascii_lowercase();
+            if section == "fonts" || section == "graphics" {
+                warnings.push(format!(
+                    "Embedded [{}] are not supported; install or bundle the fonts instead",
+                    &line[1..line.len() - 1]
+                ));
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match (section.as_str(), key.trim()) {
+            ("script info", "PlayResX") => play_res_x = value.parse().ok(),
+            ("script info", "PlayResY") => play_res_y = value.parse().ok(),
+            ("v4+ styles" | "v4 styles", "Format") => style_format = parse_format(value),
+            ("v4+ styles" | "v4 styles", "Style") => {
+                raw_styles.push((line_number, split_fields(value, &style_format)));
+            }
+            ("events", "Format") => event_format = parse_format(value),
+            ("events", "Dialogue") => {
+                if event_format.is_empty() {
+                    return Err(format!("Line {line_number}: Dialogue before Format line"));
+                }
+                raw_events.push((line_number, split_fields(value, &event_format)));
+            }
+            _ => {}
+        }
+    }
+
+    if event_format.is_empty() {
+        return Err("Not an ASS/SSA file: missing [Events] Format line".to_string());
+    }
+
+    let play_res = resolve_play_res(play_res_x, play_res_y);
+    let scale = frame_height as f32 / play_res.1;
+    let legacy = content.contains("[V4 Styles]");
+
+    let mut styles: HashMap<String, AssStyle> = HashMap::new();
+    for (line_number, fields) in &raw_styles {
+        let name = fields.get("name").cloned().unwrap_or_default();
+        let style = map_style(&name, fields, scale, legacy, &mut warnings)
+            .map_err(|e| format!("Line {line_number}: {e}"))?;
+        styles.insert(name, style);
+    }
+    let fallback_style = styles
+        .get("Default")
+        .cloned()
+        .unwrap_or_else(|| default_ass_style(scale));
+
+    let mut unsupported_tags: Vec<(String, usize)> = Vec::new();
+    let mut cues = Vec::new();
+
+    for (line_number, fields) in &raw_events {
+        let field = |name: &str| fields.get(name).map(String::as_str).unwrap_or_default();
+        let start_ms =
+            parse_timestamp(field("start")).map_err(|e| format!("Line {line_number}: {e}"))?;
+        let end_ms =
+            parse_timestamp(field("end")).map_err(|e| format!("Line {line_number}: {e}"))?;
+
+        let style_name = field("style").trim_start_matches('*');
+        let ass_style = match styles.get(style_name) {
+            Some(style) => style,
+            None if style_name == "Default" => &fallback_style,
+            None => {
+                warnings.push(format!(
+                    "Line {line_number}: unknown style '{style_name}', using Default"
+                ));
+                &fallback_style
+            }
+        };
+
+        if !field("effect").is_empty() {
+            warnings.push(format!(
+                "Line {line_number}: effect '{}' is not supported",
+                field("effect")
+            ));
+        }
+
+        // Per-event margins override the style's when non-zero
+        let margin = |name: &str, style_value: f32| match field(name).parse::<f32>() {
+            Ok(value) if value != 0.0 => value,
+            _ => style_value,
+        };
+        let margins = (
+            margin("marginl", ass_style.margins.0),
+            margin("marginr", ass_style.margins.1),
+            margin("marginv", ass_style.margins.2),
+        );
+
+        let converted = convert_text(field("text"), ass_style, scale, start_ms);
+        for tag in converted.unsupported {
+            if !unsupported_tags.iter().any(|(t, _)| *t == tag) {
+                unsupported_tags.push((tag, *line_number));
+            }
+        }
+
+        let alignment = converted.alignment.unwrap_or(ass_style.alignment);
+        let voice = Some(field("name").to_string()).filter(|n| !n.is_empty());
+
+        cues.push(SubtitleCue {
+            index: 0,
+            start_ms,
+            end_ms,
+            text: converted.text,
+            words: converted.words,
+            voice,
+            placement: Some(placement(alignment, converted.pos, margins, play_res)),
+            style: Some(ass_style.style.clone()),
+            ..Default::default()
+        });
+    }
+
+    for (tag, line_number) in unsupported_tags {
+        warnings.push(format!(
+            "Override tag \\{tag} is not supported (first used on line {line_number})"
+        ));
+    }
+
+    cues.sort_by_key(|cue| cue.start_ms);
+    for (i, cue) in cues.iter_mut().enumerate() {
+        cue.index = i + 1;
+    }
+
+    Ok(SubtitleImport {
+        cues,
+        warnings,
+        ..Default::default()
+    })
+}
+
+fn parse_format(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect()
+}
+
+/// Split a Style/Dialogue line by its Format. The last field (Text) may contain commas.
+fn split_fields(value: &str, format: &[String]) -> HashMap<String, String> {
+    value
+        .splitn(format.len().max(1), ',')
+        .zip(format)
+        .map(|(field, name)| {
+            let field = if name == "text" { field } else { field.trim() };
+            (name.clone(), field.to_string())
+        })
+        .collect()
+}
+
+fn default_ass_style(scale: f32) -> AssStyle {
+    let fields: HashMap<String, String> = [
+        ("fontname", "Arial"),
+        ("fontsize", "20"),
+        ("primarycolour", "&H00FFFFFF"),
+        ("outlinecolour", "&H00000000"),
+        ("backcolour", "&H00000000"),
+        ("borderstyle", "1"),
+        ("outline", "2"),
+        ("shadow", "2"),
+        ("alignment", "2"),
+        ("marginl", "10"),
+        ("marginr", "10"),
+        ("marginv", "10"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    map_style("Default", &fields, scale, false, &mut Vec::new())
+        .expect("built-in default style is valid")
+}
+
+fn map_style(
+    name: &str,
+    fields: &HashMap<String, String>,
+    scale: f32,
+    legacy: bool,
+    warnings: &mut Vec<String>,
+) -> Result<AssStyle, String> {
+    let field = |key: &str| fields.get(key).map(String::as_str).unwrap_or_default();
+    let number = |key: &str, default: f32| field(key).parse::<f32>().unwrap_or(default);
+    let color = |key: &str| parse_color(field(key)).unwrap_or(("#FFFFFF".to_string(), 1.0));
+
+    let font_size = field("fontsize")
+        .parse::<f32>()
+        .map_err(|_| format!("Invalid font size in style '{name}': {}", field("fontsize")))?;
+
+    let bold_value = field("bold");
+    let font_weight = match bold_value.parse::<u32>() {
+        Ok(weight) if weight > 1 => weight,
+        _ if parse_bool(bold_value) => 700,
+        _ => 400,
+    };
+
+    let raw_alignment = field("alignment").parse::<u8>().unwrap_or(2);
+    let alignment = if legacy {
+        legacy_alignment(raw_alignment)
+    } else {
+        raw_alignment.clamp(1, 9)
+    };
+
+    let outline_width = number("outline", 0.0) * scale;
+    let shadow_depth = number("shadow", 0.0) * scale;
+    let opaque_box = field("borderstyle") == "3";
+
+    let outline = (!opaque_box && outline_width > 0.0).then(|| OutlineStyle {
+        enabled: true,
+        color: color_with_opacity(color("outlinecolour")),
+        width: outline_width,
+        join: "round".to_string(),
+    });
+    let background = opaque_box.then(|| {
+        let (box_color, opacity) = color("outlinecolour");
+        BackgroundStyle {
+            enabled: true,
+            color: box_color,
+            opacity,
+            padding_x: outline_width,
+            padding_y: outline_width,
+            corner_radius: 0.0,
+            mode: BackgroundMode::Line,
+        }
+    });
+    let shadow = (shadow_depth > 0.0).then(|| ShadowStyle {
+        enabled: true,
+        color: color_with_opacity(color("backcolour")),
+        offset_x: shadow_depth,
+        offset_y: shadow_depth,
+        blur: 0.0,
+    });
+
+    for (key, label, default) in [
+        ("scalex", "ScaleX", 100.0),
+        ("scaley", "ScaleY", 100.0),
+        ("spacing", "Spacing", 0.0),
+        ("angle", "Angle", 0.0),
+    ] {
+        if number(key, default) != default {
+            warnings.push(format!("Style '{name}': {label} is not supported"));
+        }
+    }
+    if parse_bool(field("strikeout")) {
+        warnings.push(format!("Style '{name}': StrikeOut is not supported"));
+    }
+
+    let column = match (alignment - 1) % 3 {
+        0 => "left",
+        2 => "right",
+        _ => "center",
+    };
+
+    Ok(AssStyle {
+        style: TelopStyle {
+            font_family: field("fontname").to_string(),
+            font_fallbacks: vec![],
+            font_size: font_size * scale,
+            font_weight,
+            color: color_with_opacity(color("primarycolour")),
+            outline,
+            shadow,
+            position: None,
+            alignment: Some(column.to_string()),
+            fill: None,
+            background,
+            outlines: vec![],
+            inner_glow: None,
+        },
+        italic: parse_bool(field("italic")),
+        underline: parse_bool(field("underline")),
+        alignment,
+        margins: (
+            number("marginl", 0.0),
+            number("marginr", 0.0),
+            number("marginv", 0.0),
+        ),
+    })
+}
+
+/// Placement from numpad alignment, an optional \pos and margins (all in script pixels)
+fn placement(
+    alignment: u8,
+    pos: Option<(f32, f32)>,
+    (margin_l, margin_r, margin_v): (f32, f32, f32),
+    (play_res_x, play_res_y): (f32, f32),
+) -> CuePlacement {
+    let column = (alignment - 1) % 3;
+    let row = (alignment - 1) / 3;
+
+    let (position, line) = match pos {
+        Some((x, y)) => (x / play_res_x, y / play_res_y),
+        None => {
+            let x = match column {
+                0 => margin_l,
+                2 => play_res_x - margin_r,
+                _ => (margin_l + play_res_x - margin_r) / 2.0,
+            };
+            let y = match row {
+                0 => play_res_y - margin_v,
+                2 => margin_v,
+                _ => play_res_y / 2.0,
+            };
+            (x / play_res_x, y / play_res_y)
+        }
+    };
+
+    CuePlacement {
+        line: Some(line),
+        line_align: Some(
+            match row {
+                0 => "end",
+                2 => "start",
+                _ => "center",
+            }
+            .to_string(),
+        ),
+        position: Some(position),
+        align: Some(
+            match column {
+                0 => "left",
+                2 => "right",
+                _ => "center",
+            }
+            .to_string(),
+        ),
+        ..Default::default()
+    }
+}
+
+struct ConvertedText {
+    /// Cue text with inline markup
+    text: String,
+    words: Vec<WordTiming>,
+    alignment: Option<u8>,
+    pos: Option<(f32, f32)>,
+    unsupported: Vec<String>,
+}
+
+/// Convert event text with override blocks into inline markup
+fn convert_text(raw: &str, style: &AssStyle, scale: f32, start_ms: u64) -> ConvertedText {
+    let base = OverrideState {
+        bold: style.style.font_weight >= 700,
+        italic: style.italic,
+        underline: style.underline,
+        color: None,
+        font_size: None,
+    };
+    let mut state = base.clone();
+    // The style's own weight is already in TelopStyle; only deviations need markup
+    let plain = OverrideState {
+        italic: false,
+        underline: false,
+        ..base.clone()
+    };
+    let mut emitted = plain.clone();
+    let mut open_tags: Vec<&str> = Vec::new();
+
+    let mut result = ConvertedText {
+        text: String::new(),
+        words: Vec::new(),
+        alignment: None,
+        pos: None,
+        unsupported: Vec::new(),
+    };
+    // Karaoke syllables: (start offset ms, duration ms, plain text)
+    let mut syllables: Vec<(u64, u64, String)> = Vec::new();
+    let mut karaoke_offset = 0;
+    let mut rest = raw;
+
+    while !rest.is_empty() {
+        if let Some(block) = rest.strip_prefix('{') {
+            let Some(end) = block.find('}') else {
+                break;
+            };
+            for tag in block[..end].split('\\').skip(1) {
+                apply_tag(
+                    tag.trim(),
+                    &base,
+                    &mut state,
+                    &mut result,
+                    scale,
+                    |duration| {
+                        syllables.push((karaoke_offset, duration, String::new()));
+                        karaoke_offset += duration;
+                    },
+                );
+            }
+            rest = &block[end + 1..];
+            continue;
+        }
+
+        let end = rest.find('{').unwrap_or(rest.len());
+        let chunk = convert_escapes(&rest[..end]);
+        rest = &rest[end..];
+        if chunk.is_empty() {
+            continue;
+        }
+
+        if state != emitted {
+            for tag in open_tags.drain(..).rev() {
+                result.text.push_str(&format!("</{tag}>"));
+            }
+            open_tags = open_markup(&state, &plain, &mut result.text);
+            emitted = state.clone();
+        }
+        if let Some(syllable) = syllables.last_mut() {
+            syllable.2.push_str(&chunk);
+        }
+        for c in chunk.chars() {
+            match c {
+                '<' => result.text.push_str("&lt;"),
+                '&' => result.text.push_str("&amp;"),
+                _ => result.text.push(c),
+            }
+        }
+    }
+    for tag in open_tags.iter().rev() {
+        result.text.push_str(&format!("</{tag}>"));
+    }
+
+    result.words = syllables
+        .into_iter()
+        .filter(|(_, _, text)| !text.trim().is_empty())
+        .map(|(offset, duration, text)| WordTiming {
+            text: text.trim().to_string(),
+            start_ms: start_ms + offset,
+            end_ms: start_ms + offset + duration,
+        })
+        .collect();
+
+    result
+}
+
+/// \N is a hard line break, \n a soft one (a space unless smart wrapping is off), \h a hard space
+fn convert_escapes(text: &str) -> String {
+    text.replace("\\N", "\n")
+        .replace("\\n", " ")
+        .replace("\\h", "\u{00A0}")
+}
+
+/// Open markup tags for the difference between `state` and `plain`; returns the tags opened
+fn open_markup(
+    state: &OverrideState,
+    plain: &OverrideState,
+    out: &mut String,
+) -> Vec<&'static str> {
+    let mut opened = Vec::new();
+    for (on, tag) in [
+        (state.bold && !plain.bold, "b"),
+        (state.italic, "i"),
+        (state.underline, "u"),
+    ] {
+        if on {
+            out.push_str(&format!("<{tag}>"));
+            opened.push(tag);
+        }
+    }
+
+    let mut attributes = String::new();
+    if let Some(ref color) = state.color {
+        attributes.push_str(&format!(" color=\"{color}\""));
+    }
+    if let Some(size) = state.font_size {
+        attributes.push_str(&format!(" size=\"{}\"", size.round()));
+    }
+    if !attributes.is_empty() {
+        out.push_str(&format!("<font{attributes}>"));
+        opened.push("font");
+    }
+    opened
+}
+
+/// Apply a single override tag (without the leading backslash)
+fn apply_tag(
+    tag: &str,
+    base: &OverrideState,
+    state: &mut OverrideState,
+    result: &mut ConvertedText,
+    scale: f32,
+    mut karaoke: impl FnMut(u64),
+) {
+    if tag.is_empty() {
+        return;
+    }
+
+    // \fn and \r take a name argument, so their names cannot be read up to the first non-letter
+    let name_len = if tag.starts_with("fn") {
+        2
+    } else if tag.starts_with('r') {
+        1
+    } else {
+        let digits = tag.len() - tag.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        digits
+            + tag[digits..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(tag.len() - digits)
+    };
+    let (name, arg) = tag.split_at(name_len);
+
+    match name {
+        "b" => {
+            state.bold = match arg.parse::<u32>() {
+                Ok(weight) if weight > 1 => weight >= 700,
+                Ok(value) => value == 1,
+                Err(_) => base.bold,
+            };
+            // Inline markup can add weight but not remove the style's own
+            if base.bold && !state.bold {
+                record_unsupported(result, "b0 in a bold style");
+            }
+        }
+        "i" => state.italic = arg.parse::<u32>().map_or(base.italic, |v| v == 1),
+        "u" => state.underline = arg.parse::<u32>().map_or(base.underline, |v| v == 1),
+        "c" | "1c" => state.color = parse_color(arg).map(|(color, _)| color),
+        "fs" => state.font_size = arg.parse::<f32>().ok().map(|size| size * scale),
+        "an" => result.alignment = arg.parse::<u8>().ok().filter(|a| (1..=9).contains(a)),
+        "pos" => {
+            let coords: Vec<f32> = arg
+                .trim_matches(|c| c == '(' || c == ')')
+                .split(',')
+                .filter_map(|v| v.trim().parse().ok())
+                .collect();
+            if let [x, y] = coords[..] {
+                // The first \pos of a line wins
+                result.pos.get_or_insert((x, y));
+            }
+        }
+        "r" => {
+            if !arg.is_empty() {
+                record_unsupported(result, "r<style>");
+            }
+            *state = base.clone();
+        }
+        "k" | "K" | "kf" | "ko" => {
+            // Durations are in centiseconds
+            karaoke(arg.parse::<u64>().unwrap_or(0) * 10);
+        }
+        _ => record_unsupported(result, name),
+    }
+}
+
+fn record_unsupported(result: &mut ConvertedText, name: &str) {
+    if !result.unsupported.iter().any(|t| t == name) {
+        result.unsupported.push(name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 1080
+PlayResY: 1920
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Noto Sans JP,72,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,-1,0,0,0,100,100,0,0,1,6,3,2,40,40,120,1
+Style: Title,Dela Gothic One,96,&H0000E6FF,&H000000FF,&H00FFFFFF,&H00000000,0,-1,0,0,100,100,0,0,3,10,0,8,40,40,200,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:05.00,0:00:07.50,Title,,0,0,0,,今日の{\\c&H0000FF&}ポイント
+Dialogue: 0,0:00:01.00,0:00:03.50,Default,田中,0,0,0,,こんにちは、{\\b0\\fs96}世界{\\r}！\\N2行目
+Comment: 0,0:00:02.00,0:00:03.00,Default,,0,0,0,,ignored
+Dialogue: 0,0:00:04.00,0:00:05.00,Default,,0,0,0,,{\\an7\\pos(100,200)\\fad(200,200)\\blur2}a<b & c
+";
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("0:00:01.50").unwrap(), 1500);
+        assert_eq!(parse_timestamp("1:02:03.04").unwrap(), 3_723_040);
+        assert_eq!(parse_timestamp("0:00:01.234").unwrap(), 1234);
+        assert!(parse_timestamp("00:01.50").is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(
+            parse_color("&H0000E6FF"),
+            Some(("#FFE600".to_string(), 1.0))
+        );
+        assert_eq!(parse_color("&H0000FF&"), Some(("#FF0000".to_string(), 1.0)));
+        let (color, opacity) = parse_color("&H80000000").unwrap();
+        assert_eq!(color, "#000000");
+        assert!((opacity - 0.498).abs() < 0.01);
+        // SSA decimal (BGR)
+        assert_eq!(parse_color("255"), Some(("#FF0000".to_string(), 1.0)));
+    }
+
+    #[test]
+    fn test_parse_ass_styles() {
+        let import = parse_ass(SAMPLE, 1920).unwrap();
+        assert_eq!(import.cues.len(), 3);
+
+        // Sorted by start time and re-indexed
+        let first = &import.cues[0];
+        assert_eq!((first.index, first.start_ms, first.end_ms), (1, 1000, 3500));
+        assert_eq!(first.voice.as_deref(), Some("田中"));
+
+        let style = first.style.as_ref().unwrap();
+        assert_eq!(style.font_family, "Noto Sans JP");
+        assert_eq!(style.font_size, 72.0);
+        assert_eq!(style.font_weight, 700);
+        assert_eq!(style.color, "#FFFFFF");
+        let outline = style.outline.as_ref().unwrap();
+        assert_eq!((outline.color.as_str(), outline.width), ("#000000", 6.0));
+        let shadow = style.shadow.as_ref().unwrap();
+        assert_eq!(shadow.color, "#0000007F");
+        assert_eq!(shadow.offset_x, 3.0);
+
+        let title = import.cues[2].style.as_ref().unwrap();
+        assert_eq!(title.color, "#FFE600");
+        assert!(title.outline.is_none());
+        let background = title.background.as_ref().unwrap();
+        assert_eq!(
+            (background.color.as_str(), background.padding_x),
+            ("#FFFFFF", 10.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_ass_override_tags() {
+        let import = parse_ass(SAMPLE, 1920).unwrap();
+
+        assert_eq!(
+            import.cues[0].text,
+            "こんにちは、<font size=\"96\">世界</font>！\n2行目"
+        );
+        // Italic style wraps the whole text
+        assert_eq!(
+            import.cues[2].text,
+            "<i>今日の</i><i><font color=\"#FF0000\">ポイント</font></i>"
+        );
+        assert_eq!(import.cues[1].text, "a&lt;b &amp; c");
+
+        assert!(import.warnings.iter().any(|w| w.contains("\\fad")));
+        assert!(import.warnings.iter().any(|w| w.contains("\\blur")));
+    }
+
+    #[test]
+    fn test_parse_ass_placement() {
+        let import = parse_ass(SAMPLE, 1920).unwrap();
+
+        // Bottom center with MarginV 120
+        let bottom = import.cues[0].placement.as_ref().unwrap();
+        assert_eq!(bottom.align.as_deref(), Some("center"));
+        assert_eq!(bottom.line_align.as_deref(), Some("end"));
+        assert!((bottom.line.unwrap() - 1800.0 / 1920.0).abs() < 1e-6);
+
+        // \an7 with \pos
+        let positioned = import.cues[1].placement.as_ref().unwrap();
+        assert_eq!(positioned.align.as_deref(), Some("left"));
+        assert_eq!(positioned.line_align.as_deref(), Some("start"));
+        assert!((positioned.position.unwrap() - 100.0 / 1080.0).abs() < 1e-6);
+        assert!((positioned.line.unwrap() - 200.0 / 1920.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_ass_scales_to_frame_height() {
+        let import = parse_ass(SAMPLE, 960).unwrap();
+        assert_eq!(import.cues[0].style.as_ref().unwrap().font_size, 36.0);
+    }
+
+    #[test]
+    fn test_parse_ass_karaoke_words() {
+        let ass = "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,{\\k30}か{\\k20}ら{\\kf50}おけ\n";
+        let import = parse_ass(ass, 1920).unwrap();
+        let words: Vec<(&str, u64, u64)> = import.cues[0]
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start_ms, w.end_ms))
+            .collect();
+        assert_eq!(
+            words,
+            vec![("か", 1000, 1300), ("ら", 1300, 1500), ("おけ", 1500, 2000)]
+        );
+        assert!(import.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_ass_missing_events() {
+        assert!(parse_ass("[Script Info]\nTitle: x\n", 1920).is_err());
+    }
+}
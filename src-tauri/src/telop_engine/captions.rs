@@ -0,0 +1,176 @@
+use crate::models::project::ShortForgeProject;
+use crate::models::telop::SubtitleCue;
+use crate::models::timeline::{TelopClip, TrackType};
+use crate::telop_engine::{parser, vtt};
+
+/// Caption sidecar written next to the exported video
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptionFormat {
+    Srt,
+    Vtt,
+}
+
+impl CaptionFormat {
+    /// Pick the format from an output file extension (.srt or .vtt)
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "srt" => Ok(Self::Srt),
+            "vtt" => Ok(Self::Vtt),
+            _ => Err(format!("Unsupported caption format: {path}")),
+        }
+    }
+
+    pub fn write(self, cues: &[SubtitleCue]) -> String {
+        match self {
+            Self::Srt => parser::write_srt(cues),
+            Self::Vtt => vtt::write_vtt(cues),
+        }
+    }
+}
+
+/// Cues of a project's telop track, timed as they play on the timeline.
+///
+/// `track_id` selects one track; without it every telop track is merged. `offset_ms` shifts
+/// all cues (negative = earlier). Cues are clamped to the canvas duration, cues that end up
+/// empty are dropped, and the rest are sorted and renumbered from 1.
+pub fn project_cues(
+    project: &ShortForgeProject,
+    track_id: Option<&str>,
+    offset_ms: i64,
+) -> Result<Vec<SubtitleCue>, String> {
+    if let Some(id) = track_id {
+        if !project
+            .tracks
+            .iter()
+            .any(|t| t.id == id && t.track_type == TrackType::Telop)
+        {
+            return Err(format!("Telop track not found: {id}"));
+        }
+    }
+
+    let end_limit = match project.canvas.duration_ms {
+        0 => u64::MAX,
+        duration => duration,
+    };
+    let shift = |ms: u64| (ms as i64 + offset_ms).max(0) as u64;
+
+    let mut cues = Vec::new();
+    for track in &project.tracks {
+        if track.track_type != TrackType::Telop || track_id.is_some_and(|id| id != track.id) {
+            continue;
+        }
+        for value in &track.clips {
+            let clip: TelopClip = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid telop clip in track {}: {e}", track.id))?;
+
+            let start_ms = shift(clip.start_ms).min(end_limit);
+            let end_ms = shift(clip.end_ms).min(end_limit);
+            if end_ms <= start_ms || clip.text.trim().is_empty() {
+                continue;
+            }
+
+            let mut words = clip.words;
+            for word in &mut words {
+                word.start_ms = shift(word.start_ms).clamp(start_ms, end_ms);
+                word.end_ms = shift(word.end_ms).clamp(start_ms, end_ms);
+            }
+            words.retain(|word| word.end_ms > word.start_ms);
+
+            cues.push(SubtitleCue {
+                index: 0,
+                start_ms,
+                end_ms,
+                text: clip.text,
+                words,
+                id: Some(clip.id),
+                voice: clip.voice,
+                placement: clip.placement,
+                style: clip.style,
+            });
+        }
+    }
+
+    cues.sort_by_key(|cue| (cue.start_ms, cue.end_ms));
+    for (i, cue) in cues.iter_mut().enumerate() {
+        cue.index = i + 1;
+    }
+    Ok(cues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> ShortForgeProject {
+        serde_json::from_value(serde_json::json!({
+            "version": "1.0.0",
+            "metadata": { "name": "test", "created_at": "", "platform": "tiktok" },
+            "canvas": { "width": 1080, "height": 1920, "fps": 30, "duration_ms": 10000 },
+            "tracks": [
+                {
+                    "id": "telop-1",
+                    "type": "telop",
+                    "clips": [
+                        { "id": "b", "startMs": 4000, "endMs": 6000, "label": "second" },
+                        {
+                            "id": "a", "start_ms": 500, "end_ms": 2000, "text": "first",
+                            "words": [{ "text": "first", "start_ms": 500, "end_ms": 2000 }]
+                        },
+                        { "id": "c", "startMs": 9000, "endMs": 12000, "label": "last" }
+                    ]
+                },
+                {
+                    "id": "telop-2",
+                    "type": "telop",
+                    "clips": [{ "id": "d", "startMs": 3000, "endMs": 3500, "label": "other" }]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_project_cues_sorted_and_clamped() {
+        let cues = project_cues(&project(), Some("telop-1"), 0).unwrap();
+        let texts: Vec<&str> = cues.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["first", "second", "last"]);
+        assert_eq!(cues[0].index, 1);
+        assert_eq!(cues[0].id.as_deref(), Some("a"));
+        assert_eq!(cues[2].end_ms, 10_000);
+
+        let all = project_cues(&project(), None, 0).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[1].text, "other");
+    }
+
+    #[test]
+    fn test_project_cues_offset() {
+        let cues = project_cues(&project(), Some("telop-1"), -1000).unwrap();
+        assert_eq!((cues[0].start_ms, cues[0].end_ms), (0, 1000));
+        assert_eq!(cues[0].words[0].start_ms, 0);
+        assert_eq!((cues[1].start_ms, cues[1].end_ms), (3000, 5000));
+
+        let cues = project_cues(&project(), Some("telop-1"), 2000).unwrap();
+        assert_eq!(cues.len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_track_and_format() {
+        assert!(project_cues(&project(), Some("video-1"), 0).is_err());
+        assert_eq!(
+            CaptionFormat::from_path("out/Video.SRT"),
+            Ok(CaptionFormat::Srt)
+        );
+        assert_eq!(
+            CaptionFormat::from_path("out/video.vtt"),
+            Ok(CaptionFormat::Vtt)
+        );
+        assert!(CaptionFormat::from_path("out/video.mp4").is_err());
+    }
+}
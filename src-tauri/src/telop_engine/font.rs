@@ -0,0 +1,374 @@
+use crate::models::telop::TelopStyle;
+use crate::telop_engine::woff;
+use serde::{Deserialize, Serialize};
+use skia_safe::textlayout::{FontCollection, TypefaceFontProvider};
+use std::collections::HashMap;
+
+/// Families tried after a style's own fallback chain. Covers Japanese text and color emoji
+/// on macOS, Windows and typical Linux installs.
+const DEFAULT_FALLBACKS: &[&str] = &[
+    "Noto Sans JP",
+    "Noto Sans CJK JP",
+    "Hiragino Sans",
+    "Hiragino Kaku Gothic ProN",
+    "Yu Gothic",
+    "Meiryo",
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "Noto Color Emoji",
+];
+
+/// Font file types accepted from project and template `fonts/` directories. Web fonts are
+/// unwrapped to TrueType/OpenType before loading.
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "woff", "woff2"];
+
+/// Language hint passed to the platform font matcher for per-glyph fallback
+const FALLBACK_LANGUAGES: &[&str] = &["ja-JP", "en-US"];
+
+/// A font that was rendered in place of the requested one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FontSubstitution {
+    pub requested: String,
+    pub resolved: String,
+    /// Characters that needed the substitute. Empty when the requested family was not found at all.
+    pub characters: String,
+}
+
+/// A font file loaded from a project or template package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFont {
+    pub family: String,
+    pub weight: i32,
+    pub path: String,
+}
+
+/// Fonts loaded from a directory, and a warning for each font file that was skipped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FontDirReport {
+    pub fonts: Vec<ProjectFont>,
+    pub warnings: Vec<String>,
+}
+
+/// Resolves and caches typefaces for telop rendering.
+///
+/// Create one per render job and reuse it for every frame; lookups hit the platform
+/// font manager only once per family / character. Fonts registered from files take
+/// priority over installed families with the same name, so exports look the same on
+/// every machine.
+pub struct FontService {
+    font_mgr: skia_safe::FontMgr,
+    registered: HashMap<String, Vec<skia_safe::Typeface>>,
+    families: HashMap<(String, u32), Option<skia_safe::Typeface>>,
+    glyph_fallbacks: HashMap<(String, u32, char), skia_safe::Typeface>,
+    collection: Option<FontCollection>,
+    substitutions: Vec<FontSubstitution>,
+}
+
+impl Default for FontService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontService {
+    pub fn new() -> Self {
+        Self {
+            font_mgr: skia_safe::FontMgr::default(),
+            registered: HashMap::new(),
+            families: HashMap::new(),
+            glyph_fallbacks: HashMap::new(),
+            collection: None,
+            substitutions: Vec::new(),
+        }
+    }
+
+    /// Load a font file so templates can reference its family by name
+    pub fn register_font_file(&mut self, path: &str) -> Result<ProjectFont, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read font {path}: {e}"))?;
+        let data =
+            woff::to_sfnt(&data).map_err(|e| format!("Failed to decode font {path}: {e}"))?;
+        let typeface = self
+            .font_mgr
+            .new_from_data(&data, None)
+            .ok_or_else(|| format!("Unsupported or corrupt font file: {path}"))?;
+
+        let family = typeface.family_name();
+        let weight = *typeface.font_style().weight();
+        self.registered
+            .entry(family.clone())
+            .or_default()
+            .push(typeface);
+
+        // Drop lookups that may have resolved to an installed font of the same name
+        self.families.retain(|(f, _), _| *f != family);
+        self.glyph_fallbacks.clear();
+        self.collection = None;
+
+        Ok(ProjectFont {
+            family,
+            weight,
+            path: path.to_string(),
+        })
+    }
+
+    /// Load every font file in a directory (e.g. a project's `fonts/` folder).
+    /// A missing directory is not an error, and files that can't be loaded are skipped with
+    /// a warning so one bad file doesn't stop a render.
+    pub fn register_font_dir(&mut self, dir: &str) -> Result<FontDirReport, String> {
+        let dir_path = std::path::Path::new(dir);
+        let mut report = FontDirReport::default();
+        if !dir_path.is_dir() {
+            return Ok(report);
+        }
+
+        let entries =
+            std::fs::read_dir(dir_path).map_err(|e| format!("Failed to read font directory: {e}"))?;
+
+        let mut paths = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {e}"))?;
+            let path = entry.path();
+            let extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(str::to_lowercase)
+                .unwrap_or_default();
+            if FONT_EXTENSIONS.contains(&extension.as_str()) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let loaded = match path.to_str() {
+                Some(path) => self.register_font_file(path),
+                None => Err(format!("Invalid path encoding: {}", path.display())),
+            };
+            match loaded {
+                Ok(font) => report.fonts.push(font),
+                Err(warning) => report.warnings.push(warning),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Font collection for the paragraph shaper: registered fonts first, then installed ones.
+    /// The shaper uses it for per-glyph fallback (color emoji, CJK) and complex scripts.
+    pub fn font_collection(&mut self) -> FontCollection {
+        if let Some(ref collection) = self.collection {
+            return collection.clone();
+        }
+
+        let mut provider = TypefaceFontProvider::new();
+        for (family, typefaces) in &self.registered {
+            for typeface in typefaces {
+                provider.register_typeface(typeface.clone(), Some(family.as_str()));
+            }
+        }
+        let provider: skia_safe::FontMgr = provider.into();
+
+        let mut collection = FontCollection::new();
+        collection.set_asset_font_manager(Some(provider));
+        collection.set_default_font_manager(self.font_mgr.clone(), None::<&str>);
+
+        self.collection = Some(collection.clone());
+        collection
+    }
+
+    /// Families to try after the primary one, in order
+    pub fn fallback_families(&self, style: &TelopStyle) -> Vec<String> {
+        fallback_chain(style)
+    }
+
+    /// Substitutions recorded so far, for reporting back to the UI
+    pub fn substitutions(&self) -> &[FontSubstitution] {
+        &self.substitutions
+    }
+
+    /// Resolve the primary typeface of a style: the first installed family of its fallback chain
+    pub fn resolve_style(&mut self, style: &TelopStyle) -> skia_safe::Typeface {
+        for family in fallback_chain(style) {
+            if let Some(typeface) = self.match_family(&family, style.font_weight) {
+                if family != style.font_family {
+                    self.record_substitution(&style.font_family, &family, None);
+                }
+                return typeface;
+            }
+        }
+
+        let typeface = self
+            .font_mgr
+            .legacy_make_typeface(None, font_style_for_weight(style.font_weight))
+            .expect("Failed to create default typeface");
+        self.record_substitution(&style.font_family, &typeface.family_name(), None);
+        typeface
+    }
+
+    /// Pick the typeface used to draw `ch`: the primary one if it has the glyph, otherwise the
+    /// first family of the fallback chain that does, otherwise whatever the platform suggests.
+    pub fn typeface_for_char(
+        &mut self,
+        primary: &skia_safe::Typeface,
+        style: &TelopStyle,
+        ch: char,
+    ) -> skia_safe::Typeface {
+        // Joiners and variation selectors are consumed by the shaper, not drawn
+        let invisible = matches!(ch, '\u{200C}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}');
+        if ch.is_whitespace() || ch.is_control() || invisible || has_glyph(primary, ch) {
+            return primary.clone();
+        }
+
+        let key = (style.font_family.clone(), style.font_weight, ch);
+        if let Some(typeface) = self.glyph_fallbacks.get(&key) {
+            return typeface.clone();
+        }
+
+        let mut found = None;
+        for family in fallback_chain(style) {
+            if let Some(typeface) = self.match_family(&family, style.font_weight) {
+                if has_glyph(&typeface, ch) {
+                    found = Some(typeface);
+                    break;
+                }
+            }
+        }
+
+        let typeface = found
+            .or_else(|| {
+                self.font_mgr.match_family_style_character(
+                    "",
+                    font_style_for_weight(style.font_weight),
+                    FALLBACK_LANGUAGES,
+                    ch as i32,
+                )
+            })
+            .unwrap_or_else(|| primary.clone());
+
+        if !has_glyph(primary, ch) && has_glyph(&typeface, ch) {
+            self.record_substitution(&primary.family_name(), &typeface.family_name(), Some(ch));
+        }
+        self.glyph_fallbacks.insert(key, typeface.clone());
+        typeface
+    }
+
+    fn match_family(&mut self, family: &str, weight: u32) -> Option<skia_safe::Typeface> {
+        if let Some(candidates) = self.registered.get(family) {
+            return candidates
+                .iter()
+                .min_by_key(|t| (*t.font_style().weight() - weight as i32).abs())
+                .cloned();
+        }
+
+        let font_mgr = &self.font_mgr;
+        self.families
+            .entry((family.to_string(), weight))
+            .or_insert_with(|| font_mgr.match_family_style(family, font_style_for_weight(weight)))
+            .clone()
+    }
+
+    fn record_substitution(&mut self, requested: &str, resolved: &str, ch: Option<char>) {
+        let existing = self
+            .substitutions
+            .iter_mut()
+            .find(|s| s.requested == requested && s.resolved == resolved);
+
+        match existing {
+            Some(substitution) => {
+                if let Some(ch) = ch {
+                    if !substitution.characters.contains(ch) {
+                        substitution.characters.push(ch);
+                    }
+                }
+            }
+            None => self.substitutions.push(FontSubstitution {
+                requested: requested.to_string(),
+                resolved: resolved.to_string(),
+                characters: ch.map(String::from).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// The style's family, its own fallbacks, then the built-in defaults (without duplicates)
+fn fallback_chain(style: &TelopStyle) -> Vec<String> {
+    let mut chain: Vec<String> = Vec::new();
+    let candidates = std::iter::once(style.font_family.as_str())
+        .chain(style.font_fallbacks.iter().map(String::as_str))
+        .chain(DEFAULT_FALLBACKS.iter().copied());
+
+    for family in candidates {
+        if !family.is_empty() && !chain.iter().any(|f| f == family) {
+            chain.push(family.to_string());
+        }
+    }
+    chain
+}
+
+fn has_glyph(typeface: &skia_safe::Typeface, ch: char) -> bool {
+    typeface.unichar_to_glyph(ch as i32) != 0
+}
+
+/// Map a CSS-style numeric weight to a skia font style
+pub fn font_style_for_weight(weight: u32) -> skia_safe::FontStyle {
+    skia_safe::FontStyle::new(
+        match weight {
+            w if w >= 900 => skia_safe::font_style::Weight::BLACK,
+            w if w >= 700 => skia_safe::font_style::Weight::BOLD,
+            w if w >= 500 => skia_safe::font_style::Weight::MEDIUM,
+            _ => skia_safe::font_style::Weight::NORMAL,
+        },
+        skia_safe::font_style::Width::NORMAL,
+        skia_safe::font_style::Slant::Upright,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telop_engine::template::TelopTemplate;
+
+    #[test]
+    fn test_fallback_chain_order_and_dedup() {
+        let mut style = TelopTemplate::default().default_style;
+        style.font_family = "Dela Gothic One".to_string();
+        style.font_fallbacks = vec!["Noto Sans JP".to_string(), "Dela Gothic One".to_string()];
+
+        let chain = fallback_chain(&style);
+        assert_eq!(chain[0], "Dela Gothic One");
+        assert_eq!(chain[1], "Noto Sans JP");
+        assert_eq!(chain.iter().filter(|f| *f == "Noto Sans JP").count(), 1);
+        assert_eq!(chain.last().map(String::as_str), Some("Noto Color Emoji"));
+    }
+
+    #[test]
+    fn test_register_font_dir_skips_bad_files() {
+        // A valid font from the machine's own; nothing to check against without one
+        let Some((data, _)) = skia_safe::FontMgr::default()
+            .legacy_make_typeface(None, skia_safe::FontStyle::normal())
+            .and_then(|typeface| typeface.to_font_data())
+        else {
+            return;
+        };
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let dir =
+            std::env::temp_dir().join(format!("shortforge-fonts-{}-{nanos}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a-corrupt.ttf"), b"not a font").unwrap();
+        std::fs::write(dir.join("b-valid.ttf"), &data).unwrap();
+        std::fs::write(dir.join("c-web.woff2"), b"wOF2").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+        let report = FontService::new().register_font_dir(&dir.to_string_lossy());
+        let _ = std::fs::remove_dir_all(&dir);
+        let report = report.unwrap();
+        assert_eq!(report.fonts.len(), 1);
+        assert!(report.fonts[0].path.ends_with("b-valid.ttf"));
+        assert_eq!(report.warnings.len(), 2);
+        assert!(report.warnings.iter().any(|w| w.contains("a-corrupt.ttf")));
+        assert!(report.warnings.iter().any(|w| w.contains("c-web.woff2")));
+    }
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Style overrides of a span. `None` keeps the template style.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpanStyle {
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Absolute font size in px
+    #[serde(default)]
+    pub font_size: Option<f32>,
+    /// Font size relative to the style's size (1.5 = 150%)
+    #[serde(default)]
+    pub font_scale: Option<f32>,
+}
+
+impl SpanStyle {
+    /// Font size of this span given the style's base size
+    pub fn resolve_font_size(&self, base: f32) -> f32 {
+        match (self.font_size, self.font_scale) {
+            (Some(size), _) => size,
+            (None, Some(scale)) => base * scale,
+            (None, None) => base,
+        }
+    }
+}
+
+/// A run of animation units `start..end` sharing the same overrides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyledSpan {
+    pub start: usize,
+    pub end: usize,
+    pub style: SpanStyle,
+}
+
+/// Cue text with its markup removed.
+/// Span indices refer to the animation units (grapheme clusters) of `text`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StyledText {
+    pub text: String,
+    pub spans: Vec<StyledSpan>,
+    /// Attribute values that were ignored, such as colors that cannot be read
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl StyledText {
+    /// Overrides applied to the animation unit at `index`, if any
+    pub fn style_at(&self, index: usize) -> Option<&SpanStyle> {
+        self.spans
+            .iter()
+            .find(|span| index >= span.start && index < span.end)
+            .map(|span| &span.style)
+    }
+}
+
+/// Parse cue text into plain text and styled spans.
+///
+/// Supports `<b>`, `<i>`, `<u>` and `<font color="#FF0000" size="64">` (size in px, or `150%`
+/// of the style's size). Tags nest; the innermost color and size win. Anything that is not
+/// a recognised tag is kept as literal text, so a cue like `<3` survives unchanged.
+pub fn parse_markup(input: &str) -> StyledText {
+    let mut text = String::new();
+    // Byte ranges of `text` and the style in effect for each
+    let mut runs: Vec<(usize, usize, SpanStyle)> = Vec::new();
+    let mut stack: Vec<(String, SpanStyle)> = Vec::new();
+    let mut warnings = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find(['<', '&']) else {
+            push_text(&mut text, &mut runs, &stack, rest);
+            break;
+        };
+        push_text(&mut text, &mut runs, &stack, &rest[..lt]);
+        rest = &rest[lt..];
+
+        if rest.starts_with('&') {
+            let (decoded, consumed) = decode_entity(rest);
+            push_text(&mut text, &mut runs, &stack, decoded);
+            rest = &rest[consumed..];
+            continue;
+        }
+
+        match rest
+            .find('>')
+            .and_then(|gt| parse_tag(&rest[1..gt], &mut warnings).map(|tag| (gt, tag)))
+        {
+            Some((gt, Tag::Open(name, overrides))) => {
+                let base = stack.last().map(|(_, s)| s.clone()).unwrap_or_default();
+                stack.push((name, merge(base, overrides)));
+                rest = &rest[gt + 1..];
+            }
+            Some((gt, Tag::Close(name))) => {
+                // Close the innermost matching tag; stray closing tags are dropped
+                if let Some(pos) = stack.iter().rposition(|(open, _)| *open == name) {
+                    stack.truncate(pos);
+                }
+                rest = &rest[gt + 1..];
+            }
+            None => {
+                push_text(&mut text, &mut runs, &stack, "<");
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    let spans = byte_runs_to_spans(&text, runs);
+    StyledText {
+        text,
+        spans,
+        warnings,
+    }
+}
+
+/// Cue text without markup, as displayed
+pub fn strip_markup(input: &str) -> String {
+    parse_markup(input).text
+}
+
+enum Tag {
+    Open(String, SpanStyle),
+    Close(String),
+}
+
+fn parse_tag(inner: &str, warnings: &mut Vec<String>) -> Option<Tag> {
+    let inner = inner.trim();
+    if let Some(name) = inner.strip_prefix('/') {
+        let name = name.trim().to_ascii_lowercase();
+        return is_known_tag(&name).then_some(Tag::Close(name));
+    }
+
+    let (name, attrs) = match inner.find(char::is_whitespace) {
+        Some(pos) => (&inner[..pos], &inner[pos..]),
+        None => (inner, ""),
+    };
+    let name = name.to_ascii_lowercase();
+    let mut style = SpanStyle::default();
+
+    match name.as_str() {
+        "b" => style.bold = true,
+        "i" => style.italic = true,
+        "u" => style.underline = true,
+        "font" => {
+            for (key, value) in parse_attributes(attrs) {
+                match key.as_str() {
+                    "color" => match normalize_color(&value) {
+                        Some(color) => style.color = Some(color),
+                        None => warnings.push(format!("Unknown color: {value}")),
+                    },
+                    "size" => {
+                        if let Some(percent) = value.strip_suffix('%') {
+                            style.font_scale =
+                                percent.trim().parse::<f32>().ok().map(|p| p / 100.0);
+                        } else {
+                            style.font_size = value.trim_end_matches("px").parse().ok();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(Tag::Open(name, style))
+}
+
+fn is_known_tag(name: &str) -> bool {
+    matches!(name, "b" | "i" | "u" | "font")
+}
+
+/// CSS color names accepted in `<font color>`
+const NAMED_COLORS: &[(&str, &str)] = &[
+    ("black", "#000000"),
+    ("white", "#FFFFFF"),
+    ("red", "#FF0000"),
+    ("green", "#008000"),
+    ("lime", "#00FF00"),
+    ("blue", "#0000FF"),
+    ("yellow", "#FFFF00"),
+    ("cyan", "#00FFFF"),
+    ("aqua", "#00FFFF"),
+    ("magenta", "#FF00FF"),
+    ("fuchsia", "#FF00FF"),
+    ("orange", "#FFA500"),
+    ("pink", "#FFC0CB"),
+    ("purple", "#800080"),
+    ("gray", "#808080"),
+    ("grey", "#808080"),
+    ("silver", "#C0C0C0"),
+    ("maroon", "#800000"),
+    ("navy", "#000080"),
+    ("olive", "#808000"),
+    ("teal", "#008080"),
+];
+
+/// Normalize a color to `#RRGGBB` or `#RRGGBBAA`.
+/// Accepts hex with or without `#` (`RGB`, `RRGGBB`, `RRGGBBAA`) and the common CSS names.
+pub fn normalize_color(value: &str) -> Option<String> {
+    let value = value.trim();
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        match hex.len() {
+            3 => {
+                let doubled: String = hex.chars().flat_map(|c| [c, c]).collect();
+                return Some(format!("#{}", doubled.to_ascii_uppercase()));
+            }
+            6 | 8 => return Some(format!("#{}", hex.to_ascii_uppercase())),
+            _ => {}
+        }
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|(_, hex)| hex.to_string())
+}
+
+/// Parse `key="value" key2='value' key3=value` pairs
+fn parse_attributes(attrs: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut rest = attrs.trim();
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_ascii_lowercase();
+        let after = rest[eq + 1..].trim_start();
+        let (value, remaining) = match after.chars().next() {
+            Some(quote @ ('"' | '\'')) => match after[1..].find(quote) {
+                Some(end) => (&after[1..end + 1], &after[end + 2..]),
+                None => (&after[1..], ""),
+            },
+            _ => match after.find(char::is_whitespace) {
+                Some(end) => (&after[..end], &after[end..]),
+                None => (after, ""),
+            },
+        };
+        result.push((key, value.to_string()));
+        rest = remaining.trim_start();
+    }
+
+    result
+}
+
+/// Decode a leading HTML entity. Returns the text and the number of bytes consumed.
+fn decode_entity(s: &str) -> (&'static str, usize) {
+    const ENTITIES: &[(&str, &str)] = &[
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&amp;", "&"),
+        ("&quot;", "\""),
+        ("&nbsp;", "\u{00A0}"),
+    ];
+    ENTITIES
+        .iter()
+        .find(|(entity, _)| s.starts_with(entity))
+        .map(|(entity, decoded)| (*decoded, entity.len()))
+        .unwrap_or(("&", 1))
+}
+
+fn merge(mut base: SpanStyle, overrides: SpanStyle) -> SpanStyle {
+    base.bold |= overrides.bold;
+    base.italic |= overrides.italic;
+    base.underline |= overrides.underline;
+    if overrides.color.is_some() {
+        base.color = overrides.color;
+    }
+    if overrides.font_size.is_some() || overrides.font_scale.is_some() {
+        base.font_size = overrides.font_size;
+        base.font_scale = overrides.font_scale;
+    }
+    base
+}
+
+fn push_text(
+    text: &mut String,
+    runs: &mut Vec<(usize, usize, SpanStyle)>,
+    stack: &[(String, SpanStyle)],
+    s: &str,
+) {
+    if s.is_empty() {
+        return;
+    }
+    let start = text.len();
+    text.push_str(s);
+    if let Some((_, style)) = stack.last() {
+        match runs.last_mut() {
+            Some(last) if last.1 == start && last.2 == *style => last.1 = text.len(),
+            _ => runs.push((start, text.len(), style.clone())),
+        }
+    }
+}
+
+/// Convert byte ranges of the plain text to animation unit ranges
+/// (extended grapheme clusters, matching `animation_units`)
+fn byte_runs_to_spans(text: &str, runs: Vec<(usize, usize, SpanStyle)>) -> Vec<StyledSpan> {
+    let unit_starts: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+    let unit_at = |byte: usize| unit_starts.partition_point(|&start| start < byte);
+
+    runs.into_iter()
+        .map(|(start, end, style)| StyledSpan {
+            start: unit_at(start),
+            end: unit_at(end),
+            style,
+        })
+        .filter(|span| span.end > span.start)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        let styled = parse_markup("こんにちは <3 & bye");
+        assert_eq!(styled.text, "こんにちは <3 & bye");
+        assert!(styled.spans.is_empty());
+    }
+
+    #[test]
+    fn test_font_color_and_size() {
+        let styled = parse_markup("今日は<font color=\"#FF0000\" size=\"150%\">神回</font>です");
+        assert_eq!(styled.text, "今日は神回です");
+        assert_eq!(styled.spans.len(), 1);
+
+        let span = &styled.spans[0];
+        assert_eq!((span.start, span.end), (3, 5));
+        assert_eq!(span.style.color.as_deref(), Some("#FF0000"));
+        assert_eq!(span.style.resolve_font_size(48.0), 72.0);
+        assert!(styled.style_at(2).is_none());
+        assert!(styled.style_at(4).is_some());
+    }
+
+    #[test]
+    fn test_font_color_forms() {
+        let color = |markup: &str| parse_markup(markup).spans[0].style.color.clone().unwrap();
+        assert_eq!(color("<font color=red>x</font>"), "#FF0000");
+        assert_eq!(color("<font color=\"Orange\">x</font>"), "#FFA500");
+        assert_eq!(color("<font color=#0f8>x</font>"), "#00FF88");
+        assert_eq!(color("<font color=00ff0080>x</font>"), "#00FF0080");
+    }
+
+    #[test]
+    fn test_invalid_font_color_is_ignored_with_warning() {
+        // 6 bytes but not hex digits
+        let styled = parse_markup("<font color=\"#あい\">x</font>");
+        assert_eq!(styled.text, "x");
+        assert!(styled.spans.iter().all(|span| span.style.color.is_none()));
+        assert_eq!(styled.warnings, vec!["Unknown color: #あい".to_string()]);
+
+        let styled = parse_markup("<font color=\"#GG0000\" size=60>x</font>");
+        assert!(styled.spans[0].style.color.is_none());
+        assert_eq!(styled.spans[0].style.font_size, Some(60.0));
+        assert_eq!(styled.warnings.len(), 1);
+
+        assert_eq!(normalize_color("#12345"), None);
+        assert_eq!(normalize_color("rebeccapurple"), None);
+    }
+
+    #[test]
+    fn test_nested_tags_merge() {
+        let styled = parse_markup("<b>A<font color='#00FF00' size=60>B</font>C</b>");
+        assert_eq!(styled.text, "ABC");
+        assert_eq!(styled.spans.len(), 3);
+
+        let b = styled.style_at(1).unwrap();
+        assert!(b.bold);
+        assert_eq!(b.color.as_deref(), Some("#00FF00"));
+        assert_eq!(b.resolve_font_size(48.0), 60.0);
+
+        let c = styled.style_at(2).unwrap();
+        assert!(c.bold);
+        assert!(c.color.is_none());
+    }
+
+    #[test]
+    fn test_span_indices_count_grapheme_clusters() {
+        let styled = parse_markup("👍🏽<i>ok</i>");
+        assert_eq!(styled.spans[0].start, 1);
+        assert_eq!(styled.spans[0].end, 3);
+    }
+
+    #[test]
+    fn test_entities_and_unclosed_tags() {
+        assert_eq!(strip_markup("a &lt;b&gt; c"), "a <b> c");
+        let styled = parse_markup("<b>bold to the end");
+        assert_eq!(styled.text, "bold to the end");
+        assert_eq!(styled.spans[0].end, 15);
+        assert_eq!(strip_markup("stray</b> close"), "stray close");
+    }
+}
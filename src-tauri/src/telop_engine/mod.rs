@@ -1,4 +1,11 @@
 pub mod animation;
+pub mod ass;
+pub mod captions;
+pub mod font;
+pub mod markup;
 pub mod parser;
 pub mod renderer;
 pub mod template;
+pub mod timing;
+pub mod vtt;
+pub mod woff;
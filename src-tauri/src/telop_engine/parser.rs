@@ -1,4 +1,6 @@
-use crate::models::telop::SubtitleCue;
+use crate::models::telop::{SubtitleCue, SubtitleDiagnostic, SubtitleImport};
+use crate::telop_engine::{ass, vtt};
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8};
 
 /// Parse an SRT timestamp (HH:MM:SS,mmm) to milliseconds
 fn parse_timestamp(ts: &str) -> Result<u64, String> {
@@ -41,6 +43,17 @@ fn parse_timestamp(ts: &str) -> Result<u64, String> {
     Ok(hours * 3_600_000 + minutes * 60_000 + seconds * 1_000 + millis)
 }
 
+/// Format milliseconds as an SRT timestamp (HH:MM:SS,mmm)
+fn format_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1_000) % 60,
+        ms % 1_000
+    )
+}
+
 /// Parse SRT file content into a Vec of SubtitleCue
 pub fn parse_srt(content: &str) -> Result<Vec<SubtitleCue>, String> {
     let mut cues = Vec::new();
@@ -83,17 +96,276 @@ pub fn parse_srt(content: &str) -> Result<Vec<SubtitleCue>, String> {
             start_ms,
             end_ms,
             text,
+            ..Default::default()
         });
     }
 
     Ok(cues)
 }
 
-/// Parse SRT from a file path
-pub fn parse_srt_file(path: &str) -> Result<Vec<SubtitleCue>, String> {
-    let content =
-        std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {e}"))?;
-    parse_srt(&content)
+/// Parse SRT from a file path, recovering from malformed blocks like `parse_srt_lenient`
+pub fn parse_srt_file(path: &str) -> Result<SubtitleImport, String> {
+    let (content, encoding_notice) = read_subtitle_file(path)?;
+    let mut import = parse_srt_lenient(&content);
+    import.diagnostics.splice(0..0, encoding_notice);
+    Ok(import)
+}
+
+/// Parse SRT content, recovering from malformed blocks instead of failing.
+///
+/// Blocks are separated by blank or whitespace-only lines, or start at a timing line right
+/// after the previous cue's text. Timestamps may use `.` or `,` before the milliseconds and
+/// may omit the hours. Anything that cannot be read is skipped and reported with its line.
+pub fn parse_srt_lenient(content: &str) -> SubtitleImport {
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut diagnostics = Vec::new();
+    let mut diagnose = |line: usize, message: String| {
+        diagnostics.push(SubtitleDiagnostic { line, message });
+    };
+
+    // Blocks of (line number, line)
+    let mut blocks: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut current: Vec<(usize, &str)> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if line.contains("-->") && current.iter().any(|(_, l)| l.contains("-->")) {
+            // The index line just before belongs to the new cue
+            let carried = match current.last() {
+                Some((_, l)) if l.trim().parse::<usize>().is_ok() => current.pop(),
+                _ => None,
+            };
+            let start = carried.map_or(i + 1, |(n, _)| n);
+            diagnose(start, "Missing blank line before this cue".to_string());
+            blocks.push(std::mem::replace(
+                &mut current,
+                carried.into_iter().collect(),
+            ));
+        }
+        current.push((i + 1, line));
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+
+    let mut cues: Vec<SubtitleCue> = Vec::new();
+    for block in blocks {
+        let first_line = block[0].0;
+
+        let Some(timing_pos) = block.iter().position(|(_, l)| l.contains("-->")) else {
+            let orphan_index = block.len() == 1 && block[0].1.trim().parse::<usize>().is_ok();
+            match cues.last_mut() {
+                Some(previous) if !orphan_index => {
+                    for (_, line) in &block {
+                        previous.text.push('\n');
+                        previous.text.push_str(line);
+                    }
+                    diagnose(
+                        first_line,
+                        "Blank line inside cue text; joined with the previous cue".to_string(),
+                    );
+                }
+                _ => diagnose(first_line, "No timing line; block skipped".to_string()),
+            }
+            continue;
+        };
+
+        let (timing_number, timing_line) = block[timing_pos];
+        let index = match &block[..timing_pos] {
+            [] => {
+                diagnose(timing_number, "Missing subtitle index".to_string());
+                None
+            }
+            before => {
+                if before.len() > 1 {
+                    diagnose(
+                        first_line,
+                        "Unexpected text before the timing line was ignored".to_string(),
+                    );
+                }
+                let (number, line) = before[before.len() - 1];
+                let index = line.trim().parse::<usize>().ok();
+                if index.is_none() {
+                    diagnose(number, format!("Invalid subtitle index: {}", line.trim()));
+                }
+                index
+            }
+        };
+
+        let (start_ms, end_ms) = match parse_timing_lenient(timing_line) {
+            Ok(timing) => timing,
+            Err(e) => {
+                diagnose(timing_number, format!("{e}; cue skipped"));
+                continue;
+            }
+        };
+        if end_ms < start_ms {
+            diagnose(
+                timing_number,
+                "Cue ends before it starts; cue skipped".to_string(),
+            );
+            continue;
+        }
+
+        let text: Vec<&str> = block[timing_pos + 1..].iter().map(|(_, l)| *l).collect();
+        if text.is_empty() {
+            diagnose(timing_number, "Cue has no text; cue skipped".to_string());
+            continue;
+        }
+
+        cues.push(SubtitleCue {
+            index: index.unwrap_or(cues.len() + 1),
+            start_ms,
+            end_ms,
+            text: text.join("\n"),
+            ..Default::default()
+        });
+    }
+
+    diagnostics.sort_by_key(|d| d.line);
+    SubtitleImport {
+        cues,
+        warnings: Vec::new(),
+        diagnostics,
+    }
+}
+
+/// Parse "start --> end", ignoring anything after the end time (e.g. SRT box coordinates)
+fn parse_timing_lenient(line: &str) -> Result<(u64, u64), String> {
+    let invalid = || format!("Invalid timing line: {}", line.trim());
+    let (start, end) = line.split_once("-->").ok_or_else(invalid)?;
+    let end = end.split_whitespace().next().ok_or_else(invalid)?;
+
+    let parse = |ts: &str| {
+        let ts = ts.trim();
+        // MM:SS,mmm without hours
+        if ts.matches(':').count() == 1 {
+            parse_timestamp(&format!("00:{ts}"))
+        } else {
+            parse_timestamp(ts)
+        }
+    };
+    match (parse(start), parse(end)) {
+        (Ok(start_ms), Ok(end_ms)) => Ok((start_ms, end_ms)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Read a subtitle file as text.
+/// UTF-8 and UTF-16 are detected by their BOM, UTF-16 without a BOM by its NUL bytes, and
+/// anything that is not valid UTF-8 is read as Shift_JIS. Reports the encoding when it was
+/// not UTF-8.
+pub fn read_subtitle_file(path: &str) -> Result<(String, Option<SubtitleDiagnostic>), String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {e}"))?;
+    Ok(decode_subtitle_bytes(&bytes))
+}
+
+fn decode_subtitle_bytes(bytes: &[u8]) -> (String, Option<SubtitleDiagnostic>) {
+    let (encoding, body) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None => match sniff_utf16(bytes) {
+            Some(encoding) => (encoding, bytes),
+            None if std::str::from_utf8(bytes).is_ok() => (UTF_8, bytes),
+            None => (SHIFT_JIS, bytes),
+        },
+    };
+
+    let (text, had_errors) = encoding.decode_without_bom_handling(body);
+    let notice = if had_errors {
+        Some(format!(
+            "File is not valid {}; unreadable characters were replaced",
+            encoding.name()
+        ))
+    } else if encoding != UTF_8 {
+        Some(format!("File is encoded as {}", encoding.name()))
+    } else {
+        None
+    };
+
+    (
+        text.into_owned(),
+        notice.map(|message| SubtitleDiagnostic { line: 0, message }),
+    )
+}
+
+/// UTF-16 without a BOM: subtitle files are full of ASCII digits, whose high byte is NUL
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let nuls = |parity: usize| {
+        sample
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (nuls(0), nuls(1));
+    let quarter = sample.len() / 8;
+
+    if odd > quarter && even == 0 {
+        Some(UTF_16LE)
+    } else if even > quarter && odd == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Serialize cues as an SRT document.
+/// Cues are renumbered from 1 and empty cues are skipped. Inline `<b>`, `<i>`, `<u>` and
+/// `<font>` tags are kept as is, since SRT players understand the same markup.
+pub fn write_srt(cues: &[SubtitleCue]) -> String {
+    let mut out = String::new();
+
+    let cues = cues.iter().filter(|cue| !cue.text.trim().is_empty());
+    for (n, cue) in cues.enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        // A blank line would end the block early
+        let text: Vec<&str> = cue
+            .text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n",
+            n + 1,
+            format_timestamp(cue.start_ms),
+            format_timestamp(cue.end_ms),
+            text.join("\n")
+        ));
+    }
+
+    out
+}
+
+/// Import a subtitle file, choosing the format from its extension (.srt, .vtt, .ass or .ssa).
+/// ASS sizes are scaled to `frame_height`. SRT files are parsed leniently.
+pub fn import_subtitle_file(path: &str, frame_height: u32) -> Result<SubtitleImport, String> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    let (content, encoding_notice) = read_subtitle_file(path)?;
+    let mut import = match extension.as_str() {
+        "ass" | "ssa" => ass::parse_ass(&content, frame_height)?,
+        "vtt" => vtt::parse_vtt(&content)?,
+        _ => parse_srt_lenient(&content),
+    };
+
+    import.diagnostics.splice(0..0, encoding_notice);
+    Ok(import)
 }
 
 #[cfg(test)]
@@ -160,6 +432,129 @@ Second line
         assert!(cues.is_empty());
     }
 
+    #[test]
+    fn test_write_srt_round_trip() {
+        let cues = vec![
+            SubtitleCue {
+                index: 7,
+                start_ms: 1_500,
+                end_ms: 3_723_004,
+                text: "<b>Hello</b>\n\nworld".to_string(),
+                ..Default::default()
+            },
+            SubtitleCue {
+                index: 8,
+                start_ms: 4_000,
+                end_ms: 5_000,
+                text: "  ".to_string(),
+                ..Default::default()
+            },
+            SubtitleCue {
+                index: 9,
+                start_ms: 5_000,
+                end_ms: 6_000,
+                text: "こんにちは".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let srt = write_srt(&cues);
+        assert!(srt.starts_with("1\n00:00:01,500 --> 01:02:03,004\n<b>Hello</b>\nworld\n\n2\n"));
+
+        let parsed = parse_srt(&srt).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].text, "<b>Hello</b>\nworld");
+        assert_eq!(
+            (parsed[1].index, parsed[1].start_ms, parsed[1].end_ms),
+            (2, 5_000, 6_000)
+        );
+        assert_eq!(parsed[1].text, "こんにちは");
+    }
+
+    #[test]
+    fn test_parse_srt_lenient_separators() {
+        let srt = "\u{FEFF}1\r\n00:00:01.000 --> 00:00:02.500\r\nHello\r\n  \r\n2\r\n00:03,000 --> 00:04,000 X1:10 X2:20\r\nWorld\r\n";
+        let import = parse_srt_lenient(srt);
+        assert!(import.diagnostics.is_empty());
+        assert_eq!(import.cues.len(), 2);
+        assert_eq!(
+            (import.cues[0].start_ms, import.cues[0].end_ms),
+            (1000, 2500)
+        );
+        assert_eq!(
+            (import.cues[1].start_ms, import.cues[1].end_ms),
+            (3000, 4000)
+        );
+        assert_eq!(import.cues[1].text, "World");
+    }
+
+    #[test]
+    fn test_parse_srt_lenient_recovers_from_bad_blocks() {
+        let srt = "1
+00:00:01,000 --> 00:00:02,000
+First
+
+x
+00:00:02,000 --> 00:00:03,000
+Bad index
+
+3
+00:00:03,000 --> nonsense
+Bad timing
+
+4
+00:00:05,000 --> 00:00:06,000
+Fourth
+5
+00:00:06,000 --> 00:00:07,000
+Fifth
+
+still fifth
+
+6
+";
+        let import = parse_srt_lenient(srt);
+        let texts: Vec<&str> = import.cues.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["First", "Bad index", "Fourth", "Fifth\nstill fifth"]
+        );
+        assert_eq!(import.cues[1].index, 2);
+        assert_eq!(import.cues[3].index, 5);
+
+        let lines: Vec<usize> = import.diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [5, 10, 16, 20, 22]);
+        assert!(import.diagnostics[0]
+            .message
+            .contains("Invalid subtitle index"));
+        assert!(import.diagnostics[1]
+            .message
+            .contains("Invalid timing line"));
+        assert!(import.diagnostics[2].message.contains("Missing blank line"));
+    }
+
+    #[test]
+    fn test_decode_subtitle_bytes() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\nこんにちは\n";
+
+        let (decoded, notice) = decode_subtitle_bytes(text.as_bytes());
+        assert_eq!(decoded, text);
+        assert!(notice.is_none());
+
+        let (sjis, _, _) = SHIFT_JIS.encode(text);
+        let (decoded, notice) = decode_subtitle_bytes(&sjis);
+        assert_eq!(decoded, text);
+        assert_eq!(notice.unwrap().message, "File is encoded as Shift_JIS");
+
+        let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(decode_subtitle_bytes(&utf16).0, text);
+        let with_bom = [&[0xFF, 0xFE][..], &utf16].concat();
+        assert_eq!(decode_subtitle_bytes(&with_bom).0, text);
+
+        let utf16be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(decode_subtitle_bytes(&utf16be).0, text);
+    }
+
     #[test]
     fn test_parse_timestamp_invalid() {
         assert!(parse_timestamp("invalid").is_err());
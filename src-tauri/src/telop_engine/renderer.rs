@@ -1,21 +1,91 @@
-use crate::models::telop::SubtitleCue;
-use crate::telop_engine::animation::calc_char_state;
+use crate::models::telop::{
+    BackgroundMode, BackgroundStyle, CuePlacement, GradientFill, GradientType, OutlineStyle,
+    SubtitleCue, TelopStyle,
+};
+use crate::telop_engine::animation::{
+    animation_units, calc_char_state, karaoke_fill, karaoke_unit_timings,
+};
+use crate::telop_engine::font::{font_style_for_weight, FontService};
+use crate::telop_engine::markup::{normalize_color, parse_markup, SpanStyle, StyledText};
 use crate::telop_engine::template::TelopTemplate;
 use crate::video_core::frame::VideoFrame;
+use skia_safe::textlayout::{
+    FontCollection, ParagraphBuilder, ParagraphStyle, RectHeightStyle, RectWidthStyle,
+    TextDecoration, TextStyle,
+};
+use skia_safe::{Font, GlyphId, Point, TextBlob, TextBlobBuilder};
+use std::collections::HashMap;
 
-/// Render telop text onto a video frame using skia-safe
+/// Paragraphs are laid out on a single line; this is wider than any frame
+const LAYOUT_WIDTH: f32 = 100_000.0;
+
+/// Line height as a multiple of the font size for multi-line cues
+const LINE_HEIGHT: f32 = 1.25;
+
+/// A grapheme cluster positioned within its line
+struct LayoutUnit {
+    /// Animation unit index within the cue
+    index: usize,
+    text: String,
+    x: f32,
+    width: f32,
+    font_size: f32,
+    /// Fill color from inline markup
+    color: Option<String>,
+    /// The cluster's glyphs taken from the shaped line, positioned relative to the line's
+    /// left edge and baseline. None when shaping produced no glyphs for it.
+    glyphs: Option<TextBlob>,
+    /// Underline from inline markup: offset below the baseline and thickness
+    underline: Option<(f32, f32)>,
+}
+
+/// Glyphs of one shaped run that belong to a single cluster
+struct GlyphRun {
+    font: Font,
+    glyphs: Vec<GlyphId>,
+    positions: Vec<Point>,
+}
+
+struct LayoutLine {
+    units: Vec<LayoutUnit>,
+    width: f32,
+    /// Largest font size on the line, used for line spacing
+    font_size: f32,
+    /// Distance from the top of the line box to the alphabetic baseline
+    baseline: f32,
+    height: f32,
+}
+
+/// Shaped lines of the cues on screen, so a cue is shaped once instead of on every frame.
+/// A cache belongs to one cue list: entries are keyed by the cue's index in it.
+#[derive(Default)]
+pub struct TelopLayoutCache {
+    cues: HashMap<usize, Vec<LayoutLine>>,
+}
+
+/// Render telop text onto a video frame using skia-safe.
+/// `fonts` caches typefaces across frames and records any font substitutions;
+/// `layouts` keeps each cue's shaped lines while it is on screen.
 pub fn render_telop_on_frame(
     frame: &mut VideoFrame,
     cues: &[SubtitleCue],
     template: &TelopTemplate,
     current_time_ms: u64,
+    fonts: &mut FontService,
+    layouts: &mut TelopLayoutCache,
 ) {
     // Find active cues at this time
-    let active_cues: Vec<&SubtitleCue> = cues
+    let active_cues: Vec<(usize, &SubtitleCue)> = cues
         .iter()
-        .filter(|cue| current_time_ms >= cue.start_ms && current_time_ms < cue.end_ms)
+        .enumerate()
+        .filter(|(_, cue)| current_time_ms >= cue.start_ms && current_time_ms < cue.end_ms)
         .collect();
 
+    // Cues that have left the screen don't need their layout any more
+    layouts
+        .cues
+        .retain(|index, _| active_cues.iter().any(|(active, _)| active == index));
+
     if active_cues.is_empty() {
         return;
     }
@@ -45,166 +115,668 @@ pub fn render_telop_on_frame(
     };
 
     let canvas = surface.canvas();
-    let style = &template.default_style;
-
-    // Set up font
-    let font_size = style.font_size;
-    let typeface = skia_safe::FontMgr::default()
-        .match_family_style(
-            &style.font_family,
-            skia_safe::FontStyle::new(
-                match style.font_weight {
-                    w if w >= 900 => skia_safe::font_style::Weight::BLACK,
-                    w if w >= 700 => skia_safe::font_style::Weight::BOLD,
-                    w if w >= 500 => skia_safe::font_style::Weight::MEDIUM,
-                    _ => skia_safe::font_style::Weight::NORMAL,
-                },
-                skia_safe::font_style::Width::NORMAL,
-                skia_safe::font_style::Slant::Upright,
-            ),
-        )
-        .unwrap_or_else(|| {
-            skia_safe::FontMgr::default()
-                .legacy_make_typeface(None, skia_safe::FontStyle::default())
-                .expect("Failed to create default typeface")
-        });
+    let collection = fonts.font_collection();
+
+    for &(cue_index, cue) in &active_cues {
+        // A cue's own style (e.g. from an ASS import) replaces the template style
+        let style = cue.style.as_ref().unwrap_or(&template.default_style);
+        let font_size = style.font_size;
+
+        let lines = layouts.cues.entry(cue_index).or_insert_with(|| {
+            // Set up fonts: the resolved primary family first, then the fallback chain.
+            // The paragraph shaper falls back per glyph (emoji, CJK) beyond that.
+            let typeface = fonts.resolve_style(style);
+            let mut families = vec![typeface.family_name()];
+            families.extend(fonts.fallback_families(style));
+
+            let mut text_style = TextStyle::new();
+            text_style.set_font_families(&families);
+            text_style.set_font_size(font_size);
+            text_style.set_font_style(font_style_for_weight(style.font_weight));
 
-    let font = skia_safe::Font::from_typeface(&typeface, font_size);
+            let styled = parse_markup(&cue.text);
+            for warning in &styled.warnings {
+                eprintln!("Telop cue {}: {warning}", cue.index);
+            }
 
-    for cue in &active_cues {
-        let chars: Vec<char> = cue.text.chars().collect();
+            // Record substitutions for glyphs missing from the primary font
+            for ch in styled.text.chars() {
+                fonts.typeface_for_char(&typeface, style, ch);
+            }
 
-        // Measure total text width
-        let (text_width, _) = font.measure_str(&cue.text, None);
+            layout_cue(&styled, &collection, &text_style, style)
+        });
+        let karaoke = template
+            .animation
+            .karaoke
+            .as_ref()
+            .map(|config| (config, karaoke_unit_timings(cue, &config.timing)));
 
-        // Default position: centered
-        let base_x = (width as f32 - text_width) / 2.0;
-        let base_y = if let Some(ref pos) = style.position {
+        // Default position: centered, lines stacked around the anchor.
+        // Placement hints from the subtitle file take precedence.
+        let anchor_y = if let Some(ref pos) = style.position {
             pos.y
         } else {
             height as f32 * 0.5
         };
+        let mut baselines = line_baselines(lines, anchor_y);
+        if let Some(ref placement) = cue.placement {
+            apply_line_placement(&mut baselines, lines, placement, height as f32, font_size);
+        }
+        let line_xs: Vec<f32> = lines
+            .iter()
+            .map(|line| line_x(line.width, cue.placement.as_ref(), width as f32))
+            .collect();
 
-        // Draw each character with its animation state
-        let mut cursor_x = base_x;
+        // Line boxes in frame coordinates, used for background plates and gradient extents
+        let line_rects: Vec<skia_safe::Rect> = lines
+            .iter()
+            .zip(baselines.iter().zip(&line_xs))
+            .map(|(line, (base_y, base_x))| {
+                skia_safe::Rect::from_xywh(*base_x, base_y - line.baseline, line.width, line.height)
+            })
+            .collect();
 
-        for (i, ch) in chars.iter().enumerate() {
-            let char_str = ch.to_string();
-            let (char_width, _) = font.measure_str(&char_str, None);
+        if let Some(ref background) = style.background {
+            if background.enabled {
+                draw_background(canvas, background, &line_rects);
+            }
+        }
 
-            let state = calc_char_state(i, current_time_ms, cue.start_ms, template);
+        for (line_index, line) in lines.iter().enumerate() {
+            let base_x = line_xs[line_index];
+            let base_y = baselines[line_index];
+            let fill_shader = style
+                .fill
+                .as_ref()
+                .and_then(|fill| make_fill_shader(fill, &line_rects[line_index]));
 
-            if state.opacity <= 0.001 {
-                cursor_x += char_width;
-                continue;
-            }
+            // Draw each grapheme cluster with its animation state
+            for unit in &line.units {
+                let i = unit.index;
+                let state = calc_char_state(i, current_time_ms, cue.start_ms, template);
 
-            let char_x = cursor_x + state.translate_x as f32;
-            let char_y = base_y + state.translate_y as f32;
+                if state.opacity <= 0.001 {
+                    continue;
+                }
+
+                let char_x = base_x + unit.x + state.translate_x as f32;
+                let char_y = base_y + state.translate_y as f32;
+                let char_width = unit.width;
+                let font_size = unit.font_size;
 
-            canvas.save();
+                canvas.save();
 
-            // Apply scale and rotation around character center
-            let center_x = char_x + char_width / 2.0;
-            let center_y = char_y - font_size / 3.0;
+                // Apply scale and rotation around character center
+                let center_x = char_x + char_width / 2.0;
+                let center_y = char_y - font_size / 3.0;
 
-            if (state.scale - 1.0).abs() > 0.001 || state.rotate.abs() > 0.001 {
-                canvas.translate((center_x, center_y));
-                if state.rotate.abs() > 0.001 {
-                    canvas.rotate(state.rotate as f32, None);
+                if (state.scale - 1.0).abs() > 0.001 || state.rotate.abs() > 0.001 {
+                    canvas.translate((center_x, center_y));
+                    if state.rotate.abs() > 0.001 {
+                        canvas.rotate(state.rotate as f32, None);
+                    }
+                    if (state.scale - 1.0).abs() > 0.001 {
+                        canvas.scale((state.scale as f32, state.scale as f32));
+                    }
+                    canvas.translate((-center_x, -center_y));
                 }
-                if (state.scale - 1.0).abs() > 0.001 {
-                    canvas.scale((state.scale as f32, state.scale as f32));
+
+                let alpha = (state.opacity * 255.0).clamp(0.0, 255.0) as u8;
+                // The cluster's glyphs are positioned within the line, so they are drawn from
+                // the line's left edge moved by the animation
+                let origin = (char_x - unit.x, char_y);
+                // Color emoji ignore the paint color, so strokes and shadows would just duplicate them
+                let decorate = !is_emoji_cluster(&unit.text);
+
+                // Draw shadow
+                if let Some(ref shadow) = style.shadow {
+                    if shadow.enabled && decorate {
+                        let shadow_color = parse_color_with_alpha(&shadow.color, alpha);
+                        let mut shadow_paint = skia_safe::Paint::new(shadow_color, None);
+                        shadow_paint.set_anti_alias(true);
+
+                        if shadow.blur > 0.0 {
+                            shadow_paint.set_mask_filter(skia_safe::MaskFilter::blur(
+                                skia_safe::BlurStyle::Normal,
+                                shadow.blur / 2.0,
+                                false,
+                            ));
+                        }
+
+                        paint_unit(
+                            canvas,
+                            unit,
+                            &shadow_paint,
+                            (origin.0 + shadow.offset_x, origin.1 + shadow.offset_y),
+                        );
+                    }
+                }
+
+                // Draw outlines, widest first so narrower strokes stay visible on top
+                if decorate {
+                    for outline in outline_layers(style) {
+                        let outline_paint = make_outline_paint(outline, &outline.color, alpha);
+                        paint_unit(canvas, unit, &outline_paint, origin);
+                    }
                 }
-                canvas.translate((-center_x, -center_y));
-            }
 
-            let alpha = (state.opacity * 255.0).clamp(0.0, 255.0) as u8;
+                // Draw main text. A color from inline markup takes precedence over the gradient.
+                let text_color = unit.color.as_deref().unwrap_or(&style.color);
+                let mut text_paint =
+                    skia_safe::Paint::new(parse_color_with_alpha(text_color, alpha), None);
+                text_paint.set_anti_alias(true);
+                if let (Some(shader), true, None) = (&fill_shader, decorate, &unit.color) {
+                    text_paint.set_shader(shader.clone());
+                    text_paint.set_alpha(alpha);
+                }
 
-            // Draw shadow
-            if let Some(ref shadow) = style.shadow {
-                if shadow.enabled {
-                    let shadow_color = parse_color_with_alpha(&shadow.color, alpha);
-                    let mut shadow_paint = skia_safe::Paint::new(shadow_color, None);
-                    shadow_paint.set_anti_alias(true);
+                paint_unit(canvas, unit, &text_paint, origin);
 
-                    if shadow.blur > 0.0 {
-                        shadow_paint.set_mask_filter(skia_safe::MaskFilter::blur(
-                            skia_safe::BlurStyle::Normal,
-                            shadow.blur / 2.0,
+                // Inner glow: a blur confined to the inside of the glyphs
+                if let Some(ref glow) = style.inner_glow {
+                    if glow.enabled && glow.blur > 0.0 && decorate {
+                        let mut glow_paint =
+                            skia_safe::Paint::new(parse_color_with_alpha(&glow.color, alpha), None);
+                        glow_paint.set_anti_alias(true);
+                        glow_paint.set_mask_filter(skia_safe::MaskFilter::blur(
+                            skia_safe::BlurStyle::Inner,
+                            glow.blur / 2.0,
                             false,
                         ));
+                        paint_unit(canvas, unit, &glow_paint, origin);
                     }
+                }
 
-                    canvas.draw_str(
-                        &char_str,
-                        (char_x + shadow.offset_x, char_y + shadow.offset_y),
-                        &font,
-                        &shadow_paint,
-                    );
+                // Karaoke: redraw the sung part in the highlight color, clipped left to right
+                if let Some((config, timings)) = &karaoke {
+                    let fill = karaoke_fill(timings[i], current_time_ms) as f32;
+                    if fill > 0.0 && decorate {
+                        // Leave room for the outline stroke on either side of the glyph
+                        let clip_width = if fill >= 1.0 {
+                            char_width + font_size * 2.0
+                        } else {
+                            char_width * fill + font_size
+                        };
+                        canvas.save();
+                        canvas.clip_rect(
+                            skia_safe::Rect::from_xywh(
+                                char_x - font_size,
+                                char_y - font_size * 2.0,
+                                clip_width,
+                                font_size * 3.0,
+                            ),
+                            None,
+                            true,
+                        );
+
+                        // The highlight outline color replaces the outermost stroke only
+                        for (layer, outline) in outline_layers(style).into_iter().enumerate() {
+                            let color = match (layer, &config.highlight_outline_color) {
+                                (0, Some(color)) => color.as_str(),
+                                _ => outline.color.as_str(),
+                            };
+                            let outline_paint = make_outline_paint(outline, color, alpha);
+                            paint_unit(canvas, unit, &outline_paint, origin);
+                        }
+
+                        let highlight_color =
+                            parse_color_with_alpha(&config.highlight_color, alpha);
+                        let mut highlight_paint = skia_safe::Paint::new(highlight_color, None);
+                        highlight_paint.set_anti_alias(true);
+                        paint_unit(canvas, unit, &highlight_paint, origin);
+
+                        canvas.restore();
+                    }
                 }
+
+                canvas.restore();
             }
+        }
+    }
+}
 
-            // Draw outline
-            if let Some(ref outline) = style.outline {
-                if outline.enabled {
-                    let outline_color = parse_color_with_alpha(&outline.color, alpha);
-                    let mut outline_paint = skia_safe::Paint::new(outline_color, None);
-                    outline_paint.set_anti_alias(true);
-                    outline_paint.set_style(skia_safe::PaintStyle::Stroke);
-                    outline_paint.set_stroke_width(outline.width * 2.0);
-                    let join = match outline.join.as_str() {
-                        "round" => skia_safe::paint::Join::Round,
-                        "bevel" => skia_safe::paint::Join::Bevel,
-                        _ => skia_safe::paint::Join::Miter,
+/// Shape each line of a cue once and split its glyphs into grapheme clusters.
+/// Glyphs come from the whole-line layout, so kerning, ligatures and contextual forms
+/// between clusters are preserved.
+fn layout_cue(
+    styled: &StyledText,
+    collection: &FontCollection,
+    text_style: &TextStyle,
+    style: &TelopStyle,
+) -> Vec<LayoutLine> {
+    // Split into lines, keeping each cluster's unit index within the whole cue
+    let mut lines: Vec<Vec<(usize, &str)>> = vec![Vec::new()];
+    for (index, unit) in animation_units(&styled.text).into_iter().enumerate() {
+        if unit == "\n" || unit == "\r\n" {
+            lines.push(Vec::new());
+        } else if let Some(line) = lines.last_mut() {
+            line.push((index, unit));
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|units| {
+            let unit_styles: Vec<(Option<&SpanStyle>, TextStyle)> = units
+                .iter()
+                .map(|(index, _)| {
+                    let span = styled.style_at(*index);
+                    (span, span_text_style(text_style, style, span))
+                })
+                .collect();
+
+            // Consecutive clusters with the same span are shaped as one run
+            let mut builder = ParagraphBuilder::new(&ParagraphStyle::new(), collection.clone());
+            let mut start = 0;
+            while start < units.len() {
+                let span = unit_styles[start].0;
+                let end = (start..units.len())
+                    .find(|&i| unit_styles[i].0 != span)
+                    .unwrap_or(units.len());
+                let run_text: String = units[start..end].iter().map(|(_, unit)| *unit).collect();
+                builder.push_style(&unit_styles[start].1);
+                builder.add_text(&run_text);
+                builder.pop();
+                start = end;
+            }
+            let mut paragraph = builder.build();
+            paragraph.layout(LAYOUT_WIDTH);
+            let unit_runs = line_glyphs(&mut paragraph, &units);
+
+            // Paragraph ranges are expressed in UTF-16 code units
+            let mut utf16_start = 0;
+            let mut cursor_x = 0.0;
+            let layout_units: Vec<LayoutUnit> = units
+                .into_iter()
+                .zip(unit_styles)
+                .zip(unit_runs)
+                .map(|(((index, unit), (span, unit_style)), runs)| {
+                    let utf16_end = utf16_start + unit.encode_utf16().count();
+                    let boxes = paragraph.get_rects_for_range(
+                        utf16_start..utf16_end,
+                        RectHeightStyle::Tight,
+                        RectWidthStyle::Tight,
+                    );
+                    utf16_start = utf16_end;
+
+                    let left = boxes.iter().map(|b| b.rect.left).reduce(f32::min);
+                    let right = boxes.iter().map(|b| b.rect.right).reduce(f32::max);
+                    let (x, width) = match (left, right) {
+                        (Some(left), Some(right)) => (left, right - left),
+                        _ => (cursor_x, 0.0),
                     };
-                    outline_paint.set_stroke_join(join);
-                    if join == skia_safe::paint::Join::Miter {
-                        outline_paint.set_stroke_miter(4.0);
+                    cursor_x = x + width;
+
+                    let font_size = unit_style.font_size();
+                    let underline = (unit_style.decoration_type() != TextDecoration::NO_DECORATION)
+                        .then(|| {
+                            let metrics = runs.first().map(|run| run.font.metrics().1);
+                            let position = metrics
+                                .as_ref()
+                                .and_then(|m| m.underline_position())
+                                .unwrap_or(font_size / 10.0);
+                            let thickness = metrics
+                                .as_ref()
+                                .and_then(|m| m.underline_thickness())
+                                .unwrap_or(font_size / 18.0);
+                            (position, thickness.max(1.0))
+                        });
+
+                    LayoutUnit {
+                        index,
+                        text: unit.to_string(),
+                        x,
+                        width,
+                        font_size,
+                        color: span.and_then(|s| s.color.clone()),
+                        glyphs: glyph_blob(&runs),
+                        underline,
                     }
+                })
+                .collect();
 
-                    canvas.draw_str(&char_str, (char_x, char_y), &font, &outline_paint);
-                }
+            let font_size = layout_units
+                .iter()
+                .map(|unit| unit.font_size)
+                .reduce(f32::max)
+                .unwrap_or(style.font_size);
+
+            LayoutLine {
+                units: layout_units,
+                width: paragraph.max_intrinsic_width(),
+                font_size,
+                baseline: paragraph.alphabetic_baseline(),
+                height: paragraph.height(),
             }
+        })
+        .collect()
+}
 
-            // Draw main text
-            let text_color = parse_color_with_alpha(&style.color, alpha);
-            let mut text_paint = skia_safe::Paint::new(text_color, None);
-            text_paint.set_anti_alias(true);
+/// Apply inline markup overrides to the base text style
+fn span_text_style(base: &TextStyle, style: &TelopStyle, span: Option<&SpanStyle>) -> TextStyle {
+    let mut text_style = base.clone();
+    let Some(span) = span else {
+        return text_style;
+    };
 
-            canvas.draw_str(&char_str, (char_x, char_y), &font, &text_paint);
+    text_style.set_font_size(span.resolve_font_size(style.font_size));
 
-            canvas.restore();
-            cursor_x += char_width;
+    let weight = if span.bold {
+        style.font_weight.max(700)
+    } else {
+        style.font_weight
+    };
+    let font_style = font_style_for_weight(weight);
+    let slant = if span.italic {
+        skia_safe::font_style::Slant::Italic
+    } else {
+        font_style.slant()
+    };
+    text_style.set_font_style(skia_safe::FontStyle::new(
+        font_style.weight(),
+        font_style.width(),
+        slant,
+    ));
+
+    if span.underline {
+        text_style.set_decoration_type(TextDecoration::UNDERLINE);
+    }
+    text_style
+}
+
+/// Baseline of each line: lines are stacked by their largest font size and centered on `anchor_y`
+fn line_baselines(lines: &[LayoutLine], anchor_y: f32) -> Vec<f32> {
+    let mut baselines = Vec::with_capacity(lines.len());
+    let mut y = 0.0;
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            y += (lines[i - 1].font_size + line.font_size) / 2.0 * LINE_HEIGHT;
         }
+        baselines.push(y);
     }
+
+    let offset = anchor_y - y / 2.0;
+    baselines.iter().map(|baseline| baseline + offset).collect()
 }
 
-/// Parse CSS hex color string to skia Color4f with alpha override
-fn parse_color_with_alpha(hex: &str, alpha: u8) -> skia_safe::Color4f {
-    let hex = hex.trim_start_matches('#');
-    let (r, g, b, a) = match hex.len() {
-        6 => {
-            let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-            let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-            let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-            (r, g, b, alpha)
+/// Move lines to the cue's WebVTT line setting: a percentage puts the cue's top, center or
+/// bottom there (per `line_align`), a line number counts lines of the base font size from
+/// the top (or bottom when negative)
+fn apply_line_placement(
+    baselines: &mut [f32],
+    lines: &[LayoutLine],
+    placement: &CuePlacement,
+    frame_height: f32,
+    font_size: f32,
+) {
+    let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+        return;
+    };
+    let top = baselines[0] - first.baseline;
+    let bottom = baselines[baselines.len() - 1] - last.baseline + last.height;
+    let pitch = font_size * LINE_HEIGHT;
+
+    let shift = match (placement.line, placement.line_number) {
+        (Some(line), _) => {
+            let edge = match placement.line_align.as_deref() {
+                Some("center") => (top + bottom) / 2.0,
+                Some("end") => bottom,
+                _ => top,
+            };
+            line * frame_height - edge
         }
-        8 => {
-            let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-            let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-            let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-            let base_a = u8::from_str_radix(&hex[6..8], 16).unwrap_or(255);
-            let combined_a = ((base_a as u16 * alpha as u16) / 255) as u8;
-            (r, g, b, combined_a)
+        (None, Some(n)) if n >= 0 => n as f32 * pitch - top,
+        (None, Some(n)) => frame_height + (n + 1) as f32 * pitch - bottom,
+        (None, None) => return,
+    };
+    for baseline in baselines.iter_mut() {
+        *baseline += shift;
+    }
+}
+
+/// Left edge of a line: centered on the frame, or aligned to the cue's WebVTT position
+fn line_x(line_width: f32, placement: Option<&CuePlacement>, frame_width: f32) -> f32 {
+    let align = placement
+        .and_then(|p| p.align.as_deref())
+        .unwrap_or("center");
+    let default_position = match align {
+        "start" | "left" => 0.0,
+        "end" | "right" => 1.0,
+        _ => 0.5,
+    };
+    let anchor = placement
+        .and_then(|p| p.position)
+        .unwrap_or(default_position)
+        * frame_width;
+
+    match align {
+        "start" | "left" => anchor,
+        "end" | "right" => anchor - line_width,
+        _ => anchor - line_width / 2.0,
+    }
+}
+
+/// Glyph runs of a laid-out line per cluster, positioned relative to the line's left edge
+/// and baseline. Glyphs go to the cluster their text starts in, so a ligature spanning
+/// clusters is drawn (and animated) with the first of them.
+fn line_glyphs(
+    paragraph: &mut skia_safe::textlayout::Paragraph,
+    units: &[(usize, &str)],
+) -> Vec<Vec<GlyphRun>> {
+    // UTF-8 offset of each cluster within the line text
+    let starts: Vec<usize> = units
+        .iter()
+        .scan(0, |offset, (_, unit)| {
+            let start = *offset;
+            *offset += unit.len();
+            Some(start)
+        })
+        .collect();
+    let baseline = paragraph.alphabetic_baseline();
+
+    let mut unit_runs: Vec<Vec<GlyphRun>> = units.iter().map(|_| Vec::new()).collect();
+    let mut run_index = 0;
+    paragraph.visit(|_, info| {
+        let Some(info) = info else {
+            return;
+        };
+        run_index += 1;
+        let origin = info.origin();
+        let glyphs = info
+            .glyphs()
+            .iter()
+            .zip(info.positions())
+            .zip(info.utf8_starts());
+        // (run, cluster) of the last glyph, to group consecutive glyphs into one run
+        let mut current = None;
+        for ((&glyph, position), &cluster) in glyphs {
+            let unit = starts
+                .partition_point(|&start| start <= cluster as usize)
+                .saturating_sub(1);
+            let Some(runs) = unit_runs.get_mut(unit) else {
+                continue;
+            };
+            if current != Some((run_index, unit)) {
+                runs.push(GlyphRun {
+                    font: info.font().clone(),
+                    glyphs: Vec::new(),
+                    positions: Vec::new(),
+                });
+                current = Some((run_index, unit));
+            }
+            if let Some(run) = runs.last_mut() {
+                run.glyphs.push(glyph);
+                run.positions.push(Point::new(
+                    origin.x + position.x,
+                    origin.y + position.y - baseline,
+                ));
+            }
+        }
+    });
+    unit_runs
+}
+
+/// One text blob holding a cluster's glyph runs
+fn glyph_blob(runs: &[GlyphRun]) -> Option<TextBlob> {
+    let mut builder = TextBlobBuilder::new();
+    for run in runs {
+        let (glyphs, positions) = builder.alloc_run_pos(&run.font, run.glyphs.len(), None);
+        glyphs.copy_from_slice(&run.glyphs);
+        positions.copy_from_slice(&run.positions);
+    }
+    builder.make()
+}
+
+/// Draw a cluster's glyphs and underline with the line's left edge and baseline at `origin`
+fn paint_unit(
+    canvas: &skia_safe::Canvas,
+    unit: &LayoutUnit,
+    paint: &skia_safe::Paint,
+    origin: (f32, f32),
+) {
+    if let Some(ref blob) = unit.glyphs {
+        canvas.draw_text_blob(blob, origin, paint);
+    }
+    // Underlines follow the paint of each pass (shadow, outline, fill)
+    if let Some((offset, thickness)) = unit.underline {
+        canvas.draw_rect(
+            skia_safe::Rect::from_xywh(origin.0 + unit.x, origin.1 + offset, unit.width, thickness),
+            paint,
+        );
+    }
+}
+
+/// Whether a grapheme cluster is (or contains) a pictographic emoji
+fn is_emoji_cluster(cluster: &str) -> bool {
+    cluster.chars().any(|c| {
+        matches!(
+            c as u32,
+            0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F | 0x200D
+        )
+    })
+}
+
+/// Enabled outlines of a style (`outline` plus `outlines`), widest first
+fn outline_layers(style: &TelopStyle) -> Vec<&OutlineStyle> {
+    let mut layers: Vec<&OutlineStyle> = style
+        .outline
+        .iter()
+        .chain(style.outlines.iter())
+        .filter(|outline| outline.enabled && outline.width > 0.0)
+        .collect();
+    layers.sort_by(|a, b| b.width.total_cmp(&a.width));
+    layers
+}
+
+/// Draw rounded plates behind the cue: one per line, or one around all lines
+fn draw_background(
+    canvas: &skia_safe::Canvas,
+    background: &BackgroundStyle,
+    line_rects: &[skia_safe::Rect],
+) {
+    let alpha = (background.opacity.clamp(0.0, 1.0) * 255.0) as u8;
+    let mut paint = skia_safe::Paint::new(parse_color_with_alpha(&background.color, alpha), None);
+    paint.set_anti_alias(true);
+
+    let plates: Vec<skia_safe::Rect> = match background.mode {
+        BackgroundMode::Line => line_rects.to_vec(),
+        BackgroundMode::Cue => line_rects
+            .iter()
+            .copied()
+            .reduce(|a, b| skia_safe::Rect::join2(a, b))
+            .into_iter()
+            .collect(),
+    };
+
+    for plate in plates {
+        let rect = plate.with_outset((background.padding_x, background.padding_y));
+        let rrect =
+            skia_safe::RRect::new_rect_xy(rect, background.corner_radius, background.corner_radius);
+        canvas.draw_rrect(rrect, &paint);
+    }
+}
+
+/// Gradient shader spanning `bounds`. None if the fill has fewer than two valid colors.
+fn make_fill_shader(fill: &GradientFill, bounds: &skia_safe::Rect) -> Option<skia_safe::Shader> {
+    let colors: Vec<skia_safe::Color> = fill
+        .colors
+        .iter()
+        .map(|c| parse_color_with_alpha(c, 255).to_color())
+        .collect();
+    if colors.len() < 2 {
+        return None;
+    }
+    let stops = fill
+        .stops
+        .as_ref()
+        .filter(|stops| stops.len() == colors.len())
+        .map(|stops| stops.as_slice());
+
+    let center = bounds.center();
+    match fill.gradient_type {
+        GradientType::Linear => {
+            // Run the gradient through the center so it just covers the box at any angle
+            let (sin, cos) = fill.angle.to_radians().sin_cos();
+            let half_length =
+                (bounds.width() / 2.0 * cos).abs() + (bounds.height() / 2.0 * sin).abs();
+            let start = (center.x - cos * half_length, center.y - sin * half_length);
+            let end = (center.x + cos * half_length, center.y + sin * half_length);
+            skia_safe::Shader::linear_gradient(
+                (start, end),
+                colors.as_slice(),
+                stops,
+                skia_safe::TileMode::Clamp,
+                None,
+                None,
+            )
         }
-        _ => (255, 255, 255, alpha),
+        GradientType::Radial => skia_safe::Shader::radial_gradient(
+            center,
+            bounds.width().max(bounds.height()) / 2.0,
+            colors.as_slice(),
+            stops,
+            skia_safe::TileMode::Clamp,
+            None,
+            None,
+        ),
+    }
+}
+
+/// Build a stroke paint for an outline, using `color` instead of the outline's own color
+fn make_outline_paint(outline: &OutlineStyle, color: &str, alpha: u8) -> skia_safe::Paint {
+    let mut paint = skia_safe::Paint::new(parse_color_with_alpha(color, alpha), None);
+    paint.set_anti_alias(true);
+    paint.set_style(skia_safe::PaintStyle::Stroke);
+    paint.set_stroke_width(outline.width * 2.0);
+    let join = match outline.join.as_str() {
+        "round" => skia_safe::paint::Join::Round,
+        "bevel" => skia_safe::paint::Join::Bevel,
+        _ => skia_safe::paint::Join::Miter,
+    };
+    paint.set_stroke_join(join);
+    if join == skia_safe::paint::Join::Miter {
+        paint.set_stroke_miter(4.0);
+    }
+    paint
+}
+
+/// Parse a CSS hex or named color to skia Color4f with alpha override.
+/// Colors that cannot be read are drawn white.
+fn parse_color_with_alpha(color: &str, alpha: u8) -> skia_safe::Color4f {
+    let color = normalize_color(color).unwrap_or_else(|| "#FFFFFF".to_string());
+    // Normalized colors are ASCII hex, so byte slicing is safe
+    let channel = |i: usize| u8::from_str_radix(&color[1 + i * 2..3 + i * 2], 16).unwrap_or(255);
+    let a = if color.len() == 9 {
+        ((channel(3) as u16 * alpha as u16) / 255) as u8
+    } else {
+        alpha
     };
 
     skia_safe::Color4f::new(
-        r as f32 / 255.0,
-        g as f32 / 255.0,
-        b as f32 / 255.0,
+        channel(0) as f32 / 255.0,
+        channel(1) as f32 / 255.0,
+        channel(2) as f32 / 255.0,
         a as f32 / 255.0,
     )
 }
@@ -31,6 +31,33 @@ pub struct TemplateAnimation {
     pub duration_ms: u64,
     pub delay_per_unit_ms: u64,
     pub easing: String,
+    /// Progressive highlight sweep (karaoke templates)
+    #[serde(default)]
+    pub karaoke: Option<KaraokeConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KaraokeConfig {
+    /// Color of the already-sung part of the text
+    pub highlight_color: String,
+    /// Outline color of the already-sung part. Defaults to the base outline color.
+    #[serde(default)]
+    pub highlight_outline_color: Option<String>,
+    #[serde(default)]
+    pub timing: KaraokeTiming,
+}
+
+/// How the highlight timing is derived for each character
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KaraokeTiming {
+    /// Use the cue's word timings when present, otherwise distribute by syllable
+    #[default]
+    Auto,
+    /// Spread the cue duration evenly across visible characters
+    Even,
+    /// Weight each character by its approximate syllable / mora count
+    Syllable,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,9 +84,11 @@ impl Default for TelopTemplate {
                 duration_ms: 0,
                 delay_per_unit_ms: 0,
                 easing: "linear".to_string(),
+                karaoke: None,
             },
             default_style: TelopStyle {
                 font_family: "Noto Sans JP".to_string(),
+                font_fallbacks: vec![],
                 font_size: 40.0,
                 font_weight: 900,
                 color: "#FFFFFF".to_string(),
@@ -72,6 +101,10 @@ impl Default for TelopTemplate {
                 shadow: None,
                 position: None,
                 alignment: None,
+                fill: None,
+                background: None,
+                outlines: vec![],
+                inner_glow: None,
             },
             preview_text: None,
         }
@@ -187,6 +220,75 @@ mod tests {
         assert_eq!(keyframes[0].opacity, Some(0.0));
     }
 
+    #[test]
+    fn test_load_karaoke_template() {
+        let json = r##"{
+            "id": "karaoke",
+            "name": "カラオケ",
+            "description": "test",
+            "category": "karaoke",
+            "animation": {
+                "unit": "character",
+                "duration_ms": 0,
+                "delay_per_unit_ms": 0,
+                "easing": "linear",
+                "karaoke": { "highlight_color": "#FFE600", "timing": "syllable" }
+            },
+            "default_style": {
+                "font_family": "Noto Sans JP",
+                "font_size": 40,
+                "font_weight": 900,
+                "color": "#FFFFFF"
+            }
+        }"##;
+
+        let template = load_template(json).unwrap();
+        let karaoke = template.animation.karaoke.unwrap();
+        assert_eq!(karaoke.highlight_color, "#FFE600");
+        assert_eq!(karaoke.timing, KaraokeTiming::Syllable);
+        assert!(karaoke.highlight_outline_color.is_none());
+    }
+
+    #[test]
+    fn test_load_rich_style() {
+        let json = r##"{
+            "id": "variety",
+            "name": "バラエティ",
+            "description": "test",
+            "category": "variety",
+            "animation": {
+                "unit": "character",
+                "duration_ms": 0,
+                "delay_per_unit_ms": 0,
+                "easing": "linear"
+            },
+            "default_style": {
+                "font_family": "Noto Sans JP",
+                "font_size": 48,
+                "font_weight": 900,
+                "color": "#FFFFFF",
+                "fill": { "type": "linear", "colors": ["#FFF200", "#FF8A00"] },
+                "background": { "enabled": true, "color": "#000000", "opacity": 0.6, "padding_x": 16, "corner_radius": 12, "mode": "cue" },
+                "outlines": [
+                    { "enabled": true, "color": "#FFFFFF", "width": 4 },
+                    { "enabled": true, "color": "#000000", "width": 8, "join": "round" }
+                ],
+                "inner_glow": { "enabled": true, "color": "#FFFFFF", "blur": 6 }
+            }
+        }"##;
+
+        let style = load_template(json).unwrap().default_style;
+        let fill = style.fill.unwrap();
+        assert_eq!(fill.gradient_type, crate::models::telop::GradientType::Linear);
+        assert_eq!(fill.angle, 90.0);
+        let background = style.background.unwrap();
+        assert_eq!(background.mode, crate::models::telop::BackgroundMode::Cue);
+        assert_eq!(background.padding_y, 0.0);
+        assert_eq!(style.outlines.len(), 2);
+        assert_eq!(style.outlines[1].join, "round");
+        assert!(style.inner_glow.unwrap().enabled);
+    }
+
     #[test]
     fn test_load_templates_from_dir() {
         // This test uses the actual templates directory
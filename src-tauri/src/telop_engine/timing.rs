@@ -0,0 +1,518 @@
+use crate::beat_sync::analyzer::BeatInfo;
+use crate::beat_sync::detector::snap_to_beat;
+use crate::models::telop::SubtitleCue;
+use crate::telop_engine::markup::strip_markup;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters after which a line may be broken when it has no spaces
+const BREAK_AFTER: &[&str] = &["、", "。", "，", "．", "！", "？", "!", "?", ",", "・"];
+
+/// Shift all cues by `offset_ms` (negative = earlier).
+/// Cues that would end before 0 are dropped; cues that would start before 0 are clipped.
+pub fn offset(cues: &[SubtitleCue], offset_ms: i64) -> Vec<SubtitleCue> {
+    let cues = map_times(cues, |ms| (ms as i64 + offset_ms).max(0) as u64);
+    finish(cues.into_iter().filter(|cue| cue.end_ms > 0).collect())
+}
+
+/// Scale times around `anchor_ms` by `factor`.
+/// To retime cues made for another frame rate, use `from_fps / to_fps`
+/// (e.g. 25 / 23.976 for a PAL speed-up).
+pub fn stretch(
+    cues: &[SubtitleCue],
+    factor: f64,
+    anchor_ms: u64,
+) -> Result<Vec<SubtitleCue>, String> {
+    if !(factor.is_finite() && factor > 0.0) {
+        return Err(format!("Invalid stretch factor: {factor}"));
+    }
+    let anchor = anchor_ms as f64;
+    Ok(finish(map_times(cues, |ms| {
+        (anchor + (ms as f64 - anchor) * factor).round().max(0.0) as u64
+    })))
+}
+
+/// Retime cues so that `from_a` lands on `to_a` and `from_b` on `to_b`, interpolating
+/// linearly in between (and extrapolating outside). Fixes drift and offset in one step.
+pub fn two_point_sync(
+    cues: &[SubtitleCue],
+    (from_a, to_a): (u64, u64),
+    (from_b, to_b): (u64, u64),
+) -> Result<Vec<SubtitleCue>, String> {
+    if from_a == from_b {
+        return Err("Sync points must be at different times".to_string());
+    }
+    let factor = (to_b as f64 - to_a as f64) / (from_b as f64 - from_a as f64);
+    if factor <= 0.0 {
+        return Err("Sync points must keep their order".to_string());
+    }
+    Ok(finish(map_times(cues, |ms| {
+        (to_a as f64 + (ms as f64 - from_a as f64) * factor)
+            .round()
+            .max(0.0) as u64
+    })))
+}
+
+/// Make every cue last at least `min_duration_ms` and keep `min_gap_ms` between cues.
+///
+/// Long cues are trimmed where they run into the next one. When a cue is too short and the
+/// next one starts too soon to extend it, the next cue is pushed back.
+pub fn enforce_durations(
+    cues: &[SubtitleCue],
+    min_duration_ms: u64,
+    min_gap_ms: u64,
+) -> Vec<SubtitleCue> {
+    let mut cues = sorted(cues);
+
+    for i in 0..cues.len() {
+        let start = cues[i].start_ms;
+        let min_end = start + min_duration_ms.max(1);
+        let mut end = cues[i].end_ms.max(min_end);
+
+        if let Some(next) = cues.get_mut(i + 1) {
+            let latest_end = next.start_ms.saturating_sub(min_gap_ms);
+            if latest_end >= min_end {
+                end = end.min(latest_end);
+            } else {
+                end = min_end;
+                let pushed_start = end + min_gap_ms;
+                let duration = next.end_ms.saturating_sub(next.start_ms);
+                set_times(next, pushed_start, pushed_start + duration);
+            }
+        }
+
+        set_times(&mut cues[i], start, end);
+    }
+
+    finish(cues)
+}
+
+/// Merge neighbouring cues while the result fits in `max_lines` lines of at most
+/// `max_chars_per_line` characters and they are at most `max_gap_ms` apart.
+/// The merged text keeps each cue on its own line.
+pub fn merge(
+    cues: &[SubtitleCue],
+    max_chars_per_line: usize,
+    max_lines: usize,
+    max_gap_ms: u64,
+) -> Result<Vec<SubtitleCue>, String> {
+    check_limits(max_chars_per_line, max_lines)?;
+    let mut merged: Vec<SubtitleCue> = Vec::new();
+
+    for cue in sorted(cues) {
+        if let Some(last) = merged.last_mut() {
+            let text = format!("{}\n{}", last.text, cue.text);
+            let plain = strip_markup(&text);
+            let fits = plain.lines().count() <= max_lines
+                && plain
+                    .lines()
+                    .all(|line| char_count(line) <= max_chars_per_line);
+            let close = cue.start_ms <= last.end_ms + max_gap_ms;
+
+            if fits && close && last.voice == cue.voice {
+                last.text = text;
+                last.end_ms = last.end_ms.max(cue.end_ms);
+                last.words.extend(cue.words);
+                continue;
+            }
+        }
+        merged.push(cue);
+    }
+
+    Ok(finish(merged))
+}
+
+/// Wrap lines longer than `max_chars_per_line` and split cues with more than `max_lines`
+/// lines into consecutive cues. Lines break at spaces, then after punctuation, then anywhere.
+/// Time is shared out by character count. Inline markup is removed from cues that are split.
+pub fn split(
+    cues: &[SubtitleCue],
+    max_chars_per_line: usize,
+    max_lines: usize,
+) -> Result<Vec<SubtitleCue>, String> {
+    check_limits(max_chars_per_line, max_lines)?;
+    let mut result = Vec::new();
+
+    for cue in sorted(cues) {
+        let plain = strip_markup(&cue.text);
+        let lines: Vec<String> = plain
+            .lines()
+            .flat_map(|line| wrap_line(line, max_chars_per_line))
+            .collect();
+        if lines.len() <= max_lines && plain.lines().count() == lines.len() {
+            result.push(cue);
+            continue;
+        }
+
+        let chunks: Vec<String> = lines.chunks(max_lines).map(|c| c.join("\n")).collect();
+        let weights: Vec<usize> = chunks.iter().map(|c| char_count(c).max(1)).collect();
+        let total: usize = weights.iter().sum();
+        let duration = cue.end_ms.saturating_sub(cue.start_ms);
+
+        let mut elapsed = 0;
+        for (chunk, weight) in chunks.into_iter().zip(weights) {
+            let start_ms = cue.start_ms + duration * elapsed as u64 / total as u64;
+            elapsed += weight;
+            let end_ms = cue.start_ms + duration * elapsed as u64 / total as u64;
+            let words = cue
+                .words
+                .iter()
+                .filter(|w| w.start_ms >= start_ms && (w.start_ms < end_ms || elapsed == total))
+                .cloned()
+                .collect();
+
+            result.push(SubtitleCue {
+                start_ms,
+                end_ms,
+                text: chunk,
+                words,
+                id: None,
+                ..cue.clone()
+            });
+        }
+    }
+
+    Ok(finish(result))
+}
+
+/// Snap cue and word times to the nearest frame boundary. Cues keep at least one frame.
+pub fn snap_to_frames(cues: &[SubtitleCue], fps: u32) -> Result<Vec<SubtitleCue>, String> {
+    if fps == 0 {
+        return Err("Frame rate must be greater than 0".to_string());
+    }
+    let fps = fps as f64;
+    let to_frame = |ms: u64| (ms as f64 * fps / 1000.0).round();
+    let to_ms = |frame: f64| (frame * 1000.0 / fps).round() as u64;
+
+    let mut cues = map_times(cues, |ms| to_ms(to_frame(ms)));
+    for cue in &mut cues {
+        if cue.end_ms <= cue.start_ms {
+            cue.end_ms = to_ms(to_frame(cue.start_ms) + 1.0);
+        }
+    }
+    Ok(finish(cues))
+}
+
+/// A cue moved by `snap_to_beats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CueShift {
+    /// Index of the cue in the returned list
+    pub index: usize,
+    pub start_delta_ms: i64,
+    pub end_delta_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeatSnapResult {
+    pub cues: Vec<SubtitleCue>,
+    /// Cues whose start or end changed
+    pub moved: Vec<CueShift>,
+}
+
+/// Snap every cue's start (and its end when `snap_end` is set) to the nearest beat within
+/// `threshold_ms`. Without `snap_end` a cue keeps its duration.
+///
+/// Cues never overlap afterwards: a start is only snapped when it stays after the previous
+/// cue's end, and an end is cut off where the next cue starts.
+pub fn snap_to_beats(
+    cues: &[SubtitleCue],
+    beats: &[BeatInfo],
+    threshold_ms: u64,
+    snap_end: bool,
+) -> BeatSnapResult {
+    let mut cues = finish(cues.to_vec());
+    let original: Vec<(u64, u64)> = cues.iter().map(|c| (c.start_ms, c.end_ms)).collect();
+    let mut previous_end = 0;
+
+    for (i, &(start, end)) in original.iter().enumerate() {
+        let snapped = snap_to_beat(beats, start, threshold_ms);
+        let new_start = if snapped >= previous_end {
+            snapped
+        } else {
+            start.max(previous_end)
+        };
+
+        let duration = end.saturating_sub(start).max(1);
+        let mut new_end = if snap_end {
+            snap_to_beat(beats, end, threshold_ms)
+        } else {
+            new_start + duration
+        };
+        if new_end <= new_start {
+            new_end = new_start + duration;
+        }
+        if let Some(&(next_start, _)) = original.get(i + 1) {
+            if new_end > next_start && next_start > new_start {
+                new_end = next_start;
+            }
+        }
+
+        set_times(&mut cues[i], new_start, new_end);
+        previous_end = new_end;
+    }
+
+    let moved = cues
+        .iter()
+        .zip(&original)
+        .filter(|(cue, &(start, end))| cue.start_ms != start || cue.end_ms != end)
+        .map(|(cue, &(start, end))| CueShift {
+            index: cue.index,
+            start_delta_ms: cue.start_ms as i64 - start as i64,
+            end_delta_ms: cue.end_ms as i64 - end as i64,
+        })
+        .collect();
+
+    BeatSnapResult { cues, moved }
+}
+
+/// Apply `f` to every cue and word time
+fn map_times(cues: &[SubtitleCue], f: impl Fn(u64) -> u64) -> Vec<SubtitleCue> {
+    cues.iter()
+        .map(|cue| {
+            let mut cue = cue.clone();
+            cue.start_ms = f(cue.start_ms);
+            cue.end_ms = f(cue.end_ms).max(cue.start_ms);
+            for word in &mut cue.words {
+                word.start_ms = f(word.start_ms);
+                word.end_ms = f(word.end_ms).max(word.start_ms);
+            }
+            cue
+        })
+        .collect()
+}
+
+/// Set a cue's times and keep its words inside them
+fn set_times(cue: &mut SubtitleCue, start_ms: u64, end_ms: u64) {
+    let shift = start_ms as i64 - cue.start_ms as i64;
+    cue.start_ms = start_ms;
+    cue.end_ms = end_ms;
+    for word in &mut cue.words {
+        word.start_ms = ((word.start_ms as i64 + shift).max(0) as u64).clamp(start_ms, end_ms);
+        word.end_ms = ((word.end_ms as i64 + shift).max(0) as u64).clamp(word.start_ms, end_ms);
+    }
+}
+
+fn sorted(cues: &[SubtitleCue]) -> Vec<SubtitleCue> {
+    let mut cues = cues.to_vec();
+    cues.sort_by_key(|cue| (cue.start_ms, cue.end_ms));
+    cues
+}
+
+/// Sort and renumber from 1, as every public operation returns its cues
+fn finish(cues: Vec<SubtitleCue>) -> Vec<SubtitleCue> {
+    let mut cues = sorted(&cues);
+    for (i, cue) in cues.iter_mut().enumerate() {
+        cue.index = i + 1;
+    }
+    cues
+}
+
+fn check_limits(max_chars_per_line: usize, max_lines: usize) -> Result<(), String> {
+    if max_chars_per_line == 0 || max_lines == 0 {
+        return Err("Line limits must be greater than 0".to_string());
+    }
+    Ok(())
+}
+
+/// Visible characters (grapheme clusters), as counted by the animation engine
+fn char_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Break a line into pieces of at most `max_chars` characters
+fn wrap_line(line: &str, max_chars: usize) -> Vec<String> {
+    let mut units: Vec<&str> = line.graphemes(true).collect();
+    let mut lines = Vec::new();
+
+    while units.len() > max_chars {
+        let window = &units[..=max_chars];
+        let (take, skip) =
+            if let Some(space) = window[1..].iter().rposition(|u| u.trim().is_empty()) {
+                (space + 1, 1)
+            } else if let Some(punct) = window[..max_chars]
+                .iter()
+                .rposition(|u| BREAK_AFTER.contains(u))
+            {
+                (punct + 1, 0)
+            } else {
+                (max_chars, 0)
+            };
+
+        lines.push(units[..take].concat().trim_end().to_string());
+        units.drain(..take + skip);
+    }
+    let rest = units.concat();
+    if !rest.trim().is_empty() || lines.is_empty() {
+        lines.push(rest);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::telop::WordTiming;
+
+    fn cue(start_ms: u64, end_ms: u64, text: &str) -> SubtitleCue {
+        SubtitleCue {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn times(cues: &[SubtitleCue]) -> Vec<(u64, u64)> {
+        cues.iter().map(|c| (c.start_ms, c.end_ms)).collect()
+    }
+
+    #[test]
+    fn test_offset_moves_words_and_drops_early_cues() {
+        let mut first = cue(1000, 2000, "a b");
+        first.words = vec![WordTiming {
+            text: "a".to_string(),
+            start_ms: 1000,
+            end_ms: 1500,
+        }];
+        let cues = vec![first, cue(3000, 4000, "c")];
+
+        let later = offset(&cues, 500);
+        assert_eq!(times(&later), [(1500, 2500), (3500, 4500)]);
+        assert_eq!(later[0].words[0].start_ms, 1500);
+
+        let earlier = offset(&cues, -2500);
+        assert_eq!(times(&earlier), [(500, 1500)]);
+        assert_eq!(earlier[0].index, 1);
+    }
+
+    #[test]
+    fn test_stretch_and_two_point_sync() {
+        let cues = vec![cue(1000, 2000, "a"), cue(10000, 11000, "b")];
+
+        let stretched = stretch(&cues, 25.0 / 24.0, 0).unwrap();
+        assert_eq!(times(&stretched), [(1042, 2083), (10417, 11458)]);
+        assert!(stretch(&cues, 0.0, 0).is_err());
+
+        // 1s late at the start, 2s late at the end: drift and offset in one go
+        let synced = two_point_sync(&cues, (1000, 2000), (10000, 12000)).unwrap();
+        assert_eq!(times(&synced), [(2000, 3111), (12000, 13111)]);
+        assert!(two_point_sync(&cues, (1000, 0), (1000, 5)).is_err());
+    }
+
+    #[test]
+    fn test_enforce_durations() {
+        let cues = vec![
+            cue(0, 300, "too short"),
+            cue(2000, 3500, "overlaps"),
+            cue(3000, 5000, "next"),
+            cue(5000, 5100, "pushed"),
+            cue(5100, 6000, "last"),
+        ];
+        let fixed = enforce_durations(&cues, 1000, 100);
+        assert_eq!(
+            times(&fixed),
+            [
+                (0, 1000),
+                (2000, 3000),
+                (3100, 4900),
+                (5000, 6000),
+                (6100, 7100)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_by_line_length() {
+        let cues = vec![
+            cue(0, 1000, "こんにちは"),
+            cue(1100, 2000, "世界"),
+            cue(2100, 3000, "三行目は入らない"),
+            cue(9000, 9500, "遠い"),
+        ];
+        let merged = merge(&cues, 10, 2, 500).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].text, "こんにちは\n世界");
+        assert_eq!((merged[0].start_ms, merged[0].end_ms), (0, 2000));
+        assert_eq!(merged[1].text, "三行目は入らない");
+        assert_eq!(merged[2].index, 3);
+    }
+
+    #[test]
+    fn test_split_long_cue() {
+        let cues = vec![
+            cue(0, 4000, "one two three four five six"),
+            cue(5000, 6000, "short"),
+        ];
+        let split_cues = split(&cues, 10, 1).unwrap();
+        let texts: Vec<&str> = split_cues.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["one two", "three four", "five six", "short"]);
+        assert_eq!(split_cues[0].start_ms, 0);
+        assert_eq!(split_cues[2].end_ms, 4000);
+        assert_eq!(split_cues[1].start_ms, split_cues[0].end_ms);
+        assert_eq!(split_cues[3].index, 4);
+
+        assert_eq!(
+            wrap_line("今日はいい天気、散歩に行こう", 8),
+            ["今日はいい天気、", "散歩に行こう"]
+        );
+        assert_eq!(
+            wrap_line("あいうえおかきく", 3),
+            ["あいう", "えおか", "きく"]
+        );
+    }
+
+    #[test]
+    fn test_snap_to_beats() {
+        let beats: Vec<BeatInfo> = [1000, 2000, 2600, 4000]
+            .into_iter()
+            .map(|time_ms| BeatInfo {
+                time_ms,
+                strength: 1.0,
+                ..Default::default()
+            })
+            .collect();
+        let cues = vec![
+            cue(950, 2010, "snaps, end cut at the next cue"),
+            cue(2050, 2500, "beat would overlap the previous cue"),
+            cue(2620, 3000, "snaps and keeps its length"),
+            cue(3200, 3900, "too far from a beat"),
+        ];
+
+        let result = snap_to_beats(&cues, &beats, 100, false);
+        assert_eq!(
+            times(&result.cues),
+            [(1000, 2050), (2050, 2500), (2600, 2980), (3200, 3900)]
+        );
+        assert_eq!(
+            result.moved,
+            [
+                CueShift {
+                    index: 1,
+                    start_delta_ms: 50,
+                    end_delta_ms: 40
+                },
+                CueShift {
+                    index: 3,
+                    start_delta_ms: -20,
+                    end_delta_ms: -20
+                },
+            ]
+        );
+
+        let result = snap_to_beats(&cues, &beats, 100, true);
+        assert_eq!(
+            times(&result.cues),
+            [(1000, 2000), (2000, 2600), (2600, 3000), (3200, 4000)]
+        );
+        assert_eq!(result.moved.len(), 4);
+    }
+
+    #[test]
+    fn test_snap_to_frames() {
+        let cues = vec![cue(1010, 1015, "a"), cue(2049, 3051, "b")];
+        let snapped = snap_to_frames(&cues, 30).unwrap();
+        assert_eq!(times(&snapped), [(1000, 1033), (2033, 3067)]);
+        assert!(snap_to_frames(&cues, 0).is_err());
+    }
+}
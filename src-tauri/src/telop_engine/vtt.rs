@@ -0,0 +1,606 @@
+use crate::models::telop::{
+    CuePlacement, SubtitleCue, SubtitleDiagnostic, SubtitleImport, WordTiming,
+};
+use crate::telop_engine::markup::{parse_markup, strip_markup};
+use crate::telop_engine::parser::read_subtitle_file;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The WebVTT default text color classes (`<c.yellow>`), which map to inline colors
+const COLOR_CLASSES: &[&str] = &[
+    "white", "lime", "cyan", "red", "yellow", "magenta", "blue", "black",
+];
+
+/// Parse a WebVTT timestamp ([HH:]MM:SS.mmm) to milliseconds
+fn parse_timestamp(ts: &str) -> Result<u64, String> {
+    let ts = ts.trim();
+    let parts: Vec<&str> = ts.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => ("0", *m, *s),
+        _ => return Err(format!("Invalid timestamp format: {ts}")),
+    };
+
+    let (seconds, millis) = seconds
+        .split_once('.')
+        .ok_or_else(|| format!("Invalid timestamp format: {ts}"))?;
+    if millis.len() != 3 {
+        return Err(format!("Invalid milliseconds: {millis}"));
+    }
+
+    let hours: u64 = hours
+        .parse()
+        .map_err(|_| format!("Invalid hours: {hours}"))?;
+    let minutes: u64 = minutes
+        .parse()
+        .map_err(|_| format!("Invalid minutes: {minutes}"))?;
+    let seconds: u64 = seconds
+        .parse()
+        .map_err(|_| format!("Invalid seconds: {seconds}"))?;
+    let millis: u64 = millis
+        .parse()
+        .map_err(|_| format!("Invalid milliseconds: {millis}"))?;
+
+    Ok(hours * 3_600_000 + minutes * 60_000 + seconds * 1_000 + millis)
+}
+
+/// Format milliseconds as a WebVTT timestamp (HH:MM:SS.mmm)
+fn format_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        ms % 1_000
+    )
+}
+
+/// Parse WebVTT content into cues.
+/// Cue settings become placement hints, `<v>` sets the voice and inline timestamps
+/// become word timings. `<b>`, `<i>` and `<u>` are kept as inline markup and the default
+/// color classes become inline colors. Unreadable inline timestamps are skipped and
+/// reported with their line; other classes are dropped with a warning.
+pub fn parse_vtt(content: &str) -> Result<SubtitleImport, String> {
+    let content = content.trim_start_matches('\u{FEFF}');
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let signature = content.lines().next().unwrap_or_default();
+    if signature != "WEBVTT"
+        && !signature.starts_with("WEBVTT ")
+        && !signature.starts_with("WEBVTT\t")
+    {
+        return Err("Not a WebVTT file: missing WEBVTT header".to_string());
+    }
+
+    // Blocks end at lines that are blank after trimming; hand-edited files often leave
+    // spaces or tabs on the separator lines. Lines keep their 1-based number.
+    let mut blocks: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut current = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push((i + 1, line));
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+
+    let mut cues = Vec::new();
+    let mut notes = CueTextNotes::default();
+    // The first block is the header
+    for lines in blocks.into_iter().skip(1) {
+        let Some(timing_pos) = lines.iter().position(|(_, l)| l.contains("-->")) else {
+            // NOTE, STYLE and REGION blocks carry no cues
+            continue;
+        };
+        if timing_pos > 1 {
+            return Err(format!("Invalid cue block: {}", lines[0].1));
+        }
+
+        let id = (timing_pos == 1).then(|| lines[0].1.trim().to_string());
+        let (start_ms, end_ms, placement) = parse_timing_line(lines[timing_pos].1)?;
+        let raw_text: Vec<&str> = lines[timing_pos + 1..].iter().map(|(_, l)| *l).collect();
+        let raw_text = raw_text.join("\n");
+        let first_line = lines[timing_pos].0 + 1;
+        let (text, voice, words) =
+            parse_cue_text(&raw_text, start_ms, end_ms, first_line, &mut notes);
+
+        cues.push(SubtitleCue {
+            index: cues.len() + 1,
+            start_ms,
+            end_ms,
+            text,
+            words,
+            id,
+            voice,
+            placement,
+            ..Default::default()
+        });
+    }
+
+    let warnings = notes
+        .classes
+        .into_iter()
+        .map(|(class, line)| {
+            format!("Cue class .{class} is not supported (first used on line {line})")
+        })
+        .collect();
+    Ok(SubtitleImport {
+        cues,
+        warnings,
+        diagnostics: notes.diagnostics,
+    })
+}
+
+/// Parse WebVTT from a file path
+pub fn parse_vtt_file(path: &str) -> Result<SubtitleImport, String> {
+    let (content, _) = read_subtitle_file(path)?;
+    parse_vtt(&content)
+}
+
+/// Parse "start --> end [settings]"
+fn parse_timing_line(line: &str) -> Result<(u64, u64, Option<CuePlacement>), String> {
+    let (start, rest) = line
+        .split_once("-->")
+        .ok_or_else(|| format!("Invalid timing line: {line}"))?;
+    let rest = rest.trim();
+    let (end, settings) = match rest.find(char::is_whitespace) {
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => (rest, ""),
+    };
+
+    let start_ms = parse_timestamp(start)?;
+    let end_ms = parse_timestamp(end)?;
+
+    let mut placement = CuePlacement::default();
+    for setting in settings.split_whitespace() {
+        let Some((key, value)) = setting.split_once(':') else {
+            continue;
+        };
+        // "line:90%,end" carries the line alignment; other suffixes are not used for placement
+        let (value, suffix) = value.split_once(',').unwrap_or((value, ""));
+        match key {
+            "line" => {
+                if !suffix.is_empty() {
+                    placement.line_align = Some(suffix.to_string());
+                }
+                if let Some(percent) = parse_percent(value) {
+                    placement.line = Some(percent);
+                } else {
+                    placement.line_number = value.parse().ok();
+                }
+            }
+            "position" => placement.position = parse_percent(value),
+            "size" => placement.size = parse_percent(value),
+            "align" => placement.align = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    let placement = (placement != CuePlacement::default()).then_some(placement);
+    Ok((start_ms, end_ms, placement))
+}
+
+/// Parse "12.5%" as 0.125
+fn parse_percent(value: &str) -> Option<f32> {
+    value
+        .strip_suffix('%')?
+        .parse::<f32>()
+        .ok()
+        .map(|p| p / 100.0)
+}
+
+/// Problems collected across the cue texts of a file
+#[derive(Default)]
+struct CueTextNotes {
+    diagnostics: Vec<SubtitleDiagnostic>,
+    /// Unsupported classes with the line they were first used on
+    classes: Vec<(String, usize)>,
+}
+
+/// Convert cue text to inline markup, extracting the voice and inline-timestamp word timings.
+/// `first_line` is the file line number of the text's first line, for diagnostics.
+fn parse_cue_text(
+    raw: &str,
+    start_ms: u64,
+    end_ms: u64,
+    first_line: usize,
+    notes: &mut CueTextNotes,
+) -> (String, Option<String>, Vec<WordTiming>) {
+    let mut text = String::new();
+    let mut voice = None;
+    let mut words = Vec::new();
+    let mut word_start = start_ms;
+    // Markup of the text since the last inline timestamp
+    let mut word_text = String::new();
+    let mut has_timestamps = false;
+    let mut in_ruby_text = false;
+    // Whether each open `<c>` span was given a color
+    let mut class_spans: Vec<bool> = Vec::new();
+    let mut rest = raw;
+
+    while let Some(lt) = rest.find('<') {
+        if !in_ruby_text {
+            text.push_str(&rest[..lt]);
+            word_text.push_str(&rest[..lt]);
+        }
+        let Some(gt) = rest[lt..].find('>') else {
+            rest = &rest[lt..];
+            break;
+        };
+        let tag = &rest[lt + 1..lt + gt];
+        let line = first_line + raw[..raw.len() - rest.len() + lt].matches('\n').count();
+        rest = &rest[lt + gt + 1..];
+
+        if tag.starts_with(|c: char| c.is_ascii_digit()) {
+            match parse_timestamp(tag) {
+                Ok(time_ms) => {
+                    push_word(&mut words, &word_text, word_start, time_ms);
+                    word_text.clear();
+                    word_start = time_ms;
+                    has_timestamps = true;
+                }
+                Err(e) => notes.diagnostics.push(SubtitleDiagnostic {
+                    line,
+                    message: format!("Skipped inline timestamp <{tag}>: {e}"),
+                }),
+            }
+            continue;
+        }
+
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/');
+        let (name, annotation) = match tag.find(char::is_whitespace) {
+            Some(pos) => (&tag[..pos], tag[pos..].trim()),
+            None => (tag, ""),
+        };
+        // Color classes ("c.yellow") become inline colors, other classes ("b.loud") are dropped
+        let mut classes = name.split('.');
+        let name = classes.next().unwrap_or_default();
+        let mut color = None;
+        for class in classes.filter(|class| !class.is_empty()) {
+            if color.is_none() && COLOR_CLASSES.contains(&class) {
+                color = Some(class);
+            } else if !notes.classes.iter().any(|(c, _)| c == class) {
+                notes.classes.push((class.to_string(), line));
+            }
+        }
+
+        match name {
+            "b" | "i" | "u" if !in_ruby_text => {
+                let markup = if closing {
+                    format!("</{name}>")
+                } else {
+                    format!("<{name}>")
+                };
+                text.push_str(&markup);
+                word_text.push_str(&markup);
+            }
+            "v" if !closing && voice.is_none() && !annotation.is_empty() => {
+                voice = Some(annotation.to_string());
+            }
+            "c" if !closing => {
+                if let Some(color) = color.filter(|_| !in_ruby_text) {
+                    let markup = format!("<font color=\"{color}\">");
+                    text.push_str(&markup);
+                    word_text.push_str(&markup);
+                    class_spans.push(true);
+                } else {
+                    class_spans.push(false);
+                }
+            }
+            "c" if class_spans.pop() == Some(true) => {
+                text.push_str("</font>");
+                word_text.push_str("</font>");
+            }
+            "rt" => in_ruby_text = !closing,
+            // v, lang, ruby and uncolored c: keep the content, drop the tag
+            _ => {}
+        }
+    }
+
+    if !in_ruby_text {
+        text.push_str(rest);
+        word_text.push_str(rest);
+    }
+    if has_timestamps {
+        push_word(&mut words, &word_text, word_start, end_ms);
+    }
+    for colored in class_spans.into_iter().rev() {
+        if colored {
+            text.push_str("</font>");
+        }
+    }
+
+    (text, voice, words)
+}
+
+fn push_word(words: &mut Vec<WordTiming>, markup: &str, start_ms: u64, end_ms: u64) {
+    let text = strip_markup(markup).trim().to_string();
+    if !text.is_empty() {
+        words.push(WordTiming {
+            text,
+            start_ms,
+            end_ms: end_ms.max(start_ms),
+        });
+    }
+}
+
+/// Serialize cues as a WebVTT document.
+/// Word timings are written as inline timestamps; inline colors and sizes have no
+/// WebVTT equivalent and are dropped.
+pub fn write_vtt(cues: &[SubtitleCue]) -> String {
+    let mut out = String::from("WEBVTT\n");
+
+    for cue in cues {
+        out.push('\n');
+        // An identifier can't span lines or contain the timing arrow
+        if let Some(ref id) = cue.id {
+            out.push_str(&id.replace('\n', " ").replace("-->", "->"));
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "{} --> {}",
+            format_timestamp(cue.start_ms),
+            format_timestamp(cue.end_ms)
+        ));
+        if let Some(ref placement) = cue.placement {
+            out.push_str(&format_settings(placement));
+        }
+        out.push('\n');
+        if let Some(ref voice) = cue.voice {
+            out.push_str(&format!("<v {voice}>"));
+        }
+        out.push_str(&format_cue_text(cue));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn format_settings(placement: &CuePlacement) -> String {
+    let percent = |f: f32| format!("{}%", (f * 1000.0).round() / 10.0);
+    let mut settings = String::new();
+
+    let line = match (placement.line, placement.line_number) {
+        (Some(line), _) => Some(percent(line)),
+        (None, Some(line_number)) => Some(line_number.to_string()),
+        (None, None) => None,
+    };
+    if let Some(line) = line {
+        match placement.line_align {
+            Some(ref line_align) => settings.push_str(&format!(" line:{line},{line_align}")),
+            None => settings.push_str(&format!(" line:{line}")),
+        }
+    }
+    if let Some(position) = placement.position {
+        settings.push_str(&format!(" position:{}", percent(position)));
+    }
+    if let Some(size) = placement.size {
+        settings.push_str(&format!(" size:{}", percent(size)));
+    }
+    if let Some(ref align) = placement.align {
+        settings.push_str(&format!(" align:{align}"));
+    }
+    settings
+}
+
+/// Cue payload with escaped text, b/i/u tags and inline timestamps.
+/// Blank lines are dropped, since they would end the cue early.
+fn format_cue_text(cue: &SubtitleCue) -> String {
+    let styled = parse_markup(&cue.text);
+    let units: Vec<&str> = styled.text.graphemes(true).collect();
+    let timestamps = word_start_units(&units, &cue.words);
+
+    let mut out = String::new();
+    let mut open_tags: Vec<&str> = Vec::new();
+
+    for (i, unit) in units.iter().enumerate() {
+        let tags: Vec<&str> = styled.style_at(i).map_or_else(Vec::new, |style| {
+            [
+                (style.bold, "b"),
+                (style.italic, "i"),
+                (style.underline, "u"),
+            ]
+            .into_iter()
+            .filter_map(|(on, tag)| on.then_some(tag))
+            .collect()
+        });
+        let changed = tags != open_tags;
+        if changed {
+            for tag in open_tags.iter().rev() {
+                out.push_str(&format!("</{tag}>"));
+            }
+        }
+        if let Some((_, start_ms)) = timestamps.iter().find(|(unit, _)| *unit == i) {
+            out.push_str(&format!("<{}>", format_timestamp(*start_ms)));
+        }
+        if changed {
+            for tag in &tags {
+                out.push_str(&format!("<{tag}>"));
+            }
+            open_tags = tags;
+        }
+
+        for c in unit.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                _ => out.push(c),
+            }
+        }
+    }
+    for tag in open_tags.iter().rev() {
+        out.push_str(&format!("</{tag}>"));
+    }
+
+    let lines: Vec<&str> = out.lines().filter(|line| !line.trim().is_empty()).collect();
+    lines.join("\n")
+}
+
+/// Unit index and start time of every word after the first.
+/// Empty if the words cannot be located in order in the text.
+fn word_start_units(units: &[&str], words: &[WordTiming]) -> Vec<(usize, u64)> {
+    let mut result = Vec::new();
+    let mut cursor = 0;
+
+    for (n, word) in words.iter().enumerate() {
+        let word_units: Vec<&str> = word.text.trim().graphemes(true).collect();
+        if word_units.is_empty() || word_units.len() > units.len() {
+            return Vec::new();
+        }
+        let Some(pos) = (cursor..=units.len() - word_units.len())
+            .find(|&i| units[i..i + word_units.len()] == word_units[..])
+        else {
+            return Vec::new();
+        };
+        if n > 0 {
+            result.push((pos, word.start_ms));
+        }
+        cursor = pos + word_units.len();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:01.500").unwrap(), 1500);
+        assert_eq!(parse_timestamp("01:02:03.004").unwrap(), 3_723_004);
+        assert!(parse_timestamp("00:01,500").is_err());
+        assert!(parse_timestamp("1.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_vtt_basic() {
+        let vtt = "\u{FEFF}WEBVTT - sample\nKind: captions\n\nNOTE a comment\n\nSTYLE\n::cue { color: yellow }\n\nintro\n00:01.000 --> 00:04.000 line:90% align:center position:50%,center\n<v 田中>こんにちは<c.yellow>世界</c>\n\n00:00:05.000 --> 00:00:08.500 line:-1\n<b>ショート</b>&amp;動画\n";
+        let cues = parse_vtt(vtt).unwrap().cues;
+        assert_eq!(cues.len(), 2);
+
+        assert_eq!(cues[0].id.as_deref(), Some("intro"));
+        assert_eq!((cues[0].start_ms, cues[0].end_ms), (1000, 4000));
+        assert_eq!(cues[0].text, "こんにちは<font color=\"yellow\">世界</font>");
+        assert_eq!(cues[0].voice.as_deref(), Some("田中"));
+        let placement = cues[0].placement.as_ref().unwrap();
+        assert_eq!(placement.line, Some(0.9));
+        assert_eq!(placement.position, Some(0.5));
+        assert_eq!(placement.align.as_deref(), Some("center"));
+
+        assert_eq!(cues[1].index, 2);
+        assert_eq!(cues[1].text, "<b>ショート</b>&amp;動画");
+        assert_eq!(cues[1].placement.as_ref().unwrap().line_number, Some(-1));
+        assert!(cues[1].placement.as_ref().unwrap().line_align.is_none());
+    }
+
+    #[test]
+    fn test_parse_vtt_inline_timestamps() {
+        let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nhello<00:00:01.500><c> big</c><00:00:02.200><c> world</c>\n";
+        let cues = parse_vtt(vtt).unwrap().cues;
+        assert_eq!(cues[0].text, "hello big world");
+
+        let words: Vec<(&str, u64, u64)> = cues[0]
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start_ms, w.end_ms))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("hello", 1000, 1500),
+                ("big", 1500, 2200),
+                ("world", 2200, 3000)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt_ruby_text_is_dropped() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:02.000\n<ruby>漢字<rt>かんじ</rt></ruby>です\n";
+        assert_eq!(parse_vtt(vtt).unwrap().cues[0].text, "漢字です");
+    }
+
+    #[test]
+    fn test_parse_vtt_whitespace_separator_lines() {
+        let vtt = "WEBVTT\n \t\n00:01.000 --> 00:02.000\nfirst\n  \n00:03.000 --> 00:04.000\nsecond\n\t\n";
+        let cues = parse_vtt(vtt).unwrap().cues;
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "first");
+        assert_eq!((cues[1].start_ms, cues[1].text.as_str()), (3000, "second"));
+    }
+
+    #[test]
+    fn test_parse_vtt_missing_header() {
+        assert!(parse_vtt("1\n00:00:01,000 --> 00:00:02,000\nHello\n").is_err());
+    }
+
+    #[test]
+    fn test_write_vtt_round_trip() {
+        let vtt = "WEBVTT\n\nintro\n00:00:01.000 --> 00:00:03.000 line:90%,end align:center\n<v Ken>hello<00:00:01.500> <b>big</b><00:00:02.200> a&amp;b\n\n00:00:04.000 --> 00:00:05.000\nSecond\n";
+        let cues = parse_vtt(vtt).unwrap().cues;
+        let written = write_vtt(&cues);
+        assert_eq!(
+            written,
+            "WEBVTT\n\nintro\n00:00:01.000 --> 00:00:03.000 line:90%,end align:center\n<v Ken>hello <00:00:01.500><b>big</b> <00:00:02.200>a&amp;b\n\n00:00:04.000 --> 00:00:05.000\nSecond\n"
+        );
+        assert_eq!(parse_vtt(&written).unwrap().cues, cues);
+    }
+
+    #[test]
+    fn test_parse_vtt_bad_inline_timestamp_is_skipped() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:03.000\nhello\nbig<00:00:02.5> world<00:02.000>!\n";
+        let import = parse_vtt(vtt).unwrap();
+        assert_eq!(import.cues[0].text, "hello\nbig world!");
+        let words: Vec<&str> = import.cues[0]
+            .words
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect();
+        assert_eq!(words, vec!["hello\nbig world", "!"]);
+        assert_eq!(import.diagnostics.len(), 1);
+        assert_eq!(import.diagnostics[0].line, 5);
+        assert!(import.diagnostics[0].message.contains("00:00:02.5"));
+    }
+
+    #[test]
+    fn test_parse_vtt_classes() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:02.000\n<c.loud.red>a</c><c.loud>b</c>\n\n00:03.000 --> 00:04.000\n<c.bg_blue>c\n";
+        let import = parse_vtt(vtt).unwrap();
+        assert_eq!(import.cues[0].text, "<font color=\"red\">a</font>b");
+        assert_eq!(import.cues[1].text, "c");
+        assert_eq!(
+            import.warnings,
+            vec![
+                "Cue class .loud is not supported (first used on line 4)",
+                "Cue class .bg_blue is not supported (first used on line 7)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_vtt_drops_blank_lines() {
+        let cue = SubtitleCue {
+            index: 1,
+            start_ms: 0,
+            end_ms: 1000,
+            text: "first\n\n  \nsecond --> third".to_string(),
+            id: Some("a --> b\nc".to_string()),
+            ..Default::default()
+        };
+        let written = write_vtt(&[cue]);
+        assert_eq!(
+            written,
+            "WEBVTT\n\na -> b c\n00:00:00.000 --> 00:00:01.000\nfirst\nsecond --&gt; third\n"
+        );
+        let cues = parse_vtt(&written).unwrap().cues;
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].text, "first\nsecond --&gt; third");
+    }
+}
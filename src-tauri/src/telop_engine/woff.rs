@@ -0,0 +1,854 @@
+use std::io::Read;
+
+const WOFF_SIGNATURE: u32 = 0x774F_4646; // "wOFF"
+const WOFF2_SIGNATURE: u32 = 0x774F_4632; // "wOF2"
+const TTC_FLAVOR: u32 = 0x7474_6366; // "ttcf"
+
+const TAG_GLYF: u32 = 0x676C_7966;
+const TAG_LOCA: u32 = 0x6C6F_6361;
+const TAG_HMTX: u32 = 0x686D_7478;
+const TAG_HHEA: u32 = 0x6868_6561;
+const TAG_HEAD: u32 = 0x6865_6164;
+
+/// Tags of the WOFF2 known-table index, in spec order
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// Upper bound for a decompressed font, so a corrupt header can't ask for gigabytes
+const MAX_SFNT_SIZE: usize = 256 * 1024 * 1024;
+
+/// Unwrap a WOFF or WOFF2 file into the plain TrueType/OpenType data the font manager loads.
+/// Anything else is returned unchanged.
+pub fn to_sfnt(data: &[u8]) -> Result<Vec<u8>, String> {
+    match Reader::new(data).u32() {
+        Ok(WOFF_SIGNATURE) => decode_woff(data),
+        Ok(WOFF2_SIGNATURE) => decode_woff2(data),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+fn decode_woff(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut header = Reader::new(data);
+    header.skip(4)?;
+    let flavor = header.u32()?;
+    header.skip(4)?; // length
+    let num_tables = header.u16()?;
+    header.skip(2 + 4 + 2 + 2 + 4 * 5)?;
+
+    let mut tables = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let tag = header.u32()?;
+        let offset = header.u32()? as usize;
+        let comp_length = header.u32()? as usize;
+        let orig_length = header.u32()? as usize;
+        header.skip(4)?; // checksum, recomputed when the font is rebuilt
+
+        let end = offset
+            .checked_add(comp_length)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| format!("WOFF table {} is out of bounds", tag_name(tag)))?;
+        let stored = &data[offset..end];
+        let table = if comp_length < orig_length {
+            inflate(stored, orig_length)?
+        } else if comp_length == orig_length {
+            stored.to_vec()
+        } else {
+            return Err(format!(
+                "WOFF table {} is larger than its original",
+                tag_name(tag)
+            ));
+        };
+        tables.push((tag, table));
+    }
+
+    Ok(build_sfnt(flavor, tables))
+}
+
+fn inflate(stored: &[u8], orig_length: usize) -> Result<Vec<u8>, String> {
+    if orig_length > MAX_SFNT_SIZE {
+        return Err("WOFF table is too large".to_string());
+    }
+    let mut table = Vec::with_capacity(orig_length);
+    flate2::read::ZlibDecoder::new(stored)
+        .take(orig_length as u64)
+        .read_to_end(&mut table)
+        .map_err(|e| format!("Failed to decompress WOFF table: {e}"))?;
+    if table.len() != orig_length {
+        return Err("WOFF table is shorter than its declared length".to_string());
+    }
+    Ok(table)
+}
+
+struct Woff2Entry {
+    tag: u32,
+    orig_length: usize,
+    /// Length in the decompressed stream (the transformed length for transformed tables)
+    stream_length: usize,
+    transformed: bool,
+}
+
+fn decode_woff2(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(data);
+    reader.skip(4)?;
+    let flavor = reader.u32()?;
+    reader.skip(4)?; // length
+    let num_tables = reader.u16()?;
+    reader.skip(2 + 4)?; // reserved, totalSfntSize
+    let compressed_size = reader.u32()? as usize;
+    reader.skip(2 + 2 + 4 * 5)?;
+    if flavor == TTC_FLAVOR {
+        return Err("WOFF2 font collections are not supported".to_string());
+    }
+
+    let mut entries = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let flags = reader.u8()?;
+        let tag = match flags & 0x3F {
+            63 => reader.u32()?,
+            index => u32::from_be_bytes(*WOFF2_KNOWN_TAGS[index as usize]),
+        };
+        let version = flags >> 6;
+        // glyf and loca use version 3 for "not transformed", every other table uses 0
+        let transformed = if tag == TAG_GLYF || tag == TAG_LOCA {
+            version != 3
+        } else {
+            version != 0
+        };
+        let orig_length = reader.base128()? as usize;
+        let stream_length = if transformed {
+            reader.base128()? as usize
+        } else {
+            orig_length
+        };
+        entries.push(Woff2Entry {
+            tag,
+            orig_length,
+            stream_length,
+            transformed,
+        });
+    }
+
+    let stream_size = entries
+        .iter()
+        .try_fold(0usize, |sum, entry| sum.checked_add(entry.stream_length))
+        .filter(|size| *size <= MAX_SFNT_SIZE)
+        .ok_or("WOFF2 tables are too large")?;
+    let compressed = reader.bytes(compressed_size)?;
+    let mut stream = Vec::with_capacity(stream_size);
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .take(stream_size as u64)
+        .read_to_end(&mut stream)
+        .map_err(|e| format!("Failed to decompress WOFF2 data: {e}"))?;
+    if stream.len() != stream_size {
+        return Err("WOFF2 data is shorter than its table directory".to_string());
+    }
+
+    let mut offset = 0;
+    let mut raw = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        raw.push(&stream[offset..offset + entry.stream_length]);
+        offset += entry.stream_length;
+    }
+    let find = |tag: u32| entries.iter().position(|e| e.tag == tag);
+
+    let mut tables: Vec<(u32, Vec<u8>)> = Vec::with_capacity(entries.len());
+    let mut x_mins = Vec::new();
+    if let Some(glyf) = find(TAG_GLYF).filter(|i| entries[*i].transformed) {
+        let glyphs = reconstruct_glyf(raw[glyf])?;
+        x_mins = glyphs.x_mins;
+        tables.push((TAG_GLYF, glyphs.glyf));
+        tables.push((TAG_LOCA, glyphs.loca));
+    }
+
+    for (entry, data) in entries.iter().zip(&raw) {
+        if tables.iter().any(|(tag, _)| *tag == entry.tag) {
+            continue;
+        }
+        let table = match entry.tag {
+            TAG_LOCA if entry.transformed => {
+                return Err("WOFF2 loca is transformed without glyf".to_string())
+            }
+            TAG_HMTX if entry.transformed => {
+                let hhea = find(TAG_HHEA)
+                    .map(|i| raw[i])
+                    .ok_or("WOFF2 hmtx needs an hhea table")?;
+                let num_h_metrics = Reader::at(hhea, 34)?.u16()?;
+                reconstruct_hmtx(data, num_h_metrics as usize, &x_mins)?
+            }
+            _ if entry.transformed => {
+                return Err(format!(
+                    "Unknown WOFF2 transform of {}",
+                    tag_name(entry.tag)
+                ))
+            }
+            _ => data.to_vec(),
+        };
+        if table.len() != entry.orig_length && entry.tag != TAG_GLYF && entry.tag != TAG_LOCA {
+            return Err(format!(
+                "WOFF2 table {} has the wrong length",
+                tag_name(entry.tag)
+            ));
+        }
+        tables.push((entry.tag, table));
+    }
+
+    Ok(build_sfnt(flavor, tables))
+}
+
+struct Glyphs {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    x_mins: Vec<i16>,
+}
+
+/// Rebuild the glyf and loca tables from the WOFF2 transformed glyf stream
+fn reconstruct_glyf(data: &[u8]) -> Result<Glyphs, String> {
+    let mut header = Reader::new(data);
+    header.skip(2)?; // reserved
+    let option_flags = header.u16()?;
+    let num_glyphs = header.u16()? as usize;
+    let index_format = header.u16()?;
+    let mut sizes = [0usize; 7];
+    for size in &mut sizes {
+        *size = header.u32()? as usize;
+    }
+
+    let mut streams = Vec::with_capacity(8);
+    for size in sizes {
+        streams.push(Reader::new(header.bytes(size)?));
+    }
+    let overlap_bitmap = if option_flags & 1 != 0 {
+        header.bytes(num_glyphs.div_ceil(8))?
+    } else {
+        &[]
+    };
+    let [mut n_contours, mut n_points, mut flags, mut glyph_stream, mut composite, mut bbox, mut instructions]: [Reader; 7] =
+        streams.try_into().map_err(|_| "Invalid WOFF2 glyf header")?;
+    let bbox_bitmap = bbox.bytes(4 * num_glyphs.div_ceil(32))?;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+    for glyph in 0..num_glyphs {
+        offsets.push(glyf.len());
+        let contours = n_contours.i16()?;
+        let has_bbox = bbox_bitmap[glyph >> 3] & (0x80 >> (glyph & 7)) != 0;
+        let explicit_bbox = if has_bbox {
+            Some([bbox.i16()?, bbox.i16()?, bbox.i16()?, bbox.i16()?])
+        } else {
+            None
+        };
+
+        if contours == 0 {
+            if has_bbox {
+                return Err("WOFF2 empty glyph has a bounding box".to_string());
+            }
+            x_mins.push(0);
+            continue;
+        }
+
+        if contours < 0 {
+            let bounds = explicit_bbox.ok_or("WOFF2 composite glyph has no bounding box")?;
+            let (components, has_instructions) = read_composite(&mut composite)?;
+            write_i16(&mut glyf, -1);
+            for value in bounds {
+                write_i16(&mut glyf, value);
+            }
+            glyf.extend_from_slice(components);
+            if has_instructions {
+                let length = glyph_stream.u255()?;
+                write_u16(&mut glyf, length);
+                glyf.extend_from_slice(instructions.bytes(length as usize)?);
+            }
+            x_mins.push(bounds[0]);
+        } else {
+            let mut end_points = Vec::with_capacity(contours as usize);
+            let mut total = 0usize;
+            for _ in 0..contours {
+                total += n_points.u255()? as usize;
+                end_points.push(total - 1);
+            }
+            if total > u16::MAX as usize + 1 {
+                return Err("WOFF2 glyph has too many points".to_string());
+            }
+
+            let mut points = Vec::with_capacity(total);
+            let (mut x, mut y) = (0i32, 0i32);
+            for _ in 0..total {
+                let flag = flags.u8()?;
+                let (dx, dy) = decode_triplet(flag & 0x7F, &mut glyph_stream)?;
+                x += dx;
+                y += dy;
+                points.push((x, y, flag & 0x80 == 0));
+            }
+            let instruction_length = glyph_stream.u255()?;
+
+            let bounds = match explicit_bbox {
+                Some(bounds) => bounds,
+                None => point_bounds(&points),
+            };
+            let overlap = overlap_bitmap
+                .get(glyph >> 3)
+                .is_some_and(|byte| byte & (0x80 >> (glyph & 7)) != 0);
+
+            write_i16(&mut glyf, contours);
+            for value in bounds {
+                write_i16(&mut glyf, value);
+            }
+            for end in end_points {
+                write_u16(&mut glyf, end as u16);
+            }
+            write_u16(&mut glyf, instruction_length);
+            glyf.extend_from_slice(instructions.bytes(instruction_length as usize)?);
+            write_points(&mut glyf, &points, overlap);
+            x_mins.push(bounds[0]);
+        }
+
+        while glyf.len() % 4 != 0 {
+            glyf.push(0);
+        }
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::new();
+    for offset in offsets {
+        if index_format == 0 {
+            let half =
+                u16::try_from(offset / 2).map_err(|_| "WOFF2 glyf is too large for short loca")?;
+            write_u16(&mut loca, half);
+        } else {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+
+    Ok(Glyphs { glyf, loca, x_mins })
+}
+
+/// Decode one point delta of the WOFF2 triplet encoding
+fn decode_triplet(flag: u8, stream: &mut Reader) -> Result<(i32, i32), String> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_i = flag as i32;
+    Ok(match flag {
+        0..=9 => {
+            let b0 = stream.u8()? as i32;
+            (0, with_sign(flag, ((flag_i & 14) << 7) + b0))
+        }
+        10..=19 => {
+            let b0 = stream.u8()? as i32;
+            (with_sign(flag, (((flag_i - 10) & 14) << 7) + b0), 0)
+        }
+        20..=83 => {
+            let b0 = flag_i - 20;
+            let b1 = stream.u8()? as i32;
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (b1 >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+            )
+        }
+        84..=119 => {
+            let b0 = flag_i - 84;
+            let (b1, b2) = (stream.u8()? as i32, stream.u8()? as i32);
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + b1),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + b2),
+            )
+        }
+        120..=123 => {
+            let (b1, b2, b3) = (
+                stream.u8()? as i32,
+                stream.u8()? as i32,
+                stream.u8()? as i32,
+            );
+            (
+                with_sign(flag, (b1 << 4) + (b2 >> 4)),
+                with_sign(flag >> 1, ((b2 & 0x0F) << 8) + b3),
+            )
+        }
+        _ => {
+            let dx = stream.u16()? as i32;
+            let dy = stream.u16()? as i32;
+            (with_sign(flag, dx), with_sign(flag >> 1, dy))
+        }
+    })
+}
+
+/// Copy one composite glyph's component records; returns them and whether instructions follow
+fn read_composite<'a>(stream: &mut Reader<'a>) -> Result<(&'a [u8], bool), String> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+    const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+    let start = stream.pos;
+    let mut has_instructions = false;
+    loop {
+        let flags = stream.u16()?;
+        has_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+        let mut size = 2; // glyph index
+        size += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            size += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            size += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            size += 8;
+        }
+        stream.skip(size)?;
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    Ok((&stream.data[start..stream.pos], has_instructions))
+}
+
+fn point_bounds(points: &[(i32, i32, bool)]) -> [i16; 4] {
+    let Some(&(x, y, _)) = points.first() else {
+        return [0; 4];
+    };
+    let mut bounds = [x, y, x, y];
+    for &(x, y, _) in points {
+        bounds[0] = bounds[0].min(x);
+        bounds[1] = bounds[1].min(y);
+        bounds[2] = bounds[2].max(x);
+        bounds[3] = bounds[3].max(y);
+    }
+    bounds.map(|value| value as i16)
+}
+
+/// Write flags and coordinates of a simple glyph in the plain TrueType encoding
+fn write_points(out: &mut Vec<u8>, points: &[(i32, i32, bool)], overlap: bool) {
+    const ON_CURVE: u8 = 0x01;
+    const X_SHORT: u8 = 0x02;
+    const Y_SHORT: u8 = 0x04;
+    const X_SAME_OR_POSITIVE: u8 = 0x10;
+    const Y_SAME_OR_POSITIVE: u8 = 0x20;
+    const OVERLAP_SIMPLE: u8 = 0x40;
+
+    let mut flags = Vec::with_capacity(points.len());
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut last_x, mut last_y) = (0, 0);
+    for (i, &(x, y, on_curve)) in points.iter().enumerate() {
+        let mut flag = if on_curve { ON_CURVE } else { 0 };
+        if i == 0 && overlap {
+            flag |= OVERLAP_SIMPLE;
+        }
+        for (delta, short, same, coords) in [
+            (x - last_x, X_SHORT, X_SAME_OR_POSITIVE, &mut xs),
+            (y - last_y, Y_SHORT, Y_SAME_OR_POSITIVE, &mut ys),
+        ] {
+            if delta == 0 {
+                flag |= same;
+            } else if delta.abs() < 256 {
+                flag |= short;
+                if delta > 0 {
+                    flag |= same;
+                }
+                coords.push(delta.unsigned_abs() as u8);
+            } else {
+                coords.extend_from_slice(&(delta as i16).to_be_bytes());
+            }
+        }
+        flags.push(flag);
+        (last_x, last_y) = (x, y);
+    }
+
+    out.extend_from_slice(&flags);
+    out.extend_from_slice(&xs);
+    out.extend_from_slice(&ys);
+}
+
+/// Rebuild hmtx from the WOFF2 transform, taking missing side bearings from glyph bounds
+fn reconstruct_hmtx(data: &[u8], num_h_metrics: usize, x_mins: &[i16]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(data);
+    let flags = reader.u8()?;
+    let num_glyphs = x_mins.len();
+    if num_h_metrics == 0 || num_h_metrics > num_glyphs {
+        return Err("WOFF2 hmtx doesn't match the glyph count".to_string());
+    }
+
+    let mut advances = Vec::with_capacity(num_h_metrics);
+    for _ in 0..num_h_metrics {
+        advances.push(reader.u16()?);
+    }
+    let mut bearings = Vec::with_capacity(num_glyphs);
+    for (glyph, &x_min) in x_mins.iter().enumerate() {
+        let stored = if glyph < num_h_metrics {
+            flags & 1 == 0
+        } else {
+            flags & 2 == 0
+        };
+        bearings.push(if stored { reader.i16()? } else { x_min });
+    }
+
+    let mut hmtx = Vec::with_capacity(num_h_metrics * 4 + (num_glyphs - num_h_metrics) * 2);
+    for (glyph, bearing) in bearings.into_iter().enumerate() {
+        if let Some(advance) = advances.get(glyph) {
+            write_u16(&mut hmtx, *advance);
+        }
+        write_i16(&mut hmtx, bearing);
+    }
+    Ok(hmtx)
+}
+
+/// Lay tables out as an SFNT file with a sorted directory and fresh checksums
+fn build_sfnt(flavor: u32, mut tables: Vec<(u32, Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let num_tables = tables.len() as u16;
+    let entry_selector = if num_tables == 0 {
+        0
+    } else {
+        15 - num_tables.leading_zeros() as u16
+    };
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&flavor.to_be_bytes());
+    write_u16(&mut font, num_tables);
+    write_u16(&mut font, search_range);
+    write_u16(&mut font, entry_selector);
+    write_u16(&mut font, (num_tables * 16).saturating_sub(search_range));
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, table) in &mut tables {
+        if *tag == TAG_HEAD && table.len() >= 12 {
+            table[8..12].fill(0); // checkSumAdjustment, set below
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(&tag.to_be_bytes());
+        font.extend_from_slice(&checksum(table).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        font.extend_from_slice(table);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn tag_name(tag: u32) -> String {
+    String::from_utf8_lossy(&tag.to_be_bytes()).into_owned()
+}
+
+fn write_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_i16(out: &mut Vec<u8>, value: i16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Big-endian cursor over font data
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn at(data: &'a [u8], pos: usize) -> Result<Self, String> {
+        let mut reader = Self::new(data);
+        reader.skip(pos)?;
+        Ok(reader)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Unexpected end of font data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|v| v as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// WOFF2 `UIntBase128`: up to five bytes of seven bits each
+    fn base128(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for i in 0..5 {
+            let byte = self.u8()?;
+            if i == 0 && byte == 0x80 {
+                return Err("Invalid WOFF2 number".to_string());
+            }
+            if value & 0xFE00_0000 != 0 {
+                return Err("WOFF2 number overflows".to_string());
+            }
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid WOFF2 number".to_string())
+    }
+
+    /// WOFF2 `255UInt16`
+    fn u255(&mut self) -> Result<u16, String> {
+        Ok(match self.u8()? {
+            253 => self.u16()?,
+            254 => self.u8()? as u16 + 253 * 2,
+            255 => self.u8()? as u16 + 253,
+            code => code as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Minimal font tables: a head table and one arbitrary table
+    fn sample_tables() -> Vec<(u32, Vec<u8>)> {
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes()); // magic number
+        let name = b"ShortForge test font, repeated for compression ".repeat(4);
+        vec![(TAG_HEAD, head), (u32::from_be_bytes(*b"name"), name)]
+    }
+
+    fn read_tables(sfnt: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        let mut reader = Reader::new(sfnt);
+        reader.skip(4).unwrap();
+        let count = reader.u16().unwrap();
+        reader.skip(6).unwrap();
+        (0..count)
+            .map(|_| {
+                let tag = reader.u32().unwrap();
+                reader.skip(4).unwrap();
+                let offset = reader.u32().unwrap() as usize;
+                let length = reader.u32().unwrap() as usize;
+                let mut table = sfnt[offset..offset + length].to_vec();
+                if tag == TAG_HEAD {
+                    table[8..12].fill(0);
+                }
+                (tag, table)
+            })
+            .collect()
+    }
+
+    fn encode_woff(tables: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut directory = Vec::new();
+        let mut body = Vec::new();
+        let data_start = 44 + 20 * tables.len();
+        for (tag, table) in tables {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(table).unwrap();
+            let compressed = encoder.finish().unwrap();
+            directory.extend_from_slice(&tag.to_be_bytes());
+            directory.extend_from_slice(&((data_start + body.len()) as u32).to_be_bytes());
+            directory.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&checksum(table).to_be_bytes());
+            body.extend_from_slice(&compressed);
+            body.resize(body.len().next_multiple_of(4), 0);
+        }
+
+        let mut woff = Vec::new();
+        woff.extend_from_slice(&WOFF_SIGNATURE.to_be_bytes());
+        woff.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff.extend_from_slice(&((data_start + body.len()) as u32).to_be_bytes());
+        write_u16(&mut woff, tables.len() as u16);
+        woff.resize(44, 0);
+        woff.extend_from_slice(&directory);
+        woff.extend_from_slice(&body);
+        woff
+    }
+
+    /// Encode a WOFF2 file; entries are (tag, transform version, stored table data, original length)
+    fn encode_woff2(entries: &[(u32, u8, Vec<u8>, usize)]) -> Vec<u8> {
+        let mut directory = Vec::new();
+        let mut stream = Vec::new();
+        for (tag, version, data, orig_length) in entries {
+            let known = WOFF2_KNOWN_TAGS
+                .iter()
+                .position(|t| u32::from_be_bytes(**t) == *tag)
+                .unwrap();
+            directory.push(known as u8 | (version << 6));
+            let transformed = if *tag == TAG_GLYF || *tag == TAG_LOCA {
+                *version != 3
+            } else {
+                *version != 0
+            };
+            for length in [*orig_length]
+                .into_iter()
+                .chain(transformed.then_some(data.len()))
+            {
+                let mut groups = vec![(length & 0x7F) as u8];
+                let mut rest = length >> 7;
+                while rest > 0 {
+                    groups.insert(0, (rest & 0x7F) as u8 | 0x80);
+                    rest >>= 7;
+                }
+                directory.extend_from_slice(&groups);
+            }
+            stream.extend_from_slice(data);
+        }
+
+        let mut compressed = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+            writer.write_all(&stream).unwrap();
+        }
+
+        let mut woff2 = Vec::new();
+        woff2.extend_from_slice(&WOFF2_SIGNATURE.to_be_bytes());
+        woff2.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff2.extend_from_slice(&0u32.to_be_bytes());
+        write_u16(&mut woff2, entries.len() as u16);
+        write_u16(&mut woff2, 0);
+        woff2.extend_from_slice(&0u32.to_be_bytes());
+        woff2.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        woff2.resize(48, 0);
+        woff2.extend_from_slice(&directory);
+        woff2.extend_from_slice(&compressed);
+        woff2
+    }
+
+    #[test]
+    fn test_plain_fonts_pass_through() {
+        let data = build_sfnt(0x0001_0000, sample_tables());
+        assert_eq!(to_sfnt(&data).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_woff() {
+        let tables = sample_tables();
+        let sfnt = to_sfnt(&encode_woff(&tables)).unwrap();
+        assert_eq!(read_tables(&sfnt), tables);
+        assert_eq!(checksum(&sfnt), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn test_decode_woff2_untransformed_tables() {
+        let tables = sample_tables();
+        let entries: Vec<_> = tables
+            .iter()
+            .map(|(tag, data)| (*tag, 0, data.clone(), data.len()))
+            .collect();
+        let sfnt = to_sfnt(&encode_woff2(&entries)).unwrap();
+        assert_eq!(read_tables(&sfnt), tables);
+    }
+
+    #[test]
+    fn test_decode_woff2_transformed_glyf_and_hmtx() {
+        // Glyph 0 is empty, glyph 1 a triangle (-20,0) (80,0) (30,-300 off-curve)
+        let mut glyf = Vec::new();
+        for value in [0u16, 0, 2, 0] {
+            write_u16(&mut glyf, value); // reserved, option flags, glyph count, short loca
+        }
+        let n_contours = [0u8, 0, 0, 1];
+        let n_points = [3u8];
+        // dx = -20 (flag 10), dx = +100 (flag 11), then dx = -50 / dy = -300 as a 3-byte triplet
+        let flags = [10u8, 11, 0x80 | 120];
+        let glyph_stream = [20u8, 100, 0x03, 0x21, 0x2C, 0];
+        let bbox = [0u8; 4];
+        let streams: [&[u8]; 7] = [
+            &n_contours,
+            &n_points,
+            &flags,
+            &glyph_stream,
+            &[],
+            &bbox,
+            &[],
+        ];
+        for stream in streams {
+            glyf.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        }
+        for stream in streams {
+            glyf.extend_from_slice(stream);
+        }
+
+        let mut hhea = vec![0u8; 36];
+        hhea[34..36].copy_from_slice(&1u16.to_be_bytes());
+        // advance 500 for glyph 0; left side bearings come from the glyph bounds
+        let hmtx = [3u8, 0x01, 0xF4];
+        let sfnt = to_sfnt(&encode_woff2(&[
+            (u32::from_be_bytes(*b"hhea"), 0, hhea, 36),
+            (TAG_HMTX, 1, hmtx.to_vec(), 6),
+            (TAG_GLYF, 0, glyf, 0),
+            (TAG_LOCA, 0, Vec::new(), 0),
+        ]))
+        .unwrap();
+
+        let tables = read_tables(&sfnt);
+        let table = |tag: u32| &tables.iter().find(|(t, _)| *t == tag).unwrap().1;
+        let triangle = [
+            0, 1, // one contour
+            0xFF, 0xEC, 0xFE, 0xD4, 0, 80, 0, 0, // bounds -20,-300 .. 80,0
+            0, 2, // last point
+            0, 0, // no instructions
+            0x23, 0x33, 0x02, // flags
+            20, 100, 50, // short x deltas
+            0xFE, 0xD4, // y delta -300
+        ];
+        let mut expected = triangle.to_vec();
+        expected.resize(24, 0);
+        assert_eq!(table(TAG_GLYF), &expected);
+        assert_eq!(table(TAG_LOCA), &vec![0, 0, 0, 0, 0, 12]);
+        assert_eq!(table(TAG_HMTX), &vec![0x01, 0xF4, 0, 0, 0xFF, 0xEC]);
+    }
+
+    #[test]
+    fn test_corrupt_web_fonts_are_errors() {
+        assert!(to_sfnt(b"wOFF").is_err());
+        let mut woff2 = encode_woff2(&[(TAG_HEAD, 0, vec![0; 54], 54)]);
+        woff2.truncate(woff2.len() - 4);
+        assert!(to_sfnt(&woff2).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+#[cfg(test)]
+pub mod stub;
+pub mod transcriber;
+pub mod whisper;
@@ -0,0 +1,51 @@
+use crate::models::telop::{SubtitleCue, WordTiming};
+use crate::transcribe::transcriber::{group_words, TranscribeOptions, Transcriber};
+
+/// Deterministic transcriber for tests: "recognizes" a fixed script, spreading its words
+/// evenly over the audio with a short pause between them
+pub struct StubTranscriber {
+    pub script: String,
+}
+
+impl StubTranscriber {
+    pub fn new(script: &str) -> Self {
+        Self {
+            script: script.to_string(),
+        }
+    }
+}
+
+impl Transcriber for StubTranscriber {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        options: &TranscribeOptions,
+    ) -> Result<Vec<SubtitleCue>, String> {
+        if sample_rate == 0 {
+            return Err("Invalid sample rate".to_string());
+        }
+        let duration_ms = samples.len() as u64 * 1000 / sample_rate as u64;
+        let tokens: Vec<&str> = self.script.split_whitespace().collect();
+        if tokens.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let slot = duration_ms / tokens.len() as u64;
+        let words: Vec<WordTiming> = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| WordTiming {
+                text: if i == 0 {
+                    token.to_string()
+                } else {
+                    format!(" {token}")
+                },
+                start_ms: i as u64 * slot,
+                end_ms: i as u64 * slot + slot * 4 / 5,
+            })
+            .collect();
+
+        Ok(group_words(&words, options))
+    }
+}
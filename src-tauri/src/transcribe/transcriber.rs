@@ -0,0 +1,174 @@
+use crate::models::telop::{SubtitleCue, WordTiming};
+use serde::{Deserialize, Serialize};
+
+/// Punctuation that ends a cue
+const SENTENCE_END: &[char] = &['。', '！', '？', '.', '!', '?'];
+
+/// Options shared by all transcription backends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscribeOptions {
+    /// Spoken language ("ja", "en", ...). Detected by the backend when absent.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Longest cue text in characters; longer speech is split into several cues
+    #[serde(default = "default_max_chars")]
+    pub max_chars: usize,
+    /// A pause longer than this starts a new cue
+    #[serde(default = "default_max_gap_ms")]
+    pub max_gap_ms: u64,
+}
+
+fn default_max_chars() -> usize {
+    20
+}
+
+fn default_max_gap_ms() -> u64 {
+    600
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            language: None,
+            max_chars: default_max_chars(),
+            max_gap_ms: default_max_gap_ms(),
+        }
+    }
+}
+
+/// A speech-to-text backend
+pub trait Transcriber {
+    /// Recognize speech in mono samples (as returned by `beat_sync::analyzer::decode_audio_mono`)
+    /// and return cues with per-word timings
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        options: &TranscribeOptions,
+    ) -> Result<Vec<SubtitleCue>, String>;
+}
+
+/// Group recognized words into cues.
+///
+/// Word texts are joined as they are, so backends keep the leading space of words in
+/// languages that use them (" world"). A new cue starts after sentence-ending punctuation,
+/// after a pause longer than `max_gap_ms`, or when the text would exceed `max_chars`.
+pub fn group_words(words: &[WordTiming], options: &TranscribeOptions) -> Vec<SubtitleCue> {
+    let mut cues = Vec::new();
+    let mut current: Vec<&WordTiming> = Vec::new();
+
+    for word in words.iter().filter(|w| !w.text.trim().is_empty()) {
+        if let Some(last) = current.last() {
+            let pause = word.start_ms.saturating_sub(last.end_ms) > options.max_gap_ms;
+            let sentence_end = last.text.trim_end().ends_with(SENTENCE_END);
+            let length = current
+                .iter()
+                .chain(std::iter::once(&word))
+                .map(|w| w.text.as_str())
+                .collect::<String>()
+                .trim()
+                .chars()
+                .count();
+
+            if pause || sentence_end || length > options.max_chars {
+                cues.push(make_cue(cues.len() + 1, &current));
+                current.clear();
+            }
+        }
+        current.push(word);
+    }
+    if !current.is_empty() {
+        cues.push(make_cue(cues.len() + 1, &current));
+    }
+
+    cues
+}
+
+fn make_cue(index: usize, words: &[&WordTiming]) -> SubtitleCue {
+    let text: String = words.iter().map(|w| w.text.as_str()).collect();
+    SubtitleCue {
+        index,
+        start_ms: words[0].start_ms,
+        end_ms: words[words.len() - 1].end_ms,
+        text: text.trim().to_string(),
+        words: words
+            .iter()
+            .map(|w| WordTiming {
+                text: w.text.trim().to_string(),
+                start_ms: w.start_ms,
+                end_ms: w.end_ms,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcribe::stub::StubTranscriber;
+
+    fn word(text: &str, start_ms: u64, end_ms: u64) -> WordTiming {
+        WordTiming {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+        }
+    }
+
+    #[test]
+    fn test_group_words_breaks_at_sentences_pauses_and_length() {
+        let words = vec![
+            word("Hello", 0, 300),
+            word(" world.", 300, 600),
+            word(" Next", 700, 900),
+            word(" one", 900, 1100),
+            word(" after", 2000, 2200),
+            word(" a", 2200, 2300),
+            word(" pause", 2300, 2600),
+        ];
+        let options = TranscribeOptions {
+            max_chars: 12,
+            ..Default::default()
+        };
+
+        let cues = group_words(&words, &options);
+        let texts: Vec<&str> = cues.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["Hello world.", "Next one", "after a", "pause"]);
+        assert_eq!((cues[0].start_ms, cues[0].end_ms), (0, 600));
+        assert_eq!(cues[1].words[0].text, "Next");
+        assert_eq!(cues[3].index, 4);
+    }
+
+    #[test]
+    fn test_group_words_japanese() {
+        let words = vec![
+            word("今日", 0, 300),
+            word("は", 300, 400),
+            word("晴れ", 400, 800),
+            word("です。", 800, 1200),
+            word("散歩", 1300, 1700),
+        ];
+        let cues = group_words(&words, &TranscribeOptions::default());
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "今日は晴れです。");
+        assert_eq!(cues[0].words.len(), 4);
+    }
+
+    #[test]
+    fn test_stub_transcriber_is_deterministic() {
+        let transcriber = StubTranscriber::new("one two three four");
+        let samples = vec![0.0; 16_000 * 4];
+        let options = TranscribeOptions::default();
+
+        let cues = transcriber.transcribe(&samples, 16_000, &options).unwrap();
+        assert_eq!(
+            cues,
+            transcriber.transcribe(&samples, 16_000, &options).unwrap()
+        );
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].text, "one two three four");
+        assert_eq!(cues[0].words[1].start_ms, 1000);
+        assert_eq!(cues[0].end_ms, 3800);
+    }
+}
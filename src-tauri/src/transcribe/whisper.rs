@@ -0,0 +1,204 @@
+use crate::audio_engine::wav::{write_wav, TempDir, WavFormat};
+use crate::models::telop::{SubtitleCue, WordTiming};
+use crate::transcribe::transcriber::{group_words, TranscribeOptions, Transcriber};
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Sample rate whisper models expect
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Runs a whisper.cpp command-line build with a ggml model file from disk.
+///
+/// The audio is written to a temporary 16 kHz WAV file and transcribed with one segment
+/// per word (`--max-len 1 --split-on-word`), which gives the word timings.
+pub struct WhisperCppTranscriber {
+    /// Path or name of the whisper.cpp executable (`whisper-cli`, or `main` in older builds)
+    pub binary: String,
+    /// ggml model file, e.g. `ggml-large-v3-turbo.bin`
+    pub model_path: String,
+    pub threads: Option<u32>,
+}
+
+impl WhisperCppTranscriber {
+    pub fn new(binary: &str, model_path: &str) -> Self {
+        Self {
+            binary: binary.to_string(),
+            model_path: model_path.to_string(),
+            threads: None,
+        }
+    }
+}
+
+impl Transcriber for WhisperCppTranscriber {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        options: &TranscribeOptions,
+    ) -> Result<Vec<SubtitleCue>, String> {
+        if !Path::new(&self.model_path).is_file() {
+            return Err(format!("Model file not found: {}", self.model_path));
+        }
+
+        let work_dir = TempDir::new("transcribe")?;
+        let words = self.run(work_dir.path(), samples, sample_rate, options)?;
+        Ok(group_words(&words, options))
+    }
+}
+
+impl WhisperCppTranscriber {
+    fn run(
+        &self,
+        work_dir: &Path,
+        samples: &[f32],
+        sample_rate: u32,
+        options: &TranscribeOptions,
+    ) -> Result<Vec<WordTiming>, String> {
+        let wav_path = work_dir.join("audio.wav");
+        let output_base = work_dir.join("transcript");
+        write_wav(
+            &wav_path,
+            &[resample(samples, sample_rate, WHISPER_SAMPLE_RATE)],
+            WHISPER_SAMPLE_RATE,
+            WavFormat::Pcm16,
+        )?;
+
+        let mut cmd = Command::new(&self.binary);
+        cmd.arg("-m").arg(&self.model_path);
+        cmd.arg("-f").arg(&wav_path);
+        cmd.arg("-of").arg(&output_base);
+        cmd.args([
+            "--output-json",
+            "--max-len",
+            "1",
+            "--split-on-word",
+            "--no-prints",
+        ]);
+        cmd.args(["-l", options.language.as_deref().unwrap_or("auto")]);
+        if let Some(threads) = self.threads {
+            cmd.args(["-t", &threads.to_string()]);
+        }
+
+        let output = cmd.output().map_err(|e| {
+            format!(
+                "Failed to run {}: {e}. Is whisper.cpp installed?",
+                self.binary
+            )
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Transcription failed: {stderr}"));
+        }
+
+        let json = std::fs::read(output_base.with_extension("json"))
+            .map_err(|e| format!("Failed to read transcription: {e}"))?;
+        parse_whisper_json(&String::from_utf8_lossy(&json))
+    }
+}
+
+#[derive(Deserialize)]
+struct WhisperOutput {
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WhisperOffsets {
+    from: u64,
+    to: u64,
+}
+
+/// Read the words of a whisper.cpp JSON transcript. Non-speech markers such as
+/// `[BLANK_AUDIO]` or `[Music]` are skipped.
+fn parse_whisper_json(json: &str) -> Result<Vec<WordTiming>, String> {
+    let output: WhisperOutput =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse transcription: {e}"))?;
+
+    Ok(output
+        .transcription
+        .into_iter()
+        .filter(|segment| {
+            let text = segment.text.trim();
+            let non_speech = text.starts_with('[') && text.ends_with(']');
+            !text.is_empty() && !non_speech
+        })
+        .map(|segment| WordTiming {
+            text: segment.text,
+            start_ms: segment.offsets.from,
+            end_ms: segment.offsets.to.max(segment.offsets.from),
+        })
+        .collect())
+}
+
+/// Resample mono audio: averaging when downsampling, linear interpolation when upsampling
+fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || from_rate == 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            if ratio > 1.0 {
+                let end = (((i + 1) as f64 * ratio) as usize).clamp(index + 1, samples.len());
+                let window = &samples[index..end];
+                window.iter().sum::<f32>() / window.len() as f32
+            } else {
+                let a = samples[index];
+                let b = samples.get(index + 1).copied().unwrap_or(a);
+                a + (b - a) * (pos - index as f64) as f32
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_whisper_json() {
+        let json = r#"{
+            "transcription": [
+                {"timestamps": {"from": "00:00:00,000", "to": "00:00:00,320"},
+                 "offsets": {"from": 0, "to": 320}, "text": "[BLANK_AUDIO]"},
+                {"offsets": {"from": 320, "to": 700}, "text": " Hello"},
+                {"offsets": {"from": 700, "to": 1100}, "text": " world."},
+                {"offsets": {"from": 1100, "to": 1100}, "text": ""}
+            ]
+        }"#;
+        let words = parse_whisper_json(json).unwrap();
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, " Hello");
+        assert_eq!((words[1].start_ms, words[1].end_ms), (700, 1100));
+        assert!(parse_whisper_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_resample() {
+        let samples: Vec<f32> = (0..48).map(|i| i as f32).collect();
+        let down = resample(&samples, 48_000, 16_000);
+        assert_eq!(down.len(), 16);
+        assert_eq!(down[0], 1.0);
+        assert_eq!(down[15], 46.0);
+
+        let up = resample(&[0.0, 1.0], 8_000, 16_000);
+        assert_eq!(up, [0.0, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_missing_model_file() {
+        let transcriber = WhisperCppTranscriber::new("whisper-cli", "/nonexistent/model.bin");
+        let result = transcriber.transcribe(&[0.0; 160], 16_000, &TranscribeOptions::default());
+        assert!(result.unwrap_err().contains("Model file not found"));
+    }
+}
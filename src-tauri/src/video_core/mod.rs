@@ -2,3 +2,4 @@ pub mod cache;
 pub mod decoder;
 pub mod encoder;
 pub mod frame;
+pub mod motion;
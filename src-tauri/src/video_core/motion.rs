@@ -0,0 +1,111 @@
+use super::decoder::VideoDecoder;
+use super::frame::VideoFrame;
+use serde::{Deserialize, Serialize};
+
+/// Pixels sampled in each direction when comparing frames
+const SAMPLE_STEP: usize = 8;
+
+/// How much the picture changes at a frame
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MotionSample {
+    pub time_ms: u64,
+    /// Mean absolute luma change from the previous frame (0.0–1.0)
+    pub motion: f32,
+}
+
+/// Mean absolute luma difference of two frames on a sparse pixel grid (0.0–1.0)
+pub fn frame_motion(previous: &VideoFrame, current: &VideoFrame) -> f32 {
+    if previous.width != current.width || previous.height != current.height {
+        return 1.0;
+    }
+    let luma = |data: &[u8], offset: usize| {
+        0.299 * data[offset] as f32
+            + 0.587 * data[offset + 1] as f32
+            + 0.114 * data[offset + 2] as f32
+    };
+
+    let stride = current.stride();
+    let mut total = 0.0;
+    let mut count = 0usize;
+    for y in (0..current.height as usize).step_by(SAMPLE_STEP) {
+        for x in (0..current.width as usize).step_by(SAMPLE_STEP) {
+            let offset = y * stride + x * 4;
+            total += (luma(&current.data, offset) - luma(&previous.data, offset)).abs();
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    total / count as f32 / 255.0
+}
+
+/// Motion of every frame of a video. The first frame has no motion.
+pub fn analyze_motion(path: &str) -> Result<Vec<MotionSample>, String> {
+    let mut decoder = VideoDecoder::open(path)?;
+    let mut samples = Vec::new();
+    let mut previous: Option<VideoFrame> = None;
+
+    decoder.decode_frames(|frame| {
+        samples.push(MotionSample {
+            time_ms: frame.pts_ms,
+            motion: previous
+                .as_ref()
+                .map_or(0.0, |previous| frame_motion(previous, &frame)),
+        });
+        previous = Some(frame);
+        Ok(())
+    })?;
+
+    Ok(samples)
+}
+
+/// Mean motion of the samples in `start_ms..end_ms`
+pub fn mean_motion(samples: &[MotionSample], start_ms: u64, end_ms: u64) -> f32 {
+    let (total, count) = samples
+        .iter()
+        .filter(|s| s.time_ms >= start_ms && s.time_ms < end_ms)
+        .fold((0.0, 0usize), |(total, count), s| {
+            (total + s.motion, count + 1)
+        });
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(value: u8) -> VideoFrame {
+        let mut frame = VideoFrame::new(32, 16);
+        frame.data.fill(value);
+        frame
+    }
+
+    #[test]
+    fn test_frame_motion() {
+        assert_eq!(frame_motion(&frame(40), &frame(40)), 0.0);
+        let motion = frame_motion(&frame(0), &frame(255));
+        assert!((motion - 1.0).abs() < 1e-3, "{motion}");
+
+        let samples = [
+            MotionSample {
+                time_ms: 0,
+                motion: 0.0,
+            },
+            MotionSample {
+                time_ms: 40,
+                motion: 0.2,
+            },
+            MotionSample {
+                time_ms: 80,
+                motion: 0.4,
+            },
+        ];
+        assert!((mean_motion(&samples, 40, 120) - 0.3).abs() < 1e-6);
+        assert_eq!(mean_motion(&samples, 200, 300), 0.0);
+    }
+}
@@ -4,10 +4,18 @@ import { listen } from "@tauri-apps/api/event";
 import { save } from "@tauri-apps/plugin-dialog";
 import { Button } from "../Common/Button";
 import { PlatformPreset } from "./PlatformPreset";
-import type { ExportPreset, ValidationResult, AudioSourceParam } from "../../lib/tauri";
-import { getPresets, validateExport, exportVideo } from "../../lib/tauri";
+import type {
+  ExportPreset,
+  ValidationResult,
+  AudioSourceParam,
+  VideoGainParam,
+  FontSubstitution,
+} from "../../lib/tauri";
+import { getPresets, validateExport, exportVideo, exportCaptions } from "../../lib/tauri";
 import { useProjectStore } from "../../stores/projectStore";
 import { useTimelineStore } from "../../stores/timelineStore";
+import { useTelopStore } from "../../stores/telopStore";
+import { telopFontDirs } from "../../lib/fonts";
 
 interface ExportDialogProps {
   open: boolean;
@@ -15,16 +23,20 @@ interface ExportDialogProps {
 }
 
 type ExportState = "idle" | "validating" | "exporting" | "done" | "error";
+type CaptionSidecar = "none" | "srt" | "vtt";
 
 export function ExportDialog({ open, onClose }: ExportDialogProps) {
   const { t } = useTranslation();
-  const { project } = useProjectStore();
+  const { project, filePath } = useProjectStore();
   const [presets, setPresets] = useState<ExportPreset[]>([]);
   const [selectedPlatform, setSelectedPlatform] = useState<string>("");
   const [validation, setValidation] = useState<ValidationResult | null>(null);
   const [exportState, setExportState] = useState<ExportState>("idle");
   const [progress, setProgress] = useState(0);
   const [errorMessage, setErrorMessage] = useState("");
+  const [captionSidecar, setCaptionSidecar] = useState<CaptionSidecar>("none");
+  const [fontSubstitutions, setFontSubstitutions] = useState<FontSubstitution[]>([]);
+  const [fontWarnings, setFontWarnings] = useState<string[]>([]);
 
   useEffect(() => {
     if (!open) return;
@@ -67,8 +79,17 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
     const unlisten = listen<{ percent: number }>("export-progress", (event) => {
       setProgress(Math.round(event.payload.percent));
     });
+    const unlistenFonts = listen<FontSubstitution[]>(
+      "export-font-substitutions",
+      (event) => setFontSubstitutions(event.payload),
+    );
+    const unlistenFontWarnings = listen<string[]>("export-font-warnings", (event) =>
+      setFontWarnings(event.payload),
+    );
     return () => {
       unlisten.then((fn) => fn());
+      unlistenFonts.then((fn) => fn());
+      unlistenFontWarnings.then((fn) => fn());
     };
   }, [open]);
 
@@ -84,6 +105,8 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
     setExportState("exporting");
     setProgress(0);
     setErrorMessage("");
+    setFontSubstitutions([]);
+    setFontWarnings([]);
 
     try {
       // Find the first video clip's source path
@@ -114,19 +137,51 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
                 path: clip.source,
                 startMs: clip.startMs,
                 endMs: clip.endMs,
+                trimStartMs: clip.trimStartMs,
+                duckEnvelope: clip.duckEnvelope,
+                gainEnvelope: clip.gainEnvelope,
+                effects: clip.effects,
               });
             }
           }
         }
       }
 
+      // Volume automation of the video clips' own audio
+      const videoGain: VideoGainParam[] = [];
+      for (const track of timelineTracks) {
+        if (track.type !== "video") continue;
+        for (const clip of track.clips) {
+          if (clip.gainEnvelope && clip.gainEnvelope.length > 0) {
+            videoGain.push({
+              startMs: clip.startMs,
+              endMs: clip.endMs,
+              gainEnvelope: clip.gainEnvelope,
+            });
+          }
+        }
+      }
+
       await exportVideo({
         inputVideo,
         outputPath,
         platform: selectedPlatform,
         audioSources: audioSources.length > 0 ? audioSources : undefined,
+        videoGain: videoGain.length > 0 ? videoGain : undefined,
+        fontDirs: telopFontDirs(filePath, useTelopStore.getState().fontDirs),
       });
 
+      // Closed captions next to the video, e.g. video.mp4 -> video.srt
+      const hasTelops = timelineTracks.some(
+        (track) => track.type === "telop" && track.clips.length > 0,
+      );
+      if (captionSidecar !== "none" && hasTelops) {
+        await exportCaptions({
+          project: { ...project, tracks: timelineTracks },
+          outputPath: outputPath.replace(/\.[^./\\]+$/, "") + `.${captionSidecar}`,
+        });
+      }
+
       setExportState("done");
     } catch (err) {
       setExportState("error");
@@ -140,6 +195,8 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
     setProgress(0);
     setValidation(null);
     setErrorMessage("");
+    setFontSubstitutions([]);
+    setFontWarnings([]);
     onClose();
   };
 
@@ -179,6 +236,23 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
             </div>
           </div>
 
+          {/* Caption sidecar */}
+          <div>
+            <h3 className="text-sm font-medium text-gray-300 mb-3">
+              {t("exportDialog.captionSidecar")}
+            </h3>
+            <select
+              value={captionSidecar}
+              onChange={(e) => setCaptionSidecar(e.target.value as CaptionSidecar)}
+              disabled={exportState === "exporting"}
+              className="w-full px-3 py-2 rounded-lg bg-gray-800 border border-gray-700 text-sm text-white"
+            >
+              <option value="none">{t("exportDialog.captionSidecarNone")}</option>
+              <option value="srt">SRT</option>
+              <option value="vtt">WebVTT</option>
+            </select>
+          </div>
+
           {/* Validation results */}
           {validation && (
             <div className="space-y-2">
@@ -238,6 +312,33 @@ export function ExportDialog({ open, onClose }: ExportDialogProps) {
             </div>
           )}
 
+          {/* Fonts rendered in place of the requested ones */}
+          {fontSubstitutions.length > 0 && (
+            <div className="p-3 rounded-lg bg-yellow-900/30 border border-yellow-700 space-y-1">
+              <p className="text-sm text-yellow-300">
+                {t("exportDialog.fontSubstitutions")}
+              </p>
+              {fontSubstitutions.map((sub, i) => (
+                <p key={i} className="text-xs text-yellow-200">
+                  {sub.characters
+                    ? t("exportDialog.fontSubstitutedChars", { ...sub })
+                    : t("exportDialog.fontSubstituted", { ...sub })}
+                </p>
+              ))}
+            </div>
+          )}
+
+          {/* Font files that could not be loaded */}
+          {fontWarnings.map((warning, i) => (
+            <div
+              key={i}
+              className="flex items-start gap-2 p-3 rounded-lg bg-yellow-900/30 border border-yellow-700"
+            >
+              <span className="text-yellow-400 text-sm">!</span>
+              <span className="text-sm text-yellow-300">{warning}</span>
+            </div>
+          ))}
+
           {/* Error */}
           {exportState === "error" && (
             <div className="p-4 rounded-lg bg-red-900/30 border border-red-700">
@@ -4,12 +4,15 @@ import { open } from "@tauri-apps/plugin-dialog";
 import { Button } from "../Common/Button";
 import { useMediaStore, type MediaItem } from "../../stores/mediaStore";
 import { useTimelineStore } from "../../stores/timelineStore";
-import { probeMedia } from "../../lib/tauri";
+import { findMusicHighlight, probeMedia } from "../../lib/tauri";
 
 const VIDEO_EXTENSIONS = ["mp4", "mov", "webm", "avi"];
 const IMAGE_EXTENSIONS = ["png", "jpg", "jpeg", "gif", "webp"];
 const AUDIO_EXTENSIONS = ["mp3", "wav", "aac", "m4a"];
 
+// Length of the excerpt proposed by the highlight button
+const HIGHLIGHT_DURATION_MS = 15000;
+
 function getMediaType(
   filename: string,
 ): "video" | "image" | "audio" | null {
@@ -32,6 +35,7 @@ export function MediaPanel() {
   const { importedMedia, addMedia, removeMedia } = useMediaStore();
   const { addTrack } = useTimelineStore();
   const [loading, setLoading] = useState(false);
+  const [highlightingId, setHighlightingId] = useState<string | null>(null);
 
   const handleAddMedia = async () => {
     try {
@@ -128,6 +132,41 @@ export function MediaPanel() {
     }
   };
 
+  // Add the best bar-aligned excerpt of a music file as a trimmed audio clip
+  const handleAddHighlight = async (item: MediaItem) => {
+    setHighlightingId(item.id);
+    try {
+      const { clip } = await findMusicHighlight(
+        item.path,
+        HIGHLIGHT_DURATION_MS,
+      );
+      const clipId = `clip-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`;
+      addTrack({
+        id: `track-${clipId}`,
+        type: "audio",
+        label: t("media.highlightTrackLabel", { name: item.filename }),
+        muted: false,
+        clips: [
+          {
+            id: clipId,
+            trackId: `track-${clipId}`,
+            type: "audio",
+            startMs: clip.start_ms,
+            endMs: clip.end_ms,
+            label: item.filename,
+            source: clip.source,
+            color: "#22c55e",
+            trimStartMs: clip.trim_start_ms,
+          },
+        ],
+      });
+    } catch {
+      // No bars found or the file can't be decoded
+    } finally {
+      setHighlightingId(null);
+    }
+  };
+
   const typeLabel: Record<string, string> = {
     video: t("media.typeVideo"),
     image: t("media.typeImage"),
@@ -187,6 +226,16 @@ export function MediaPanel() {
                 </span>
               </div>
             </div>
+            {item.type === "audio" && (
+              <button
+                className="opacity-0 group-hover:opacity-100 text-gray-500 hover:text-green-400 text-xs transition-opacity disabled:opacity-50"
+                onClick={() => handleAddHighlight(item)}
+                disabled={highlightingId !== null}
+                title={t("media.highlight")}
+              >
+                {highlightingId === item.id ? "…" : "★"}
+              </button>
+            )}
             <button
               className="opacity-0 group-hover:opacity-100 text-gray-500 hover:text-red-400 text-xs transition-opacity"
               onClick={() => removeMedia(item.id)}
@@ -4,10 +4,12 @@ import userEvent from "@testing-library/user-event";
 import { MediaPanel } from "../MediaPanel";
 import { useMediaStore } from "../../../stores/mediaStore";
 import { useTimelineStore } from "../../../stores/timelineStore";
+import { findMusicHighlight } from "../../../lib/tauri";
 
 // Mock tauri.ts module
 vi.mock("../../../lib/tauri", () => ({
   probeMedia: vi.fn(),
+  findMusicHighlight: vi.fn(),
 }));
 
 // open is already mocked globally in setup.ts
@@ -122,6 +124,59 @@ describe("MediaPanel", () => {
     });
   });
 
+  describe("highlight button", () => {
+    beforeEach(() => {
+      useMediaStore.setState({
+        importedMedia: [
+          {
+            id: "media-3",
+            path: "/path/to/bgm.mp3",
+            filename: "bgm.mp3",
+            type: "audio",
+            durationMs: 180000,
+          },
+        ],
+      });
+    });
+
+    it("adds the highlight as a trimmed audio clip", async () => {
+      vi.mocked(findMusicHighlight).mockResolvedValue({
+        structure: {
+          bpm: 120,
+          duration_ms: 180000,
+          bars: [],
+          bar_energy: [],
+          bar_repetition: [],
+          sections: [],
+        },
+        highlight: { start_ms: 64000, end_ms: 78000, bar_count: 7, score: 1.2 },
+        clip: {
+          id: "audio-highlight-64000",
+          source: "/path/to/bgm.mp3",
+          start_ms: 0,
+          end_ms: 14000,
+          volume: 1,
+          trim_start_ms: 64000,
+        },
+      });
+      const user = userEvent.setup();
+      render(<MediaPanel />);
+
+      await user.click(screen.getByTitle("サビ15秒をタイムラインに追加"));
+
+      expect(findMusicHighlight).toHaveBeenCalledWith("/path/to/bgm.mp3", 15000);
+      const track = useTimelineStore.getState().tracks[0];
+      expect(track.type).toBe("audio");
+      expect(track.label).toBe("bgm.mp3 (ハイライト)");
+      expect(track.clips[0]).toMatchObject({
+        startMs: 0,
+        endMs: 14000,
+        source: "/path/to/bgm.mp3",
+        trimStartMs: 64000,
+      });
+    });
+  });
+
   describe("add media button", () => {
     it("button is enabled by default", () => {
       render(<MediaPanel />);
@@ -199,7 +199,8 @@ export function Preview() {
       if (!audioEl) continue;
       const inRange = timeMs >= clip.startMs && timeMs <= clip.endMs;
       if (inRange) {
-        audioEl.currentTime = (timeMs - clip.startMs) / 1000;
+        audioEl.currentTime =
+          (timeMs - clip.startMs + (clip.trimStartMs ?? 0)) / 1000;
         audioEl.play().catch(() => {});
       }
     }
@@ -278,7 +279,8 @@ export function Preview() {
           if (!audioEl) continue;
           const inRange = newTimeMs >= ac.startMs && newTimeMs <= ac.endMs;
           if (inRange) {
-            const targetSec = (newTimeMs - ac.startMs) / 1000;
+            const targetSec =
+              (newTimeMs - ac.startMs + (ac.trimStartMs ?? 0)) / 1000;
             if (audioEl.paused) {
               audioEl.currentTime = targetSec;
               audioEl.play().catch(() => {});
@@ -314,7 +316,8 @@ export function Preview() {
       if (!audioEl) continue;
       const inRange = currentTimeMs >= clip.startMs && currentTimeMs <= clip.endMs;
       if (inRange) {
-        audioEl.currentTime = (currentTimeMs - clip.startMs) / 1000;
+        audioEl.currentTime =
+          (currentTimeMs - clip.startMs + (clip.trimStartMs ?? 0)) / 1000;
       }
     }
   }, [currentTimeMs, isPlaying, videoClip]);
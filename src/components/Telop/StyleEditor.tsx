@@ -1,11 +1,15 @@
 import { useState, useEffect, useRef, useMemo } from "react";
 import { useTranslation } from "react-i18next";
+import { open } from "@tauri-apps/plugin-dialog";
 import { useTelopStore } from "../../stores/telopStore";
+import { useProjectStore } from "../../stores/projectStore";
 import {
   useTimelineStore,
   DEFAULT_TRANSFORM,
 } from "../../stores/timelineStore";
-import { listSystemFonts } from "../../lib/tauri";
+import { listSystemFonts, listProjectFonts } from "../../lib/tauri";
+import { telopFontDirs } from "../../lib/fonts";
+import { showToast } from "../Common/Toast";
 
 /** Canvas dimensions (must match Preview.tsx) */
 const CANVAS_W = 1080;
@@ -13,13 +17,15 @@ const CANVAS_H = 1920;
 
 export function StyleEditor() {
   const { t } = useTranslation();
-  const { customStyle, updateCustomStyle, getSelectedTemplate } =
+  const { customStyle, updateCustomStyle, getSelectedTemplate, fontDirs, addFontDir } =
     useTelopStore();
+  const filePath = useProjectStore((s) => s.filePath);
   const template = getSelectedTemplate();
 
   const { tracks, selectedClipId, updateClipTransform } = useTimelineStore();
 
   const [systemFonts, setSystemFonts] = useState<string[]>([]);
+  const [projectFonts, setProjectFonts] = useState<string[]>([]);
   const [fontSearch, setFontSearch] = useState("");
   const [fontDropdownOpen, setFontDropdownOpen] = useState(false);
   const fontDropdownRef = useRef<HTMLDivElement>(null);
@@ -30,6 +36,26 @@ export function StyleEditor() {
       .catch((err) => console.error("Failed to load system fonts:", err));
   }, []);
 
+  // Fonts bundled with the project come first in the list
+  useEffect(() => {
+    const dirs = telopFontDirs(filePath, fontDirs);
+    Promise.all(dirs.map((dir) => listProjectFonts(dir)))
+      .then((reports) => {
+        const families = new Set<string>();
+        for (const report of reports) {
+          report.fonts.forEach((font) => families.add(font.family));
+          report.warnings.forEach((warning) => showToast("warning", warning));
+        }
+        setProjectFonts([...families]);
+      })
+      .catch((err) => console.error("Failed to load project fonts:", err));
+  }, [filePath, fontDirs]);
+
+  const handleAddFontDir = async () => {
+    const selected = await open({ directory: true });
+    if (typeof selected === "string") addFontDir(selected);
+  };
+
   // Close dropdown on outside click
   useEffect(() => {
     function handleClickOutside(e: MouseEvent) {
@@ -65,10 +91,14 @@ export function StyleEditor() {
   const clipTransform = selectedClip?.transform ?? DEFAULT_TRANSFORM;
 
   const filteredFonts = useMemo(() => {
-    if (!fontSearch) return systemFonts;
+    const fonts = [
+      ...projectFonts,
+      ...systemFonts.filter((f) => !projectFonts.includes(f)),
+    ];
+    if (!fontSearch) return fonts;
     const lower = fontSearch.toLowerCase();
-    return systemFonts.filter((f) => f.toLowerCase().includes(lower));
-  }, [systemFonts, fontSearch]);
+    return fonts.filter((f) => f.toLowerCase().includes(lower));
+  }, [projectFonts, systemFonts, fontSearch]);
 
   return (
     <div className="flex flex-col gap-3 p-3">
@@ -268,6 +298,13 @@ export function StyleEditor() {
             </div>
           </div>
         )}
+        <button
+          type="button"
+          onClick={handleAddFontDir}
+          className="self-start text-[11px] text-blue-400 hover:text-blue-300"
+        >
+          {t("style.addFontDir")}
+        </button>
       </div>
 
       {/* Font Size */}
@@ -6,7 +6,11 @@ import { StyleEditor } from "./StyleEditor";
 import { AnimationPreview } from "./AnimationPreview";
 import { Button } from "../Common/Button";
 import { useTimelineStore } from "../../stores/timelineStore";
-import { parseSrt } from "../../lib/tauri";
+import type { TimelineTrackData } from "../../stores/timelineStore";
+import { useProjectStore } from "../../stores/projectStore";
+import { placeClipCues } from "../../lib/captions";
+import { importSubtitles, transcribeMedia } from "../../lib/tauri";
+import type { SubtitleCue } from "../../lib/tauri";
 
 function formatTimeInput(ms: number): string {
   const totalSec = Math.floor(ms / 1000);
@@ -26,52 +30,59 @@ function parseTimeInput(value: string): number {
   return min * 60000 + sec * 1000 + ms;
 }
 
+function cuesToTrack(cues: SubtitleCue[], label: string): TimelineTrackData {
+  const trackId = `telop-track-${Date.now()}`;
+  return {
+    id: trackId,
+    type: "telop",
+    label,
+    muted: false,
+    clips: cues.map((cue) => ({
+      id: `telop-${Date.now()}-${cue.index}`,
+      trackId,
+      type: "telop" as const,
+      startMs: cue.start_ms,
+      endMs: cue.end_ms,
+      label: cue.text,
+      color: "#f59e0b",
+      words: cue.words,
+    })),
+  };
+}
+
 function SrtImport() {
   const { t } = useTranslation();
   const { addTrack } = useTimelineStore();
   const [loading, setLoading] = useState(false);
   const [importedCount, setImportedCount] = useState<number | null>(null);
+  const [warnings, setWarnings] = useState<string[]>([]);
 
   const handleImportSrt = async () => {
     try {
       const selected = await open({
         multiple: false,
-        filters: [{ name: t("telop.srtFilterName"), extensions: ["srt"] }],
+        filters: [{ name: t("telop.srtFilterName"), extensions: ["srt", "vtt", "ass", "ssa"] }],
       });
       if (!selected) return;
 
       setLoading(true);
-      const cues = await parseSrt(selected);
+      const frameHeight = useProjectStore.getState().project?.canvas.height;
+      const { cues, warnings, diagnostics } = await importSubtitles(selected, frameHeight);
+      setWarnings([
+        ...diagnostics.map((d) =>
+          d.line > 0
+            ? t("telop.diagnosticLine", { line: d.line, message: d.message })
+            : d.message,
+        ),
+        ...warnings,
+      ]);
 
       if (cues.length === 0) {
         setImportedCount(0);
         return;
       }
 
-      const clips = cues.map((cue) => {
-        const clipId = `telop-${Date.now()}-${cue.index}`;
-        return {
-          id: clipId,
-          trackId: "",
-          type: "telop" as const,
-          startMs: cue.start_ms,
-          endMs: cue.end_ms,
-          label: cue.text,
-          color: "#f59e0b",
-        };
-      });
-
-      const trackId = `telop-track-${Date.now()}`;
-      clips.forEach((c) => (c.trackId = trackId));
-
-      addTrack({
-        id: trackId,
-        type: "telop",
-        label: t("telop.srtTrackLabel"),
-        muted: false,
-        clips,
-      });
-
+      addTrack(cuesToTrack(cues, t("telop.srtTrackLabel")));
       setImportedCount(cues.length);
     } catch {
       // Dialog cancelled or parse error
@@ -99,6 +110,88 @@ function SrtImport() {
           {t("telop.srtImportedCount", { count: importedCount })}
         </p>
       )}
+      {warnings.length > 0 && (
+        <ul className="text-xs text-yellow-500 mt-1 list-disc list-inside">
+          {warnings.map((warning) => (
+            <li key={warning}>{warning}</li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
+
+function AutoCaption() {
+  const { t } = useTranslation();
+  const { addTrack } = useTimelineStore();
+  const [loading, setLoading] = useState(false);
+  const [captionCount, setCaptionCount] = useState<number | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleAutoCaption = async () => {
+    const videoClips = () =>
+      useTimelineStore
+        .getState()
+        .tracks.flatMap((track) => track.clips)
+        .filter((clip) => clip.type === "video" && clip.source);
+    const sources = new Set(videoClips().map((clip) => clip.source!));
+    if (sources.size === 0) {
+      setError(t("telop.autoCaptionNoMedia"));
+      return;
+    }
+
+    try {
+      const modelPath = await open({
+        multiple: false,
+        filters: [{ name: t("telop.autoCaptionModelFilter"), extensions: ["bin"] }],
+      });
+      if (!modelPath) return;
+
+      setLoading(true);
+      setError(null);
+      // Each source is transcribed once, however many clips play parts of it
+      const transcripts = new Map<string, SubtitleCue[]>();
+      for (const mediaPath of sources) {
+        transcripts.set(mediaPath, await transcribeMedia({ mediaPath, modelPath }));
+      }
+
+      // The transcripts are in source time; place them where each clip plays.
+      // The timeline is re-read since it may have been edited meanwhile.
+      const cues = videoClips()
+        .flatMap((clip) => placeClipCues(transcripts.get(clip.source!) ?? [], clip))
+        .sort((a, b) => a.start_ms - b.start_ms)
+        .map((cue, i) => ({ ...cue, index: i + 1 }));
+      if (cues.length > 0) {
+        addTrack(cuesToTrack(cues, t("telop.autoCaptionTrackLabel")));
+      }
+      setCaptionCount(cues.length);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="p-3">
+      <h3 className="text-xs font-semibold text-gray-400 uppercase tracking-wider mb-2">
+        {t("telop.autoCaptionHeader")}
+      </h3>
+      <Button
+        variant="secondary"
+        size="sm"
+        className="w-full"
+        onClick={handleAutoCaption}
+        disabled={loading}
+      >
+        {loading ? t("telop.autoCaptionRunning") : t("telop.autoCaptionButton")}
+      </Button>
+      {captionCount !== null && (
+        <p className="text-xs text-gray-500 mt-1">
+          {t("telop.autoCaptionCount", { count: captionCount })}
+        </p>
+      )}
+      {error && <p className="text-xs text-red-400 mt-1">{error}</p>}
     </div>
   );
 }
@@ -209,6 +302,8 @@ export function TelopPanel() {
     <div className="flex flex-col h-full overflow-y-auto bg-gray-850">
       <SrtImport />
       <div className="border-t border-gray-700" />
+      <AutoCaption />
+      <div className="border-t border-gray-700" />
       <ManualTelopAdd />
       <div className="border-t border-gray-700" />
       <TemplateList />
@@ -55,7 +55,7 @@ describe("TelopPanel", () => {
   });
 
   describe("SRT import", () => {
-    it("calls open dialog with srt filter when clicked", async () => {
+    it("calls open dialog with subtitle filter when clicked", async () => {
       const user = userEvent.setup();
       vi.mocked(open).mockResolvedValue(null);
 
@@ -64,17 +64,23 @@ describe("TelopPanel", () => {
 
       expect(open).toHaveBeenCalledWith({
         multiple: false,
-        filters: [{ name: "SRT字幕", extensions: ["srt"] }],
+        filters: [
+          { name: "字幕 (SRT/VTT/ASS)", extensions: ["srt", "vtt", "ass", "ssa"] },
+        ],
       });
     });
 
     it("adds telop track to timeline after successful SRT parse", async () => {
       const user = userEvent.setup();
       vi.mocked(open).mockResolvedValue("/path/to/subtitle.srt");
-      vi.mocked(invoke).mockResolvedValue([
-        { index: 1, start_ms: 1000, end_ms: 4000, text: "こんにちは" },
-        { index: 2, start_ms: 5000, end_ms: 8000, text: "世界" },
-      ]);
+      vi.mocked(invoke).mockResolvedValue({
+        cues: [
+          { index: 1, start_ms: 1000, end_ms: 4000, text: "こんにちは" },
+          { index: 2, start_ms: 5000, end_ms: 8000, text: "世界" },
+        ],
+        warnings: [],
+        diagnostics: [],
+      });
 
       render(<TelopPanel />);
       await user.click(screen.getByText("SRTファイルを読み込み"));
@@ -98,7 +104,7 @@ describe("TelopPanel", () => {
     it("shows 0 count message when SRT file is empty", async () => {
       const user = userEvent.setup();
       vi.mocked(open).mockResolvedValue("/path/to/empty.srt");
-      vi.mocked(invoke).mockResolvedValue([]);
+      vi.mocked(invoke).mockResolvedValue({ cues: [], warnings: [], diagnostics: [] });
 
       render(<TelopPanel />);
       await user.click(screen.getByText("SRTファイルを読み込み"));
@@ -107,6 +113,45 @@ describe("TelopPanel", () => {
       expect(useTimelineStore.getState().tracks).toHaveLength(0);
     });
 
+    it("shows warnings returned by the import", async () => {
+      const user = userEvent.setup();
+      vi.mocked(open).mockResolvedValue("/path/to/subtitle.ass");
+      vi.mocked(invoke).mockResolvedValue({
+        cues: [{ index: 1, start_ms: 0, end_ms: 1000, text: "テスト" }],
+        warnings: ["Override tag \\fad is not supported (first used on line 12)"],
+        diagnostics: [],
+      });
+
+      render(<TelopPanel />);
+      await user.click(screen.getByText("SRTファイルを読み込み"));
+
+      expect(
+        await screen.findByText(
+          "Override tag \\fad is not supported (first used on line 12)",
+        ),
+      ).toBeInTheDocument();
+    });
+
+    it("shows line-numbered diagnostics from lenient SRT parsing", async () => {
+      const user = userEvent.setup();
+      vi.mocked(open).mockResolvedValue("/path/to/broken.srt");
+      vi.mocked(invoke).mockResolvedValue({
+        cues: [{ index: 1, start_ms: 0, end_ms: 1000, text: "テスト" }],
+        warnings: [],
+        diagnostics: [
+          { line: 0, message: "File is encoded as Shift_JIS" },
+          { line: 5, message: "Invalid subtitle index: x" },
+        ],
+      });
+
+      render(<TelopPanel />);
+      await user.click(screen.getByText("SRTファイルを読み込み"));
+
+      expect(await screen.findByText("File is encoded as Shift_JIS")).toBeInTheDocument();
+      expect(screen.getByText("5行目: Invalid subtitle index: x")).toBeInTheDocument();
+      expect(useTimelineStore.getState().tracks[0].clips).toHaveLength(1);
+    });
+
     it("does nothing when dialog is cancelled", async () => {
       const user = userEvent.setup();
       vi.mocked(open).mockResolvedValue(null);
@@ -119,6 +164,110 @@ describe("TelopPanel", () => {
     });
   });
 
+  describe("auto captions", () => {
+    it("asks for a video clip when the timeline has none", async () => {
+      const user = userEvent.setup();
+      render(<TelopPanel />);
+      await user.click(screen.getByText("動画の音声を文字起こし"));
+
+      expect(
+        screen.getByText("先にタイムラインへ動画クリップを追加してください"),
+      ).toBeInTheDocument();
+      expect(open).not.toHaveBeenCalled();
+    });
+
+    it("adds a telop track with word timings from the transcription", async () => {
+      const user = userEvent.setup();
+      useTimelineStore.setState({
+        tracks: [
+          {
+            id: "video-1",
+            type: "video",
+            label: "Video",
+            muted: false,
+            clips: [
+              {
+                id: "clip-1",
+                trackId: "video-1",
+                type: "video",
+                startMs: 0,
+                endMs: 5000,
+                label: "input.mp4",
+                source: "/path/to/input.mp4",
+                color: "#3b82f6",
+              },
+            ],
+          },
+        ],
+      });
+      vi.mocked(open).mockResolvedValue("/models/ggml-base.bin");
+      const words = [
+        { text: "今日", start_ms: 500, end_ms: 800 },
+        { text: "は", start_ms: 800, end_ms: 1000 },
+      ];
+      vi.mocked(invoke).mockResolvedValue([
+        { index: 1, start_ms: 500, end_ms: 1000, text: "今日は", words },
+      ]);
+
+      render(<TelopPanel />);
+      await user.click(screen.getByText("動画の音声を文字起こし"));
+
+      await screen.findByText("1件の字幕を追加しました");
+      expect(invoke).toHaveBeenCalledWith("transcribe_media", {
+        mediaPath: "/path/to/input.mp4",
+        modelPath: "/models/ggml-base.bin",
+      });
+      const tracks = useTimelineStore.getState().tracks;
+      expect(tracks).toHaveLength(2);
+      expect(tracks[1].label).toBe("テロップ (自動)");
+      expect(tracks[1].clips[0].label).toBe("今日は");
+      expect(tracks[1].clips[0].words).toEqual(words);
+    });
+
+    it("places captions of every clip at its timeline position", async () => {
+      const user = userEvent.setup();
+      const clip = {
+        trackId: "video-1",
+        type: "video" as const,
+        label: "input.mp4",
+        source: "/path/to/input.mp4",
+        color: "#3b82f6",
+      };
+      useTimelineStore.setState({
+        tracks: [
+          {
+            id: "video-1",
+            type: "video",
+            label: "Video",
+            muted: false,
+            clips: [
+              { ...clip, id: "clip-1", startMs: 0, endMs: 2000 },
+              // a jump cut: the source from 5000 plays at 2000
+              { ...clip, id: "clip-2", startMs: 2000, endMs: 4000, trimStartMs: 5000 },
+            ],
+          },
+        ],
+      });
+      vi.mocked(open).mockResolvedValue("/models/ggml-base.bin");
+      vi.mocked(invoke).mockResolvedValue([
+        { index: 1, start_ms: 500, end_ms: 1500, text: "first" },
+        { index: 2, start_ms: 3000, end_ms: 4000, text: "cut out" },
+        { index: 3, start_ms: 5500, end_ms: 6500, text: "second" },
+      ]);
+
+      render(<TelopPanel />);
+      await user.click(screen.getByText("動画の音声を文字起こし"));
+
+      await screen.findByText("2件の字幕を追加しました");
+      expect(invoke).toHaveBeenCalledTimes(1);
+      const clips = useTimelineStore.getState().tracks[1].clips;
+      expect(clips.map((c) => [c.label, c.startMs, c.endMs])).toEqual([
+        ["first", 500, 1500],
+        ["second", 2500, 3500],
+      ]);
+    });
+  });
+
   describe("manual telop add", () => {
     it("renders text input", () => {
       render(<TelopPanel />);
@@ -19,14 +19,15 @@ export function BeatMarkers({
     <div className="absolute inset-0 pointer-events-none">
       {beats.map((beat, i) => {
         const x = (beat.time_ms / durationMs) * width;
-        const opacity = 0.3 + beat.strength * 0.5;
+        const downbeat = beat.phase === 1;
+        const opacity = downbeat ? 0.9 : 0.3 + beat.strength * 0.5;
         return (
           <div
             key={i}
             className="absolute top-0"
             style={{
               left: x,
-              width: 1,
+              width: downbeat ? 2 : 1,
               height,
               backgroundColor: `rgba(251, 191, 36, ${opacity})`,
             }}
@@ -1,6 +1,8 @@
-import { useRef, useCallback, useState } from "react";
+import { useRef, useCallback, useMemo, useState } from "react";
 import type { TimelineClip } from "../../stores/timelineStore";
 import { useTimelineStore } from "../../stores/timelineStore";
+import { gainDbAt } from "../../lib/envelope";
+import { ClipWaveform } from "./ClipWaveform";
 
 interface ClipProps {
   clip: TimelineClip;
@@ -9,6 +11,9 @@ interface ClipProps {
 
 type DragMode = "move" | "trim-start" | "trim-end" | null;
 
+/** Gain drawn at the bottom of the clip by the ducking curve */
+const DUCK_FLOOR_DB = -24;
+
 export function Clip({ clip, zoom }: ClipProps) {
   const { selectedClipId, selectClip, moveClip, trimClip, setEditingClipId } =
     useTimelineStore();
@@ -19,6 +24,19 @@ export function Clip({ clip, zoom }: ClipProps) {
   const left = (clip.startMs / 1000) * 100 * zoom;
   const width = ((clip.endMs - clip.startMs) / 1000) * 100 * zoom;
 
+  // Ducking curve (timeline ms) across the clip, 0 dB at the top
+  const duckPoints = useMemo(() => {
+    const envelope = clip.duckEnvelope;
+    if (!envelope?.length || width <= 0) return null;
+    const steps = Math.max(2, Math.ceil(width / 4));
+    return Array.from({ length: steps + 1 }, (_, i) => {
+      const timeMs = clip.startMs + (i / steps) * (clip.endMs - clip.startMs);
+      const depth = Math.min(1, gainDbAt(envelope, timeMs) / DUCK_FLOOR_DB);
+      const x = (i / steps) * width;
+      return `${x.toFixed(1)},${(2 + depth * 28).toFixed(1)}`;
+    }).join(" ");
+  }, [clip.duckEnvelope, clip.startMs, clip.endMs, width]);
+
   const pxToMs = useCallback(
     (px: number) => (px / (100 * zoom)) * 1000,
     [zoom],
@@ -97,6 +115,31 @@ export function Clip({ clip, zoom }: ClipProps) {
         setEditingClipId(clip.id);
       }}
     >
+      {clip.type === "audio" && clip.source && (
+        <ClipWaveform
+          source={clip.source}
+          startMs={clip.trimStartMs ?? 0}
+          endMs={(clip.trimStartMs ?? 0) + clip.endMs - clip.startMs}
+          width={width}
+          height={32}
+        />
+      )}
+
+      {duckPoints && (
+        <svg
+          className="absolute inset-0 pointer-events-none"
+          width={width}
+          height="100%"
+        >
+          <polyline
+            points={duckPoints}
+            fill="none"
+            stroke="rgba(255,255,255,0.8)"
+            strokeWidth={1.5}
+          />
+        </svg>
+      )}
+
       {/* Trim handle left */}
       <div
         className="w-1.5 cursor-col-resize hover:bg-white/30 rounded-l flex-shrink-0"
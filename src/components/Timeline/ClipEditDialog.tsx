@@ -5,6 +5,11 @@ import { useTimelineStore } from "../../stores/timelineStore";
 import { useMediaStore } from "../../stores/mediaStore";
 import { Button } from "../Common/Button";
 import { msToTimecode, timecodeToMs } from "../../lib/time";
+import { captureNoiseProfile } from "../../lib/tauri";
+import { EffectChainEditor } from "./EffectChainEditor";
+import { KeyframeEditor } from "./KeyframeEditor";
+import type { GainPoint } from "../../types/timeline";
+import type { AudioEffect } from "../../types/effects";
 
 /** Frame-step presets (label, delta in ms) */
 const STEP_PRESETS = [
@@ -16,6 +21,10 @@ const STEP_PRESETS = [
   { label: "+1s", delta: 1000 },
 ] as const;
 
+/** Length and floor of the quick fade-out at the clip end */
+const FADE_OUT_MS = 1000;
+const FADE_OUT_DB = -60;
+
 export function ClipEditDialog() {
   const { t } = useTranslation();
   const {
@@ -23,6 +32,9 @@ export function ClipEditDialog() {
     editingClipId,
     setEditingClipId,
     trimClip,
+    setClipGainEnvelope,
+    setClipDuckEnvelope,
+    setClipEffects,
     splitClip,
     deleteClip,
     durationMs: timelineDurationMs,
@@ -50,6 +62,15 @@ export function ClipEditDialog() {
   const [cursorText, setCursorText] = useState("");
   const [durationText, setDurationText] = useState("");
 
+  // Volume keyframes, in ms from the clip start
+  const [gainPoints, setGainPoints] = useState<GainPoint[]>([]);
+
+  // BGM ducking, in timeline ms
+  const [duckPoints, setDuckPoints] = useState<GainPoint[]>([]);
+
+  // Audio effect chain
+  const [effects, setEffects] = useState<AudioEffect[]>([]);
+
   // Video preview
   const videoRef = useRef<HTMLVideoElement>(null);
   const [isPreviewPlaying, setIsPreviewPlaying] = useState(false);
@@ -84,6 +105,9 @@ export function ClipEditDialog() {
     setStartMs(clip.startMs);
     setEndMs(clip.endMs);
     setCursorMs(clip.startMs);
+    setGainPoints(clip.gainEnvelope ?? []);
+    setDuckPoints(clip.duckEnvelope ?? []);
+    setEffects(clip.effects ?? []);
     setIsPreviewPlaying(false);
   }, [editingClipId]); // eslint-disable-line react-hooks/exhaustive-deps
 
@@ -243,6 +267,16 @@ export function ClipEditDialog() {
     if (cursorMs > startMs + 100) setEndMs(cursorMs);
   }, [cursorMs, startMs]);
 
+  // ---- Volume keyframes ----
+  const addFadeOut = useCallback(() => {
+    const lengthMs = Math.round(endMs - startMs);
+    setGainPoints((prev) => [
+      ...prev.filter((p) => p.time_ms < lengthMs - FADE_OUT_MS),
+      { time_ms: Math.max(0, lengthMs - FADE_OUT_MS), gain_db: 0, curve: "linear" },
+      { time_ms: lengthMs, gain_db: FADE_OUT_DB, curve: "linear" },
+    ]);
+  }, [startMs, endMs]);
+
   // ---- Actions ----
   const handleClose = useCallback(() => {
     setIsPreviewPlaying(false);
@@ -254,8 +288,36 @@ export function ClipEditDialog() {
     if (startMs !== clip.startMs || endMs !== clip.endMs) {
       trimClip(clip.id, Math.round(startMs), Math.round(endMs));
     }
+    if (JSON.stringify(gainPoints) !== JSON.stringify(clip.gainEnvelope ?? [])) {
+      setClipGainEnvelope(clip.id, gainPoints);
+    }
+    if (JSON.stringify(duckPoints) !== JSON.stringify(clip.duckEnvelope ?? [])) {
+      setClipDuckEnvelope(clip.id, duckPoints);
+    }
+    if (JSON.stringify(effects) !== JSON.stringify(clip.effects ?? [])) {
+      setClipEffects(clip.id, effects);
+    }
     setEditingClipId(null);
-  }, [clip, startMs, endMs, trimClip, setEditingClipId]);
+  }, [
+    clip,
+    startMs,
+    endMs,
+    gainPoints,
+    duckPoints,
+    effects,
+    trimClip,
+    setClipGainEnvelope,
+    setClipDuckEnvelope,
+    setClipEffects,
+    setEditingClipId,
+  ]);
+
+  /** Noise profile from the second of source audio at the cursor, e.g. room tone */
+  const captureNoiseAtCursor = useCallback(() => {
+    if (!clip?.source) return Promise.reject(t("effects.noSource"));
+    const sourceMs = (clip.trimStartMs ?? 0) + Math.max(0, cursorMs - startMs);
+    return captureNoiseProfile(clip.source, Math.round(sourceMs), Math.round(sourceMs) + 1000);
+  }, [clip, cursorMs, startMs, t]);
 
   const handleSplit = useCallback(() => {
     if (!clip) return;
@@ -450,6 +512,51 @@ export function ClipEditDialog() {
             </div>
           </div>
 
+          {/* Volume keyframes */}
+          {clip.type !== "telop" && (
+            <KeyframeEditor
+              label={t("clipEdit.volume")}
+              emptyText={t("clipEdit.noKeyframes")}
+              points={gainPoints}
+              onChange={setGainPoints}
+              cursorMs={cursorMs - startMs}
+              offsetMs={startMs}
+            >
+              <Button variant="ghost" size="sm" onClick={addFadeOut}>
+                {t("clipEdit.fadeOut")}
+              </Button>
+            </KeyframeEditor>
+          )}
+
+          {/* BGM ducking */}
+          {clip.type === "audio" && (
+            <KeyframeEditor
+              label={t("clipEdit.ducking")}
+              emptyText={t("clipEdit.noDucking")}
+              points={duckPoints}
+              onChange={setDuckPoints}
+              cursorMs={cursorMs}
+              offsetMs={0}
+            />
+          )}
+
+          {/* Audio effects */}
+          {clip.type === "audio" && (
+            <div className="border-t border-gray-700 pt-4 space-y-3">
+              <div className="flex items-baseline justify-between">
+                <span className="text-[11px] text-gray-400 font-medium">
+                  {t("effects.title")}
+                </span>
+                <span className="text-[10px] text-gray-500">{t("effects.captureHint")}</span>
+              </div>
+              <EffectChainEditor
+                effects={effects}
+                onChange={setEffects}
+                onCaptureNoise={captureNoiseAtCursor}
+              />
+            </div>
+          )}
+
           {/* Split */}
           <div className="border-t border-gray-700 pt-4">
             <div className="flex items-center gap-3">
@@ -0,0 +1,90 @@
+import { useRef, useEffect, useState } from "react";
+import { queryWaveformPeaks, type WaveformPeaks } from "../../lib/tauri";
+
+interface ClipWaveformProps {
+  source: string;
+  /** Source range shown by the clip */
+  startMs: number;
+  endMs: number;
+  width: number;
+  height: number;
+  color?: string;
+}
+
+/** Points requested per query; wider clips are drawn stretched */
+const MAX_POINTS = 4096;
+
+/** Wait for zooming and trimming to settle before querying new peaks */
+const QUERY_DELAY_MS = 80;
+
+/** Min/max waveform of an audio clip, read from the source's cached peak pyramid */
+export function ClipWaveform({
+  source,
+  startMs,
+  endMs,
+  width,
+  height,
+  color = "rgba(255,255,255,0.45)",
+}: ClipWaveformProps) {
+  const canvasRef = useRef<HTMLCanvasElement>(null);
+  const [peaks, setPeaks] = useState<WaveformPeaks | null>(null);
+
+  useEffect(() => {
+    const points = Math.min(Math.ceil(width), MAX_POINTS);
+    if (points <= 0 || endMs <= startMs) return;
+
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      queryWaveformPeaks(source, Math.round(startMs), Math.round(endMs), points)
+        .then((result) => {
+          if (!cancelled) setPeaks(result);
+        })
+        .catch((err) => console.error("Failed to load waveform peaks:", err));
+    }, QUERY_DELAY_MS);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [source, startMs, endMs, width]);
+
+  useEffect(() => {
+    const canvas = canvasRef.current;
+    const channels = peaks?.channels ?? [];
+    const count = channels[0]?.length ?? 0;
+    if (!canvas || count === 0) return;
+
+    const ctx = canvas.getContext("2d");
+    if (!ctx) return;
+
+    const dpr = window.devicePixelRatio || 1;
+    canvas.width = width * dpr;
+    canvas.height = height * dpr;
+    ctx.scale(dpr, dpr);
+    ctx.clearRect(0, 0, width, height);
+
+    const barWidth = width / count;
+    const centerY = height / 2;
+    ctx.fillStyle = color;
+
+    for (let i = 0; i < count; i++) {
+      // Channels share the lane: the widest swing of any channel
+      let min = 0;
+      let max = 0;
+      for (const channel of channels) {
+        min = Math.min(min, channel[i].min);
+        max = Math.max(max, channel[i].max);
+      }
+      const top = centerY - Math.min(max, 1) * centerY;
+      const bottom = centerY - Math.max(min, -1) * centerY;
+      ctx.fillRect(i * barWidth, top, Math.max(barWidth, 0.5), Math.max(bottom - top, 0.5));
+    }
+  }, [peaks, width, height, color]);
+
+  return (
+    <canvas
+      ref={canvasRef}
+      style={{ width, height }}
+      className="absolute inset-0 pointer-events-none"
+    />
+  );
+}
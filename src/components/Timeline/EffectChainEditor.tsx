@@ -0,0 +1,248 @@
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "../Common/Button";
+import type {
+  AudioEffect,
+  AudioEffectType,
+  EqBand,
+  EqBandKind,
+  NoiseProfile,
+} from "../../types/effects";
+
+/** Starting parameters of each effect, matching the Rust defaults */
+const DEFAULT_EFFECTS: { [K in AudioEffectType]: Extract<AudioEffect, { type: K }> } = {
+  high_pass: { type: "high_pass", cutoff_hz: 80, slope_db: 24 },
+  equalizer: {
+    type: "equalizer",
+    bands: [
+      { kind: "low_shelf", freq_hz: 150, gain_db: 0, q: 0.7 },
+      { kind: "peak", freq_hz: 3000, gain_db: 0, q: 1 },
+      { kind: "high_shelf", freq_hz: 8000, gain_db: 0, q: 0.7 },
+    ],
+  },
+  compressor: {
+    type: "compressor",
+    threshold_db: -18,
+    ratio: 4,
+    knee_db: 6,
+    attack_ms: 10,
+    release_ms: 150,
+    makeup_db: 0,
+  },
+  limiter: { type: "limiter", ceiling_db: -1, lookahead_ms: 5, release_ms: 50 },
+  noise_gate: {
+    type: "noise_gate",
+    threshold_db: -50,
+    range_db: -60,
+    attack_ms: 2,
+    hold_ms: 80,
+    release_ms: 150,
+  },
+  noise_reduction: { type: "noise_reduction", reduction_db: 18, sensitivity: 1.5 },
+};
+
+const EFFECT_TYPES = Object.keys(DEFAULT_EFFECTS) as AudioEffectType[];
+
+const EQ_BAND_KINDS: EqBandKind[] = ["peak", "low_shelf", "high_shelf"];
+
+/** Numeric parameters shown for each effect (the equalizer edits its bands instead) */
+const PARAM_FIELDS: Record<AudioEffectType, string[]> = {
+  high_pass: ["cutoff_hz", "slope_db"],
+  equalizer: [],
+  compressor: ["threshold_db", "ratio", "knee_db", "attack_ms", "release_ms", "makeup_db"],
+  limiter: ["ceiling_db", "lookahead_ms", "release_ms"],
+  noise_gate: ["threshold_db", "range_db", "attack_ms", "hold_ms", "release_ms"],
+  noise_reduction: ["reduction_db", "sensitivity"],
+};
+
+const inputClass =
+  "w-full bg-gray-800 border border-gray-700 rounded px-2 py-1 text-xs text-white focus:border-blue-500 focus:outline-none";
+
+interface EffectChainEditorProps {
+  effects: AudioEffect[];
+  onChange: (effects: AudioEffect[]) => void;
+  /** Capture the noise profile for noise reduction from the clip */
+  onCaptureNoise: () => Promise<NoiseProfile>;
+}
+
+export function EffectChainEditor({
+  effects,
+  onChange,
+  onCaptureNoise,
+}: EffectChainEditorProps) {
+  const { t } = useTranslation();
+  const [newType, setNewType] = useState<AudioEffectType>("high_pass");
+  const [capturingIndex, setCapturingIndex] = useState<number | null>(null);
+  const [captureError, setCaptureError] = useState("");
+
+  const update = (index: number, effect: AudioEffect) =>
+    onChange(effects.map((e, i) => (i === index ? effect : e)));
+
+  const move = (index: number, delta: number) => {
+    const target = index + delta;
+    if (target < 0 || target >= effects.length) return;
+    const next = [...effects];
+    [next[index], next[target]] = [next[target], next[index]];
+    onChange(next);
+  };
+
+  const updateBand = (index: number, bandIndex: number, band: Partial<EqBand>) => {
+    const effect = effects[index];
+    if (effect.type !== "equalizer") return;
+    update(index, {
+      ...effect,
+      bands: effect.bands.map((b, i) => (i === bandIndex ? { ...b, ...band } : b)),
+    });
+  };
+
+  const captureNoise = async (index: number) => {
+    const effect = effects[index];
+    if (effect.type !== "noise_reduction") return;
+    setCapturingIndex(index);
+    setCaptureError("");
+    try {
+      update(index, { ...effect, profile: await onCaptureNoise() });
+    } catch (err) {
+      setCaptureError(String(err));
+    } finally {
+      setCapturingIndex(null);
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      {effects.length === 0 && (
+        <p className="text-[11px] text-gray-500">{t("effects.empty")}</p>
+      )}
+      {effects.map((effect, index) => (
+        <div
+          key={index}
+          className="bg-gray-800/60 border border-gray-700 rounded-lg p-2 space-y-2"
+        >
+          <div className="flex items-center gap-2">
+            <span className="text-xs font-medium text-gray-200 flex-1">
+              {index + 1}. {t(`effects.types.${effect.type}`)}
+            </span>
+            <button
+              onClick={() => move(index, -1)}
+              disabled={index === 0}
+              title={t("effects.moveUp")}
+              className="text-gray-500 hover:text-white disabled:opacity-30 text-xs"
+            >
+              ▲
+            </button>
+            <button
+              onClick={() => move(index, 1)}
+              disabled={index === effects.length - 1}
+              title={t("effects.moveDown")}
+              className="text-gray-500 hover:text-white disabled:opacity-30 text-xs"
+            >
+              ▼
+            </button>
+            <button
+              onClick={() => onChange(effects.filter((_, i) => i !== index))}
+              title={t("effects.remove")}
+              className="text-gray-500 hover:text-red-400"
+            >
+              &times;
+            </button>
+          </div>
+
+          {PARAM_FIELDS[effect.type].length > 0 && (
+            <div className="grid grid-cols-3 gap-2">
+              {PARAM_FIELDS[effect.type].map((key) => (
+                <label key={key} className="flex flex-col gap-0.5">
+                  <span className="text-[10px] text-gray-500">
+                    {t(`effects.params.${key}`)}
+                  </span>
+                  <input
+                    type="number"
+                    value={(effect as unknown as Record<string, number>)[key]}
+                    onChange={(e) =>
+                      update(index, { ...effect, [key]: Number(e.target.value) })
+                    }
+                    className={inputClass}
+                  />
+                </label>
+              ))}
+            </div>
+          )}
+
+          {effect.type === "equalizer" &&
+            effect.bands.map((band, bandIndex) => (
+              <div key={bandIndex} className="grid grid-cols-4 gap-2">
+                <select
+                  value={band.kind}
+                  onChange={(e) =>
+                    updateBand(index, bandIndex, { kind: e.target.value as EqBandKind })
+                  }
+                  className={inputClass}
+                >
+                  {EQ_BAND_KINDS.map((kind) => (
+                    <option key={kind} value={kind}>
+                      {t(`effects.bandKinds.${kind}`)}
+                    </option>
+                  ))}
+                </select>
+                {(["freq_hz", "gain_db", "q"] as const).map((key) => (
+                  <label key={key} className="flex items-center gap-1">
+                    <span className="text-[10px] text-gray-500 shrink-0">
+                      {t(`effects.params.${key}`)}
+                    </span>
+                    <input
+                      type="number"
+                      value={band[key]}
+                      onChange={(e) =>
+                        updateBand(index, bandIndex, { [key]: Number(e.target.value) })
+                      }
+                      className={inputClass}
+                    />
+                  </label>
+                ))}
+              </div>
+            ))}
+
+          {effect.type === "noise_reduction" && (
+            <div className="flex items-center gap-2">
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={() => captureNoise(index)}
+                disabled={capturingIndex !== null}
+              >
+                {capturingIndex === index
+                  ? t("effects.capturing")
+                  : t("effects.captureNoise")}
+              </Button>
+              <span className="text-[11px] text-gray-500">
+                {effect.profile ? t("effects.profileCaptured") : t("effects.profileAuto")}
+              </span>
+            </div>
+          )}
+        </div>
+      ))}
+      {captureError && <p className="text-[11px] text-red-400">{captureError}</p>}
+
+      <div className="flex items-center gap-2">
+        <select
+          value={newType}
+          onChange={(e) => setNewType(e.target.value as AudioEffectType)}
+          className="text-sm text-white bg-gray-800 border border-gray-600 rounded px-2 py-1 focus:border-blue-500 focus:outline-none"
+        >
+          {EFFECT_TYPES.map((type) => (
+            <option key={type} value={type}>
+              {t(`effects.types.${type}`)}
+            </option>
+          ))}
+        </select>
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={() => onChange([...effects, structuredClone(DEFAULT_EFFECTS[newType])])}
+        >
+          {t("effects.add")}
+        </Button>
+      </div>
+    </div>
+  );
+}
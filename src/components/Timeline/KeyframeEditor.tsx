@@ -0,0 +1,114 @@
+import { useState, type ReactNode } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "../Common/Button";
+import { msToTimecode } from "../../lib/time";
+import type { GainCurve, GainPoint } from "../../types/timeline";
+
+const GAIN_CURVES: GainCurve[] = ["linear", "hold", "ease_in", "ease_out", "s_curve"];
+
+interface KeyframeEditorProps {
+  label: string;
+  /** Shown when there are no points */
+  emptyText: string;
+  points: GainPoint[];
+  onChange: (points: GainPoint[]) => void;
+  /** The dialog cursor, in the envelope's time */
+  cursorMs: number;
+  /** Timeline position of the envelope's time 0, for display */
+  offsetMs: number;
+  /** Extra actions next to "add at cursor" */
+  children?: ReactNode;
+}
+
+/** List of gain keyframes with a control to add one at the cursor */
+export function KeyframeEditor({
+  label,
+  emptyText,
+  points,
+  onChange,
+  cursorMs,
+  offsetMs,
+  children,
+}: KeyframeEditorProps) {
+  const { t } = useTranslation();
+  const [gainText, setGainText] = useState("0");
+  const [curve, setCurve] = useState<GainCurve>("linear");
+
+  const addAtCursor = () => {
+    const gainDb = parseFloat(gainText);
+    if (isNaN(gainDb)) {
+      setGainText("0");
+      return;
+    }
+    const timeMs = Math.round(Math.max(0, cursorMs));
+    onChange(
+      [
+        ...points.filter((p) => p.time_ms !== timeMs),
+        { time_ms: timeMs, gain_db: gainDb, curve },
+      ].sort((a, b) => a.time_ms - b.time_ms),
+    );
+  };
+
+  return (
+    <div className="border-t border-gray-700 pt-4 space-y-3">
+      <span className="text-[11px] text-gray-400 font-medium">{label}</span>
+      <div className="flex items-end gap-2">
+        <div className="flex flex-col gap-1 w-24">
+          <span className="text-[11px] text-gray-500">{t("clipEdit.gainDb")}</span>
+          <input
+            type="text"
+            value={gainText}
+            onChange={(e) => setGainText(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === "Enter") addAtCursor();
+            }}
+            className="text-sm text-white font-mono bg-gray-800 border border-gray-600 rounded px-2 py-1 focus:border-blue-500 focus:outline-none w-full"
+          />
+        </div>
+        <div className="flex flex-col gap-1">
+          <span className="text-[11px] text-gray-500">{t("clipEdit.curve")}</span>
+          <select
+            value={curve}
+            onChange={(e) => setCurve(e.target.value as GainCurve)}
+            className="text-sm text-white bg-gray-800 border border-gray-600 rounded px-2 py-1 focus:border-blue-500 focus:outline-none"
+          >
+            {GAIN_CURVES.map((c) => (
+              <option key={c} value={c}>
+                {t(`clipEdit.curves.${c}`)}
+              </option>
+            ))}
+          </select>
+        </div>
+        <Button variant="secondary" size="sm" onClick={addAtCursor}>
+          {t("clipEdit.addKeyframe")}
+        </Button>
+        {children}
+      </div>
+      {points.length === 0 ? (
+        <p className="text-[11px] text-gray-500">{emptyText}</p>
+      ) : (
+        <ul className="space-y-1 max-h-40 overflow-y-auto">
+          {points.map((point, index) => (
+            <li
+              key={index}
+              className="flex items-center gap-3 text-[11px] text-gray-300 font-mono"
+            >
+              <span>{msToTimecode(offsetMs + point.time_ms)}</span>
+              <span className="w-16 text-right">{point.gain_db.toFixed(1)} dB</span>
+              <span className="flex-1 text-gray-500 font-sans">
+                {t(`clipEdit.curves.${point.curve ?? "linear"}`)}
+              </span>
+              <button
+                onClick={() => onChange(points.filter((_, i) => i !== index))}
+                title={t("clipEdit.removeKeyframe")}
+                className="text-gray-500 hover:text-red-400"
+              >
+                &times;
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -6,6 +6,8 @@ import { Playhead } from "./Playhead";
 import { Waveform } from "./Waveform";
 import { BeatMarkers } from "./BeatMarkers";
 import { msToTimecode } from "../../lib/time";
+import { mergeEnvelopes, placeSourceEnvelope } from "../../lib/envelope";
+import { analyzeDucking, snapCuesToBeats } from "../../lib/tauri";
 
 export function Timeline() {
   const { t } = useTranslation();
@@ -87,6 +89,91 @@ export function Timeline() {
     [setCurrentTime],
   );
 
+  // Snap every telop clip to the nearest beat, track by track so cues never overlap
+  const handleSnapTelops = useCallback(async () => {
+    const { tracks, beats, snapThresholdMs, setTracks } = useTimelineStore.getState();
+    const snapped = await Promise.all(
+      tracks.map(async (track) => {
+        if (track.type !== "telop" || track.clips.length === 0) return track;
+        const { cues } = await snapCuesToBeats({
+          cues: track.clips.map((clip, i) => ({
+            index: i + 1,
+            id: clip.id,
+            start_ms: clip.startMs,
+            end_ms: clip.endMs,
+            text: clip.label,
+            words: clip.words,
+          })),
+          beats,
+          thresholdMs: snapThresholdMs,
+        });
+        const byId = new Map(cues.map((cue) => [cue.id, cue]));
+        return {
+          ...track,
+          clips: track.clips.map((clip) => {
+            const cue = byId.get(clip.id);
+            return cue
+              ? { ...clip, startMs: cue.start_ms, endMs: cue.end_ms, words: cue.words }
+              : clip;
+          }),
+        };
+      }),
+    );
+    setTracks(snapped);
+  }, []);
+
+  // Duck the BGM under the dialogue of every video clip
+  const handleDuckBgm = useCallback(async () => {
+    const sources = new Set(
+      useTimelineStore
+        .getState()
+        .tracks.filter((track) => track.type === "video")
+        .flatMap((track) => track.clips)
+        .flatMap((clip) => (clip.source ? [clip.source] : [])),
+    );
+    // Each source is analyzed once, however many clips (e.g. jump cuts) play it
+    const analyses = new Map(
+      await Promise.all(
+        [...sources].map(
+          async (source) =>
+            [source, (await analyzeDucking(source)).envelope] as const,
+        ),
+      ),
+    );
+
+    // Re-read the timeline: it may have been edited while the analysis ran.
+    // The analysis is in source time, the export reads timeline time.
+    const { tracks, setTracks } = useTimelineStore.getState();
+    const dialogue = tracks
+      .filter((track) => track.type === "video")
+      .flatMap((track) => track.clips)
+      .sort((a, b) => a.startMs - b.startMs);
+    const duckEnvelope = mergeEnvelopes(
+      dialogue.map((clip) =>
+        placeSourceEnvelope(
+          (clip.source && analyses.get(clip.source)) || [],
+          clip.startMs,
+          clip.endMs,
+          clip.trimStartMs ?? 0,
+        ),
+      ),
+    );
+    setTracks(
+      tracks.map((track) =>
+        track.type === "audio"
+          ? {
+              ...track,
+              clips: track.clips.map((c) => ({
+                ...c,
+                duckEnvelope:
+                  duckEnvelope.length > 0 ? duckEnvelope : undefined,
+              })),
+            }
+          : track,
+      ),
+    );
+  }, []);
+
   // --- Time ruler marks ---
   const totalWidthPx = (durationMs / 1000) * 100 * zoom;
   const intervalSec = zoom > 2 ? 1 : zoom > 0.5 ? 5 : 10;
@@ -133,6 +220,23 @@ export function Timeline() {
             Snap
           </button>
         )}
+        {beats.length > 0 && tracks.some((track) => track.type === "telop") && (
+          <button
+            onClick={handleSnapTelops}
+            className="px-2 py-1 bg-gray-700 hover:bg-gray-600 text-gray-300 text-xs rounded transition-colors"
+          >
+            {t("timeline.snapTelops")}
+          </button>
+        )}
+        {tracks.some((track) => track.type === "video" && track.clips.length > 0) &&
+          tracks.some((track) => track.type === "audio" && track.clips.length > 0) && (
+            <button
+              onClick={handleDuckBgm}
+              className="px-2 py-1 bg-gray-700 hover:bg-gray-600 text-gray-300 text-xs rounded transition-colors"
+            >
+              {t("timeline.duckBgm")}
+            </button>
+          )}
 
         <button
           onClick={zoomOut}
@@ -0,0 +1,52 @@
+import { describe, it, expect } from "vitest";
+import { placeClipCues } from "../captions";
+import type { SubtitleCue } from "../tauri";
+
+const cues: SubtitleCue[] = [
+  { index: 1, start_ms: 500, end_ms: 1500, text: "one" },
+  {
+    index: 2,
+    start_ms: 2000,
+    end_ms: 3000,
+    text: "two words",
+    words: [
+      { text: "two", start_ms: 2000, end_ms: 2400 },
+      { text: "words", start_ms: 2600, end_ms: 3000 },
+    ],
+  },
+  { index: 3, start_ms: 4000, end_ms: 5000, text: "three" },
+];
+
+describe("placeClipCues", () => {
+  it("keeps source time for a clip at 0 without trim", () => {
+    expect(placeClipCues(cues, { startMs: 0, endMs: 6000 })).toEqual(cues);
+  });
+
+  it("moves cues to the clip and drops those outside its part", () => {
+    // plays 1800–3800 of the source at 10000
+    const placed = placeClipCues(cues, {
+      startMs: 10000,
+      endMs: 12000,
+      trimStartMs: 1800,
+    });
+    expect(placed).toHaveLength(1);
+    expect(placed[0].start_ms).toBe(10200);
+    expect(placed[0].end_ms).toBe(11200);
+    expect(placed[0].words?.map((w) => w.start_ms)).toEqual([10200, 10800]);
+  });
+
+  it("cuts cues crossing the clip's edges", () => {
+    const placed = placeClipCues(cues, {
+      startMs: 0,
+      endMs: 1500,
+      trimStartMs: 1000,
+    });
+    expect(placed.map((c) => [c.start_ms, c.end_ms])).toEqual([
+      [0, 500],
+      [1000, 1500],
+    ]);
+    expect(placed[1].words?.map((w) => [w.start_ms, w.end_ms])).toEqual([
+      [1000, 1400],
+    ]);
+  });
+});
@@ -0,0 +1,86 @@
+import { describe, it, expect } from "vitest";
+import { gainDbAt, mergeEnvelopes, placeSourceEnvelope } from "../envelope";
+import type { GainPoint } from "../../types/timeline";
+
+const duck: GainPoint[] = [
+  { time_ms: 1000, gain_db: 0 },
+  { time_ms: 1200, gain_db: -12 },
+  { time_ms: 3000, gain_db: -12 },
+  { time_ms: 3400, gain_db: 0 },
+];
+
+describe("gainDbAt", () => {
+  it("interpolates between points and holds outside them", () => {
+    expect(gainDbAt(duck, 1100)).toBeCloseTo(-6);
+    expect(gainDbAt(duck, 0)).toBe(0);
+    expect(gainDbAt(duck, 5000)).toBe(0);
+    expect(gainDbAt([], 500)).toBe(0);
+  });
+
+  it("holds through a hold segment", () => {
+    const held: GainPoint[] = [
+      { time_ms: 0, gain_db: -6, curve: "hold" },
+      { time_ms: 1000, gain_db: 0 },
+    ];
+    expect(gainDbAt(held, 900)).toBe(-6);
+  });
+
+  it("follows the segment's curve", () => {
+    const eased: GainPoint[] = [
+      { time_ms: 0, gain_db: 0, curve: "ease_in" },
+      { time_ms: 1000, gain_db: -12 },
+    ];
+    expect(gainDbAt(eased, 500)).toBeCloseTo(-3);
+  });
+});
+
+describe("placeSourceEnvelope", () => {
+  it("shifts source time to the clip's timeline position", () => {
+    // the clip plays its source from 500ms at 2000ms on the timeline
+    const placed = placeSourceEnvelope(duck, 2000, 6000, 500);
+    expect(placed.map((p) => p.time_ms)).toEqual([
+      2000, 2000, 2500, 2700, 4500, 4900, 6000, 6000,
+    ]);
+    expect(gainDbAt(placed, 2600)).toBeCloseTo(-6);
+    expect(gainDbAt(placed, 1600)).toBe(0);
+  });
+
+  it("cuts the envelope at the clip's span and is 0 dB outside it", () => {
+    // trimmed into the middle of the ducked part
+    const placed = placeSourceEnvelope(duck, 0, 1000, 2200);
+    expect(placed.map((p) => [p.time_ms, p.gain_db])).toEqual([
+      [0, 0],
+      [0, -12],
+      [800, -12],
+      [1000, -6],
+      [1000, 0],
+    ]);
+    expect(gainDbAt(placed, 500)).toBe(-12);
+    expect(gainDbAt(placed, 1500)).toBe(0);
+  });
+
+  it("returns nothing for an empty envelope or span", () => {
+    expect(placeSourceEnvelope([], 0, 1000, 0)).toEqual([]);
+    expect(placeSourceEnvelope(duck, 1000, 1000, 0)).toEqual([]);
+  });
+});
+
+describe("mergeEnvelopes", () => {
+  it("joins the envelopes of jump-cut clips of one source", () => {
+    const merged = mergeEnvelopes([
+      // the second part of the source, cut to play right after the first
+      placeSourceEnvelope(duck, 1500, 3000, 2500),
+      placeSourceEnvelope(duck, 0, 1500, 0),
+    ]);
+    expect(gainDbAt(merged, 500)).toBe(0);
+    expect(gainDbAt(merged, 1300)).toBe(-12);
+    expect(gainDbAt(merged, 2200)).toBeCloseTo(-6);
+    expect(gainDbAt(merged, 3500)).toBe(0);
+    const times = merged.map((p) => p.time_ms);
+    expect(times).toEqual([...times].sort((a, b) => a - b));
+  });
+
+  it("skips empty envelopes", () => {
+    expect(mergeEnvelopes([[], []])).toEqual([]);
+  });
+});
@@ -0,0 +1,21 @@
+import { describe, it, expect } from "vitest";
+import { telopFontDirs } from "../fonts";
+
+describe("telopFontDirs", () => {
+  it("puts the fonts folder next to the project file first", () => {
+    expect(telopFontDirs("/work/clip/project.json", ["/shared/fonts"])).toEqual([
+      "/work/clip/fonts",
+      "/shared/fonts",
+    ]);
+  });
+
+  it("keeps Windows separators", () => {
+    expect(telopFontDirs("C:\\work\\project.json", [])).toEqual([
+      "C:\\work\\fonts",
+    ]);
+  });
+
+  it("uses only the picked folders for an unsaved project, once each", () => {
+    expect(telopFontDirs(null, ["/a", "/b", "/a"])).toEqual(["/a", "/b"]);
+  });
+});
@@ -0,0 +1,36 @@
+import type { SubtitleCue } from "./tauri";
+
+/** Where a clip plays its source: `trimStartMs` of the source at `startMs` */
+interface ClipPlacement {
+  startMs: number;
+  endMs: number;
+  trimStartMs?: number;
+}
+
+/**
+ * Cues in a clip's source time moved to the clip's place on the timeline. Cues
+ * outside the part of the source the clip plays are dropped, cues crossing its
+ * edges are cut to it.
+ */
+export function placeClipCues(
+  cues: SubtitleCue[],
+  clip: ClipPlacement,
+): SubtitleCue[] {
+  const trimStartMs = clip.trimStartMs ?? 0;
+  const trimEndMs = trimStartMs + clip.endMs - clip.startMs;
+  const inside = (item: { start_ms: number; end_ms: number }) =>
+    item.end_ms > trimStartMs && item.start_ms < trimEndMs;
+  const toTimeline = (ms: number) =>
+    clip.startMs + Math.min(Math.max(ms, trimStartMs), trimEndMs) - trimStartMs;
+
+  return cues.filter(inside).map((cue) => ({
+    ...cue,
+    start_ms: toTimeline(cue.start_ms),
+    end_ms: toTimeline(cue.end_ms),
+    words: cue.words?.filter(inside).map((word) => ({
+      ...word,
+      start_ms: toTimeline(word.start_ms),
+      end_ms: toTimeline(word.end_ms),
+    })),
+  }));
+}
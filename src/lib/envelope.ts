@@ -0,0 +1,139 @@
+import type { GainCurve, GainPoint } from "../types/timeline";
+
+/** Point a time falls after, or -1 before the first point */
+function segmentAt(points: GainPoint[], timeMs: number): number {
+  let index = -1;
+  while (index + 1 < points.length && points[index + 1].time_ms <= timeMs) {
+    index++;
+  }
+  return index;
+}
+
+/** Share (0–1) of a segment's change in dB reached at `progress` through it */
+function shaped(curve: GainCurve | undefined, progress: number): number {
+  switch (curve) {
+    case "hold":
+      return 0;
+    case "ease_in":
+      return progress * progress;
+    case "ease_out":
+      return 1 - (1 - progress) * (1 - progress);
+    case "s_curve":
+      return progress * progress * (3 - 2 * progress);
+    default:
+      return progress;
+  }
+}
+
+/**
+ * Gain (dB) of an envelope at a time. Before the first point and after the last
+ * the gain holds.
+ */
+export function gainDbAt(points: GainPoint[], timeMs: number): number {
+  if (points.length === 0) return 0;
+  const index = segmentAt(points, timeMs);
+  if (index < 0) return points[0].gain_db;
+  const previous = points[index];
+  const next = points[index + 1];
+  if (!next) return previous.gain_db;
+  const progress =
+    (timeMs - previous.time_ms) / (next.time_ms - previous.time_ms);
+  return (
+    previous.gain_db +
+    (next.gain_db - previous.gain_db) * shaped(previous.curve, progress)
+  );
+}
+
+/** A point on an envelope at a time, continuing the segment playing there */
+function pointAt(points: GainPoint[], timeMs: number): GainPoint {
+  return {
+    time_ms: timeMs,
+    gain_db: gainDbAt(points, timeMs),
+    curve: points[segmentAt(points, timeMs)]?.curve,
+  };
+}
+
+/**
+ * The part of an envelope between `fromMs` and `toMs`, with times relative to
+ * `fromMs` and points added at both ends, like `slice_envelope` in the export.
+ */
+export function sliceEnvelope(
+  points: GainPoint[],
+  fromMs: number,
+  toMs: number,
+): GainPoint[] {
+  if (points.length === 0 || toMs <= fromMs) return [];
+  return [
+    pointAt(points, fromMs),
+    ...points.filter((point) => point.time_ms > fromMs && point.time_ms < toMs),
+    pointAt(points, toMs),
+  ].map((point) => ({ ...point, time_ms: point.time_ms - fromMs }));
+}
+
+/**
+ * A clip-relative envelope after the clip's start moved by `deltaMs` over its
+ * source. Points before the new start collapse into one at 0.
+ */
+export function shiftEnvelope(
+  points: GainPoint[],
+  deltaMs: number,
+): GainPoint[] {
+  if (points.length === 0 || deltaMs <= 0) {
+    return points.map((point) => ({
+      ...point,
+      time_ms: point.time_ms - deltaMs,
+    }));
+  }
+  const after = points.filter((point) => point.time_ms > deltaMs);
+  return [pointAt(points, deltaMs), ...after].map((point) => ({
+    ...point,
+    time_ms: point.time_ms - deltaMs,
+  }));
+}
+
+/**
+ * An envelope in source time moved onto the timeline for a clip that plays its
+ * source from `trimStartMs` at `startMs`, keeping only the clip's span. Outside
+ * the span the gain is 0 dB, like the envelopes the export places for clips.
+ */
+export function placeSourceEnvelope(
+  points: GainPoint[],
+  startMs: number,
+  endMs: number,
+  trimStartMs: number,
+): GainPoint[] {
+  const start = Math.round(startMs);
+  const end = Math.round(endMs);
+  if (points.length === 0 || end <= start) return [];
+
+  const offset = start - Math.round(trimStartMs);
+  const shifted = points.map((point) => ({
+    ...point,
+    time_ms: point.time_ms + offset,
+  }));
+  const startIndex = segmentAt(shifted, start);
+  const startCurve: GainCurve = shifted[startIndex]?.curve ?? "linear";
+  return [
+    { time_ms: start, gain_db: 0, curve: "hold" },
+    { time_ms: start, gain_db: gainDbAt(shifted, start), curve: startCurve },
+    ...shifted.filter((point) => point.time_ms > start && point.time_ms < end),
+    { time_ms: end, gain_db: gainDbAt(shifted, end), curve: "hold" },
+    { time_ms: end, gain_db: 0, curve: "hold" },
+  ];
+}
+
+/**
+ * Envelopes placed by `placeSourceEnvelope` for several clips, joined into one.
+ * Where clips overlap, the earlier clip's envelope holds until it ends.
+ */
+export function mergeEnvelopes(envelopes: GainPoint[][]): GainPoint[] {
+  const sorted = envelopes
+    .filter((envelope) => envelope.length > 0)
+    .sort((a, b) => a[0].time_ms - b[0].time_ms);
+  const merged: GainPoint[] = [];
+  for (const envelope of sorted) {
+    const endMs = merged.length > 0 ? merged[merged.length - 1].time_ms : 0;
+    merged.push(...envelope.filter((point) => point.time_ms >= endMs));
+  }
+  return merged;
+}
@@ -0,0 +1,16 @@
+/**
+ * Font folders to register for a render: the `fonts/` folder next to the project file,
+ * then the folders the user picked, without duplicates
+ */
+export function telopFontDirs(projectPath: string | null, pickedDirs: string[]): string[] {
+  const dirs: string[] = [];
+  if (projectPath) {
+    const end = Math.max(projectPath.lastIndexOf("/"), projectPath.lastIndexOf("\\"));
+    const separator = end >= 0 ? projectPath[end] : "/";
+    dirs.push(`${end >= 0 ? projectPath.slice(0, end) : "."}${separator}fonts`);
+  }
+  for (const dir of pickedDirs) {
+    if (!dirs.includes(dir)) dirs.push(dir);
+  }
+  return dirs;
+}
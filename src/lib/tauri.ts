@@ -1,6 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { TelopTemplate } from "../types/telop";
-import type { ShortForgeProject, Platform } from "../types/project";
+import type { AudioEffect, NoiseProfile } from "../types/effects";
+import type { TelopStyle, TelopTemplate } from "../types/telop";
+import type {
+  BeatGrid,
+  Platform,
+  ShortForgeProject,
+  TempoSegment,
+} from "../types/project";
+import type { AudioClip, GainPoint } from "../types/timeline";
 
 export async function getTemplates(
   templatesDir?: string,
@@ -8,17 +15,127 @@ export async function getTemplates(
   return invoke("get_templates", { templatesDir });
 }
 
+export interface WordTiming {
+  text: string;
+  start_ms: number;
+  end_ms: number;
+}
+
+export interface CuePlacement {
+  line?: number;
+  line_align?: "start" | "center" | "end";
+  line_number?: number;
+  position?: number;
+  align?: "start" | "center" | "end" | "left" | "right";
+  size?: number;
+}
+
 export interface SubtitleCue {
   index: number;
   start_ms: number;
   end_ms: number;
   text: string;
+  words?: WordTiming[];
+  id?: string;
+  voice?: string;
+  placement?: CuePlacement;
+  style?: TelopStyle;
+}
+
+export interface SubtitleDiagnostic {
+  /** 1-based line number, or 0 for the whole file */
+  line: number;
+  message: string;
+}
+
+export interface SubtitleImport {
+  cues: SubtitleCue[];
+  warnings: string[];
+  diagnostics: SubtitleDiagnostic[];
 }
 
-export async function parseSrt(path: string): Promise<SubtitleCue[]> {
+export async function parseSrt(path: string): Promise<SubtitleImport> {
   return invoke("parse_srt", { path });
 }
 
+export async function parseVtt(path: string): Promise<SubtitleImport> {
+  return invoke("parse_vtt", { path });
+}
+
+export async function importSubtitles(
+  path: string,
+  frameHeight?: number,
+): Promise<SubtitleImport> {
+  return invoke("import_subtitles", { path, frameHeight });
+}
+
+export async function exportCaptions(params: {
+  project: ShortForgeProject;
+  outputPath: string;
+  trackId?: string;
+  offsetMs?: number;
+}): Promise<string> {
+  return invoke("export_captions", params);
+}
+
+// Cue timing
+export async function offsetCues(
+  cues: SubtitleCue[],
+  offsetMs: number,
+): Promise<SubtitleCue[]> {
+  return invoke("offset_cues", { cues, offsetMs });
+}
+
+export async function stretchCues(
+  cues: SubtitleCue[],
+  factor: number,
+  anchorMs?: number,
+): Promise<SubtitleCue[]> {
+  return invoke("stretch_cues", { cues, factor, anchorMs });
+}
+
+export async function syncCues(params: {
+  cues: SubtitleCue[];
+  fromAMs: number;
+  toAMs: number;
+  fromBMs: number;
+  toBMs: number;
+}): Promise<SubtitleCue[]> {
+  return invoke("sync_cues", params);
+}
+
+export async function enforceCueDurations(
+  cues: SubtitleCue[],
+  minDurationMs?: number,
+  minGapMs?: number,
+): Promise<SubtitleCue[]> {
+  return invoke("enforce_cue_durations", { cues, minDurationMs, minGapMs });
+}
+
+export async function mergeCues(params: {
+  cues: SubtitleCue[];
+  maxCharsPerLine: number;
+  maxLines?: number;
+  maxGapMs?: number;
+}): Promise<SubtitleCue[]> {
+  return invoke("merge_cues", params);
+}
+
+export async function splitCues(params: {
+  cues: SubtitleCue[];
+  maxCharsPerLine: number;
+  maxLines?: number;
+}): Promise<SubtitleCue[]> {
+  return invoke("split_cues", params);
+}
+
+export async function snapCuesToFrames(
+  cues: SubtitleCue[],
+  fps: number,
+): Promise<SubtitleCue[]> {
+  return invoke("snap_cues_to_frames", { cues, fps });
+}
+
 export async function burnTelop(params: {
   inputVideo: string;
   srtFile: string;
@@ -26,6 +143,7 @@ export async function burnTelop(params: {
   templatesDir?: string;
   outputPath: string;
   bitrate?: number;
+  fontDirs?: string[];
 }): Promise<string> {
   return invoke("burn_telop", params);
 }
@@ -68,6 +186,52 @@ export async function importMedia(
   return invoke("import_media", { project, mediaPath });
 }
 
+export interface TimeRange {
+  start_ms: number;
+  end_ms: number;
+}
+
+/** Jump-cut the clips of `source` down to `keep` (source time), closing the gaps on every track */
+export async function applyJumpCuts(
+  project: ShortForgeProject,
+  source: string,
+  keep: TimeRange[],
+): Promise<ShortForgeProject> {
+  return invoke("apply_jump_cuts", { project, source, keep });
+}
+
+export interface MotionSample {
+  time_ms: number;
+  motion: number;
+}
+
+export interface MontageSource {
+  path: string;
+  duration_ms: number;
+  /** Per-frame motion; analyzed on demand by the "motion" strategy when omitted */
+  motion?: MotionSample[];
+}
+
+export interface AutoEditOptions {
+  cut_on?: "beats" | "downbeats";
+  /** Cut on every n-th beat (or downbeat) */
+  every?: number;
+  beat_filter?: BeatFilter;
+  strategy?: "sequential" | "random" | "motion";
+  seed?: number;
+  target_duration_ms: number;
+}
+
+/** Fill a new video track with segments of `sources` cut on the beats */
+export async function autoEdit(
+  project: ShortForgeProject,
+  sources: MontageSource[],
+  analysis: BeatAnalysis,
+  options: AutoEditOptions,
+): Promise<ShortForgeProject> {
+  return invoke("auto_edit", { project, sources, analysis, options });
+}
+
 // Preview
 export async function extractFrame(
   videoPath: string,
@@ -119,6 +283,20 @@ export interface AudioSourceParam {
   path: string;
   startMs: number;
   endMs: number;
+  trimStartMs?: number;
+  duckEnvelope?: GainPoint[];
+  /** Volume automation, in ms from the clip start */
+  gainEnvelope?: GainPoint[];
+  /** Effect chain run over the clip before mixing */
+  effects?: AudioEffect[];
+}
+
+/** Volume automation of a video clip's embedded audio */
+export interface VideoGainParam {
+  startMs: number;
+  endMs: number;
+  /** In ms from the clip start */
+  gainEnvelope: GainPoint[];
 }
 
 export async function exportVideo(params: {
@@ -129,6 +307,8 @@ export async function exportVideo(params: {
   platform: string;
   presetsDir?: string;
   audioSources?: AudioSourceParam[];
+  videoGain?: VideoGainParam[];
+  fontDirs?: string[];
 }): Promise<string> {
   return invoke("export_video", params);
 }
@@ -138,10 +318,61 @@ export async function listSystemFonts(): Promise<string[]> {
   return invoke("list_system_fonts");
 }
 
+export interface FontSubstitution {
+  requested: string;
+  resolved: string;
+  characters: string;
+}
+
+export interface ProjectFont {
+  family: string;
+  weight: number;
+  path: string;
+}
+
+/** Fonts in a `fonts/` directory, with a warning for each file that can't be loaded */
+export interface FontDirReport {
+  fonts: ProjectFont[];
+  warnings: string[];
+}
+
+export async function listProjectFonts(
+  fontsDir: string,
+): Promise<FontDirReport> {
+  return invoke("list_project_fonts", { fontsDir });
+}
+
+export async function checkTelopFonts(
+  style: TelopStyle,
+  texts: string[],
+  fontDirs?: string[],
+): Promise<FontSubstitution[]> {
+  return invoke("check_telop_fonts", { style, texts, fontDirs });
+}
+
 // Beat Sync
+export type BeatKind = "kick" | "snare" | "hi_hat" | "other";
+
 export interface BeatInfo {
   time_ms: number;
+  /** Accent strength (0-1, 1 = strongest beat of the track) */
   strength: number;
+  /** 1-based bar number; 0 for a pickup before the first downbeat */
+  bar?: number;
+  /** Beat within the bar (1 = downbeat); 0 when unknown */
+  phase?: number;
+  kind?: BeatKind;
+}
+
+/** Only strong beats or certain drum hits, e.g. `{ kinds: ["snare"] }` */
+export interface BeatFilter {
+  min_strength?: number;
+  kinds?: BeatKind[];
+}
+
+export interface TempoPoint {
+  time_ms: number;
+  bpm: number;
 }
 
 export interface BeatAnalysis {
@@ -149,10 +380,46 @@ export interface BeatAnalysis {
   beats: BeatInfo[];
   sample_rate: number;
   duration_ms: number;
+  beats_per_bar: number;
+  tempo_curve: TempoPoint[];
+  /** Steady-tempo segments; more than one when the tempo changes */
+  tempo_map: TempoSegment[];
+}
+
+export async function detectBeats(
+  audioPath: string,
+  beatsPerBar?: number,
+): Promise<BeatAnalysis> {
+  return invoke("detect_beats", { audioPath, beatsPerBar });
+}
+
+export async function tapTempo(tapsMs: number[]): Promise<BeatGrid> {
+  return invoke("tap_tempo", { tapsMs });
 }
 
-export async function detectBeats(audioPath: string): Promise<BeatAnalysis> {
-  return invoke("detect_beats", { audioPath });
+export async function shiftBeatGrid(
+  grid: BeatGrid,
+  deltaMs: number,
+  beatsPerBar?: number,
+): Promise<BeatGrid> {
+  return invoke("shift_beat_grid", { grid, deltaMs, beatsPerBar });
+}
+
+export async function generateBeatGrid(
+  grid: BeatGrid,
+  durationMs: number,
+  beatsPerBar?: number,
+): Promise<BeatAnalysis> {
+  return invoke("generate_beat_grid", { grid, beatsPerBar, durationMs });
+}
+
+export async function storeBeatMarkers(params: {
+  project: ShortForgeProject;
+  analysis: BeatAnalysis;
+  audioPath?: string;
+  grid?: BeatGrid;
+}): Promise<ShortForgeProject> {
+  return invoke("store_beat_markers", params);
 }
 
 export async function getWaveform(
@@ -162,10 +429,239 @@ export async function getWaveform(
   return invoke("get_waveform", { audioPath, numPoints });
 }
 
+export interface Peak {
+  min: number;
+  max: number;
+  rms: number;
+}
+
+export interface WaveformPeaks {
+  start_ms: number;
+  end_ms: number;
+  /** Samples per peak of the pyramid level the points were read from */
+  samples_per_peak: number;
+  /** One array of points per channel */
+  channels: Peak[][];
+}
+
+export interface WaveformInfo {
+  /** Hash of the file contents the peaks are cached under */
+  hash: string;
+  sample_rate: number;
+  channels: number;
+  duration_ms: number;
+  levels: number[];
+}
+
+export async function buildWaveformPeaks(
+  audioPath: string,
+): Promise<WaveformInfo> {
+  return invoke("build_waveform_peaks", { audioPath });
+}
+
+export async function queryWaveformPeaks(
+  audioPath: string,
+  startMs: number,
+  endMs: number,
+  points: number,
+): Promise<WaveformPeaks> {
+  return invoke("query_waveform_peaks", { audioPath, startMs, endMs, points });
+}
+
+export type FrequencyScale = "mel" | "log";
+
+export interface SpectrogramOptions {
+  /** STFT frame length in samples (default 2048) */
+  fft_size?: number;
+  /** Samples between frames (default 512) */
+  hop_size?: number;
+  scale?: FrequencyScale;
+  /** Number of frequency bands (default 128) */
+  bands?: number;
+  min_hz?: number;
+  max_hz?: number;
+}
+
+export interface Spectrogram {
+  start_ms: number;
+  end_ms: number;
+  /** Frames per second */
+  frame_rate: number;
+  frame_count: number;
+  /** Center frequency of each band, lowest first */
+  band_hz: number[];
+  max_db: number;
+  min_db: number;
+  /** frame_count * band_hz.length levels (0-255), frame by frame, lowest band first */
+  data: number[];
+  /** Onset strength per frame (0-1) */
+  onset: number[];
+}
+
+export interface DuckingOptions {
+  /** BGM gain under speech in dB (default -12) */
+  depth_db?: number;
+  attack_ms?: number;
+  release_ms?: number;
+  /** Quietest level (dBFS) counted as speech (default -45) */
+  threshold_db?: number;
+  min_speech_ms?: number;
+  /** Pauses shorter than this keep the BGM down */
+  hold_ms?: number;
+}
+
+export interface DuckingAnalysis {
+  speech: TimeRange[];
+  /** BGM gain over time, in the primary audio's timeline ms */
+  envelope: GainPoint[];
+  duration_ms: number;
+}
+
+/** Detect speech in `audioPath` and build the envelope that ducks the BGM under it */
+export async function analyzeDucking(
+  audioPath: string,
+  options?: DuckingOptions,
+): Promise<DuckingAnalysis> {
+  return invoke("analyze_ducking", { audioPath, options });
+}
+
+export async function computeSpectrogram(
+  audioPath: string,
+  startMs: number,
+  endMs: number,
+  options?: SpectrogramOptions,
+): Promise<Spectrogram> {
+  return invoke("compute_spectrogram", { audioPath, startMs, endMs, options });
+}
+
+/** Noise profile of a stretch of `audioPath` holding only background noise */
+export async function captureNoiseProfile(
+  audioPath: string,
+  startMs: number,
+  endMs: number,
+): Promise<NoiseProfile> {
+  return invoke("capture_noise_profile", { audioPath, startMs, endMs });
+}
+
+export interface SilenceOptions {
+  /** RMS level in dBFS below which audio counts as silent (default -40) */
+  threshold_db?: number;
+  min_silence_ms?: number;
+  padding_ms?: number;
+  window_ms?: number;
+}
+
+export interface SilenceAnalysis {
+  silences: TimeRange[];
+  keep: TimeRange[];
+  duration_ms: number;
+}
+
+export async function detectSilence(
+  mediaPath: string,
+  options?: SilenceOptions,
+): Promise<SilenceAnalysis> {
+  return invoke("detect_silence", { mediaPath, options });
+}
+
+export type SectionLabel = "intro" | "verse" | "chorus" | "drop" | "outro";
+
+export interface MusicSection {
+  label: SectionLabel;
+  start_ms: number;
+  end_ms: number;
+  start_bar: number;
+  bar_count: number;
+  energy: number;
+  repetition: number;
+}
+
+export interface StructureAnalysis {
+  bpm: number;
+  duration_ms: number;
+  bars: number[];
+  bar_energy: number[];
+  bar_repetition: number[];
+  sections: MusicSection[];
+}
+
+export interface MusicHighlight {
+  start_ms: number;
+  end_ms: number;
+  bar_count: number;
+  score: number;
+}
+
+export interface HighlightProposal {
+  structure: StructureAnalysis;
+  highlight: MusicHighlight;
+  clip: AudioClip;
+}
+
+export async function analyzeMusicStructure(
+  audioPath: string,
+  beatsPerBar?: number,
+): Promise<StructureAnalysis> {
+  return invoke("analyze_music_structure", { audioPath, beatsPerBar });
+}
+
+export async function findMusicHighlight(
+  audioPath: string,
+  durationMs: number,
+  beatsPerBar?: number,
+  atMs?: number,
+): Promise<HighlightProposal> {
+  return invoke("find_music_highlight", {
+    audioPath,
+    durationMs,
+    beatsPerBar,
+    atMs,
+  });
+}
+
 export async function snapTimeToBeat(
   beats: BeatInfo[],
   timeMs: number,
   thresholdMs?: number,
+  filter?: BeatFilter,
 ): Promise<number> {
-  return invoke("snap_time_to_beat", { beats, timeMs, thresholdMs });
+  return invoke("snap_time_to_beat", { beats, timeMs, thresholdMs, filter });
+}
+
+export interface CueShift {
+  index: number;
+  start_delta_ms: number;
+  end_delta_ms: number;
+}
+
+export interface BeatSnapResult {
+  cues: SubtitleCue[];
+  moved: CueShift[];
+}
+
+export async function snapCuesToBeats(params: {
+  cues: SubtitleCue[];
+  beats: BeatInfo[];
+  thresholdMs?: number;
+  snapEnd?: boolean;
+  filter?: BeatFilter;
+}): Promise<BeatSnapResult> {
+  return invoke("snap_cues_to_beats", params);
+}
+
+export interface TranscribeOptions {
+  /** Spoken language ("ja", "en", ...). Detected automatically when omitted. */
+  language?: string;
+  max_chars?: number;
+  max_gap_ms?: number;
+}
+
+export async function transcribeMedia(params: {
+  mediaPath: string;
+  modelPath: string;
+  binaryPath?: string;
+  threads?: number;
+  options?: TranscribeOptions;
+}): Promise<SubtitleCue[]> {
+  return invoke("transcribe_media", params);
 }
@@ -31,6 +31,7 @@ describe("telopStore", () => {
       selectedTemplateId: null,
       customStyle: {},
       previewText: "サンプルテキスト",
+      fontDirs: [],
     });
   });
 
@@ -146,4 +147,16 @@ describe("telopStore", () => {
       expect(selected).toBeUndefined();
     });
   });
+
+  describe("addFontDir", () => {
+    it("adds each folder once", () => {
+      useTelopStore.getState().addFontDir("/projects/a/fonts");
+      useTelopStore.getState().addFontDir("/shared/fonts");
+      useTelopStore.getState().addFontDir("/projects/a/fonts");
+      expect(useTelopStore.getState().fontDirs).toEqual([
+        "/projects/a/fonts",
+        "/shared/fonts",
+      ]);
+    });
+  });
 });
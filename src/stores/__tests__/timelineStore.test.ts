@@ -262,6 +262,124 @@ describe("timelineStore", () => {
     });
   });
 
+  describe("gain envelopes", () => {
+    const envelope = [
+      { time_ms: 0, gain_db: 0 },
+      { time_ms: 3000, gain_db: -6, curve: "hold" as const },
+      { time_ms: 4000, gain_db: -60 },
+    ];
+
+    beforeEach(() => {
+      const track = createMockTrack({
+        clips: [createMockClip({ id: "clip-1", startMs: 1000, endMs: 6000 })],
+      });
+      useTimelineStore.getState().setTracks([track]);
+    });
+
+    it("sets and clears a clip's envelope", () => {
+      useTimelineStore.getState().setClipGainEnvelope("clip-1", envelope);
+      expect(useTimelineStore.getState().tracks[0].clips[0].gainEnvelope).toEqual(envelope);
+      useTimelineStore.getState().setClipGainEnvelope("clip-1", []);
+      expect(useTimelineStore.getState().tracks[0].clips[0].gainEnvelope).toBeUndefined();
+    });
+
+    it("sets and clears a clip's ducking envelope", () => {
+      useTimelineStore.getState().setClipDuckEnvelope("clip-1", envelope);
+      expect(useTimelineStore.getState().tracks[0].clips[0].duckEnvelope).toEqual(envelope);
+      useTimelineStore.getState().setClipDuckEnvelope("clip-1", []);
+      expect(useTimelineStore.getState().tracks[0].clips[0].duckEnvelope).toBeUndefined();
+    });
+
+    it("splits the envelope with the clip", () => {
+      useTimelineStore.getState().setClipGainEnvelope("clip-1", envelope);
+      useTimelineStore.getState().splitClip("clip-1", 4000);
+      const [first, second] = useTimelineStore.getState().tracks[0].clips;
+      expect(first.gainEnvelope).toEqual(envelope.slice(0, 2));
+      expect(second.gainEnvelope).toEqual([
+        { time_ms: 0, gain_db: -6, curve: "hold" },
+        { time_ms: 1000, gain_db: -60 },
+        { time_ms: 2000, gain_db: -60 },
+      ]);
+    });
+
+    it("adds a point at the cut when splitting inside a ramp", () => {
+      const ramp = [
+        { time_ms: 0, gain_db: 0 },
+        { time_ms: 2000, gain_db: -12 },
+      ];
+      useTimelineStore.getState().setClipGainEnvelope("clip-1", ramp);
+      useTimelineStore.getState().splitClip("clip-1", 2000);
+      const [first, second] = useTimelineStore.getState().tracks[0].clips;
+      expect(first.gainEnvelope).toEqual([
+        { time_ms: 0, gain_db: 0 },
+        { time_ms: 1000, gain_db: -6 },
+      ]);
+      expect(second.gainEnvelope).toEqual([
+        { time_ms: 0, gain_db: -6 },
+        { time_ms: 1000, gain_db: -12 },
+        { time_ms: 4000, gain_db: -12 },
+      ]);
+    });
+
+    it("moves the envelope and source position when trimming the start", () => {
+      useTimelineStore.getState().setTracks([
+        createMockTrack({
+          clips: [
+            createMockClip({
+              id: "clip-1",
+              startMs: 1000,
+              endMs: 6000,
+              source: "/media/a.mp4",
+              trimStartMs: 500,
+              gainEnvelope: [
+                { time_ms: 0, gain_db: 0 },
+                { time_ms: 2000, gain_db: -12 },
+              ],
+            }),
+          ],
+        }),
+      ]);
+      useTimelineStore.getState().trimClip("clip-1", 2000, 6000);
+      let clip = useTimelineStore.getState().tracks[0].clips[0];
+      expect(clip.trimStartMs).toBe(1500);
+      expect(clip.gainEnvelope).toEqual([
+        { time_ms: 0, gain_db: -6 },
+        { time_ms: 1000, gain_db: -12 },
+      ]);
+
+      useTimelineStore.getState().trimClip("clip-1", 1500, 6000);
+      clip = useTimelineStore.getState().tracks[0].clips[0];
+      expect(clip.trimStartMs).toBe(1000);
+      expect(clip.gainEnvelope).toEqual([
+        { time_ms: 500, gain_db: -6 },
+        { time_ms: 1500, gain_db: -12 },
+      ]);
+    });
+  });
+
+  describe("effects", () => {
+    it("sets and clears a clip's effect chain", () => {
+      useTimelineStore.getState().setTracks([
+        createMockTrack({ clips: [createMockClip({ id: "clip-1", type: "audio" })] }),
+      ]);
+      const effects = [
+        { type: "high_pass" as const, cutoff_hz: 100, slope_db: 24 },
+        {
+          type: "noise_gate" as const,
+          threshold_db: -45,
+          range_db: -40,
+          attack_ms: 2,
+          hold_ms: 80,
+          release_ms: 150,
+        },
+      ];
+      useTimelineStore.getState().setClipEffects("clip-1", effects);
+      expect(useTimelineStore.getState().tracks[0].clips[0].effects).toEqual(effects);
+      useTimelineStore.getState().setClipEffects("clip-1", []);
+      expect(useTimelineStore.getState().tracks[0].clips[0].effects).toBeUndefined();
+    });
+  });
+
   describe("beat sync", () => {
     it("sets beats and BPM", () => {
       const beats = [
@@ -6,10 +6,13 @@ interface TelopState {
   selectedTemplateId: string | null;
   customStyle: Partial<TelopStyle>;
   previewText: string;
+  /** Font folders picked by the user, registered for preview checks and export */
+  fontDirs: string[];
   setTemplates: (templates: TelopTemplate[]) => void;
   setSelectedTemplate: (id: string | null) => void;
   updateCustomStyle: (style: Partial<TelopStyle>) => void;
   setPreviewText: (text: string) => void;
+  addFontDir: (dir: string) => void;
   getSelectedTemplate: () => TelopTemplate | undefined;
 }
 
@@ -18,6 +21,7 @@ export const useTelopStore = create<TelopState>((set, get) => ({
   selectedTemplateId: null,
   customStyle: {},
   previewText: "サンプルテキスト",
+  fontDirs: [],
   setTemplates: (templates) => set({ templates }),
   setSelectedTemplate: (id) => set({ selectedTemplateId: id }),
   updateCustomStyle: (style) =>
@@ -25,6 +29,10 @@ export const useTelopStore = create<TelopState>((set, get) => ({
       customStyle: { ...state.customStyle, ...style },
     })),
   setPreviewText: (text) => set({ previewText: text }),
+  addFontDir: (dir) =>
+    set((state) =>
+      state.fontDirs.includes(dir) ? state : { fontDirs: [...state.fontDirs, dir] },
+    ),
   getSelectedTemplate: () => {
     const { templates, selectedTemplateId } = get();
     return templates.find((t) => t.id === selectedTemplateId);
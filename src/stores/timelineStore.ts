@@ -1,6 +1,9 @@
 import { create } from "zustand";
 import { temporal } from "zundo";
-import type { BeatInfo } from "../lib/tauri";
+import { shiftEnvelope, sliceEnvelope } from "../lib/envelope";
+import type { BeatInfo, WordTiming } from "../lib/tauri";
+import type { AudioEffect } from "../types/effects";
+import type { GainPoint } from "../types/timeline";
 
 export interface ClipTransform {
   /** X position in canvas coordinates (0–1080) */
@@ -30,6 +33,16 @@ export interface TimelineClip {
   source?: string;
   color: string;
   transform?: ClipTransform;
+  /** Per-word timings of a telop clip (karaoke animation), in timeline ms */
+  words?: WordTiming[];
+  /** Source position (ms) an audio clip starts playing from */
+  trimStartMs?: number;
+  /** BGM ducking under speech (audio clips), in timeline ms */
+  duckEnvelope?: GainPoint[];
+  /** Volume automation (video and audio clips), in ms from the clip start */
+  gainEnvelope?: GainPoint[];
+  /** Effect chain of an audio clip, applied on export */
+  effects?: AudioEffect[];
 }
 
 export interface TimelineTrackData {
@@ -69,6 +82,9 @@ interface TimelineState {
   moveClip: (clipId: string, newStartMs: number) => void;
   trimClip: (clipId: string, newStartMs: number, newEndMs: number) => void;
   updateClipTransform: (clipId: string, transform: Partial<ClipTransform>) => void;
+  setClipGainEnvelope: (clipId: string, envelope: GainPoint[]) => void;
+  setClipDuckEnvelope: (clipId: string, envelope: GainPoint[]) => void;
+  setClipEffects: (clipId: string, effects: AudioEffect[]) => void;
   setEditingClipId: (id: string | null) => void;
   splitClip: (clipId: string, splitAtMs: number) => void;
   deleteClip: (clipId: string) => void;
@@ -130,10 +146,16 @@ export const useTimelineStore = create<TimelineState>()(
             clips: track.clips.map((clip) => {
               if (clip.id !== clipId) return clip;
               const duration = clip.endMs - clip.startMs;
+              const delta = Math.max(0, newStartMs) - clip.startMs;
               return {
                 ...clip,
                 startMs: Math.max(0, newStartMs),
                 endMs: Math.max(0, newStartMs) + duration,
+                words: clip.words?.map((w) => ({
+                  ...w,
+                  start_ms: w.start_ms + delta,
+                  end_ms: w.end_ms + delta,
+                })),
               };
             }),
           })),
@@ -143,11 +165,21 @@ export const useTimelineStore = create<TimelineState>()(
         set((state) => ({
           tracks: state.tracks.map((track) => ({
             ...track,
-            clips: track.clips.map((clip) =>
-              clip.id === clipId
-                ? { ...clip, startMs: newStartMs, endMs: newEndMs }
-                : clip,
-            ),
+            clips: track.clips.map((clip) => {
+              if (clip.id !== clipId) return clip;
+              // The source stays in place; the clip starts further into it
+              const delta = newStartMs - clip.startMs;
+              return {
+                ...clip,
+                startMs: newStartMs,
+                endMs: newEndMs,
+                trimStartMs: clip.source
+                  ? Math.max(0, (clip.trimStartMs ?? 0) + delta)
+                  : clip.trimStartMs,
+                gainEnvelope:
+                  clip.gainEnvelope && shiftEnvelope(clip.gainEnvelope, delta),
+              };
+            }),
           })),
         })),
 
@@ -166,6 +198,42 @@ export const useTimelineStore = create<TimelineState>()(
           })),
         })),
 
+      setClipGainEnvelope: (clipId, envelope) =>
+        set((state) => ({
+          tracks: state.tracks.map((track) => ({
+            ...track,
+            clips: track.clips.map((clip) =>
+              clip.id === clipId
+                ? { ...clip, gainEnvelope: envelope.length > 0 ? envelope : undefined }
+                : clip,
+            ),
+          })),
+        })),
+
+      setClipDuckEnvelope: (clipId, envelope) =>
+        set((state) => ({
+          tracks: state.tracks.map((track) => ({
+            ...track,
+            clips: track.clips.map((clip) =>
+              clip.id === clipId
+                ? { ...clip, duckEnvelope: envelope.length > 0 ? envelope : undefined }
+                : clip,
+            ),
+          })),
+        })),
+
+      setClipEffects: (clipId, effects) =>
+        set((state) => ({
+          tracks: state.tracks.map((track) => ({
+            ...track,
+            clips: track.clips.map((clip) =>
+              clip.id === clipId
+                ? { ...clip, effects: effects.length > 0 ? effects : undefined }
+                : clip,
+            ),
+          })),
+        })),
+
       setEditingClipId: (id) => set({ editingClipId: id }),
 
       splitClip: (clipId, splitAtMs) =>
@@ -178,16 +246,29 @@ export const useTimelineStore = create<TimelineState>()(
               return track;
             const ts = Date.now();
             const rand = () => Math.random().toString(36).slice(2, 8);
+            // Envelopes are clip-relative; both halves get a point at the cut
+            const splitOffset = splitAtMs - clip.startMs;
+            const length = clip.endMs - clip.startMs;
             const firstHalf: TimelineClip = {
               ...clip,
               id: `clip-${ts}-${rand()}`,
               endMs: splitAtMs,
+              gainEnvelope:
+                clip.gainEnvelope &&
+                sliceEnvelope(clip.gainEnvelope, 0, splitOffset),
             };
             const secondHalf: TimelineClip = {
               ...clip,
               id: `clip-${ts}-${rand()}`,
               startMs: splitAtMs,
+              gainEnvelope:
+                clip.gainEnvelope &&
+                sliceEnvelope(clip.gainEnvelope, splitOffset, length),
             };
+            if (clip.type === "audio") {
+              secondHalf.trimStartMs =
+                (clip.trimStartMs ?? 0) + splitAtMs - clip.startMs;
+            }
             const newClips = [...track.clips];
             newClips.splice(clipIndex, 1, firstHalf, secondHalf);
             return { ...track, clips: newClips };
@@ -0,0 +1,69 @@
+/** One stage of a clip's audio effect chain; stages run in order on export */
+export type AudioEffect =
+  | ({ type: "high_pass" } & HighPass)
+  | ({ type: "equalizer" } & Equalizer)
+  | ({ type: "compressor" } & Compressor)
+  | ({ type: "limiter" } & Limiter)
+  | ({ type: "noise_gate" } & NoiseGate)
+  | ({ type: "noise_reduction" } & NoiseReduction);
+
+export type AudioEffectType = AudioEffect["type"];
+
+export interface HighPass {
+  cutoff_hz: number;
+  /** 12, 24, 36 or 48 dB per octave */
+  slope_db: number;
+}
+
+export type EqBandKind = "peak" | "low_shelf" | "high_shelf";
+
+export interface EqBand {
+  kind: EqBandKind;
+  freq_hz: number;
+  gain_db: number;
+  /** Bandwidth of a peak band; higher is narrower */
+  q: number;
+}
+
+export interface Equalizer {
+  bands: EqBand[];
+}
+
+export interface Compressor {
+  threshold_db: number;
+  ratio: number;
+  knee_db: number;
+  attack_ms: number;
+  release_ms: number;
+  makeup_db: number;
+}
+
+export interface Limiter {
+  ceiling_db: number;
+  lookahead_ms: number;
+  release_ms: number;
+}
+
+export interface NoiseGate {
+  threshold_db: number;
+  /** Gain while closed (negative dB) */
+  range_db: number;
+  attack_ms: number;
+  hold_ms: number;
+  release_ms: number;
+}
+
+/** Average magnitude spectrum of a stretch of background noise */
+export interface NoiseProfile {
+  sample_rate: number;
+  fft_size: number;
+  magnitudes: number[];
+}
+
+export interface NoiseReduction {
+  /** Captured noise; estimated from the quietest parts of the clip when missing */
+  profile?: NoiseProfile;
+  reduction_db: number;
+  sensitivity: number;
+}
+
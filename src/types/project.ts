@@ -6,6 +6,8 @@ export interface ShortForgeProject {
   canvas: Canvas;
   tracks: TimelineTrack[];
   beat_markers: BeatMarker[];
+  /** How beat_markers were produced, so they can be regenerated */
+  beat_settings?: BeatSettings | null;
 }
 
 export interface ProjectMetadata {
@@ -30,4 +32,27 @@ export interface TimelineTrack {
 export interface BeatMarker {
   time_ms: number;
   strength: number;
+  bar?: number;
+  phase?: number;
+  kind?: "kick" | "snare" | "hi_hat" | "other";
+}
+
+export interface BeatSettings {
+  audio_path?: string | null;
+  beats_per_bar: number;
+  /** Manual grid the markers were generated from */
+  grid?: BeatGrid | null;
+  tempo_map: TempoSegment[];
+}
+
+/** A constant-tempo beat grid; the anchor is a downbeat */
+export interface BeatGrid {
+  anchor_ms: number;
+  bpm: number;
+}
+
+export interface TempoSegment {
+  start_ms: number;
+  end_ms: number;
+  bpm: number;
 }
@@ -18,6 +18,13 @@ export interface TelopAnimationDef {
   duration_ms: number;
   delay_per_unit_ms: number;
   easing: string;
+  karaoke?: KaraokeConfig;
+}
+
+export interface KaraokeConfig {
+  highlight_color: string;
+  highlight_outline_color?: string;
+  timing?: "auto" | "even" | "syllable";
 }
 
 export interface Keyframe {
@@ -31,6 +38,7 @@ export interface Keyframe {
 
 export interface TelopStyle {
   font_family: string;
+  font_fallbacks?: string[];
   font_size: number;
   font_weight: number;
   color: string;
@@ -38,6 +46,33 @@ export interface TelopStyle {
   shadow?: ShadowStyle;
   position?: { x: number; y: number };
   alignment?: string;
+  fill?: GradientFill;
+  background?: BackgroundStyle;
+  outlines?: OutlineStyle[];
+  inner_glow?: GlowStyle;
+}
+
+export interface GradientFill {
+  type: "linear" | "radial";
+  colors: string[];
+  stops?: number[];
+  angle?: number;
+}
+
+export interface BackgroundStyle {
+  enabled: boolean;
+  color: string;
+  opacity?: number;
+  padding_x?: number;
+  padding_y?: number;
+  corner_radius?: number;
+  mode?: "line" | "cue";
+}
+
+export interface GlowStyle {
+  enabled: boolean;
+  color: string;
+  blur: number;
 }
 
 export interface OutlineStyle {
@@ -1,3 +1,5 @@
+import type { AudioEffect } from "./effects";
+
 export interface VideoClip {
   id: string;
   source: string;
@@ -5,6 +7,8 @@ export interface VideoClip {
   end_ms: number;
   trim_start_ms: number;
   trim_end_ms: number;
+  /** Volume automation of the embedded audio, in ms from the clip start */
+  gain_envelope?: GainPoint[];
 }
 
 export interface AudioClip {
@@ -13,4 +17,21 @@ export interface AudioClip {
   start_ms: number;
   end_ms: number;
   volume: number;
+  trim_start_ms: number;
+  /** Automatic ducking under speech, in timeline ms */
+  duck_envelope?: GainPoint[];
+  /** Volume automation, in ms from the clip start */
+  gain_envelope?: GainPoint[];
+  /** Effect chain applied on export, in order */
+  effects?: AudioEffect[];
+}
+
+/** How the gain moves from an envelope point to the next */
+export type GainCurve = "linear" | "hold" | "ease_in" | "ease_out" | "s_curve";
+
+/** A point of a gain envelope; `curve` shapes the change in dB to the next point */
+export interface GainPoint {
+  time_ms: number;
+  gain_db: number;
+  curve?: GainCurve;
 }